@@ -1,19 +1,275 @@
 // In src-tauri/src/ai_engine/communication.rs
-use super::types::JsonRpcMessage;
+use super::types::{JsonRpcMessage, JsonRpcResponse, RequestId};
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
 use tokio::process::Child;
+use tokio::sync::{oneshot, Mutex};
 
-pub struct IPCChannel;
+/// How long `recv` and `call` wait for a response unless configured otherwise
+pub const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+type FrameReader = Pin<Box<dyn AsyncBufRead + Send>>;
+type FrameWriter = Pin<Box<dyn AsyncWrite + Send>>;
+
+/// Newline-delimited JSON-RPC over the AI Core's stdin and stdout.
+///
+/// Concurrent `call`s are matched to their replies by request id: whichever caller holds
+/// the reader hands responses for other ids to their waiting callers.
+pub struct IPCChannel {
+    writer: Mutex<Option<FrameWriter>>,
+    reader: Option<Mutex<FrameReader>>,
+    pending: Mutex<HashMap<RequestId, oneshot::Sender<JsonRpcResponse>>>,
+    max_frame_size: usize,
+    read_timeout: Duration,
+}
 
 impl IPCChannel {
-    pub fn new(_child: &mut Child) -> Self {
-        IPCChannel
+    /// Take over the child's stdin and stdout
+    pub fn new(child: &mut Child, max_frame_size: usize) -> Self {
+        let reader = child.stdout.take().map(|stdout| Box::pin(BufReader::new(stdout)) as FrameReader);
+        let writer = child.stdin.take().map(|stdin| Box::pin(stdin) as FrameWriter);
+        Self::from_parts(reader, writer, max_frame_size)
+    }
+
+    /// Channel over arbitrary streams, e.g. in-memory pipes in tests
+    pub fn from_streams<R, W>(reader: R, writer: W, max_frame_size: usize) -> Self
+    where
+        R: AsyncBufRead + Send + 'static,
+        W: AsyncWrite + Send + 'static,
+    {
+        Self::from_parts(Some(Box::pin(reader)), Some(Box::pin(writer)), max_frame_size)
+    }
+
+    fn from_parts(reader: Option<FrameReader>, writer: Option<FrameWriter>, max_frame_size: usize) -> Self {
+        IPCChannel {
+            writer: Mutex::new(writer),
+            reader: reader.map(Mutex::new),
+            pending: Mutex::new(HashMap::new()),
+            max_frame_size,
+            read_timeout: DEFAULT_READ_TIMEOUT,
+        }
+    }
+
+    pub fn with_read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = read_timeout;
+        self
+    }
+
+    /// Write a request as one newline-terminated JSON frame
+    pub async fn send(&self, message: &JsonRpcMessage) -> Result<(), String> {
+        // Compact serialization never contains a raw newline, so one line is one frame
+        let mut frame = serde_json::to_vec(message)
+            .map_err(|e| format!("Failed to serialize JSON-RPC request: {}", e))?;
+        frame.push(b'\n');
+
+        let mut writer = self.writer.lock().await;
+        let writer = writer.as_mut()
+            .ok_or_else(|| "AI Core stdin is not available".to_string())?;
+        writer.write_all(&frame).await
+            .map_err(|e| format!("Failed to write to AI Core: {}", e))?;
+        writer.flush().await
+            .map_err(|e| format!("Failed to flush AI Core stdin: {}", e))
+    }
+
+    /// Close the AI Core's stdin, its cue to finish up and exit
+    pub async fn close(&self) {
+        if let Some(mut writer) = self.writer.lock().await.take() {
+            writer.shutdown().await.ok();
+        }
+    }
+
+    /// Read the next response from the AI Core; `Ok(None)` means the stream closed.
+    /// Fails if nothing arrives within the read timeout.
+    pub async fn recv(&self) -> Result<Option<JsonRpcResponse>, String> {
+        let reader = self.reader.as_ref()
+            .ok_or_else(|| "AI Core stdout is not available".to_string())?;
+
+        tokio::time::timeout(self.read_timeout, async {
+            let mut reader = reader.lock().await;
+            match read_frame(&mut *reader, self.max_frame_size).await? {
+                Some(frame) => parse_response(&frame).map(Some),
+                None => Ok(None),
+            }
+        })
+        .await
+        .map_err(|_| format!("No response from AI Core within {} ms", self.read_timeout.as_millis()))?
+    }
+
+    /// Send a request and wait for the response carrying the same id
+    pub async fn call(&self, message: &JsonRpcMessage) -> Result<JsonRpcResponse, String> {
+        let id = message.id.clone()
+            .ok_or_else(|| "A JSON-RPC call needs a request id".to_string())?;
+
+        let (tx, mut rx) = oneshot::channel();
+        self.pending.lock().await.insert(id.clone(), tx);
+
+        let outcome = match self.send(message).await {
+            Ok(()) => tokio::time::timeout(self.read_timeout, self.await_response(&id, &mut rx))
+                .await
+                .unwrap_or_else(|_| Err(format!(
+                    "No response to '{}' from AI Core within {} ms",
+                    message.method,
+                    self.read_timeout.as_millis()
+                ))),
+            Err(e) => Err(e),
+        };
+
+        // A late reply for an abandoned call is dropped rather than kept forever
+        self.pending.lock().await.remove(&id);
+        outcome
     }
 
-    pub async fn send(&self, _message: &JsonRpcMessage) -> Result<(), String> {
-        // In a real implementation, this would serialize and write to stdin
-        Ok(())
+    /// Wait for our response, reading and routing frames whenever no one else is
+    async fn await_response(
+        &self,
+        id: &RequestId,
+        rx: &mut oneshot::Receiver<JsonRpcResponse>,
+    ) -> Result<JsonRpcResponse, String> {
+        let reader = self.reader.as_ref()
+            .ok_or_else(|| "AI Core stdout is not available".to_string())?;
+
+        loop {
+            let mut reader = tokio::select! {
+                response = &mut *rx => {
+                    return response.map_err(|_| "AI Core channel closed".to_string());
+                }
+                reader = reader.lock() => reader,
+            };
+
+            // The previous reader may have routed our response just before handing over
+            if let Ok(response) = rx.try_recv() {
+                return Ok(response);
+            }
+
+            let frame = match read_frame(&mut *reader, self.max_frame_size).await {
+                Ok(Some(frame)) => frame,
+                Ok(None) => return Err("AI Core closed its output".to_string()),
+                Err(e) if e.contains("exceeds maximum size") => {
+                    log::warn!("{}", e);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            let response = match parse_response(&frame) {
+                Ok(response) => response,
+                Err(e) => {
+                    log::warn!("Ignoring invalid frame from AI Core: {}", e);
+                    continue;
+                }
+            };
+
+            match response.id.clone() {
+                Some(response_id) if &response_id == id => return Ok(response),
+                Some(response_id) => match self.pending.lock().await.remove(&response_id) {
+                    Some(waiter) => {
+                        let _ = waiter.send(response);
+                    }
+                    None => log::warn!("Dropping AI Core response for unknown request {}", response_id),
+                },
+                None => log::warn!("Dropping AI Core response without a request id: {:?}", response.error),
+            }
+        }
     }
 }
 
+/// Read one newline-delimited frame without buffering more than `max_frame_size` bytes.
+/// Oversized frames are skipped up to the next newline so the stream stays in sync.
+pub async fn read_frame<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    max_frame_size: usize,
+) -> Result<Option<String>, String> {
+    let mut frame: Vec<u8> = Vec::new();
+
+    loop {
+        let available = reader.fill_buf().await
+            .map_err(|e| format!("Failed to read from AI Core: {}", e))?;
+        if available.is_empty() {
+            if frame.is_empty() {
+                return Ok(None);
+            }
+            break;
+        }
 
+        let (chunk_len, found_newline) = match available.iter().position(|&b| b == b'\n') {
+            Some(pos) => (pos + 1, true),
+            None => (available.len(), false),
+        };
+        let content_len = frame.len() + chunk_len - found_newline as usize;
 
+        if content_len > max_frame_size {
+            reader.consume(chunk_len);
+            if !found_newline {
+                skip_line(reader).await?;
+            }
+            return Err(format!(
+                "AI Core frame exceeds maximum size of {} bytes",
+                max_frame_size
+            ));
+        }
+
+        frame.extend_from_slice(&available[..chunk_len]);
+        reader.consume(chunk_len);
+        if found_newline {
+            break;
+        }
+    }
+
+    while matches!(frame.last(), Some(b'\n') | Some(b'\r')) {
+        frame.pop();
+    }
+    String::from_utf8(frame)
+        .map(Some)
+        .map_err(|_| "AI Core frame is not valid UTF-8".to_string())
+}
+
+/// Discard input up to and including the next newline
+async fn skip_line<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<(), String> {
+    loop {
+        let available = reader.fill_buf().await
+            .map_err(|e| format!("Failed to read from AI Core: {}", e))?;
+        if available.is_empty() {
+            return Ok(());
+        }
+        match available.iter().position(|&b| b == b'\n') {
+            Some(pos) => {
+                reader.consume(pos + 1);
+                return Ok(());
+            }
+            None => {
+                let len = available.len();
+                reader.consume(len);
+            }
+        }
+    }
+}
+
+/// Validate a frame against the JSON-RPC 2.0 response envelope
+pub fn parse_response(frame: &str) -> Result<JsonRpcResponse, String> {
+    let value: serde_json::Value = serde_json::from_str(frame)
+        .map_err(|e| format!("Malformed JSON-RPC frame: {}", e))?;
+    let envelope = value.as_object()
+        .ok_or_else(|| "JSON-RPC response must be an object".to_string())?;
+
+    if envelope.get("jsonrpc").and_then(|v| v.as_str()) != Some("2.0") {
+        return Err("JSON-RPC response must declare jsonrpc \"2.0\"".to_string());
+    }
+    match envelope.get("id") {
+        Some(serde_json::Value::String(_)) | Some(serde_json::Value::Null) => {}
+        Some(_) => return Err("JSON-RPC response id must be a string request id or null".to_string()),
+        None => return Err("JSON-RPC response is missing an id".to_string()),
+    }
+    match (envelope.contains_key("result"), envelope.contains_key("error")) {
+        (true, false) => {}
+        (false, true) => {
+            let error = &envelope["error"];
+            if !error.get("code").map_or(false, |c| c.is_i64()) || !error.get("message").map_or(false, |m| m.is_string()) {
+                return Err("JSON-RPC error must have an integer code and a string message".to_string());
+            }
+        }
+        _ => return Err("JSON-RPC response must contain exactly one of result or error".to_string()),
+    }
+
+    serde_json::from_value(value).map_err(|e| format!("Invalid JSON-RPC response: {}", e))
+}
@@ -1,5 +1,5 @@
 // In src-tauri/src/ai_engine/health.rs
-use super::types::{HealthStats, JsonRpcMessage};
+use super::types::{AITaskType, HealthStats, JsonRpcMessage, RequestId};
 use super::communication::IPCChannel;
 use std::collections::HashMap;
 
@@ -16,20 +16,38 @@ impl<'a> HealthMonitor<'a> {
             jsonrpc: "2.0".to_string(),
             method: "health.ping".to_string(),
             params: HashMap::new(),
-            id: Some(rand::random()),
+            id: Some(RequestId::new()),
         };
 
-        if self.ipc_channel.send(&ping_message).await.is_ok() {
-            // In a real implementation, we would wait for a "pong" response.
-            HealthStats {
+        match self.ipc_channel.call(&ping_message).await {
+            Ok(response) if response.error.is_none() => HealthStats {
                 is_healthy: true,
                 last_result: "OK".to_string(),
-            }
-        } else {
-            HealthStats {
+                capabilities: response.result.as_ref().and_then(advertised_capabilities),
+            },
+            Ok(response) => HealthStats {
                 is_healthy: false,
-                last_result: "Failed to send ping".to_string(),
-            }
+                last_result: response.error
+                    .map(|e| format!("Ping failed: {}", e.message))
+                    .unwrap_or_default(),
+                capabilities: None,
+            },
+            Err(e) => HealthStats {
+                is_healthy: false,
+                last_result: e,
+                capabilities: None,
+            },
         }
     }
 }
+
+/// Task types listed under `capabilities` in a pong. Names this build doesn't know are skipped.
+pub fn advertised_capabilities(pong: &serde_json::Value) -> Option<Vec<AITaskType>> {
+    let names = pong.get("capabilities")?.as_array()?;
+    Some(
+        names
+            .iter()
+            .filter_map(|name| serde_json::from_value(name.clone()).ok())
+            .collect(),
+    )
+}
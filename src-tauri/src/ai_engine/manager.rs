@@ -1,15 +1,20 @@
 // In src-tauri/src/ai_engine/manager.rs
+use super::communication::IPCChannel;
+use super::health::HealthMonitor;
+use super::queue::{PendingGeneration, PendingGenerationStore};
 use super::types::{
-    AIEngineConfig, AIEngineStatus, SQLGenerationRequest, SQLGenerationResponse,
-    SQLGenerationProgress, CancellationRequest
+    ActiveGeneration, AIEngineConfig, AIEngineStatus, AITaskType, AIEngineStatusReport, HealthStats, SQLGenerationRequest, SQLGenerationResponse,
+    SQLGenerationProgress, SQLGenerationComplete, CancellationRequest, JsonRpcMessage, JsonRpcResponse, ProgressStage, RequestId
 };
 use std::process::Stdio;
 use std::sync::Arc;
-use std::collections::HashMap;
-use tokio::process::{Child, Command};
-use tokio::sync::{broadcast, RwLock, mpsc};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::collections::{HashMap, VecDeque};
+use tokio::process::{Child, ChildStderr, Command};
+use tokio::sync::{broadcast, Mutex, OwnedSemaphorePermit, RwLock, Semaphore, mpsc};
 use tokio::time::{self, Duration, timeout};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use serde::Deserialize;
 use serde_json;
 use uuid::Uuid;
 use chrono::Utc;
@@ -19,73 +24,185 @@ pub struct AIEngineManager {
     pub status: Arc<RwLock<AIEngineStatus>>,
     status_broadcaster: broadcast::Sender<AIEngineStatus>,
     process_handle: Arc<RwLock<Option<Child>>>,
-    active_generations: Arc<RwLock<HashMap<String, mpsc::Sender<()>>>>, // For cancellation
+    ipc_channel: Arc<RwLock<Option<Arc<IPCChannel>>>>, // JSON-RPC over the running AI Core's stdin/stdout
+    last_health: Arc<RwLock<Option<HealthStats>>>, // Outcome of the most recent health.ping
+    active_generations: Arc<RwLock<HashMap<String, (ActiveGeneration, mpsc::Sender<String>)>>>, // For cancellation, carrying the error to report
+    stderr_buffer: Arc<RwLock<VecDeque<String>>>, // Last lines written by the AI Core to stderr
+    pending_store: Option<Arc<Mutex<PendingGenerationStore>>>, // Crash-resilient queue, when configured
+    generation_slots: Arc<Semaphore>, // One permit per generation the AI Core may run at once
+    queued_generations: Arc<AtomicUsize>, // Generations waiting for a permit
+    pending_clarifications: Arc<RwLock<HashMap<String, PendingClarification>>>, // Generations waiting on the user's answers
 }
 
+/// A generation the AI Core answered with questions, kept until the user answers or it expires
+#[derive(Debug, Clone)]
+struct PendingClarification {
+    request: SQLGenerationRequest,
+    questions: Vec<String>,
+    asked_at: std::time::Instant,
+}
+
+/// Counts one generation waiting for a slot for as long as it is alive
+struct QueuedGeneration(Arc<AtomicUsize>);
+
+impl Drop for QueuedGeneration {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Number of stderr lines kept for `get_ai_engine_logs`
+const STDERR_BUFFER_LINES: usize = 500;
+/// Number of trailing stderr lines attached to error statuses
+const STDERR_TAIL_LINES: usize = 20;
+/// How long the AI Core gets to exit after its stdin closes before it is killed
+const PROCESS_EXIT_GRACE: Duration = Duration::from_secs(2);
+/// How long clarifying questions stay answerable before the generation is forgotten
+pub const CLARIFICATION_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// Error reported to generations still in flight when the engine is torn down
+pub const ENGINE_SHUT_DOWN_ERROR: &str = "AI engine shut down";
+
 impl AIEngineManager {
     pub fn new(config: AIEngineConfig) -> Self {
         let (tx, _) = broadcast::channel(32);
+        let pending_store = config
+            .pending_queue_path
+            .clone()
+            .map(|path| Arc::new(Mutex::new(PendingGenerationStore::new(path))));
+        let generation_slots = Arc::new(Semaphore::new(config.max_concurrent_generations.max(1)));
         Self {
             config: Arc::new(config),
             status: Arc::new(RwLock::new(AIEngineStatus::Stopped)),
             status_broadcaster: tx,
             process_handle: Arc::new(RwLock::new(None)),
+            ipc_channel: Arc::new(RwLock::new(None)),
+            last_health: Arc::new(RwLock::new(None)),
             active_generations: Arc::new(RwLock::new(HashMap::new())),
+            stderr_buffer: Arc::new(RwLock::new(VecDeque::with_capacity(STDERR_BUFFER_LINES))),
+            pending_store,
+            generation_slots,
+            queued_generations: Arc::new(AtomicUsize::new(0)),
+            pending_clarifications: Arc::new(RwLock::new(HashMap::new())),
         }
     }
     pub async fn start(&self) {
-        let mut status = self.status.write().await;
-        if *status != AIEngineStatus::Stopped {
-            println!("Engine is already running or starting.");
-            return;
-        }
+        {
+            let mut status = self.status.write().await;
+            if *status != AIEngineStatus::Stopped {
+                println!("Engine is already running or starting.");
+                return;
+            }
 
-        *status = AIEngineStatus::Starting;
-        self.status_broadcaster.send(status.clone()).ok();
-        println!("Attempting to start AI Core process...");
-
-        let child_process = Command::new(&self.config.python_executable)
-            .arg(&self.config.ai_core_script)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn();
-
-        if let Ok(mut process) = child_process {
-            *self.process_handle.write().await = Some(process);
-            *status = AIEngineStatus::Ready;
-            println!("AI Core process started successfully.");
+            *status = AIEngineStatus::Starting;
             self.status_broadcaster.send(status.clone()).ok();
+        }
+        self.stderr_buffer.write().await.clear();
+        *self.last_health.write().await = None;
 
-            self.spawn_health_check_loop();
-        } else {
-            let error_msg = "Failed to spawn AI Core process.".to_string();
-            *status = AIEngineStatus::Error(error_msg.clone());
-            println!("{}", error_msg);
+        let mut attempt: u32 = 0;
+        loop {
+            println!("Attempting to start AI Core process (attempt {})...", attempt + 1);
+
+            let child_process = spawn_ai_core(&self.config);
+
+            let error_msg = match child_process {
+                Ok(mut process) => {
+                    let mut status = self.status.write().await;
+                    // stop() may have been called while we were waiting to retry
+                    if *status != AIEngineStatus::Starting {
+                        drop(status);
+                        process.kill().await.ok();
+                        return;
+                    }
+                    if let Some(stderr) = process.stderr.take() {
+                        spawn_stderr_reader(stderr, Arc::clone(&self.stderr_buffer));
+                    }
+                    let channel = IPCChannel::new(&mut process, self.config.max_ipc_frame_bytes);
+                    *self.ipc_channel.write().await = Some(Arc::new(channel));
+                    *self.process_handle.write().await = Some(process);
+                    *status = AIEngineStatus::Ready;
+                    println!("AI Core process started successfully.");
+                    self.status_broadcaster.send(status.clone()).ok();
+
+                    self.spawn_health_check_loop();
+                    return;
+                }
+                // A missing interpreter will not appear by waiting, so fail immediately
+                Err(e) if matches!(e.kind(), std::io::ErrorKind::NotFound | std::io::ErrorKind::PermissionDenied) => {
+                    self.fail_start(format!(
+                        "Python executable '{}' could not be launched: {}",
+                        self.config.python_executable, e
+                    )).await;
+                    return;
+                }
+                Err(e) => format!("Failed to spawn AI Core process: {}", e),
+            };
+
+            if attempt >= self.config.max_restart_attempts {
+                self.fail_start(format!("{} (gave up after {} attempts)", error_msg, attempt + 1)).await;
+                return;
+            }
+
+            let delay = restart_delay(&self.config, attempt);
+            println!("{}; retrying in {}ms", error_msg, delay.as_millis());
+            time::sleep(delay).await;
+            attempt += 1;
+
+            let status = self.status.read().await;
+            if *status != AIEngineStatus::Starting {
+                println!("AI Core startup aborted.");
+                return;
+            }
             self.status_broadcaster.send(status.clone()).ok();
         }
     }
+
+    /// Talk to an AI Core over `channel` instead of spawning one, so tests can script its replies
+    #[cfg(test)]
+    pub(crate) async fn attach_channel(&self, channel: IPCChannel) {
+        *self.ipc_channel.write().await = Some(Arc::new(channel));
+        *self.status.write().await = AIEngineStatus::Ready;
+    }
+
+    /// Move to the Error state unless startup was aborted in the meantime
+    async fn fail_start(&self, error_msg: String) {
+        let mut status = self.status.write().await;
+        if *status != AIEngineStatus::Starting {
+            return;
+        }
+        println!("{}", error_msg);
+        let error_msg = with_stderr_tail(error_msg, &self.stderr_buffer).await;
+        *status = AIEngineStatus::Error(error_msg);
+        self.status_broadcaster.send(status.clone()).ok();
+    }
     pub async fn stop(&self) {
+        self.cancel_all_generations(ENGINE_SHUT_DOWN_ERROR).await;
+
         let mut status = self.status.write().await;
         let mut process_handle = self.process_handle.write().await;
+        let channel = self.ipc_channel.write().await.take();
 
-        if let Some(mut child) = process_handle.take() {
-            if let Err(e) = child.kill().await {
-                println!("Failed to kill AI Core process: {}", e);
-            } else {
-                println!("AI Core process stopped.");
-            }
+        if let Some(child) = process_handle.take() {
+            shutdown_child(child, channel).await;
         }
         *status = AIEngineStatus::Stopped;
         self.status_broadcaster.send(status.clone()).ok();
     }
+    /// Watch the AI Core, restarting it with backoff when it crashes or stops answering pings
     fn spawn_health_check_loop(&self) {
         let status = Arc::clone(&self.status);
         let config = Arc::clone(&self.config);
         let process_handle = Arc::clone(&self.process_handle);
+        let ipc_channel = Arc::clone(&self.ipc_channel);
+        let last_health = Arc::clone(&self.last_health);
         let broadcaster = self.status_broadcaster.clone();
+        let stderr_buffer = Arc::clone(&self.stderr_buffer);
 
         tokio::spawn(async move {
+            // Restarts since the last successful ping, so a core that keeps dying eventually gives up
+            let mut restart_attempt: u32 = 0;
+
             loop {
                 time::sleep(Duration::from_millis(config.health_check_interval)).await;
                 let current_status = status.read().await.clone();
@@ -94,27 +211,108 @@ impl AIEngineManager {
                     break;
                 }
 
-                if let Some(child) = process_handle.write().await.as_mut() {
-                     match child.try_wait() {
-                        Ok(Some(_)) => {
-                            let mut s = status.write().await;
-                            *s = AIEngineStatus::ProcessCrashed;
-                            broadcaster.send(s.clone()).ok();
-                            break;
-                        }
-                        Ok(None) => {
-                            // Health check logic would go here
+                let exit = match process_handle.write().await.as_mut() {
+                    Some(child) => child.try_wait().map(|exit| exit.map(|code| code.to_string())),
+                    None => Ok(Some("process handle missing".to_string())),
+                };
+
+                let failure = match exit {
+                    Ok(Some(exit_status)) => {
+                        // Give the stderr reader a moment to drain the final traceback
+                        time::sleep(Duration::from_millis(200)).await;
+                        let message = with_stderr_tail(
+                            format!("AI Core process exited ({})", exit_status),
+                            &stderr_buffer,
+                        ).await;
+                        AIEngineStatus::ProcessCrashed(message)
+                    }
+                    Ok(None) => {
+                        let Some(channel) = ipc_channel.read().await.clone() else { continue };
+                        let stats = check_health_within(&channel, config.health_check_timeout).await;
+                        let healthy = stats.is_healthy;
+                        *last_health.write().await = Some(stats);
+
+                        if healthy {
+                            restart_attempt = 0;
+                            continue;
                         }
-                        Err(_) => {
-                            let mut s = status.write().await;
-                            *s = AIEngineStatus::HealthCheckFailed;
+                        AIEngineStatus::HealthCheckFailed
+                    }
+                    Err(_) => AIEngineStatus::HealthCheckFailed,
+                };
+
+                let mut reason = {
+                    let mut s = status.write().await;
+                    if *s == AIEngineStatus::Stopped {
+                        break;
+                    }
+                    *s = failure.clone();
+                    broadcaster.send(s.clone()).ok();
+                    match failure {
+                        AIEngineStatus::ProcessCrashed(message) => message,
+                        _ => "AI Core stopped answering health checks".to_string(),
+                    }
+                };
+
+                // Tear down what is left of the old process before replacing it
+                let channel = ipc_channel.write().await.take();
+                if let Some(child) = process_handle.write().await.take() {
+                    shutdown_child(child, channel).await;
+                }
+
+                loop {
+                    if restart_attempt >= config.max_restart_attempts {
+                        let mut s = status.write().await;
+                        if *s != AIEngineStatus::Stopped {
+                            let message = format!(
+                                "{} (gave up after {} restart attempts)",
+                                reason, config.max_restart_attempts
+                            );
+                            println!("{}", message);
+                            *s = AIEngineStatus::Error(with_stderr_tail(message, &stderr_buffer).await);
                             broadcaster.send(s.clone()).ok();
-                            break;
                         }
+                        return;
                     }
-                } else {
+
+                    let delay = restart_delay(&config, restart_attempt);
+                    println!("{}; restarting AI Core in {}ms", reason, delay.as_millis());
+                    time::sleep(delay).await;
+                    restart_attempt += 1;
+
+                    {
+                        let mut s = status.write().await;
+                        if *s == AIEngineStatus::Stopped {
+                            return;
+                        }
+                        *s = AIEngineStatus::Starting;
+                        broadcaster.send(s.clone()).ok();
+                    }
+                    *last_health.write().await = None;
+
+                    let mut process = match spawn_ai_core(&config) {
+                        Ok(process) => process,
+                        Err(e) => {
+                            reason = format!("Failed to respawn AI Core process: {}", e);
+                            continue;
+                        }
+                    };
+
                     let mut s = status.write().await;
-                    *s = AIEngineStatus::ProcessCrashed;
+                    // stop() may have been called while the process was spawning
+                    if *s != AIEngineStatus::Starting {
+                        drop(s);
+                        process.kill().await.ok();
+                        return;
+                    }
+                    if let Some(stderr) = process.stderr.take() {
+                        spawn_stderr_reader(stderr, Arc::clone(&stderr_buffer));
+                    }
+                    let channel = IPCChannel::new(&mut process, config.max_ipc_frame_bytes);
+                    *ipc_channel.write().await = Some(Arc::new(channel));
+                    *process_handle.write().await = Some(process);
+                    *s = AIEngineStatus::Ready;
+                    println!("AI Core process restarted (attempt {}).", restart_attempt);
                     broadcaster.send(s.clone()).ok();
                     break;
                 }
@@ -122,6 +320,11 @@ impl AIEngineManager {
         });
     }
 
+    /// Get the captured AI Core stderr output, oldest line first
+    pub async fn get_stderr_logs(&self) -> Vec<String> {
+        self.stderr_buffer.read().await.iter().cloned().collect()
+    }
+
     pub fn get_status_receiver(&self) -> broadcast::Receiver<AIEngineStatus> {
         self.status_broadcaster.subscribe()
     }
@@ -130,11 +333,22 @@ impl AIEngineManager {
         self.status.read().await.clone()
     }
 
+    /// Result of the last health.ping, or `None` if none has completed since the engine started
+    pub async fn get_health_stats(&self) -> Option<HealthStats> {
+        self.last_health.read().await.clone()
+    }
+
+    /// Task types the running AI Core advertised in its last pong; `None` until it has said
+    pub async fn capabilities(&self) -> Option<Vec<AITaskType>> {
+        self.last_health.read().await.as_ref().and_then(|health| health.capabilities.clone())
+    }
+
     /// Generate SQL from natural language prompt with progress tracking and cancellation support
     pub async fn generate_sql_from_prompt(
         &self,
         request: SQLGenerationRequest,
         progress_callback: Option<mpsc::Sender<SQLGenerationProgress>>,
+        completion_callback: Option<mpsc::Sender<SQLGenerationComplete>>,
     ) -> Result<SQLGenerationResponse, String> {
         // Check if AI engine is ready
         let status = self.get_status().await;
@@ -142,51 +356,203 @@ impl AIEngineManager {
             return Err(format!("AI Engine is not ready. Current status: {:?}", status));
         }
 
-        let generation_id = Uuid::new_v4().to_string();
-        let (cancel_tx, mut cancel_rx) = mpsc::channel::<()>(1);
+        let pending = PendingGeneration {
+            generation_id: Uuid::new_v4().to_string(),
+            request,
+            accepted_at: Utc::now(),
+        };
+        if let Some(store) = &self.pending_store {
+            if let Err(e) = store.lock().await.insert(pending.clone()) {
+                log::warn!("Generation {} will not survive a restart: {}", pending.generation_id, e);
+            }
+        }
+
+        self.run_pending_generation(pending, progress_callback, completion_callback).await
+    }
+
+    /// Generations persisted by an earlier session that never completed or were cancelled.
+    /// Call once the engine is Ready, before accepting new work, and replay each with
+    /// `resume_generation`.
+    pub async fn pending_generations(&self) -> Vec<PendingGeneration> {
+        match &self.pending_store {
+            Some(store) => store.lock().await.load(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Run a generation restored from the persisted queue under its original id
+    pub async fn resume_generation(
+        &self,
+        pending: PendingGeneration,
+        progress_callback: Option<mpsc::Sender<SQLGenerationProgress>>,
+        completion_callback: Option<mpsc::Sender<SQLGenerationComplete>>,
+    ) -> Result<SQLGenerationResponse, String> {
+        let status = self.get_status().await;
+        if status != AIEngineStatus::Ready {
+            return Err(format!("AI Engine is not ready. Current status: {:?}", status));
+        }
+
+        self.run_pending_generation(pending, progress_callback, completion_callback).await
+    }
 
-        // Register this generation for potential cancellation
+    /// Run a generation and drop it from the persisted queue unless the engine went away mid-run
+    async fn run_pending_generation(
+        &self,
+        pending: PendingGeneration,
+        progress_callback: Option<mpsc::Sender<SQLGenerationProgress>>,
+        completion_callback: Option<mpsc::Sender<SQLGenerationComplete>>,
+    ) -> Result<SQLGenerationResponse, String> {
+        let generation_id = pending.generation_id.clone();
+
+        // Registered before waiting for a slot, so a queued generation can be cancelled too
+        let (cancel_tx, mut cancel_rx) = mpsc::channel::<String>(1);
         {
             let mut active = self.active_generations.write().await;
-            active.insert(generation_id.clone(), cancel_tx);
+            let info = ActiveGeneration {
+                generation_id: generation_id.clone(),
+                prompt: pending.request.prompt.clone(),
+                started_at: Utc::now(),
+            };
+            active.insert(generation_id.clone(), (info, cancel_tx));
+        }
+
+        let slot = tokio::select! {
+            slot = self.acquire_generation_slot(progress_callback.as_ref()) => slot,
+            // A dropped sender means the engine went away without saying why
+            reason = cancel_rx.recv() => Err(reason.unwrap_or_else(|| ENGINE_SHUT_DOWN_ERROR.to_string())),
+        };
+        let outcome = match slot {
+            Ok(_slot) => {
+                self.run_generation(pending.generation_id, pending.request, cancel_rx, progress_callback, completion_callback).await
+            }
+            Err(e) => {
+                self.active_generations.write().await.remove(&generation_id);
+                if let Some(completion_tx) = completion_callback {
+                    let _ = completion_tx.send(SQLGenerationComplete {
+                        generation_id: generation_id.clone(),
+                        success: false,
+                        error_message: Some(e.clone()),
+                        generation_time_ms: None,
+                        timestamp: Utc::now(),
+                    }).await;
+                }
+                Err(e)
+            }
+        };
+
+        let interrupted = matches!(&outcome, Err(e) if e == ENGINE_SHUT_DOWN_ERROR);
+        if let (Some(store), false) = (&self.pending_store, interrupted) {
+            if let Err(e) = store.lock().await.remove(&generation_id) {
+                log::warn!("Failed to remove finished generation {} from the queue: {}", generation_id, e);
+            }
+        }
+
+        outcome
+    }
+
+    /// Wait for one of the `max_concurrent_generations` slots, queueing behind at most
+    /// `max_queued_generations` others. The slot is released when the permit is dropped.
+    async fn acquire_generation_slot(
+        &self,
+        progress_callback: Option<&mpsc::Sender<SQLGenerationProgress>>,
+    ) -> Result<OwnedSemaphorePermit, String> {
+        if let Ok(permit) = Arc::clone(&self.generation_slots).try_acquire_owned() {
+            return Ok(permit);
         }
 
+        let ahead = self.queued_generations.fetch_add(1, Ordering::SeqCst);
+        // Leaves the queue on every exit, including this future being dropped by a cancellation
+        let _queued = QueuedGeneration(Arc::clone(&self.queued_generations));
+        if ahead >= self.config.max_queued_generations {
+            return Err(format!(
+                "Too many SQL generations in progress ({} running, {} waiting); try again when one finishes",
+                self.config.max_concurrent_generations, ahead
+            ));
+        }
+
+        if let Some(progress_tx) = progress_callback {
+            let _ = progress_tx.send(SQLGenerationProgress {
+                stage: ProgressStage::Queued,
+                progress_percent: Some(0),
+                message: format!("Waiting for a free generation slot ({} ahead)", ahead),
+                timestamp: Utc::now(),
+            }).await;
+        }
+
+        let permit = Arc::clone(&self.generation_slots)
+            .acquire_owned()
+            .await
+            .map_err(|_| ENGINE_SHUT_DOWN_ERROR.to_string())?;
+
+        // The engine may have stopped while this one waited; keep it persisted for the next session
+        if self.get_status().await != AIEngineStatus::Ready {
+            return Err(ENGINE_SHUT_DOWN_ERROR.to_string());
+        }
+        Ok(permit)
+    }
+
+    /// Current status along with how many generations are running and waiting
+    pub async fn get_status_report(&self) -> AIEngineStatusReport {
+        // Queued generations are registered too, so they can be cancelled while waiting
+        let registered = self.active_generations.read().await.len();
+        let queued = self.queued_generations.load(Ordering::SeqCst);
+        AIEngineStatusReport {
+            status: self.get_status().await,
+            active_generations: registered.saturating_sub(queued),
+            queued_generations: queued,
+            max_concurrent_generations: self.config.max_concurrent_generations,
+            max_queued_generations: self.config.max_queued_generations,
+        }
+    }
+
+    /// The generation itself; `run_pending_generation` has registered it for cancellation
+    async fn run_generation(
+        &self,
+        generation_id: String,
+        request: SQLGenerationRequest,
+        mut cancel_rx: mpsc::Receiver<String>,
+        progress_callback: Option<mpsc::Sender<SQLGenerationProgress>>,
+        completion_callback: Option<mpsc::Sender<SQLGenerationComplete>>,
+    ) -> Result<SQLGenerationResponse, String> {
         let start_time = std::time::Instant::now();
 
         // Send initial progress
         if let Some(ref progress_tx) = progress_callback {
             let _ = progress_tx.send(SQLGenerationProgress {
-                stage: "analyzing".to_string(),
+                stage: ProgressStage::Analyzing,
                 progress_percent: Some(10),
                 message: "Analyzing request...".to_string(),
                 timestamp: Utc::now(),
             }).await;
         }
 
-        // Prepare the JSON-RPC request for the AI Core
-        let ai_request = serde_json::json!({
-            "jsonrpc": "2.0",
-            "method": "generate_sql",
-            "params": {
-                "prompt": request.prompt,
-                "connection_id": request.connection_id,
-                "schema_context": request.schema_context,
-                "options": request.generation_options.unwrap_or_default()
-            },
-            "id": generation_id
-        });
+        // Prepare the JSON-RPC request for the AI Core; its id is the generation id
+        let mut params = HashMap::new();
+        params.insert("prompt".to_string(), serde_json::json!(request.prompt));
+        params.insert("connection_id".to_string(), serde_json::json!(request.connection_id));
+        params.insert("schema_context".to_string(), serde_json::json!(request.schema_context));
+        params.insert(
+            "options".to_string(),
+            serde_json::json!(request.generation_options.clone().unwrap_or_default()),
+        );
+        let ai_request = JsonRpcMessage {
+            jsonrpc: "2.0".to_string(),
+            method: "generate_sql".to_string(),
+            params,
+            id: Some(RequestId::from(generation_id.clone())),
+        };
 
         // Send schema consultation progress
         if let Some(ref progress_tx) = progress_callback {
             let _ = progress_tx.send(SQLGenerationProgress {
-                stage: "consulting_schema".to_string(),
+                stage: ProgressStage::ConsultingSchema,
                 progress_percent: Some(30),
                 message: "Consulting database schema...".to_string(),
                 timestamp: Utc::now(),
             }).await;
         }
 
-        // Simulate AI processing with timeout and cancellation support
+        // The AI Core gets the generation's own timeout; cancellation stops waiting for it
         let timeout_duration = Duration::from_secs(
             request.generation_options
                 .as_ref()
@@ -195,54 +561,38 @@ impl AIEngineManager {
         );
 
         let generation_result = timeout(timeout_duration, async {
-            // Send generation progress
+            let channel = self.ipc_channel.read().await.clone()
+                .ok_or_else(|| ENGINE_SHUT_DOWN_ERROR.to_string())?;
+
             if let Some(ref progress_tx) = progress_callback {
                 let _ = progress_tx.send(SQLGenerationProgress {
-                    stage: "generating".to_string(),
+                    stage: ProgressStage::Generating,
                     progress_percent: Some(60),
                     message: "Generating SQL...".to_string(),
                     timestamp: Utc::now(),
                 }).await;
             }
 
-            // Check for cancellation
-            tokio::select! {
-                _ = cancel_rx.recv() => {
-                    return Err("Generation cancelled by user".to_string());
+            let rpc_response = tokio::select! {
+                reason = cancel_rx.recv() => {
+                    // A dropped sender means the engine went away without saying why
+                    return Err(reason.unwrap_or_else(|| ENGINE_SHUT_DOWN_ERROR.to_string()));
                 }
-                _ = tokio::time::sleep(Duration::from_millis(500)) => {
-                    // Continue with generation
-                }
-            }
+                response = channel.call(&ai_request) => response?,
+            };
 
-            // Send validation progress
             if let Some(ref progress_tx) = progress_callback {
                 let _ = progress_tx.send(SQLGenerationProgress {
-                    stage: "validating".to_string(),
+                    stage: ProgressStage::Validating,
                     progress_percent: Some(90),
                     message: "Validating query...".to_string(),
                     timestamp: Utc::now(),
                 }).await;
             }
 
-            // For now, return a mock response - this will be replaced with actual AI Core communication
-            Ok(SQLGenerationResponse {
-                success: true,
-                generated_sql: Some(format!(
-                    "-- Generated SQL for: {}\nSELECT * FROM users WHERE created_at >= NOW() - INTERVAL '30 days';",
-                    request.prompt
-                )),
-                explanation: Some(format!(
-                    "This query retrieves all users who were created in the last 30 days based on your request: '{}'",
-                    request.prompt
-                )),
-                confidence_level: Some("High".to_string()),
-                confidence_score: Some(0.85),
-                warnings: None,
-                clarifying_questions: None,
-                error_message: None,
-                generation_time_ms: Some(start_time.elapsed().as_millis() as u64),
-            })
+            let mut response = generation_response(rpc_response);
+            response.generation_time_ms = Some(start_time.elapsed().as_millis() as u64);
+            Ok(response)
         }).await;
 
         // Clean up the active generation
@@ -251,21 +601,282 @@ impl AIEngineManager {
             active.remove(&generation_id);
         }
 
-        match generation_result {
+        let outcome = match generation_result {
             Ok(result) => result,
             Err(_) => Err("SQL generation timed out".to_string()),
+        };
+        let outcome = match outcome {
+            Ok(response) => Ok(self.hold_for_clarification(&request, response).await),
+            Err(e) => Err(e),
+        };
+
+        // Send the terminal completion signal
+        if let Some(completion_tx) = completion_callback {
+            let _ = completion_tx.send(SQLGenerationComplete {
+                generation_id,
+                success: outcome.as_ref().map(|r| r.success).unwrap_or(false),
+                error_message: match &outcome {
+                    Ok(response) => response.error_message.clone(),
+                    Err(e) => Some(e.clone()),
+                },
+                generation_time_ms: Some(start_time.elapsed().as_millis() as u64),
+                timestamp: Utc::now(),
+            }).await;
         }
+
+        outcome
+    }
+
+    /// Remember a generation the AI Core answered with questions and tag the response with the
+    /// id `continue_sql_generation` takes. Responses without questions pass through untouched.
+    async fn hold_for_clarification(
+        &self,
+        request: &SQLGenerationRequest,
+        mut response: SQLGenerationResponse,
+    ) -> SQLGenerationResponse {
+        let questions = match &response.clarifying_questions {
+            Some(questions) if !questions.is_empty() => questions.clone(),
+            _ => return response,
+        };
+
+        let clarification_id = Uuid::new_v4().to_string();
+        let mut pending = self.pending_clarifications.write().await;
+        prune_expired_clarifications(&mut pending);
+        pending.insert(clarification_id.clone(), PendingClarification {
+            request: request.clone(),
+            questions,
+            asked_at: std::time::Instant::now(),
+        });
+        response.pending_clarification_id = Some(clarification_id);
+        response
+    }
+
+    /// Resume a generation that asked clarifying questions. `answers` follow the order of the
+    /// questions and are appended to the original prompt, so the AI Core sees the whole exchange.
+    pub async fn continue_sql_generation(
+        &self,
+        clarification_id: &str,
+        answers: Vec<String>,
+        progress_callback: Option<mpsc::Sender<SQLGenerationProgress>>,
+        completion_callback: Option<mpsc::Sender<SQLGenerationComplete>>,
+    ) -> Result<SQLGenerationResponse, String> {
+        let pending = {
+            let mut pending = self.pending_clarifications.write().await;
+            prune_expired_clarifications(&mut pending);
+            let clarification = pending
+                .remove(clarification_id)
+                .ok_or_else(|| format!("Clarification {} not found or expired", clarification_id))?;
+            if answers.len() != clarification.questions.len() {
+                let expected = clarification.questions.len();
+                // Leave it answerable so the caller can retry with the right number of answers
+                pending.insert(clarification_id.to_string(), clarification);
+                return Err(format!("Expected {} answers, got {}", expected, answers.len()));
+            }
+            clarification
+        };
+
+        let mut request = pending.request;
+        request.prompt.push_str("\n\nClarifications:");
+        for (question, answer) in pending.questions.iter().zip(&answers) {
+            request.prompt.push_str(&format!("\nQ: {}\nA: {}", question, answer.trim()));
+        }
+
+        self.generate_sql_from_prompt(request, progress_callback, completion_callback).await
     }
 
     /// Cancel an active SQL generation
     pub async fn cancel_sql_generation(&self, generation_id: String) -> Result<(), String> {
         let active = self.active_generations.read().await;
-        if let Some(cancel_tx) = active.get(&generation_id) {
-            let _ = cancel_tx.send(()).await;
+        if let Some((_, cancel_tx)) = active.get(&generation_id) {
+            let _ = cancel_tx.try_send("Generation cancelled by user".to_string());
             Ok(())
         } else {
             Err("Generation not found or already completed".to_string())
         }
     }
+
+    /// Generations currently in progress
+    pub async fn active_generations(&self) -> Vec<ActiveGeneration> {
+        let active = self.active_generations.read().await;
+        active.values().map(|(info, _)| info.clone()).collect()
+    }
+
+    /// Fail every in-flight generation with `reason`; returns how many were signalled
+    pub async fn cancel_all_generations(&self, reason: &str) -> usize {
+        let mut active = self.active_generations.write().await;
+        signal_generations(&mut active, reason)
+    }
+}
+
+impl Drop for AIEngineManager {
+    /// Teardown for a manager dropped without `stop()`: in-flight generations get
+    /// `ENGINE_SHUT_DOWN_ERROR` and the AI Core is reaped instead of orphaned.
+    fn drop(&mut self) {
+        if let Ok(mut active) = self.active_generations.try_write() {
+            signal_generations(&mut active, ENGINE_SHUT_DOWN_ERROR);
+        }
+
+        if let Ok(mut status) = self.status.try_write() {
+            // Also ends the health check loop, which would otherwise report a crash
+            *status = AIEngineStatus::Stopped;
+        }
+
+        let channel = self.ipc_channel.try_write().ok().and_then(|mut channel| channel.take());
+        let child = self.process_handle.try_write().ok().and_then(|mut handle| handle.take());
+        if let Some(mut child) = child {
+            match tokio::runtime::Handle::try_current() {
+                Ok(runtime) => {
+                    runtime.spawn(shutdown_child(child, channel));
+                }
+                // Outside a runtime the exit can't be awaited; at least don't leave it running
+                Err(_) => {
+                    child.start_kill().ok();
+                }
+            }
+        }
+    }
+}
+
+/// Forget clarifications nobody answered within `CLARIFICATION_TTL`
+/// What the AI Core's `generate_sql` method returns
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GeneratedSqlResult {
+    generated_sql: Option<String>,
+    explanation: Option<String>,
+    confidence_level: Option<String>,
+    confidence_score: Option<f32>,
+    warnings: Option<Vec<String>>,
+    clarifying_questions: Option<Vec<String>>,
+}
+
+/// Map the AI Core's reply to `generate_sql` onto the response the frontend gets.
+/// A JSON-RPC error is a failed generation rather than a failure to reach the AI Core.
+fn generation_response(rpc_response: JsonRpcResponse) -> SQLGenerationResponse {
+    let failed = |message: String| SQLGenerationResponse {
+        success: false,
+        generated_sql: None,
+        explanation: None,
+        confidence_level: None,
+        confidence_score: None,
+        warnings: None,
+        clarifying_questions: None,
+        pending_clarification_id: None,
+        error_message: Some(message),
+        generation_time_ms: None,
+    };
+
+    if let Some(error) = rpc_response.error {
+        return failed(format!("AI Core could not generate SQL: {}", error.message));
+    }
+    let result = match serde_json::from_value::<GeneratedSqlResult>(rpc_response.result.unwrap_or_default()) {
+        Ok(result) => result,
+        Err(e) => return failed(format!("Unexpected generate_sql result from AI Core: {}", e)),
+    };
+
+    SQLGenerationResponse {
+        success: true,
+        generated_sql: result.generated_sql,
+        explanation: result.explanation,
+        confidence_level: result.confidence_level,
+        confidence_score: result.confidence_score,
+        warnings: result.warnings,
+        clarifying_questions: result.clarifying_questions,
+        pending_clarification_id: None,
+        error_message: None,
+        generation_time_ms: None,
+    }
+}
+
+fn prune_expired_clarifications(pending: &mut HashMap<String, PendingClarification>) {
+    pending.retain(|_, clarification| clarification.asked_at.elapsed() < CLARIFICATION_TTL);
+}
+
+/// Send `reason` to each registered generation and forget them.
+/// Generations that miss the message still fail cleanly once their sender is dropped.
+fn signal_generations(active: &mut HashMap<String, (ActiveGeneration, mpsc::Sender<String>)>, reason: &str) -> usize {
+    let count = active.len();
+    for (_, (_, cancel_tx)) in active.drain() {
+        let _ = cancel_tx.try_send(reason.to_string());
+    }
+    count
+}
+
+/// Launch the AI Core with its stdio piped back to us
+fn spawn_ai_core(config: &AIEngineConfig) -> std::io::Result<Child> {
+    Command::new(&config.python_executable)
+        .arg(&config.ai_core_script)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+}
+
+/// Backoff before restart `attempt` (counted from 0): doubles from `restart_delay_base`, capped at `max_restart_delay`
+fn restart_delay(config: &AIEngineConfig, attempt: u32) -> Duration {
+    let delay = config.restart_delay_base.saturating_mul(1u64 << attempt.min(16));
+    Duration::from_millis(delay.min(config.max_restart_delay))
+}
+
+/// Continuously drain the child's stderr into the log and the ring buffer
+fn spawn_stderr_reader(stderr: ChildStderr, buffer: Arc<RwLock<VecDeque<String>>>) {
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(stderr).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            log::warn!("AI Core stderr: {}", line);
+            let mut buffer = buffer.write().await;
+            if buffer.len() >= STDERR_BUFFER_LINES {
+                buffer.pop_front();
+            }
+            buffer.push_back(line);
+        }
+    });
+}
+
+/// Ping the AI Core, counting a pong that doesn't arrive within `timeout_ms` as a failure
+pub async fn check_health_within(channel: &IPCChannel, timeout_ms: u64) -> HealthStats {
+    match timeout(Duration::from_millis(timeout_ms), HealthMonitor::new(channel).check_health()).await {
+        Ok(stats) => stats,
+        Err(_) => HealthStats {
+            is_healthy: false,
+            last_result: format!("No pong from AI Core within {} ms", timeout_ms),
+            capabilities: None,
+        },
+    }
 }
 
+/// Close the AI Core's stdin and wait for it to exit, killing it after `PROCESS_EXIT_GRACE`
+async fn shutdown_child(mut child: Child, channel: Option<Arc<IPCChannel>>) {
+    // EOF on stdin is the AI Core's cue to finish its current write and exit.
+    // Once the IPC channel has taken stdin over, closing the channel is what delivers it.
+    match channel {
+        Some(channel) => channel.close().await,
+        None => drop(child.stdin.take()),
+    }
+
+    match timeout(PROCESS_EXIT_GRACE, child.wait()).await {
+        Ok(_) => println!("AI Core process stopped."),
+        Err(_) => {
+            if let Err(e) = child.kill().await {
+                println!("Failed to kill AI Core process: {}", e);
+            } else {
+                println!("AI Core process killed after not exiting in time.");
+            }
+        }
+    }
+}
+
+/// Append the last few stderr lines to an error message
+async fn with_stderr_tail(message: String, buffer: &RwLock<VecDeque<String>>) -> String {
+    let buffer = buffer.read().await;
+    if buffer.is_empty() {
+        return message;
+    }
+
+    let tail: Vec<&str> = buffer
+        .iter()
+        .skip(buffer.len().saturating_sub(STDERR_TAIL_LINES))
+        .map(String::as_str)
+        .collect();
+    format!("{}\n--- AI Core stderr ---\n{}", message, tail.join("\n"))
+}
@@ -3,6 +3,8 @@ pub mod communication;
 pub mod config;
 pub mod health;
 pub mod manager;
+pub mod query_analysis;
+pub mod queue;
 pub mod task_manager;
 pub mod types;
 
@@ -11,7 +13,9 @@ pub mod types;
 mod tests;
 
 pub use manager::AIEngineManager;
+pub use queue::PendingGeneration;
 pub use types::{
     AIEngineStatus, AIEngineConfig, SQLGenerationRequest, SQLGenerationOptions,
-    SQLGenerationResponse, SQLGenerationProgress, CancellationRequest
+    SQLGenerationResponse, SQLGenerationProgress, SQLGenerationComplete, CancellationRequest, ProgressStage,
+    ActiveGeneration, HealthStats
 };
@@ -0,0 +1,457 @@
+// Offline structure of a SQL statement for the Explain and Optimize handlers: which tables
+// it reads and which columns it selects, joins, filters, groups and sorts on
+use crate::database::policy::StatementKind;
+use sqlparser::ast::{
+    Expr, FunctionArg, FunctionArgExpr, GroupByExpr, Ident, JoinConstraint, JoinOperator, Query, Select,
+    SelectItem, SetExpr, Statement, TableFactor, TableWithJoins,
+};
+use sqlparser::dialect::GenericDialect;
+use sqlparser::parser::Parser;
+use std::collections::HashSet;
+
+/// A table the statement reads, with the alias it goes by
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableRef {
+    pub name: String,
+    pub alias: Option<String>,
+}
+
+/// A column reference, attributed to a table when the query or the schema says which
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ColumnRef {
+    pub table: Option<String>,
+    pub name: String,
+}
+
+/// What a statement touches, clause by clause
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryShape {
+    /// Leading keyword, e.g. "SELECT" or "UPDATE"
+    pub statement: String,
+    /// Table written by an INSERT or UPDATE
+    pub target: Option<String>,
+    pub tables: Vec<TableRef>,
+    pub selected_columns: Vec<ColumnRef>,
+    /// `SELECT *` or `t.*`
+    pub selects_all: bool,
+    pub join_columns: Vec<ColumnRef>,
+    pub filter_columns: Vec<ColumnRef>,
+    pub group_by_columns: Vec<ColumnRef>,
+    pub order_by_columns: Vec<ColumnRef>,
+    /// Columns set by an UPDATE or listed by an INSERT
+    pub assigned_columns: Vec<ColumnRef>,
+    pub has_limit: bool,
+}
+
+impl QueryShape {
+    pub fn table_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for name in self.target.iter().chain(self.tables.iter().map(|table| &table.name)) {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        names
+    }
+
+    /// Column names for display, qualified with their table only when several tables are involved
+    pub fn column_names(&self, columns: &[ColumnRef]) -> Vec<String> {
+        let qualify = self.tables.len() > 1;
+        columns
+            .iter()
+            .map(|column| match (&column.table, qualify) {
+                (Some(table), true) => format!("{}.{}", table, column.name),
+                _ => column.name.clone(),
+            })
+            .collect()
+    }
+}
+
+/// A table as described by a schema context, reduced to what the analysis needs
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaTable {
+    pub name: String,
+    pub columns: Vec<String>,
+    /// First column of each index, primary key included
+    pub leading_index_columns: Vec<String>,
+}
+
+/// Array `key` of a node in `get_database_schema` output, empty when absent
+pub fn schema_children<'a>(value: &'a serde_json::Value, key: &str) -> &'a [serde_json::Value] {
+    value.get(key).and_then(|v| v.as_array()).map(|v| v.as_slice()).unwrap_or(&[])
+}
+
+/// Whether a node in `get_database_schema` output is called `name`
+pub fn schema_node_named(value: &serde_json::Value, name: &str) -> bool {
+    value.get("name").and_then(|v| v.as_str()) == Some(name)
+}
+
+/// Tables in a schema context shaped like `get_database_schema` output
+/// (`databases[].schemas[].tables[]`)
+pub fn schema_tables(schema_context: &serde_json::Value) -> Vec<SchemaTable> {
+    fn name(value: &serde_json::Value) -> Option<String> {
+        value.get("name").and_then(|v| v.as_str()).map(str::to_string)
+    }
+
+    schema_children(schema_context, "databases")
+        .iter()
+        .flat_map(|database| schema_children(database, "schemas"))
+        .flat_map(|schema| schema_children(schema, "tables").iter().chain(schema_children(schema, "views")))
+        .filter_map(|table| {
+            let columns = schema_children(table, "columns");
+            let mut leading_index_columns: Vec<String> = schema_children(table, "indexes")
+                .iter()
+                .filter_map(|index| {
+                    schema_children(index, "columns").first().and_then(|c| c.as_str()).map(str::to_string)
+                })
+                .collect();
+            leading_index_columns.extend(
+                columns
+                    .iter()
+                    .filter(|c| c.get("is_primary_key").and_then(|v| v.as_bool()).unwrap_or(false))
+                    .filter_map(name)
+                    .take(1),
+            );
+
+            Some(SchemaTable {
+                name: name(table)?,
+                columns: columns.iter().filter_map(name).collect(),
+                leading_index_columns,
+            })
+        })
+        .collect()
+}
+
+/// Find a referenced table (possibly schema-qualified) among the schema's tables
+pub fn find_table<'a>(tables: &'a [SchemaTable], reference: &str) -> Option<&'a SchemaTable> {
+    let unqualified = reference.rsplit('.').next().unwrap_or(reference);
+    tables.iter().find(|table| table.name.eq_ignore_ascii_case(unqualified))
+}
+
+/// Schema (if qualified) and name of the only table a plain `SELECT ... FROM table` reads.
+///
+/// Anything this can't vouch for - joins, comma joins, derived tables, CTEs, set operations -
+/// yields `None`.
+pub fn single_source_table(sql: &str) -> Option<(Option<String>, String)> {
+    let statements = Parser::parse_sql(&GenericDialect {}, sql).ok()?;
+    let [Statement::Query(query)] = statements.as_slice() else { return None };
+    if query.with.is_some() {
+        return None;
+    }
+    let SetExpr::Select(select) = query.body.as_ref() else { return None };
+    let [from] = select.from.as_slice() else { return None };
+    if !from.joins.is_empty() {
+        return None;
+    }
+    let TableFactor::Table { name, args: None, .. } = &from.relation else { return None };
+    match name.0.as_slice() {
+        [table] => Some((None, table.value.clone())),
+        [schema, table] => Some((Some(schema.value.clone()), table.value.clone())),
+        _ => None,
+    }
+}
+
+/// Which clause a column reference came from
+#[derive(Clone, Copy)]
+enum Clause {
+    Select,
+    Join,
+    Filter,
+    GroupBy,
+    OrderBy,
+}
+
+/// Column references as written, before aliases are resolved
+#[derive(Default)]
+struct Collector {
+    tables: Vec<TableRef>,
+    /// CTE and derived-table names, which are not tables of their own
+    virtual_names: HashSet<String>,
+    columns: Vec<(Clause, Option<String>, String)>,
+    assigned: Vec<String>,
+    selects_all: bool,
+    has_limit: bool,
+}
+
+/// Parse the first statement of `sql` and describe what it touches. With a schema context,
+/// unqualified columns are attributed to whichever of the statement's tables has them.
+pub fn analyze_query(sql: &str, schema_context: Option<&serde_json::Value>) -> Result<QueryShape, String> {
+    let statements = Parser::parse_sql(&GenericDialect {}, sql).map_err(|e| format!("Could not parse query: {}", e))?;
+    let statement = statements.first().ok_or_else(|| "Query is empty".to_string())?;
+
+    let mut collector = Collector::default();
+    let mut target = None;
+    match statement {
+        Statement::Query(query) => collector.query(query),
+        Statement::Update { table, assignments, from, selection, .. } => {
+            collector.table_with_joins(table);
+            target = collector.tables.first().map(|table| table.name.clone());
+            if let Some(from) = from {
+                collector.table_with_joins(from);
+            }
+            for assignment in assignments {
+                if let Some(column) = assignment.id.last() {
+                    collector.assigned.push(column.value.clone());
+                }
+                collector.expr(&assignment.value, Clause::Filter);
+            }
+            if let Some(selection) = selection {
+                collector.expr(selection, Clause::Filter);
+            }
+        }
+        Statement::Insert { table_name, columns, source, .. } => {
+            target = Some(table_name.to_string());
+            collector.assigned.extend(columns.iter().map(|column| column.value.clone()));
+            if let Some(source) = source {
+                collector.query(source);
+            }
+        }
+        Statement::Delete { selection: Some(selection), .. } => collector.expr(selection, Clause::Filter),
+        _ => {}
+    }
+
+    let schema = schema_context.map(schema_tables).unwrap_or_default();
+    Ok(collector.into_shape(StatementKind::from_statement(statement).to_string(), target, &schema))
+}
+
+impl Collector {
+    fn query(&mut self, query: &Query) {
+        if let Some(with) = &query.with {
+            for cte in &with.cte_tables {
+                self.virtual_names.insert(cte.alias.name.value.to_lowercase());
+                self.query(&cte.query);
+            }
+        }
+        self.set_expr(&query.body);
+        for order in &query.order_by {
+            self.expr(&order.expr, Clause::OrderBy);
+        }
+        if query.limit.is_some() || query.fetch.is_some() {
+            self.has_limit = true;
+        }
+    }
+
+    fn set_expr(&mut self, body: &SetExpr) {
+        match body {
+            SetExpr::Select(select) => self.select(select),
+            SetExpr::Query(query) => self.query(query),
+            SetExpr::SetOperation { left, right, .. } => {
+                self.set_expr(left);
+                self.set_expr(right);
+            }
+            _ => {}
+        }
+    }
+
+    fn select(&mut self, select: &Select) {
+        if select.top.is_some() {
+            self.has_limit = true;
+        }
+        for from in &select.from {
+            self.table_with_joins(from);
+        }
+        for item in &select.projection {
+            match item {
+                SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => self.expr(expr, Clause::Select),
+                SelectItem::Wildcard(_) | SelectItem::QualifiedWildcard(..) => self.selects_all = true,
+            }
+        }
+        if let Some(selection) = &select.selection {
+            self.expr(selection, Clause::Filter);
+        }
+        if let GroupByExpr::Expressions(exprs) = &select.group_by {
+            for expr in exprs {
+                self.expr(expr, Clause::GroupBy);
+            }
+        }
+        if let Some(having) = &select.having {
+            self.expr(having, Clause::Filter);
+        }
+    }
+
+    fn table_with_joins(&mut self, from: &TableWithJoins) {
+        self.table_factor(&from.relation);
+        for join in &from.joins {
+            self.table_factor(&join.relation);
+            let constraint = match &join.join_operator {
+                JoinOperator::Inner(constraint)
+                | JoinOperator::LeftOuter(constraint)
+                | JoinOperator::RightOuter(constraint)
+                | JoinOperator::FullOuter(constraint) => Some(constraint),
+                _ => None,
+            };
+            match constraint {
+                Some(JoinConstraint::On(expr)) => self.expr(expr, Clause::Join),
+                Some(JoinConstraint::Using(columns)) => {
+                    for column in columns {
+                        self.columns.push((Clause::Join, None, column.value.clone()));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn table_factor(&mut self, factor: &TableFactor) {
+        match factor {
+            TableFactor::Table { name, alias, .. } => {
+                let name = name.to_string();
+                if self.virtual_names.contains(&name.to_lowercase()) {
+                    return;
+                }
+                self.tables.push(TableRef { name, alias: alias.as_ref().map(|alias| alias.name.value.clone()) });
+            }
+            TableFactor::Derived { subquery, alias, .. } => {
+                if let Some(alias) = alias {
+                    self.virtual_names.insert(alias.name.value.to_lowercase());
+                }
+                self.query(subquery);
+            }
+            TableFactor::NestedJoin { table_with_joins, .. } => self.table_with_joins(table_with_joins),
+            _ => {}
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr, clause: Clause) {
+        match expr {
+            Expr::Identifier(ident) => self.columns.push((clause, None, ident.value.clone())),
+            Expr::CompoundIdentifier(idents) => {
+                if let [.., qualifier, column] = idents.as_slice() {
+                    self.columns.push((clause, Some(qualifier.value.clone()), column.value.clone()));
+                }
+            }
+            Expr::BinaryOp { left, right, .. } => {
+                self.expr(left, clause);
+                self.expr(right, clause);
+            }
+            Expr::UnaryOp { expr, .. }
+            | Expr::Nested(expr)
+            | Expr::IsNull(expr)
+            | Expr::IsNotNull(expr)
+            | Expr::Cast { expr, .. } => self.expr(expr, clause),
+            Expr::InList { expr, list, .. } => {
+                self.expr(expr, clause);
+                for item in list {
+                    self.expr(item, clause);
+                }
+            }
+            Expr::InSubquery { expr, subquery, .. } => {
+                self.expr(expr, clause);
+                self.query(subquery);
+            }
+            Expr::Between { expr, low, high, .. } => {
+                self.expr(expr, clause);
+                self.expr(low, clause);
+                self.expr(high, clause);
+            }
+            Expr::Like { expr, pattern, .. } | Expr::ILike { expr, pattern, .. } => {
+                self.expr(expr, clause);
+                self.expr(pattern, clause);
+            }
+            Expr::Subquery(query) | Expr::Exists { subquery: query, .. } => self.query(query),
+            Expr::Function(function) => {
+                for arg in &function.args {
+                    match arg {
+                        FunctionArg::Unnamed(FunctionArgExpr::Expr(expr))
+                        | FunctionArg::Named { arg: FunctionArgExpr::Expr(expr), .. } => self.expr(expr, clause),
+                        _ => {}
+                    }
+                }
+            }
+            Expr::Case { operand, conditions, results, else_result } => {
+                for expr in operand.iter().chain(else_result) {
+                    self.expr(expr, clause);
+                }
+                for expr in conditions.iter().chain(results) {
+                    self.expr(expr, clause);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn into_shape(self, statement: String, target: Option<String>, schema: &[SchemaTable]) -> QueryShape {
+        let tables = self.tables;
+
+        // Which table a qualifier (alias or table name) refers to
+        let table_for_qualifier = |qualifier: &str| {
+            tables.iter().find_map(|table| {
+                let alias_matches = table.alias.as_deref().map_or(false, |alias| alias.eq_ignore_ascii_case(qualifier));
+                let name_matches = table.name.eq_ignore_ascii_case(qualifier)
+                    || table.name.rsplit('.').next().map_or(false, |name| name.eq_ignore_ascii_case(qualifier));
+                (alias_matches || name_matches).then(|| table.name.clone())
+            })
+        };
+        // The one statement table the schema says has this column
+        let table_with_column = |column: &str| {
+            let owners: Vec<&TableRef> = tables
+                .iter()
+                .filter(|table| {
+                    find_table(schema, &table.name)
+                        .map_or(false, |found| found.columns.iter().any(|c| c.eq_ignore_ascii_case(column)))
+                })
+                .collect();
+            match owners.as_slice() {
+                [owner] => Some(owner.name.clone()),
+                _ => None,
+            }
+        };
+
+        let resolve = |qualifier: &Option<String>, name: &str| -> ColumnRef {
+            let table = match qualifier {
+                Some(qualifier) => Some(table_for_qualifier(qualifier).unwrap_or_else(|| qualifier.clone())),
+                None if tables.len() == 1 => Some(tables[0].name.clone()),
+                None => table_with_column(name),
+            };
+            ColumnRef { table, name: name.to_string() }
+        };
+
+        let mut shape = QueryShape {
+            statement,
+            selects_all: self.selects_all,
+            has_limit: self.has_limit,
+            ..Default::default()
+        };
+        for (clause, qualifier, name) in &self.columns {
+            let column = resolve(qualifier, name);
+            let bucket = match clause {
+                Clause::Select => &mut shape.selected_columns,
+                Clause::Join => &mut shape.join_columns,
+                Clause::Filter => &mut shape.filter_columns,
+                Clause::GroupBy => &mut shape.group_by_columns,
+                Clause::OrderBy => &mut shape.order_by_columns,
+            };
+            if !bucket.contains(&column) {
+                bucket.push(column);
+            }
+        }
+        for name in &self.assigned {
+            let column = ColumnRef { table: target.clone(), name: name.clone() };
+            if !shape.assigned_columns.contains(&column) {
+                shape.assigned_columns.push(column);
+            }
+        }
+
+        shape.target = target;
+        shape.tables = tables;
+        shape
+    }
+}
+
+/// Rewrite `SELECT *` over a single table to list `columns`; `None` when the query isn't that shape
+pub fn expand_wildcard(sql: &str, columns: &[String]) -> Option<String> {
+    let mut statements = Parser::parse_sql(&GenericDialect {}, sql).ok()?;
+    let [Statement::Query(query)] = statements.as_mut_slice() else { return None };
+    let SetExpr::Select(select) = query.body.as_mut() else { return None };
+    let single_table = select.from.len() == 1 && select.from[0].joins.is_empty();
+    if !single_table || columns.is_empty() || !matches!(select.projection.as_slice(), [SelectItem::Wildcard(_)]) {
+        return None;
+    }
+
+    select.projection = columns
+        .iter()
+        .map(|column| SelectItem::UnnamedExpr(Expr::Identifier(Ident::new(column.clone()))))
+        .collect();
+    Some(statements[0].to_string())
+}
+
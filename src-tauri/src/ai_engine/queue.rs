@@ -0,0 +1,78 @@
+// In src-tauri/src/ai_engine/queue.rs
+// Disk-backed record of accepted SQL generations, so unfinished ones survive a crash
+use super::types::SQLGenerationRequest;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// A generation that was accepted but has not completed or been cancelled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingGeneration {
+    pub generation_id: String,
+    pub request: SQLGenerationRequest,
+    pub accepted_at: DateTime<Utc>,
+}
+
+/// Pending generations stored as a single JSON file, rewritten on every change
+#[derive(Debug, Clone)]
+pub struct PendingGenerationStore {
+    path: PathBuf,
+}
+
+impl PendingGenerationStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Every persisted generation, oldest first. A missing or unreadable file is an empty queue.
+    pub fn load(&self) -> Vec<PendingGeneration> {
+        let data = match std::fs::read_to_string(&self.path) {
+            Ok(data) => data,
+            Err(_) => return Vec::new(),
+        };
+
+        match serde_json::from_str::<Vec<PendingGeneration>>(&data) {
+            Ok(mut pending) => {
+                pending.sort_by_key(|p| p.accepted_at);
+                pending
+            }
+            Err(e) => {
+                log::warn!("Ignoring unreadable pending generation queue {}: {}", self.path.display(), e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Record an accepted generation, replacing any earlier entry with the same id
+    pub fn insert(&self, pending: PendingGeneration) -> Result<(), String> {
+        let mut queue = self.load();
+        queue.retain(|p| p.generation_id != pending.generation_id);
+        queue.push(pending);
+        self.save(&queue)
+    }
+
+    /// Forget a generation once it has completed or been cancelled
+    pub fn remove(&self, generation_id: &str) -> Result<(), String> {
+        let mut queue = self.load();
+        let before = queue.len();
+        queue.retain(|p| p.generation_id != generation_id);
+        if queue.len() == before {
+            return Ok(());
+        }
+        self.save(&queue)
+    }
+
+    fn save(&self, queue: &[PendingGeneration]) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create queue directory: {}", e))?;
+        }
+
+        let data = serde_json::to_string_pretty(queue)
+            .map_err(|e| format!("Failed to serialize pending generations: {}", e))?;
+
+        // Write then rename, so a crash mid-write never leaves a truncated queue behind
+        let temp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&temp_path, data).map_err(|e| format!("Failed to write pending generations: {}", e))?;
+        std::fs::rename(&temp_path, &self.path).map_err(|e| format!("Failed to replace pending generations: {}", e))
+    }
+}
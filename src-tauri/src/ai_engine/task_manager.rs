@@ -1,15 +1,18 @@
 // AI Task Manager - Story 3.6
 // Unified task manager for handling different AI analysis types without code duplication
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use chrono::Utc;
 use tauri::{AppHandle, Manager};
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
+use crate::ai_engine::query_analysis::{analyze_query, expand_wildcard, find_table, schema_tables, QueryShape, SchemaTable};
 use crate::ai_engine::types::{
     AITaskType, AIAnalysisRequest, AIAnalysisResult, AIAnalysisProgress,
-    AITaskInfo, AITaskStatus, AnalysisResultData, ExplanationStep,
+    AIAnalysisComplete, AITaskInfo, AITaskStatus, AnalysisResultData, ExplanationStep, ProgressStage,
     OptimizationSuggestion, PerformanceEstimate, ValidationIssue, QuickFix
 };
 
@@ -18,37 +21,29 @@ pub trait AITaskHandler: Send + Sync {
     fn handle_task(&self, request: &AIAnalysisRequest) -> Result<AnalysisResultData, String>;
     fn get_task_type(&self) -> AITaskType;
     fn estimate_duration(&self, request: &AIAnalysisRequest) -> u32; // seconds
+
+    /// Handlers that never reach the AI core run whatever capabilities it advertised
+    fn runs_locally(&self) -> bool {
+        false
+    }
 }
 
 // Explain Task Handler
+// Walks the parsed query, so steps name the tables and columns it actually touches
 pub struct ExplainTaskHandler;
 
 impl AITaskHandler for ExplainTaskHandler {
     fn handle_task(&self, request: &AIAnalysisRequest) -> Result<AnalysisResultData, String> {
-        // Mock implementation - will be replaced with actual AI Core integration
-        let steps = vec![
-            ExplanationStep {
-                step_number: 1,
-                operation: "SELECT".to_string(),
-                description: "Retrieve specific columns from the database".to_string(),
-                tables_involved: vec!["users".to_string()],
-                columns_involved: vec!["id".to_string(), "name".to_string(), "email".to_string()],
-            },
-            ExplanationStep {
-                step_number: 2,
-                operation: "WHERE".to_string(),
-                description: "Filter records based on specified conditions".to_string(),
-                tables_involved: vec!["users".to_string()],
-                columns_involved: vec!["created_at".to_string()],
-            },
-        ];
+        let shape = analyze_query(&request.sql, request.schema_context.as_ref())?;
+        let schema = request.schema_context.as_ref().map(schema_tables).unwrap_or_default();
+        let detailed_steps = explanation_steps(&shape, &schema);
 
         Ok(AnalysisResultData::Explanation {
-            summary: format!("This query retrieves user information from the database with specific filtering criteria."),
-            detailed_steps: steps,
-            data_sources: vec!["users".to_string()],
-            operations: vec!["SELECT".to_string(), "WHERE".to_string()],
-            expected_result_description: "A list of user records matching the specified criteria".to_string(),
+            summary: explanation_summary(&shape),
+            operations: detailed_steps.iter().map(|step| step.operation.clone()).collect(),
+            detailed_steps,
+            data_sources: shape.table_names(),
+            expected_result_description: expected_result(&shape),
         })
     }
 
@@ -61,36 +56,183 @@ impl AITaskHandler for ExplainTaskHandler {
     }
 }
 
+/// Columns a `SELECT *` returns, when the schema knows every table involved
+fn wildcard_columns(shape: &QueryShape, schema: &[SchemaTable]) -> Option<Vec<String>> {
+    let qualify = shape.tables.len() > 1;
+    let mut columns = Vec::new();
+    for table in &shape.tables {
+        let found = find_table(schema, &table.name)?;
+        columns.extend(found.columns.iter().map(|column| {
+            if qualify { format!("{}.{}", table.name, column) } else { column.clone() }
+        }));
+    }
+    (!columns.is_empty()).then_some(columns)
+}
+
+fn explanation_steps(shape: &QueryShape, schema: &[SchemaTable]) -> Vec<ExplanationStep> {
+    let mut steps: Vec<ExplanationStep> = Vec::new();
+    let mut push = |operation: &str, description: String, tables: Vec<String>, columns: Vec<String>| {
+        steps.push(ExplanationStep {
+            step_number: steps.len() as u32 + 1,
+            operation: operation.to_string(),
+            description,
+            tables_involved: tables,
+            columns_involved: columns,
+        });
+    };
+    let all_tables = shape.table_names();
+
+    if let Some(first) = shape.tables.first() {
+        push("FROM", format!("Read rows from {}", first.name), vec![first.name.clone()], Vec::new());
+    }
+    if shape.tables.len() > 1 {
+        let joined: Vec<String> = shape.tables[1..].iter().map(|table| table.name.clone()).collect();
+        let on = shape.column_names(&shape.join_columns);
+        let description = if on.is_empty() {
+            format!("Combine with {}", joined.join(", "))
+        } else {
+            format!("Combine with {} by matching {}", joined.join(", "), on.join(", "))
+        };
+        push("JOIN", description, joined, on);
+    }
+    if !shape.filter_columns.is_empty() {
+        let columns = shape.column_names(&shape.filter_columns);
+        push("WHERE", format!("Keep only rows whose {} match the conditions", columns.join(", ")), all_tables.clone(), columns);
+    }
+    if !shape.group_by_columns.is_empty() {
+        let columns = shape.column_names(&shape.group_by_columns);
+        push("GROUP BY", format!("Group rows by {}", columns.join(", ")), all_tables.clone(), columns);
+    }
+
+    match shape.statement.as_str() {
+        "SELECT" => {
+            let (description, columns) = match (shape.selects_all, wildcard_columns(shape, schema)) {
+                (true, Some(columns)) => ("Return every column".to_string(), columns),
+                (true, None) => ("Return every column".to_string(), shape.column_names(&shape.selected_columns)),
+                (false, _) => {
+                    let columns = shape.column_names(&shape.selected_columns);
+                    (format!("Return {}", columns.join(", ")), columns)
+                }
+            };
+            push("SELECT", description, all_tables.clone(), columns);
+        }
+        "INSERT" | "UPDATE" => {
+            let target = shape.target.clone().unwrap_or_default();
+            let columns = shape.column_names(&shape.assigned_columns);
+            let description = match shape.statement.as_str() {
+                "INSERT" => format!("Insert the rows into {}", target),
+                _ => format!("Set {} on the matching rows of {}", columns.join(", "), target),
+            };
+            push(&shape.statement, description, vec![target], columns);
+        }
+        other => push(other, format!("Run the {} statement", other), all_tables.clone(), Vec::new()),
+    }
+
+    if !shape.order_by_columns.is_empty() {
+        let columns = shape.column_names(&shape.order_by_columns);
+        push("ORDER BY", format!("Sort the result by {}", columns.join(", ")), all_tables.clone(), columns);
+    }
+    if shape.has_limit {
+        push("LIMIT", "Stop after the requested number of rows".to_string(), Vec::new(), Vec::new());
+    }
+
+    steps
+}
+
+fn explanation_summary(shape: &QueryShape) -> String {
+    let tables = shape.table_names();
+    let mut summary = match shape.statement.as_str() {
+        "SELECT" => format!("This query reads from {}", tables.join(", ")),
+        "INSERT" => format!("This statement inserts rows into {}", shape.target.as_deref().unwrap_or("a table")),
+        "UPDATE" => format!("This statement updates rows in {}", shape.target.as_deref().unwrap_or("a table")),
+        "DELETE" => "This statement deletes rows".to_string(),
+        other => format!("This is a {} statement", other),
+    };
+    if !shape.filter_columns.is_empty() {
+        summary.push_str(&format!(", filtered on {}", shape.column_names(&shape.filter_columns).join(", ")));
+    }
+    if !shape.group_by_columns.is_empty() {
+        summary.push_str(&format!(", grouped by {}", shape.column_names(&shape.group_by_columns).join(", ")));
+    }
+    if !shape.order_by_columns.is_empty() {
+        summary.push_str(&format!(", sorted by {}", shape.column_names(&shape.order_by_columns).join(", ")));
+    }
+    summary.push('.');
+    summary
+}
+
+fn expected_result(shape: &QueryShape) -> String {
+    if shape.statement != "SELECT" {
+        return "The number of rows affected".to_string();
+    }
+    let columns = if shape.selects_all {
+        "every column".to_string()
+    } else {
+        shape.column_names(&shape.selected_columns).join(", ")
+    };
+    let limit = if shape.has_limit { ", limited to the requested number of rows" } else { "" };
+    format!("Rows with {} from {}{}", columns, shape.table_names().join(", "), limit)
+}
+
 // Optimize Task Handler
+// Suggestions come from the parsed query and, when present, the schema context's columns and indexes
 pub struct OptimizeTaskHandler;
 
 impl AITaskHandler for OptimizeTaskHandler {
     fn handle_task(&self, request: &AIAnalysisRequest) -> Result<AnalysisResultData, String> {
-        // Mock implementation - will be replaced with actual AI Core integration
-        let optimizations = vec![
-            OptimizationSuggestion {
+        let shape = analyze_query(&request.sql, request.schema_context.as_ref())?;
+        let schema = request.schema_context.as_ref().map(schema_tables).unwrap_or_default();
+
+        let mut optimizations = Vec::new();
+        let mut seen = HashSet::new();
+        let candidates = shape.filter_columns.iter().map(|column| (column, "filter", "High"))
+            .chain(shape.join_columns.iter().map(|column| (column, "join", "Medium")));
+        for (column, usage, impact_level) in candidates {
+            let Some(table) = column.table.as_deref().and_then(|table| find_table(&schema, table)) else { continue };
+            let has_column = table.columns.iter().any(|c| c.eq_ignore_ascii_case(&column.name));
+            let indexed = table.leading_index_columns.iter().any(|c| c.eq_ignore_ascii_case(&column.name));
+            if !has_column || indexed || !seen.insert((table.name.clone(), column.name.to_lowercase())) {
+                continue;
+            }
+
+            optimizations.push(OptimizationSuggestion {
                 suggestion_id: Uuid::new_v4().to_string(),
                 category: "index".to_string(),
-                description: "Add index on frequently queried columns".to_string(),
-                impact_level: "High".to_string(),
-                before_snippet: Some("WHERE created_at > '2024-01-01'".to_string()),
-                after_snippet: Some("WHERE created_at > '2024-01-01' -- Consider adding index on created_at".to_string()),
-                reasoning: "Adding an index on created_at column will significantly improve query performance for date-based filtering".to_string(),
-            },
-        ];
+                description: format!("Add an index on {}({})", table.name, column.name),
+                impact_level: impact_level.to_string(),
+                before_snippet: None,
+                after_snippet: Some(format!(
+                    "CREATE INDEX idx_{}_{} ON {} ({});",
+                    table.name, column.name, table.name, column.name
+                )),
+                reasoning: format!(
+                    "{}.{} is used to {} rows but no index starts with it, so the database has to scan {}",
+                    table.name, column.name, usage, table.name
+                ),
+            });
+        }
 
-        let performance_estimate = PerformanceEstimate {
-            estimated_improvement_percent: Some(75.0),
-            execution_time_before: Some("2.3 seconds".to_string()),
-            execution_time_after: Some("0.6 seconds".to_string()),
-            resource_usage_impact: Some("Reduced CPU usage by 60%".to_string()),
-        };
+        let mut optimized_query = None;
+        if shape.selects_all && shape.statement == "SELECT" {
+            let columns = wildcard_columns(&shape, &schema);
+            optimized_query = columns.as_ref().and_then(|columns| expand_wildcard(&request.sql, columns));
+            optimizations.push(OptimizationSuggestion {
+                suggestion_id: Uuid::new_v4().to_string(),
+                category: "query_structure".to_string(),
+                description: "Select only the columns you need instead of SELECT *".to_string(),
+                impact_level: "Medium".to_string(),
+                before_snippet: Some("SELECT *".to_string()),
+                after_snippet: columns.map(|columns| format!("SELECT {}", columns.join(", "))),
+                reasoning: "Listing columns avoids reading and transferring data the caller ignores and keeps the result stable when the table changes".to_string(),
+            });
+        }
 
         Ok(AnalysisResultData::Optimization {
             original_query: request.sql.clone(),
-            optimized_query: Some(format!("-- Optimized version\n{}", request.sql)),
+            optimized_query,
             optimizations,
-            performance_impact: Some(performance_estimate),
+            // Filled from planner output by `attach_plans` when the connection can explain
+            performance_impact: None,
         })
     }
 
@@ -103,6 +245,53 @@ impl AITaskHandler for OptimizeTaskHandler {
     }
 }
 
+impl OptimizeTaskHandler {
+    /// Replace guessed figures with real planner output.
+    /// `optimized` is only present when the handler produced a rewrite.
+    pub fn attach_plans(
+        result: &mut AnalysisResultData,
+        original: Option<(serde_json::Value, Option<f64>)>,
+        optimized: Option<(serde_json::Value, Option<f64>)>,
+    ) {
+        let AnalysisResultData::Optimization { performance_impact, .. } = result else {
+            return;
+        };
+        if original.is_none() && optimized.is_none() {
+            return;
+        }
+
+        let estimate = performance_impact.get_or_insert(PerformanceEstimate {
+            estimated_improvement_percent: None,
+            execution_time_before: None,
+            execution_time_after: None,
+            resource_usage_impact: None,
+            original_plan: None,
+            optimized_plan: None,
+            estimated_cost_before: None,
+            estimated_cost_after: None,
+            estimated_cost_delta: None,
+        });
+
+        let (original_plan, cost_before) = original.map_or((None, None), |(plan, cost)| (Some(plan), cost));
+        let (optimized_plan, cost_after) = optimized.map_or((None, None), |(plan, cost)| (Some(plan), cost));
+
+        estimate.original_plan = original_plan;
+        estimate.optimized_plan = optimized_plan;
+        estimate.estimated_cost_before = cost_before;
+        estimate.estimated_cost_after = cost_after;
+        estimate.estimated_cost_delta = match (cost_before, cost_after) {
+            (Some(before), Some(after)) => Some(after - before),
+            _ => None,
+        };
+
+        // Derive the improvement from planner costs instead of the canned figure
+        estimate.estimated_improvement_percent = match (cost_before, cost_after) {
+            (Some(before), Some(after)) if before > 0.0 => Some(((before - after) / before * 100.0) as f32),
+            _ => estimate.estimated_improvement_percent,
+        };
+    }
+}
+
 // Validate Task Handler
 pub struct ValidateTaskHandler;
 
@@ -148,22 +337,150 @@ impl AITaskHandler for ValidateTaskHandler {
     }
 }
 
+// Format Task Handler
+// Runs offline with sqlformat so the same input always formats the same way
+pub struct FormatTaskHandler;
+
+impl AITaskHandler for FormatTaskHandler {
+    fn handle_task(&self, request: &AIAnalysisRequest) -> Result<AnalysisResultData, String> {
+        if request.sql.trim().is_empty() {
+            return Err("Nothing to format".to_string());
+        }
+
+        let options = sqlformat::FormatOptions {
+            indent: sqlformat::Indent::Spaces(2),
+            uppercase: true,
+            lines_between_queries: 1,
+            ..Default::default()
+        };
+        let formatted_sql = sqlformat::format(&request.sql, &sqlformat::QueryParams::None, options);
+        let changed = formatted_sql.trim() != request.sql.trim();
+
+        Ok(AnalysisResultData::Formatted { formatted_sql, changed })
+    }
+
+    fn get_task_type(&self) -> AITaskType {
+        AITaskType::Format
+    }
+
+    fn estimate_duration(&self, _request: &AIAnalysisRequest) -> u32 {
+        1
+    }
+
+    fn runs_locally(&self) -> bool {
+        true
+    }
+}
+
+/// Used when a request doesn't set `analysis_options.timeout_seconds`
+pub const DEFAULT_ANALYSIS_TIMEOUT_SECONDS: u64 = 30;
+
+/// Why a handler run ended without a result
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnalysisExit {
+    Failed(String),
+    TimedOut(Duration),
+    Cancelled,
+}
+
+impl AnalysisExit {
+    fn status(&self) -> AITaskStatus {
+        match self {
+            AnalysisExit::Cancelled => AITaskStatus::Cancelled,
+            AnalysisExit::Failed(_) | AnalysisExit::TimedOut(_) => AITaskStatus::Failed,
+        }
+    }
+}
+
+impl std::fmt::Display for AnalysisExit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnalysisExit::Failed(error) => write!(f, "{}", error),
+            AnalysisExit::TimedOut(timeout) => write!(f, "analysis timed out after {}s", timeout.as_secs()),
+            AnalysisExit::Cancelled => write!(f, "analysis cancelled"),
+        }
+    }
+}
+
+/// Run a handler off the async runtime until it finishes, `timeout` passes or `cancel` fires.
+/// Handlers are synchronous, so one that overruns keeps its blocking thread until it returns;
+/// its result is simply discarded.
+pub async fn run_handler(
+    handler: Arc<dyn AITaskHandler>,
+    request: AIAnalysisRequest,
+    timeout: Duration,
+    cancel: CancellationToken,
+) -> Result<AnalysisResultData, AnalysisExit> {
+    let work = tokio::task::spawn_blocking(move || handler.handle_task(&request));
+    let finished = tokio::time::timeout(timeout, async {
+        tokio::select! {
+            joined = work => Some(joined),
+            _ = cancel.cancelled() => None,
+        }
+    })
+    .await;
+
+    match finished {
+        Ok(Some(Ok(result))) => result.map_err(AnalysisExit::Failed),
+        Ok(Some(Err(join_error))) => Err(AnalysisExit::Failed(format!("Analysis task failed: {}", join_error))),
+        Ok(None) => Err(AnalysisExit::Cancelled),
+        Err(_) => Err(AnalysisExit::TimedOut(timeout)),
+    }
+}
+
 // Main AI Task Manager
 pub struct AITaskManager {
     active_tasks: Arc<Mutex<HashMap<String, AITaskInfo>>>,
-    explain_handler: ExplainTaskHandler,
-    optimize_handler: OptimizeTaskHandler,
-    validate_handler: ValidateTaskHandler,
+    // Fired by `cancel_analysis` to stop waiting on a running handler
+    cancellations: Arc<Mutex<HashMap<String, CancellationToken>>>,
+    // Task types the AI core advertised; None until capabilities are negotiated
+    capabilities: Arc<Mutex<Option<HashSet<AITaskType>>>>,
+    handlers: HashMap<AITaskType, Arc<dyn AITaskHandler>>,
 }
 
 impl AITaskManager {
     pub fn new() -> Self {
-        Self {
+        let mut manager = Self {
             active_tasks: Arc::new(Mutex::new(HashMap::new())),
-            explain_handler: ExplainTaskHandler,
-            optimize_handler: OptimizeTaskHandler,
-            validate_handler: ValidateTaskHandler,
-        }
+            cancellations: Arc::new(Mutex::new(HashMap::new())),
+            capabilities: Arc::new(Mutex::new(None)),
+            handlers: HashMap::new(),
+        };
+
+        manager.register_handler(Box::new(ExplainTaskHandler));
+        manager.register_handler(Box::new(OptimizeTaskHandler));
+        manager.register_handler(Box::new(ValidateTaskHandler));
+        manager.register_handler(Box::new(FormatTaskHandler));
+
+        manager
+    }
+
+    /// Register (or replace) the handler for the task type it reports
+    pub fn register_handler(&mut self, handler: Box<dyn AITaskHandler>) {
+        self.handlers.insert(handler.get_task_type(), Arc::from(handler));
+    }
+
+    pub fn has_handler(&self, task_type: &AITaskType) -> bool {
+        self.handlers.contains_key(task_type)
+    }
+
+    /// Record the task types supported by the connected AI core
+    pub fn set_capabilities(&self, task_types: Vec<AITaskType>) {
+        *self.capabilities.lock().unwrap() = Some(task_types.into_iter().collect());
+    }
+
+    /// Forget negotiated capabilities, e.g. after the AI core stops
+    pub fn clear_capabilities(&self) {
+        *self.capabilities.lock().unwrap() = None;
+    }
+
+    /// Whether the AI core can run this task type; everything is allowed until negotiated
+    pub fn supports(&self, task_type: &AITaskType) -> bool {
+        self.capabilities
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map_or(true, |capabilities| capabilities.contains(task_type))
     }
 
     pub async fn execute_analysis(
@@ -171,6 +488,18 @@ impl AITaskManager {
         request: AIAnalysisRequest,
         app_handle: AppHandle,
     ) -> Result<AIAnalysisResult, String> {
+        // Get appropriate handler
+        let handler = self
+            .handlers
+            .get(&request.task_type)
+            .cloned()
+            .ok_or_else(|| format!("No handler registered for task type {:?}", request.task_type))?;
+
+        // Fail fast instead of round-tripping a task the AI core can't handle
+        if !handler.runs_locally() && !self.supports(&request.task_type) {
+            return Err(format!("task type {:?} not supported by current AI core", request.task_type));
+        }
+
         let analysis_id = Uuid::new_v4().to_string();
         let start_time = std::time::Instant::now();
 
@@ -184,27 +513,29 @@ impl AITaskManager {
         };
 
         // Add to active tasks
+        let cancel = CancellationToken::new();
         {
             let mut tasks = self.active_tasks.lock().unwrap();
             tasks.insert(analysis_id.clone(), task_info);
+            self.cancellations.lock().unwrap().insert(analysis_id.clone(), cancel.clone());
         }
+        let timeout = Duration::from_secs(
+            request
+                .analysis_options
+                .as_ref()
+                .and_then(|options| options.timeout_seconds)
+                .map_or(DEFAULT_ANALYSIS_TIMEOUT_SECONDS, u64::from),
+        );
 
         // Emit progress event
         self.emit_progress(&app_handle, &analysis_id, &request.task_type, "Starting analysis", 0).await;
 
-        // Get appropriate handler
-        let handler: &dyn AITaskHandler = match request.task_type {
-            AITaskType::Explain => &self.explain_handler,
-            AITaskType::Optimize => &self.optimize_handler,
-            AITaskType::Validate => &self.validate_handler,
-        };
-
         // Update status to processing
         self.update_task_status(&analysis_id, AITaskStatus::Processing);
         self.emit_progress(&app_handle, &analysis_id, &request.task_type, "Processing analysis", 50).await;
 
-        // Execute the analysis
-        let result = match handler.handle_task(&request) {
+        // Execute the analysis; timeouts and cancellation come back as errors and share the exit below
+        let result = match run_handler(handler, request.clone(), timeout, cancel).await {
             Ok(data) => {
                 self.update_task_status(&analysis_id, AITaskStatus::Completed);
                 self.emit_progress(&app_handle, &analysis_id, &request.task_type, "Analysis completed", 100).await;
@@ -219,9 +550,12 @@ impl AITaskManager {
                     error_message: None,
                 }
             }
-            Err(error) => {
-                self.update_task_status(&analysis_id, AITaskStatus::Failed);
-                
+            Err(exit) => {
+                if exit != AnalysisExit::Cancelled {
+                    log::warn!("Analysis {} ended: {}", analysis_id, exit);
+                }
+                self.update_task_status(&analysis_id, exit.status());
+
                 AIAnalysisResult {
                     analysis_id: analysis_id.clone(),
                     task_type: request.task_type,
@@ -229,7 +563,7 @@ impl AITaskManager {
                     result: None,
                     confidence_score: None,
                     execution_time_ms: Some(start_time.elapsed().as_millis() as u64),
-                    error_message: Some(error),
+                    error_message: Some(exit.to_string()),
                 }
             }
         };
@@ -238,8 +572,11 @@ impl AITaskManager {
         {
             let mut tasks = self.active_tasks.lock().unwrap();
             tasks.remove(&analysis_id);
+            self.cancellations.lock().unwrap().remove(&analysis_id);
         }
 
+        self.emit_complete(&app_handle, &result);
+
         Ok(result)
     }
 
@@ -248,6 +585,9 @@ impl AITaskManager {
         if let Some(task) = tasks.get_mut(analysis_id) {
             task.status = AITaskStatus::Cancelled;
             task.updated_at = Utc::now();
+            if let Some(cancel) = self.cancellations.lock().unwrap().get(analysis_id) {
+                cancel.cancel();
+            }
             Ok(())
         } else {
             Err(format!("Analysis with ID {} not found", analysis_id))
@@ -279,11 +619,11 @@ impl AITaskManager {
             analysis_id: analysis_id.to_string(),
             task_type: task_type.clone(),
             stage: match progress {
-                0..=25 => "analyzing".to_string(),
-                26..=75 => "processing".to_string(),
-                76..=99 => "formatting".to_string(),
-                100 => "completed".to_string(),
-                _ => "unknown".to_string(),
+                0..=25 => ProgressStage::Analyzing,
+                26..=75 => ProgressStage::Processing,
+                76..=99 => ProgressStage::Formatting,
+                100 => ProgressStage::Completed,
+                _ => ProgressStage::Unknown,
             },
             progress_percent: Some(progress),
             message: message.to_string(),
@@ -292,4 +632,17 @@ impl AITaskManager {
 
         let _ = app_handle.emit_all("ai-analysis-progress", &progress_event);
     }
+
+    fn emit_complete(&self, app_handle: &AppHandle, result: &AIAnalysisResult) {
+        let complete_event = AIAnalysisComplete {
+            analysis_id: result.analysis_id.clone(),
+            task_type: result.task_type.clone(),
+            success: result.success,
+            error_message: result.error_message.clone(),
+            execution_time_ms: result.execution_time_ms,
+            timestamp: Utc::now(),
+        };
+
+        let _ = app_handle.emit_all("ai-analysis-complete", &complete_event);
+    }
 }
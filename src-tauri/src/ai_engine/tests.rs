@@ -4,6 +4,51 @@ use super::types::{AIEngineConfig, AIEngineStatus};
 use std::time::Duration;
 use tokio::time;
 
+/// Connect `manager` to an in-process AI Core that answers each request with the result
+/// `reply` makes from its method and params, after `delay`. Requests it returns `None` for
+/// are never answered.
+async fn attach_scripted_core<F>(manager: &AIEngineManager, delay: Duration, reply: F)
+where
+    F: Fn(&str, &serde_json::Value) -> Option<serde_json::Value> + Send + Sync + 'static,
+{
+    use super::communication::IPCChannel;
+    use std::sync::Arc;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let (host_side, core_side) = tokio::io::duplex(64 * 1024);
+    let (host_read, host_write) = tokio::io::split(host_side);
+    manager
+        .attach_channel(IPCChannel::from_streams(BufReader::new(host_read), host_write, 64 * 1024))
+        .await;
+
+    let reply = Arc::new(reply);
+    tokio::spawn(async move {
+        let (core_read, core_write) = tokio::io::split(core_side);
+        let core_write = Arc::new(tokio::sync::Mutex::new(core_write));
+        let mut lines = BufReader::new(core_read).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let request: serde_json::Value = serde_json::from_str(&line).unwrap();
+            let Some(result) = reply(request["method"].as_str().unwrap_or(""), &request["params"]) else {
+                continue;
+            };
+            let core_write = Arc::clone(&core_write);
+            tokio::spawn(async move {
+                time::sleep(delay).await;
+                let frame = serde_json::json!({ "jsonrpc": "2.0", "id": request["id"], "result": result });
+                let _ = core_write.lock().await.write_all(format!("{}\n", frame).as_bytes()).await;
+            });
+        }
+    });
+}
+
+/// A `generate_sql` result whose SQL quotes the prompt it was given
+fn echo_prompt(params: &serde_json::Value) -> serde_json::Value {
+    serde_json::json!({
+        "generated_sql": format!("-- {}\nSELECT 1;", params["prompt"].as_str().unwrap_or("")),
+        "confidence_score": 0.9,
+    })
+}
+
 #[tokio::test]
 async fn test_engine_manager_creation_and_status() {
     let config = AIEngineConfig::default();
@@ -45,3 +90,825 @@ async fn test_start_stop_and_status_broadcast() {
     assert!(received_statuses.contains(&AIEngineStatus::Ready), "Should have broadcasted Ready");
     assert!(received_statuses.contains(&AIEngineStatus::Stopped), "Should have broadcasted Stopped");
 }
+
+#[tokio::test]
+async fn test_missing_executable_fails_without_retry() {
+    let config = AIEngineConfig {
+        python_executable: "symbiotic-nonexistent-python".to_string(),
+        restart_delay_base: 10_000,
+        ..Default::default()
+    };
+
+    let manager = AIEngineManager::new(config);
+
+    // A retry would sleep for the 10s base delay before giving up
+    time::timeout(Duration::from_secs(2), manager.start())
+        .await
+        .expect("Missing executable should not be retried");
+    assert!(matches!(manager.get_status().await, AIEngineStatus::Error(_)));
+}
+
+#[tokio::test]
+async fn test_ipc_frame_size_limit() {
+    use super::communication::read_frame;
+
+    let oversized = format!("{}\n{{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":null}}\n", "x".repeat(64));
+    let mut reader = tokio::io::BufReader::with_capacity(16, oversized.as_bytes());
+
+    let err = read_frame(&mut reader, 32).await.unwrap_err();
+    assert!(err.contains("exceeds maximum size"));
+
+    // The oversized line is skipped, so the next frame is still readable
+    let next = read_frame(&mut reader, 64).await.unwrap();
+    assert_eq!(next.as_deref(), Some("{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":null}"));
+    assert_eq!(read_frame(&mut reader, 64).await.unwrap(), None);
+}
+
+#[test]
+fn test_ipc_response_envelope_validation() {
+    use super::communication::parse_response;
+
+    let ok = parse_response(r#"{"jsonrpc":"2.0","id":"7","result":{"sql":"SELECT 1"}}"#).unwrap();
+    assert_eq!(ok.id.unwrap().0, "7");
+    assert!(ok.result.is_some());
+
+    let err = parse_response(r#"{"jsonrpc":"2.0","id":"a","error":{"code":-32601,"message":"Method not found"}}"#).unwrap();
+    assert_eq!(err.error.unwrap().code, -32601);
+
+    let malformed = [
+        "{not json",
+        "[1, 2, 3]",
+        r#"{"jsonrpc":"1.0","id":1,"result":null}"#,
+        r#"{"jsonrpc":"2.0","result":null}"#,
+        r#"{"jsonrpc":"2.0","id":{},"result":null}"#,
+        r#"{"jsonrpc":"2.0","id":7,"result":null}"#,
+        r#"{"jsonrpc":"2.0","id":1}"#,
+        r#"{"jsonrpc":"2.0","id":1,"result":1,"error":{"code":1,"message":"x"}}"#,
+        r#"{"jsonrpc":"2.0","id":1,"error":{"message":"no code"}}"#,
+    ];
+    for frame in malformed {
+        assert!(parse_response(frame).is_err(), "frame should be rejected: {}", frame);
+    }
+}
+
+#[test]
+fn test_json_rpc_response_correlates_with_request_id() {
+    use super::communication::parse_response;
+    use super::types::{JsonRpcMessage, RequestId};
+    use std::collections::HashMap;
+
+    let request = JsonRpcMessage {
+        jsonrpc: "2.0".to_string(),
+        method: "health.ping".to_string(),
+        params: HashMap::new(),
+        id: Some(RequestId::new()),
+    };
+    let wire = serde_json::to_value(&request).unwrap();
+    assert!(wire["id"].is_string(), "ids are sent as strings");
+
+    // The AI Core echoes the request id back verbatim
+    let frame = serde_json::json!({"jsonrpc": "2.0", "id": wire["id"], "result": "pong"}).to_string();
+    let response = parse_response(&frame).unwrap();
+    assert_eq!(response.id, request.id);
+}
+
+#[test]
+fn test_task_capability_gating() {
+    use super::health::advertised_capabilities;
+    use super::task_manager::AITaskManager;
+    use super::types::AITaskType;
+
+    let task_manager = AITaskManager::new();
+    assert!(task_manager.supports(&AITaskType::Optimize), "all tasks allowed before negotiation");
+
+    task_manager.set_capabilities(vec![AITaskType::Explain, AITaskType::Validate]);
+    assert!(task_manager.supports(&AITaskType::Explain));
+    assert!(!task_manager.supports(&AITaskType::Optimize));
+
+    task_manager.clear_capabilities();
+    assert!(task_manager.supports(&AITaskType::Optimize));
+
+    // Capabilities come from the pong; names this build doesn't know are skipped
+    let pong = serde_json::json!({ "pong": true, "capabilities": ["Explain", "Convert"] });
+    task_manager.set_capabilities(advertised_capabilities(&pong).unwrap());
+    assert!(task_manager.supports(&AITaskType::Explain));
+    assert!(!task_manager.supports(&AITaskType::Validate));
+    assert_eq!(advertised_capabilities(&serde_json::json!({ "pong": true })), None);
+}
+
+#[test]
+fn test_task_handler_registry() {
+    use super::task_manager::{AITaskHandler, AITaskManager};
+    use super::types::{AIAnalysisRequest, AITaskType, AnalysisResultData};
+
+    struct CustomValidateHandler;
+
+    impl AITaskHandler for CustomValidateHandler {
+        fn handle_task(&self, _request: &AIAnalysisRequest) -> Result<AnalysisResultData, String> {
+            Err("not implemented".to_string())
+        }
+
+        fn get_task_type(&self) -> AITaskType {
+            AITaskType::Validate
+        }
+
+        fn estimate_duration(&self, _request: &AIAnalysisRequest) -> u32 {
+            1
+        }
+    }
+
+    let mut task_manager = AITaskManager::new();
+    for task_type in [AITaskType::Explain, AITaskType::Optimize, AITaskType::Validate, AITaskType::Format] {
+        assert!(task_manager.has_handler(&task_type), "{:?} should be registered by default", task_type);
+    }
+
+    task_manager.register_handler(Box::new(CustomValidateHandler));
+    assert!(task_manager.has_handler(&AITaskType::Validate));
+}
+
+#[test]
+fn test_format_task_is_deterministic() {
+    use super::task_manager::{AITaskHandler, FormatTaskHandler};
+    use super::types::{AIAnalysisRequest, AITaskType, AnalysisResultData};
+
+    let request = |sql: &str| AIAnalysisRequest {
+        sql: sql.to_string(),
+        task_type: AITaskType::Format,
+        connection_id: String::new(),
+        schema_context: None,
+        analysis_options: None,
+    };
+
+    let handler = FormatTaskHandler;
+    assert!(handler.runs_locally(), "formatting never needs the AI core");
+
+    let AnalysisResultData::Formatted { formatted_sql, changed } =
+        handler.handle_task(&request("select id, name from users where id = 1")).unwrap()
+    else {
+        panic!("expected a Formatted result");
+    };
+    assert!(changed);
+    assert!(formatted_sql.starts_with("SELECT"));
+    assert!(formatted_sql.contains("\nFROM"));
+    assert!(formatted_sql.contains("\nWHERE"));
+
+    // Formatting formatted SQL is a no-op
+    let AnalysisResultData::Formatted { formatted_sql: again, changed } = handler.handle_task(&request(&formatted_sql)).unwrap() else {
+        panic!("expected a Formatted result");
+    };
+    assert_eq!(again, formatted_sql);
+    assert!(!changed);
+
+    assert!(handler.handle_task(&request("   ")).is_err());
+}
+
+#[test]
+fn test_progress_stage_wire_format() {
+    use super::types::ProgressStage;
+
+    assert_eq!(serde_json::to_value(ProgressStage::Analyzing).unwrap(), "analyzing");
+    assert_eq!(serde_json::to_value(ProgressStage::ConsultingSchema).unwrap(), "consulting_schema");
+    let stage: ProgressStage = serde_json::from_str("\"formatting\"").unwrap();
+    assert_eq!(stage, ProgressStage::Formatting);
+}
+
+#[test]
+fn test_optimize_attaches_plan_cost_delta() {
+    use super::task_manager::OptimizeTaskHandler;
+    use super::types::AnalysisResultData;
+
+    let mut data = AnalysisResultData::Optimization {
+        original_query: "SELECT * FROM orders".to_string(),
+        optimized_query: Some("SELECT id FROM orders".to_string()),
+        optimizations: vec![],
+        performance_impact: None,
+    };
+
+    OptimizeTaskHandler::attach_plans(
+        &mut data,
+        Some((serde_json::json!([{"Plan": {"Total Cost": 200.0}}]), Some(200.0))),
+        Some((serde_json::json!([{"Plan": {"Total Cost": 50.0}}]), Some(50.0))),
+    );
+
+    let AnalysisResultData::Optimization { performance_impact: Some(estimate), .. } = data else {
+        panic!("expected an optimization result with a performance estimate");
+    };
+    assert_eq!(estimate.estimated_cost_delta, Some(-150.0));
+    assert_eq!(estimate.estimated_improvement_percent, Some(75.0));
+    assert!(estimate.original_plan.is_some() && estimate.optimized_plan.is_some());
+}
+
+#[tokio::test]
+async fn test_stop_fails_in_flight_generation_with_shutdown_error() {
+    use super::manager::ENGINE_SHUT_DOWN_ERROR;
+    use super::types::SQLGenerationRequest;
+    use std::sync::Arc;
+
+    let manager = Arc::new(AIEngineManager::new(AIEngineConfig::default()));
+    // An AI Core that never finishes the generation
+    attach_scripted_core(&manager, Duration::ZERO, |_, _| None).await;
+    assert_eq!(manager.get_status().await, AIEngineStatus::Ready);
+
+    let generating = Arc::clone(&manager);
+    let generation = tokio::spawn(async move {
+        generating.generate_sql_from_prompt(
+            SQLGenerationRequest {
+                prompt: "recent users".to_string(),
+                connection_id: "test".to_string(),
+                schema_context: None,
+                generation_options: None,
+            },
+            None,
+            None,
+        ).await
+    });
+
+    // Wait for the generation to register before tearing the engine down
+    while manager.active_generations().await.is_empty() {
+        time::sleep(Duration::from_millis(10)).await;
+    }
+    manager.stop().await;
+
+    let result = time::timeout(Duration::from_secs(2), generation)
+        .await
+        .expect("generation should not hang after stop")
+        .unwrap();
+    assert_eq!(result.unwrap_err(), ENGINE_SHUT_DOWN_ERROR);
+    assert!(manager.active_generations().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_generation_comes_from_the_ai_core() {
+    use super::types::SQLGenerationRequest;
+
+    let manager = AIEngineManager::new(AIEngineConfig::default());
+    attach_scripted_core(&manager, Duration::ZERO, |method, params| {
+        (method == "generate_sql").then(|| echo_prompt(params))
+    })
+    .await;
+
+    let response = manager
+        .generate_sql_from_prompt(
+            SQLGenerationRequest {
+                prompt: "orders per month".to_string(),
+                connection_id: "test".to_string(),
+                schema_context: None,
+                generation_options: None,
+            },
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(response.success);
+    assert_eq!(response.generated_sql.as_deref(), Some("-- orders per month\nSELECT 1;"));
+    assert_eq!(response.confidence_score, Some(0.9));
+    assert!(response.generation_time_ms.is_some());
+
+    manager.stop().await;
+}
+
+#[tokio::test]
+async fn test_pending_queue_keeps_only_interrupted_generations() {
+    use super::manager::ENGINE_SHUT_DOWN_ERROR;
+    use super::queue::PendingGenerationStore;
+    use super::types::SQLGenerationRequest;
+    use std::sync::Arc;
+
+    let queue_path = std::env::temp_dir().join(format!("pending-generations-{}.json", uuid::Uuid::new_v4()));
+    let config = AIEngineConfig {
+        pending_queue_path: Some(queue_path.clone()),
+        ..Default::default()
+    };
+    // Answers every generation except the one left to be interrupted
+    let core = |_: &str, params: &serde_json::Value| (params["prompt"] != "interrupted").then(|| echo_prompt(params));
+    let request = |prompt: &str| SQLGenerationRequest {
+        prompt: prompt.to_string(),
+        connection_id: "test".to_string(),
+        schema_context: None,
+        generation_options: None,
+    };
+
+    let manager = Arc::new(AIEngineManager::new(config.clone()));
+    attach_scripted_core(&manager, Duration::ZERO, core).await;
+
+    // A generation that completes is dropped from the queue
+    manager.generate_sql_from_prompt(request("finished"), None, None).await.unwrap();
+    assert!(manager.pending_generations().await.is_empty());
+
+    // One interrupted by shutdown stays queued for the next session
+    let generating = Arc::clone(&manager);
+    let generation = tokio::spawn(async move {
+        generating.generate_sql_from_prompt(request("interrupted"), None, None).await
+    });
+    while manager.active_generations().await.is_empty() {
+        time::sleep(Duration::from_millis(10)).await;
+    }
+    manager.stop().await;
+    assert_eq!(generation.await.unwrap().unwrap_err(), ENGINE_SHUT_DOWN_ERROR);
+
+    let restarted = AIEngineManager::new(config);
+    let pending = restarted.pending_generations().await;
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].request.prompt, "interrupted");
+
+    // Replaying it under its original id finishes it and empties the queue
+    attach_scripted_core(&restarted, Duration::ZERO, |_, params| Some(echo_prompt(params))).await;
+    let resumed = restarted.resume_generation(pending[0].clone(), None, None).await.unwrap();
+    assert!(resumed.generated_sql.unwrap().contains("interrupted"));
+    assert!(PendingGenerationStore::new(queue_path.clone()).load().is_empty());
+
+    restarted.stop().await;
+    std::fs::remove_file(&queue_path).ok();
+}
+
+#[tokio::test]
+async fn test_ipc_calls_are_matched_to_replies_by_id() {
+    use super::communication::IPCChannel;
+    use super::types::{JsonRpcMessage, RequestId};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let (host_side, core_side) = tokio::io::duplex(4096);
+    let (host_read, host_write) = tokio::io::split(host_side);
+    let channel = Arc::new(
+        IPCChannel::from_streams(BufReader::new(host_read), host_write, 1024)
+            .with_read_timeout(Duration::from_secs(2)),
+    );
+
+    // A fake AI Core that waits for both requests, then answers them in reverse order
+    let core = tokio::spawn(async move {
+        let (core_read, mut core_write) = tokio::io::split(core_side);
+        let mut lines = BufReader::new(core_read).lines();
+        let mut requests = Vec::new();
+        while requests.len() < 2 {
+            let line = lines.next_line().await.unwrap().unwrap();
+            requests.push(serde_json::from_str::<serde_json::Value>(&line).unwrap());
+        }
+        for request in requests.iter().rev() {
+            let reply = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": { "echo": request["method"] },
+            });
+            core_write.write_all(format!("{}\n", reply).as_bytes()).await.unwrap();
+        }
+    });
+
+    let request = |method: &str| JsonRpcMessage {
+        jsonrpc: "2.0".to_string(),
+        method: method.to_string(),
+        params: HashMap::new(),
+        id: Some(RequestId::new()),
+    };
+    let (first, second) = (request("sql.generate"), request("health.ping"));
+    let (first_reply, second_reply) = tokio::join!(channel.call(&first), channel.call(&second));
+
+    let first_reply = first_reply.unwrap();
+    assert_eq!(first_reply.id, first.id);
+    assert_eq!(first_reply.result.unwrap()["echo"], "sql.generate");
+    let second_reply = second_reply.unwrap();
+    assert_eq!(second_reply.id, second.id);
+    assert_eq!(second_reply.result.unwrap()["echo"], "health.ping");
+    core.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_ipc_send_writes_one_line_and_recv_times_out() {
+    use super::communication::IPCChannel;
+    use super::types::{JsonRpcMessage, RequestId};
+    use std::collections::HashMap;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    let (host_side, core_side) = tokio::io::duplex(4096);
+    let (host_read, host_write) = tokio::io::split(host_side);
+    let channel = IPCChannel::from_streams(BufReader::new(host_read), host_write, 1024)
+        .with_read_timeout(Duration::from_millis(50));
+
+    let mut params = HashMap::new();
+    params.insert("prompt".to_string(), serde_json::json!("first line\nsecond line"));
+    let message = JsonRpcMessage {
+        jsonrpc: "2.0".to_string(),
+        method: "sql.generate".to_string(),
+        params,
+        id: Some(RequestId::new()),
+    };
+    channel.send(&message).await.unwrap();
+
+    // Newlines inside values are escaped, so the request arrives as exactly one frame
+    let mut core_reader = BufReader::new(core_side);
+    let mut line = String::new();
+    core_reader.read_line(&mut line).await.unwrap();
+    assert!(line.ends_with('\n'));
+    let sent: serde_json::Value = serde_json::from_str(line.trim_end()).unwrap();
+    assert_eq!(sent["params"]["prompt"], "first line\nsecond line");
+
+    // The core never answers
+    let err = channel.recv().await.unwrap_err();
+    assert!(err.contains("No response"));
+
+    channel.close().await;
+    assert!(channel.send(&message).await.is_err());
+}
+
+#[tokio::test]
+async fn test_health_check_pongs_and_times_out() {
+    use super::communication::IPCChannel;
+    use super::manager::check_health_within;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let (host_side, core_side) = tokio::io::duplex(4096);
+    let (host_read, host_write) = tokio::io::split(host_side);
+    let channel = IPCChannel::from_streams(BufReader::new(host_read), host_write, 1024);
+
+    // A fake AI Core that answers the first ping and ignores the second
+    let core = tokio::spawn(async move {
+        let (core_read, mut core_write) = tokio::io::split(core_side);
+        let mut lines = BufReader::new(core_read).lines();
+        let line = lines.next_line().await.unwrap().unwrap();
+        let ping: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(ping["method"], "health.ping");
+        let pong = serde_json::json!({ "jsonrpc": "2.0", "id": ping["id"], "result": "pong" });
+        core_write.write_all(format!("{}\n", pong).as_bytes()).await.unwrap();
+        lines.next_line().await.unwrap();
+        (lines, core_write)
+    });
+
+    let healthy = check_health_within(&channel, 2000).await;
+    assert!(healthy.is_healthy, "{}", healthy.last_result);
+
+    let unhealthy = check_health_within(&channel, 50).await;
+    assert!(!unhealthy.is_healthy);
+    assert!(unhealthy.last_result.contains("No pong"));
+    core.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_crashing_core_is_restarted_until_attempts_run_out() {
+    let config = AIEngineConfig {
+        python_executable: if cfg!(windows) { "cmd" } else { "sh" }.to_string(),
+        ai_core_script: if cfg!(windows) { "/C exit 1" } else { "-c 'exit 1'" }.to_string(),
+        health_check_interval: 50,
+        max_restart_attempts: 2,
+        restart_delay_base: 10,
+        max_restart_delay: 20,
+        ..Default::default()
+    };
+    let manager = AIEngineManager::new(config);
+    let mut status_receiver = manager.get_status_receiver();
+    manager.start().await;
+
+    let mut received = vec![];
+    loop {
+        let status = time::timeout(Duration::from_secs(5), status_receiver.recv())
+            .await
+            .expect("engine should give up within the timeout")
+            .unwrap();
+        let done = matches!(status, AIEngineStatus::Error(_));
+        received.push(status);
+        if done {
+            break;
+        }
+    }
+
+    // The initial start plus one Ready per restart, each followed by a crash
+    let readies = received.iter().filter(|s| **s == AIEngineStatus::Ready).count();
+    let crashes = received.iter().filter(|s| matches!(s, AIEngineStatus::ProcessCrashed(_))).count();
+    assert_eq!(readies, 3, "{:?}", received);
+    assert_eq!(crashes, 3, "{:?}", received);
+    match manager.get_status().await {
+        AIEngineStatus::Error(message) => assert!(message.contains("gave up after 2 restart attempts")),
+        other => panic!("expected a terminal error, got {:?}", other),
+    }
+}
+
+fn orders_schema() -> serde_json::Value {
+    serde_json::json!({
+        "databases": [{ "name": "shop", "schemas": [{ "name": "public", "tables": [
+            {
+                "name": "orders",
+                "columns": [
+                    { "name": "id", "is_primary_key": true },
+                    { "name": "customer_id", "is_primary_key": false },
+                    { "name": "status", "is_primary_key": false }
+                ],
+                "indexes": [{ "name": "idx_orders_customer", "columns": ["customer_id"] }]
+            },
+            {
+                "name": "customers",
+                "columns": [{ "name": "id", "is_primary_key": true }, { "name": "country", "is_primary_key": false }],
+                "indexes": []
+            }
+        ]}]}]
+    })
+}
+
+#[test]
+fn test_query_analysis_resolves_aliases_and_schema_columns() {
+    use super::query_analysis::{analyze_query, expand_wildcard, ColumnRef};
+
+    let column = |table: &str, name: &str| ColumnRef { table: Some(table.to_string()), name: name.to_string() };
+    let shape = analyze_query(
+        "SELECT o.id, country FROM orders o JOIN customers c ON c.id = o.customer_id \
+         WHERE status = 'open' ORDER BY o.id LIMIT 10",
+        Some(&orders_schema()),
+    )
+    .unwrap();
+
+    assert_eq!(shape.statement, "SELECT");
+    assert_eq!(shape.table_names(), vec!["orders", "customers"]);
+    assert_eq!(shape.selected_columns, vec![column("orders", "id"), column("customers", "country")]);
+    assert_eq!(shape.join_columns, vec![column("customers", "id"), column("orders", "customer_id")]);
+    // `status` is unqualified; only the schema says it belongs to orders
+    assert_eq!(shape.filter_columns, vec![column("orders", "status")]);
+    assert!(shape.has_limit && !shape.selects_all);
+
+    // CTE names are not tables
+    let shape = analyze_query("WITH recent AS (SELECT id FROM events WHERE at > now()) SELECT * FROM recent", None).unwrap();
+    assert_eq!(shape.table_names(), vec!["events"]);
+    assert!(shape.selects_all);
+
+    let shape = analyze_query("UPDATE accounts SET balance = 0 WHERE owner = 'x'", None).unwrap();
+    assert_eq!(shape.target.as_deref(), Some("accounts"));
+    assert_eq!(shape.assigned_columns, vec![column("accounts", "balance")]);
+
+    let columns = vec!["id".to_string(), "status".to_string()];
+    assert_eq!(
+        expand_wildcard("SELECT * FROM orders WHERE id = 1", &columns).as_deref(),
+        Some("SELECT id, status FROM orders WHERE id = 1")
+    );
+    assert!(expand_wildcard("SELECT * FROM orders o JOIN customers c ON c.id = o.customer_id", &columns).is_none());
+}
+
+#[test]
+fn test_explain_and_optimize_use_the_query_and_schema() {
+    use super::task_manager::{AITaskHandler, ExplainTaskHandler, OptimizeTaskHandler};
+    use super::types::{AIAnalysisRequest, AITaskType, AnalysisResultData};
+
+    let request = |task_type: AITaskType, sql: &str, schema_context: Option<serde_json::Value>| AIAnalysisRequest {
+        sql: sql.to_string(),
+        task_type,
+        connection_id: String::new(),
+        schema_context,
+        analysis_options: None,
+    };
+    let sql = "SELECT c.country, count(*) FROM orders o JOIN customers c ON c.id = o.customer_id \
+               WHERE o.status = 'open' GROUP BY c.country";
+
+    let AnalysisResultData::Explanation { detailed_steps, data_sources, .. } =
+        ExplainTaskHandler.handle_task(&request(AITaskType::Explain, sql, None)).unwrap()
+    else {
+        panic!("expected an Explanation result");
+    };
+    assert_eq!(data_sources, vec!["orders", "customers"]);
+    let operations: Vec<&str> = detailed_steps.iter().map(|step| step.operation.as_str()).collect();
+    assert_eq!(operations, vec!["FROM", "JOIN", "WHERE", "GROUP BY", "SELECT"]);
+    assert_eq!(detailed_steps[2].columns_involved, vec!["orders.status"]);
+    assert!(detailed_steps.iter().all(|step| !step.tables_involved.contains(&"users".to_string())));
+
+    // status has no index, customer_id does and customers.id is the primary key
+    let AnalysisResultData::Optimization { optimizations, optimized_query, performance_impact, .. } =
+        OptimizeTaskHandler.handle_task(&request(AITaskType::Optimize, sql, Some(orders_schema()))).unwrap()
+    else {
+        panic!("expected an Optimization result");
+    };
+    let descriptions: Vec<&str> = optimizations.iter().map(|s| s.description.as_str()).collect();
+    assert_eq!(descriptions, vec!["Add an index on orders(status)"]);
+    assert!(optimized_query.is_none());
+    assert!(performance_impact.is_none(), "no canned performance figures");
+
+    // SELECT * is rewritten only when the schema lists the columns
+    let AnalysisResultData::Optimization { optimized_query, .. } = OptimizeTaskHandler
+        .handle_task(&request(AITaskType::Optimize, "SELECT * FROM customers", Some(orders_schema())))
+        .unwrap()
+    else {
+        panic!("expected an Optimization result");
+    };
+    assert_eq!(optimized_query.as_deref(), Some("SELECT id, country FROM customers"));
+    let AnalysisResultData::Optimization { optimized_query, .. } =
+        OptimizeTaskHandler.handle_task(&request(AITaskType::Optimize, "SELECT * FROM customers", None)).unwrap()
+    else {
+        panic!("expected an Optimization result");
+    };
+    assert!(optimized_query.is_none());
+}
+
+#[tokio::test]
+async fn test_run_handler_enforces_timeout_and_cancellation() {
+    use super::task_manager::{run_handler, AITaskHandler, AnalysisExit, FormatTaskHandler};
+    use super::types::{AIAnalysisRequest, AITaskType, AnalysisResultData};
+    use std::sync::Arc;
+    use tokio_util::sync::CancellationToken;
+
+    struct SlowHandler;
+
+    impl AITaskHandler for SlowHandler {
+        fn handle_task(&self, _request: &AIAnalysisRequest) -> Result<AnalysisResultData, String> {
+            std::thread::sleep(Duration::from_millis(500));
+            Err("should have been abandoned".to_string())
+        }
+
+        fn get_task_type(&self) -> AITaskType {
+            AITaskType::Optimize
+        }
+
+        fn estimate_duration(&self, _request: &AIAnalysisRequest) -> u32 {
+            1
+        }
+    }
+
+    let request = AIAnalysisRequest {
+        sql: "select 1".to_string(),
+        task_type: AITaskType::Optimize,
+        connection_id: String::new(),
+        schema_context: None,
+        analysis_options: None,
+    };
+
+    let exit = run_handler(Arc::new(SlowHandler), request.clone(), Duration::from_millis(50), CancellationToken::new())
+        .await
+        .unwrap_err();
+    assert_eq!(exit, AnalysisExit::TimedOut(Duration::from_millis(50)));
+
+    let cancel = CancellationToken::new();
+    let cancelled = tokio::spawn(run_handler(Arc::new(SlowHandler), request.clone(), Duration::from_secs(5), cancel.clone()));
+    cancel.cancel();
+    assert_eq!(cancelled.await.unwrap().unwrap_err(), AnalysisExit::Cancelled);
+
+    // A handler that finishes in time returns its result
+    let formatted = run_handler(Arc::new(FormatTaskHandler), request, Duration::from_secs(5), CancellationToken::new()).await;
+    assert!(matches!(formatted, Ok(AnalysisResultData::Formatted { .. })));
+}
+
+#[tokio::test]
+async fn test_generations_beyond_the_limit_queue_then_get_rejected() {
+    use super::types::{ProgressStage, SQLGenerationRequest};
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    let config = AIEngineConfig {
+        max_concurrent_generations: 1,
+        max_queued_generations: 1,
+        ..Default::default()
+    };
+    let manager = Arc::new(AIEngineManager::new(config));
+    attach_scripted_core(&manager, Duration::from_millis(500), |_, params| Some(echo_prompt(params))).await;
+    let request = |prompt: &str| SQLGenerationRequest {
+        prompt: prompt.to_string(),
+        connection_id: "test".to_string(),
+        schema_context: None,
+        generation_options: None,
+    };
+
+    let running = tokio::spawn({
+        let manager = Arc::clone(&manager);
+        let request = request("first");
+        async move { manager.generate_sql_from_prompt(request, None, None).await }
+    });
+    while manager.active_generations().await.is_empty() {
+        time::sleep(Duration::from_millis(10)).await;
+    }
+
+    // The second waits for the slot and says so
+    let (progress_tx, mut progress_rx) = mpsc::channel(8);
+    let queued = tokio::spawn({
+        let manager = Arc::clone(&manager);
+        let request = request("second");
+        async move { manager.generate_sql_from_prompt(request, Some(progress_tx), None).await }
+    });
+    assert_eq!(progress_rx.recv().await.unwrap().stage, ProgressStage::Queued);
+    let report = manager.get_status_report().await;
+    assert_eq!((report.active_generations, report.queued_generations), (1, 1));
+
+    // The third finds the queue full
+    let error = manager.generate_sql_from_prompt(request("third"), None, None).await.unwrap_err();
+    assert!(error.contains("Too many SQL generations"), "{}", error);
+
+    assert!(running.await.unwrap().unwrap().success);
+    assert!(queued.await.unwrap().unwrap().success);
+    assert_eq!(manager.get_status_report().await.queued_generations, 0);
+
+    manager.stop().await;
+}
+
+#[tokio::test]
+async fn test_queued_generation_can_be_cancelled() {
+    use super::types::{ProgressStage, SQLGenerationRequest};
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    let config = AIEngineConfig {
+        max_concurrent_generations: 1,
+        ..Default::default()
+    };
+    let manager = Arc::new(AIEngineManager::new(config));
+    attach_scripted_core(&manager, Duration::from_millis(500), |_, params| Some(echo_prompt(params))).await;
+    let request = |prompt: &str| SQLGenerationRequest {
+        prompt: prompt.to_string(),
+        connection_id: "test".to_string(),
+        schema_context: None,
+        generation_options: None,
+    };
+
+    let running = tokio::spawn({
+        let manager = Arc::clone(&manager);
+        let request = request("first");
+        async move { manager.generate_sql_from_prompt(request, None, None).await }
+    });
+    while manager.active_generations().await.is_empty() {
+        time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let (progress_tx, mut progress_rx) = mpsc::channel(8);
+    let queued = tokio::spawn({
+        let manager = Arc::clone(&manager);
+        let request = request("second");
+        async move { manager.generate_sql_from_prompt(request, Some(progress_tx), None).await }
+    });
+    assert_eq!(progress_rx.recv().await.unwrap().stage, ProgressStage::Queued);
+
+    let queued_id = manager
+        .active_generations()
+        .await
+        .into_iter()
+        .find(|generation| generation.prompt == "second")
+        .unwrap()
+        .generation_id;
+    manager.cancel_sql_generation(queued_id).await.unwrap();
+
+    let error = queued.await.unwrap().unwrap_err();
+    assert!(error.contains("cancelled"), "{}", error);
+    assert_eq!(manager.get_status_report().await.queued_generations, 0);
+    assert!(running.await.unwrap().unwrap().success);
+
+    manager.stop().await;
+}
+
+#[tokio::test]
+async fn test_clarifying_questions_resume_with_answers() {
+    use super::types::SQLGenerationRequest;
+
+    let manager = AIEngineManager::new(AIEngineConfig::default());
+    // Asks how far back "recent" goes until the prompt carries an answer
+    attach_scripted_core(&manager, Duration::ZERO, |_, params| {
+        let prompt = params["prompt"].as_str().unwrap_or("");
+        Some(if prompt.contains("Clarifications:") {
+            echo_prompt(params)
+        } else {
+            serde_json::json!({ "clarifying_questions": ["How far back is recent?"] })
+        })
+    })
+    .await;
+
+    let request = SQLGenerationRequest {
+        prompt: "show me recent orders".to_string(),
+        connection_id: "test".to_string(),
+        schema_context: None,
+        generation_options: None,
+    };
+    let asked = manager.generate_sql_from_prompt(request, None, None).await.unwrap();
+    assert_eq!(asked.clarifying_questions, Some(vec!["How far back is recent?".to_string()]));
+    assert!(asked.generated_sql.is_none());
+    let clarification_id = asked.pending_clarification_id.expect("questions get a clarification id");
+
+    // A wrong number of answers leaves the clarification open
+    let error = manager.continue_sql_generation(&clarification_id, vec![], None, None).await.unwrap_err();
+    assert!(error.contains("Expected 1 answers"), "{}", error);
+
+    let response = manager
+        .continue_sql_generation(&clarification_id, vec!["7 days".to_string()], None, None)
+        .await
+        .unwrap();
+    let generated_sql = response.generated_sql.unwrap();
+    assert!(generated_sql.contains("show me recent orders"));
+    assert!(generated_sql.contains("Q: How far back is recent?\nA: 7 days"));
+
+    // Each clarification is answered once
+    assert!(manager.continue_sql_generation(&clarification_id, vec!["7 days".to_string()], None, None).await.is_err());
+
+    manager.stop().await;
+}
+
+#[test]
+fn test_active_task_serializes_with_age() {
+    use super::types::{AITaskInfo, AITaskStatus, AITaskType, ActiveAITask};
+
+    let created_at = chrono::Utc::now() - chrono::Duration::seconds(42);
+    let task = ActiveAITask::new(
+        AITaskInfo {
+            task_id: "task-1".to_string(),
+            task_type: AITaskType::Optimize,
+            status: AITaskStatus::Processing,
+            created_at,
+            updated_at: created_at,
+        },
+        created_at + chrono::Duration::seconds(42),
+    );
+
+    let value = serde_json::to_value(&task).unwrap();
+    assert_eq!(value["task_id"], "task-1");
+    assert_eq!(value["task_type"], "Optimize");
+    assert_eq!(value["status"], "Processing");
+    assert_eq!(value["age_seconds"], 42);
+}
@@ -11,7 +11,7 @@ pub enum AIEngineStatus {
     Processing,
     Error(String),
     HealthCheckFailed,
-    ProcessCrashed,
+    ProcessCrashed(String),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,7 +19,28 @@ pub struct AIEngineConfig {
     pub python_executable: String,
     pub ai_core_script: String,
     pub health_check_interval: u64, // in milliseconds
-    pub restart_attempts: u32,
+    pub health_check_timeout: u64, // in milliseconds, how long a health.ping may go unanswered
+    pub max_restart_attempts: u32,
+    pub restart_delay_base: u64, // in milliseconds, doubled after each failed attempt
+    pub max_restart_delay: u64, // in milliseconds, upper bound for the doubled delay
+    pub max_ipc_frame_bytes: usize, // largest single JSON-RPC line accepted from the AI Core
+    /// File where accepted generations are kept until they finish; `None` disables persistence
+    #[serde(default)]
+    pub pending_queue_path: Option<std::path::PathBuf>,
+    /// Generations the AI Core runs at once; later ones wait for a slot
+    #[serde(default = "default_max_concurrent_generations")]
+    pub max_concurrent_generations: usize,
+    /// Generations allowed to wait for a slot before new ones are rejected
+    #[serde(default = "default_max_queued_generations")]
+    pub max_queued_generations: usize,
+}
+
+fn default_max_concurrent_generations() -> usize {
+    2
+}
+
+fn default_max_queued_generations() -> usize {
+    8
 }
 
 impl Default for AIEngineConfig {
@@ -28,15 +49,66 @@ impl Default for AIEngineConfig {
             python_executable: "python".to_string(), // Assumes python is in PATH
             ai_core_script: "ai_core/main.py".to_string(),
             health_check_interval: 5000, // 5 seconds
-            restart_attempts: 3,
+            health_check_timeout: 2000,
+            max_restart_attempts: 3,
+            restart_delay_base: 500,
+            max_restart_delay: 30_000,
+            max_ipc_frame_bytes: 8 * 1024 * 1024, // 8 MiB
+            pending_queue_path: None,
+            max_concurrent_generations: default_max_concurrent_generations(),
+            max_queued_generations: default_max_queued_generations(),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct HealthStats {
     pub is_healthy: bool,
     pub last_result: String,
+    /// Task types the AI Core advertised in its pong; `None` when it doesn't say
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<Vec<AITaskType>>,
+}
+
+/// Engine status plus generation load, as returned by `get_ai_engine_status`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AIEngineStatusReport {
+    pub status: AIEngineStatus,
+    /// Generations holding a slot
+    pub active_generations: usize,
+    /// Generations waiting for a slot
+    pub queued_generations: usize,
+    pub max_concurrent_generations: usize,
+    pub max_queued_generations: usize,
+}
+
+/// JSON-RPC request id shared by requests and their responses (a UUID string on the wire)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    pub fn new() -> Self {
+        RequestId(uuid::Uuid::new_v4().to_string())
+    }
+}
+
+impl Default for RequestId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<String> for RequestId {
+    fn from(id: String) -> Self {
+        RequestId(id)
+    }
+}
+
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -44,7 +116,24 @@ pub struct JsonRpcMessage {
     pub jsonrpc: String,
     pub method: String,
     pub params: HashMap<String, serde_json::Value>,
-    pub id: Option<u64>,
+    pub id: Option<RequestId>,
+}
+
+/// Response envelope received from the AI Core
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcResponse {
+    pub jsonrpc: String,
+    /// `None` only when the AI Core could not read the request's id
+    pub id: Option<RequestId>,
+    pub result: Option<serde_json::Value>,
+    pub error: Option<JsonRpcError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+    pub data: Option<serde_json::Value>,
 }
 
 // SQL Generation Types
@@ -86,18 +175,54 @@ pub struct SQLGenerationResponse {
     pub confidence_score: Option<f32>,
     pub warnings: Option<Vec<String>>,
     pub clarifying_questions: Option<Vec<String>>,
+    /// Set alongside `clarifying_questions`; pass it to `continue_sql_generation` with the answers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_clarification_id: Option<String>,
     pub error_message: Option<String>,
     pub generation_time_ms: Option<u64>,
 }
 
+/// Stage reported in progress events; serialized as the stable strings the frontend matches on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressStage {
+    Queued,
+    Analyzing,
+    ConsultingSchema,
+    Generating,
+    Validating,
+    Processing,
+    Formatting,
+    Completed,
+    Unknown,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SQLGenerationProgress {
-    pub stage: String, // "analyzing", "generating", "validating", etc.
+    pub stage: ProgressStage,
     pub progress_percent: Option<u8>,
     pub message: String,
     pub timestamp: DateTime<Utc>,
 }
 
+/// Terminal event sent once per generation, whether it succeeded or not
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SQLGenerationComplete {
+    pub generation_id: String,
+    pub success: bool,
+    pub error_message: Option<String>,
+    pub generation_time_ms: Option<u64>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// An SQL generation that has started and not yet completed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveGeneration {
+    pub generation_id: String,
+    pub prompt: String,
+    pub started_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CancellationRequest {
     pub generation_id: String,
@@ -105,11 +230,12 @@ pub struct CancellationRequest {
 }
 
 // AI Analysis Types for Story 3.6
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum AITaskType {
     Explain,
     Optimize,
     Validate,
+    Format,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -175,6 +301,11 @@ pub enum AnalysisResultData {
         quick_fixes: Vec<QuickFix>,
         overall_score: Option<f32>,
     },
+    Formatted {
+        formatted_sql: String,
+        /// False when the input was already formatted
+        changed: bool,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -203,6 +334,18 @@ pub struct PerformanceEstimate {
     pub execution_time_before: Option<String>,
     pub execution_time_after: Option<String>,
     pub resource_usage_impact: Option<String>,
+    // Planner output for the original and rewritten query, when EXPLAIN could be run
+    #[serde(default)]
+    pub original_plan: Option<serde_json::Value>,
+    #[serde(default)]
+    pub optimized_plan: Option<serde_json::Value>,
+    #[serde(default)]
+    pub estimated_cost_before: Option<f64>,
+    #[serde(default)]
+    pub estimated_cost_after: Option<f64>,
+    /// Rewritten cost minus original cost; negative means cheaper
+    #[serde(default)]
+    pub estimated_cost_delta: Option<f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -229,13 +372,24 @@ pub struct QuickFix {
 pub struct AIAnalysisProgress {
     pub analysis_id: String,
     pub task_type: AITaskType,
-    pub stage: String, // "analyzing", "processing", "formatting", etc.
+    pub stage: ProgressStage,
     pub progress_percent: Option<u8>,
     pub message: String,
     pub timestamp: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone)]
+/// Terminal event sent once per analysis, whether it succeeded or not
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AIAnalysisComplete {
+    pub analysis_id: String,
+    pub task_type: AITaskType,
+    pub success: bool,
+    pub error_message: Option<String>,
+    pub execution_time_ms: Option<u64>,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AITaskInfo {
     pub task_id: String,
     pub task_type: AITaskType,
@@ -244,7 +398,22 @@ pub struct AITaskInfo {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// An in-flight analysis as listed by `get_active_ai_tasks`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveAITask {
+    #[serde(flatten)]
+    pub task: AITaskInfo,
+    pub age_seconds: i64,
+}
+
+impl ActiveAITask {
+    pub fn new(task: AITaskInfo, now: DateTime<Utc>) -> Self {
+        let age_seconds = now.signed_duration_since(task.created_at).num_seconds().max(0);
+        Self { task, age_seconds }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum AITaskStatus {
     Pending,
     Processing,
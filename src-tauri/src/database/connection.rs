@@ -1,11 +1,13 @@
 use crate::database::drivers::{DatabaseDriver, DatabaseDriverFactory};
+use crate::database::pool::DatabasePool;
 use crate::database::types::{
-    ConnectionConfig, ConnectionError, ConnectionResult, ConnectionStats,
-    ConnectionStatus, ConnectionTestResult, DatabaseCredentials,
+    ConnectionConfig, ConnectionError, ConnectionFailureKind, ConnectionResult, ConnectionStats,
+    ConnectionStatus, ConnectionTestResult, DatabaseCredentials, SslMode,
 };
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 /// Represents an active database connection with its associated metadata
@@ -15,10 +17,32 @@ pub struct DatabaseConnection {
     pub status: Arc<RwLock<ConnectionStatus>>,
     pub stats: Arc<RwLock<ConnectionStats>>,
     driver: Box<dyn DatabaseDriver>,
+    /// Long-lived pool, opened on first use and closed by `disconnect`
+    pool: Arc<RwLock<Option<DatabasePool>>>,
+    /// Version reported by the last full connection test, reused for pooled pings
+    server_version: Arc<RwLock<Option<String>>>,
     created_at: Instant,
     last_activity: Arc<RwLock<Instant>>,
 }
 
+/// A copy shares the original's pool, status and statistics; only the config and driver are
+/// its own. The manager copies a connection to change its config while operations still
+/// hold the old one.
+impl Clone for DatabaseConnection {
+    fn clone(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            status: Arc::clone(&self.status),
+            stats: Arc::clone(&self.stats),
+            driver: DatabaseDriverFactory::create_driver(&self.config.database_type),
+            pool: Arc::clone(&self.pool),
+            server_version: Arc::clone(&self.server_version),
+            created_at: self.created_at,
+            last_activity: Arc::clone(&self.last_activity),
+        }
+    }
+}
+
 impl DatabaseConnection {
     /// Create a new database connection
     pub fn new(config: ConnectionConfig) -> Self {
@@ -33,6 +57,8 @@ impl DatabaseConnection {
             status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
             stats: Arc::new(RwLock::new(stats)),
             driver,
+            pool: Arc::new(RwLock::new(None)),
+            server_version: Arc::new(RwLock::new(None)),
             created_at: now,
             last_activity: Arc::new(RwLock::new(now)),
         }
@@ -40,6 +66,15 @@ impl DatabaseConnection {
 
     /// Test the connection to the database
     pub async fn test_connection(&self, credentials: &DatabaseCredentials) -> ConnectionResult<ConnectionTestResult> {
+        self.test_connection_with_cancel(credentials, None).await
+    }
+
+    /// Test the connection, aborting early if the token is cancelled
+    pub async fn test_connection_with_cancel(
+        &self,
+        credentials: &DatabaseCredentials,
+        cancel: Option<&CancellationToken>,
+    ) -> ConnectionResult<ConnectionTestResult> {
         // Update status to testing
         {
             let mut status = self.status.write().await;
@@ -52,8 +87,11 @@ impl DatabaseConnection {
             *last_activity = Instant::now();
         }
 
-        // Perform the actual connection test
-        let result = self.driver.test_connection(&self.config, credentials).await;
+        // An open pool only needs a round trip; a failed one is replaced after a full test
+        let result = match self.ping_pool(cancel).await {
+            Some(result) => result,
+            None => self.test_and_open_pool(credentials, cancel).await,
+        };
 
         // Update status based on result
         {
@@ -89,6 +127,78 @@ impl DatabaseConnection {
         result
     }
 
+    /// Round-trip through the open pool, if there is one and it still works
+    async fn ping_pool(&self, cancel: Option<&CancellationToken>) -> Option<ConnectionResult<ConnectionTestResult>> {
+        let pool = self.current_pool().await?;
+        let start_time = Instant::now();
+
+        match self.driver.ping(&pool, cancel).await {
+            Ok(()) => {
+                let version = self.server_version.read().await.clone();
+                Some(Ok(ConnectionTestResult::success(start_time.elapsed().as_millis() as u64, version)))
+            }
+            Err(e @ ConnectionError::Cancelled(_)) => Some(Err(e)),
+            Err(e) => {
+                log::debug!("Pooled ping for {} failed, reconnecting: {}", self.config.id, e);
+                self.close_pool().await;
+                None
+            }
+        }
+    }
+
+    /// Run the driver's full connection test and, if it passes, open the pool for later use
+    async fn test_and_open_pool(
+        &self,
+        credentials: &DatabaseCredentials,
+        cancel: Option<&CancellationToken>,
+    ) -> ConnectionResult<ConnectionTestResult> {
+        let result = self.driver.test_connection(&self.config, credentials, cancel).await?;
+        if !result.success {
+            return Ok(result);
+        }
+        *self.server_version.write().await = result.server_version.clone();
+
+        match self.get_or_create_pool(credentials).await {
+            Ok(_) => Ok(result),
+            Err(e) => Ok(ConnectionTestResult::failure_of_kind(
+                ConnectionFailureKind::from_connection_error(&e),
+                e.to_string(),
+            )),
+        }
+    }
+
+    /// Get the pool, opening it (sized by `config.max_connections`) on first use
+    pub async fn get_or_create_pool(&self, credentials: &DatabaseCredentials) -> ConnectionResult<DatabasePool> {
+        if let Some(pool) = self.current_pool().await {
+            return Ok(pool);
+        }
+
+        let mut pool = self.pool.write().await;
+        // Another caller may have opened the pool while we waited for the lock
+        if let Some(existing) = pool.as_ref().filter(|p| !p.is_closed()) {
+            return Ok(existing.clone());
+        }
+
+        let opened = self.driver.create_pool(&self.config, credentials).await?;
+        *pool = Some(opened.clone());
+        log::debug!("Opened connection pool for {}", self.config.id);
+        Ok(opened)
+    }
+
+    /// The pool, if it has been opened and not closed since
+    pub async fn current_pool(&self) -> Option<DatabasePool> {
+        self.pool.read().await.as_ref().filter(|pool| !pool.is_closed()).cloned()
+    }
+
+    /// Close the pool; the next operation opens a fresh one
+    pub async fn close_pool(&self) {
+        let pool = self.pool.write().await.take();
+        if let Some(pool) = pool {
+            pool.close().await;
+            log::debug!("Closed connection pool for {}", self.config.id);
+        }
+    }
+
     /// Get the current connection status
     pub async fn get_status(&self) -> ConnectionStatus {
         let status = self.status.read().await;
@@ -99,6 +209,11 @@ impl DatabaseConnection {
     pub async fn get_stats(&self) -> ConnectionStats {
         let mut stats = self.stats.read().await.clone();
         stats.uptime_seconds = self.created_at.elapsed().as_secs();
+        if let Some(pool) = self.current_pool().await {
+            stats.pool_size = pool.size();
+            stats.idle_connections = pool.num_idle().min(stats.pool_size);
+            stats.active_connections = stats.pool_size - stats.idle_connections;
+        }
         stats
     }
 
@@ -153,8 +268,9 @@ impl DatabaseConnection {
         *last_activity = Instant::now();
     }
 
-    /// Disconnect the connection
+    /// Disconnect the connection, closing its pool
     pub async fn disconnect(&self) {
+        self.close_pool().await;
         let mut status = self.status.write().await;
         *status = ConnectionStatus::Disconnected;
     }
@@ -241,8 +357,8 @@ impl ConnectionBuilder {
         self
     }
 
-    pub fn ssl_enabled(mut self, enabled: bool) -> Self {
-        self.config.ssl_enabled = enabled;
+    pub fn ssl_mode(mut self, mode: SslMode) -> Self {
+        self.config.ssl_mode = mode;
         self
     }
 
@@ -299,7 +415,7 @@ mod tests {
             .port(5432)
             .database("testdb".to_string())
             .username("testuser".to_string())
-            .ssl_enabled(true)
+            .ssl_mode(SslMode::VerifyFull)
             .build_with_config();
 
         assert_eq!(config.name, "Test");
@@ -307,7 +423,7 @@ mod tests {
         assert_eq!(config.port, 5432);
         assert_eq!(config.database, "testdb");
         assert_eq!(config.username, "testuser");
-        assert!(config.ssl_enabled);
+        assert_eq!(config.ssl_mode, SslMode::VerifyFull);
     }
 
     #[test]
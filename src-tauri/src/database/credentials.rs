@@ -1,5 +1,5 @@
-use crate::database::security::SecureCredentialManager;
-use crate::database::types::{ConnectionConfig, ConnectionError, ConnectionResult, DatabaseCredentials};
+use crate::database::security::{AuditFile, SecureCredentialManager};
+use crate::database::types::{ConnectionConfig, ConnectionError, ConnectionResult, DatabaseCredentials, DatabaseType};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
@@ -14,8 +14,17 @@ pub struct CredentialManager {
 
 impl CredentialManager {
     pub fn new() -> Self {
+        Self::with_secure_manager(SecureCredentialManager::new())
+    }
+
+    /// Persist the security audit to `audit_file`, or nowhere if it is `None`
+    pub fn with_audit_file(audit_file: Option<AuditFile>) -> Self {
+        Self::with_secure_manager(SecureCredentialManager::with_audit_file(audit_file))
+    }
+
+    fn with_secure_manager(secure_manager: SecureCredentialManager) -> Self {
         Self {
-            secure_manager: Arc::new(SecureCredentialManager::new()),
+            secure_manager: Arc::new(secure_manager),
             config_cache: Arc::new(RwLock::new(std::collections::HashMap::new())),
         }
     }
@@ -195,7 +204,8 @@ impl CredentialManager {
             return Err(ConnectionError::ConfigurationError("Username cannot be empty".to_string()));
         }
 
-        if config.port == 0 || config.port > 65535 {
+        // SQLite is file-based and has no port
+        if config.database_type != DatabaseType::SQLite && config.port == 0 {
             return Err(ConnectionError::ConfigurationError("Invalid port number".to_string()));
         }
 
@@ -207,6 +217,31 @@ impl CredentialManager {
             return Err(ConnectionError::ConfigurationError("Max connections must be between 1 and 100".to_string()));
         }
 
+        if let Some(schema) = &config.default_schema {
+            if schema.trim().is_empty() || schema.len() > 128 || schema.contains('\0') {
+                return Err(ConnectionError::ConfigurationError(
+                    "Default schema must be a non-empty name of at most 128 characters".to_string()
+                ));
+            }
+        }
+
+        if config.metadata.len() > 50 {
+            return Err(ConnectionError::ConfigurationError("At most 50 metadata entries are allowed".to_string()));
+        }
+
+        for (key, value) in &config.metadata {
+            if key.trim().is_empty() || key.chars().count() > 64 {
+                return Err(ConnectionError::ConfigurationError(
+                    "Metadata keys must be non-empty and at most 64 characters".to_string()
+                ));
+            }
+            if value.chars().count() > 1024 {
+                return Err(ConnectionError::ConfigurationError(
+                    format!("Metadata value for '{}' exceeds 1024 characters", key)
+                ));
+            }
+        }
+
         Ok(())
     }
 
@@ -235,7 +270,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_credential_manager_basic_operations() {
-        let manager = CredentialManager::new();
+        let manager = CredentialManager::with_audit_file(None);
         
         let config = ConnectionConfig::new(
             "Test Connection".to_string(),
@@ -269,7 +304,7 @@ mod tests {
 
     #[test]
     fn test_config_validation() {
-        let manager = CredentialManager::new();
+        let manager = CredentialManager::with_audit_file(None);
         
         let mut config = ConnectionConfig::new(
             "Test".to_string(),
@@ -0,0 +1,75 @@
+use crate::database::types::{ConnectionConfig, DatabaseCredentials};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Placeholder written wherever a secret was left out of an export
+pub const REDACTED_SECRET: &str = "<redacted: re-enter after import>";
+
+/// Current format of the connection export bundle
+pub const EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// Portable, secret-free set of connection definitions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionExportBundle {
+    pub version: u32,
+    pub exported_at: chrono::DateTime<chrono::Utc>,
+    pub connections: Vec<ExportedConnection>,
+}
+
+/// A connection's full non-secret configuration plus the locations of its TLS files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedConnection {
+    pub config: ConnectionConfig,
+    /// Always `REDACTED_SECRET` in exports; a real value is accepted on import
+    pub password: String,
+    pub certificate_path: Option<String>,
+    pub private_key_path: Option<String>,
+    pub ca_certificate_path: Option<String>,
+}
+
+impl ExportedConnection {
+    /// Export a connection, keeping file locations but never the password
+    pub fn redacted(config: ConnectionConfig, credentials: Option<&DatabaseCredentials>) -> Self {
+        Self {
+            config,
+            password: REDACTED_SECRET.to_string(),
+            certificate_path: credentials.and_then(|c| c.certificate_path.clone()),
+            private_key_path: credentials.and_then(|c| c.private_key_path.clone()),
+            ca_certificate_path: credentials.and_then(|c| c.ca_certificate_path.clone()),
+        }
+    }
+
+    /// Whether the user must supply the password before this connection can be used
+    pub fn needs_password(&self) -> bool {
+        self.password.is_empty() || self.password == REDACTED_SECRET
+    }
+
+    /// Credentials for the imported connection, using the bundle's password if it carried one
+    pub fn credentials_for(&self, connection_id: Uuid) -> DatabaseCredentials {
+        let password = if self.needs_password() { String::new() } else { self.password.clone() };
+        let mut credentials = DatabaseCredentials::new(connection_id, password);
+        credentials.certificate_path = self.certificate_path.clone();
+        credentials.private_key_path = self.private_key_path.clone();
+        credentials.ca_certificate_path = self.ca_certificate_path.clone();
+        credentials
+    }
+}
+
+/// A connection imported without a usable secret
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingCredentials {
+    pub connection_id: Uuid,
+    pub name: String,
+    /// TLS file locations from the bundle, to pre-fill the credential prompt
+    pub certificate_path: Option<String>,
+    pub private_key_path: Option<String>,
+    pub ca_certificate_path: Option<String>,
+}
+
+/// Outcome of importing a connection bundle
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConnectionImportResult {
+    pub imported: Vec<Uuid>,
+    pub needs_credentials: Vec<PendingCredentials>,
+    pub errors: Vec<String>,
+}
@@ -1,23 +1,52 @@
 use crate::database::connection::{ConnectionSummary, DatabaseConnection};
 use crate::database::credentials::CredentialManager;
+use crate::database::drivers::{redact_connection_string, DatabaseDriverFactory, DatabaseFeature};
+use crate::database::export::{
+    ConnectionExportBundle, ConnectionImportResult, ExportedConnection, PendingCredentials, EXPORT_FORMAT_VERSION,
+};
+use crate::database::policy::{check_read_only, classify_statements, StatementKind};
+use crate::database::pool::{DatabasePool, PoolMetrics};
+use crate::database::result_sql::RecentResult;
+use crate::database::security::AuditFile;
+use crate::database::schema::{DatabaseSchema, DistinctValues, ForeignKeyGraph, IndexUsageReport, SchemaFilter};
 use crate::database::types::{
-    ConnectionConfig, ConnectionError, ConnectionResult, ConnectionTestResult,
-    DatabaseCredentials, DatabaseType,
+    ConnectionConfig, ConnectionError, ConnectionFailureKind, ConnectionResult, ConnectionSort, ConnectionStats,
+    ConnectionStatus, ConnectionStatusChange, ConnectionTestProgress, ConnectionTestResult, DatabaseCredentials, DatabaseType, QueryOutput, QueryPlan,
+    ResultColumnInfo, RunningQuery, TransactionOutcome, TransactionStatementResult,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, OwnedSemaphorePermit, RwLock, Semaphore};
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
+/// Upper bound on connections tested at once by `test_all_connections`
+const BULK_TEST_CONCURRENCY: usize = 8;
+
+/// Finished query results kept for `recent_result`, oldest dropped first
+const RECENT_RESULTS_KEPT: usize = 20;
+
 /// Main connection manager that coordinates all database operations
 #[derive(Debug)]
 pub struct ConnectionManager {
-    /// Active database connections
-    connections: Arc<RwLock<HashMap<Uuid, DatabaseConnection>>>,
+    /// Active database connections, each owning its lazily opened pool
+    connections: Arc<RwLock<HashMap<Uuid, Arc<DatabaseConnection>>>>,
+    /// Queries in flight, keyed by the caller's query id
+    running_queries: Arc<RwLock<HashMap<String, (RunningQuery, CancellationToken)>>>,
+    /// Most recent query results, newest last
+    recent_results: Arc<RwLock<VecDeque<RecentResult>>>,
     /// Credential manager for secure storage
     credential_manager: Arc<CredentialManager>,
     /// Manager configuration
     config: ConnectionManagerConfig,
+    /// One permit per query allowed in flight across all connections
+    query_slots: Arc<Semaphore>,
+    /// Status changes found by the health check
+    status_broadcaster: broadcast::Sender<ConnectionStatusChange>,
+    /// Stops the cleanup and health check loops when the manager is dropped
+    background_tasks: CancellationToken,
 }
 
 #[derive(Debug, Clone)]
@@ -27,6 +56,18 @@ pub struct ConnectionManagerConfig {
     pub idle_timeout_seconds: u32,
     pub auto_cleanup_enabled: bool,
     pub auto_cleanup_interval_seconds: u32,
+    /// Default cap on rows returned by a single query. This is a safety net that
+    /// keeps a runaway `SELECT *` from exhausting memory, not a pagination mechanism.
+    pub default_max_rows: usize,
+    /// Queries allowed to run at once across all connections
+    pub max_concurrent_queries: usize,
+    /// How long a query waits for a free slot before failing
+    pub query_queue_timeout_seconds: u32,
+    /// How often every open pool is pinged to catch servers that went away; `None` disables
+    /// the health check
+    pub health_check_interval_seconds: Option<u32>,
+    /// Where credential access is audited; `None` keeps the audit in memory only
+    pub security_audit_file: Option<AuditFile>,
 }
 
 impl Default for ConnectionManagerConfig {
@@ -37,6 +78,13 @@ impl Default for ConnectionManagerConfig {
             idle_timeout_seconds: 300, // 5 minutes
             auto_cleanup_enabled: true,
             auto_cleanup_interval_seconds: 60, // 1 minute
+            default_max_rows: 10_000,
+            max_concurrent_queries: 16,
+            query_queue_timeout_seconds: 30,
+            health_check_interval_seconds: Some(30),
+            security_audit_file: AuditFile::default_location()
+                .map_err(|e| log::warn!("Security audit will not be persisted: {}", e))
+                .ok(),
         }
     }
 }
@@ -44,52 +92,70 @@ impl Default for ConnectionManagerConfig {
 impl ConnectionManager {
     /// Create a new connection manager
     pub async fn new() -> ConnectionResult<Self> {
-        let credential_manager = Arc::new(CredentialManager::new());
+        let config = ConnectionManagerConfig::default();
+        let credential_manager = Arc::new(CredentialManager::with_audit_file(config.security_audit_file.clone()));
         
         // Load existing connections from storage
         credential_manager.load_connections().await?;
         
         let manager = Self {
             connections: Arc::new(RwLock::new(HashMap::new())),
+            running_queries: Arc::new(RwLock::new(HashMap::new())),
+            recent_results: Arc::new(RwLock::new(VecDeque::new())),
             credential_manager,
-            config: ConnectionManagerConfig::default(),
+            query_slots: Arc::new(Semaphore::new(config.max_concurrent_queries.max(1))),
+            config,
+            status_broadcaster: broadcast::channel(32).0,
+            background_tasks: CancellationToken::new(),
         };
 
         // Start background cleanup task if enabled
         if manager.config.auto_cleanup_enabled {
             manager.start_cleanup_task().await;
         }
+        manager.start_health_check_task();
 
         Ok(manager)
     }
 
     /// Create a new connection manager with custom configuration
     pub async fn new_with_config(config: ConnectionManagerConfig) -> ConnectionResult<Self> {
-        let credential_manager = Arc::new(CredentialManager::new());
+        let credential_manager = Arc::new(CredentialManager::with_audit_file(config.security_audit_file.clone()));
         credential_manager.load_connections().await?;
         
         let manager = Self {
             connections: Arc::new(RwLock::new(HashMap::new())),
+            running_queries: Arc::new(RwLock::new(HashMap::new())),
+            recent_results: Arc::new(RwLock::new(VecDeque::new())),
             credential_manager,
+            query_slots: Arc::new(Semaphore::new(config.max_concurrent_queries.max(1))),
             config,
+            status_broadcaster: broadcast::channel(32).0,
+            background_tasks: CancellationToken::new(),
         };
 
         if manager.config.auto_cleanup_enabled {
             manager.start_cleanup_task().await;
         }
+        manager.start_health_check_task();
 
         Ok(manager)
     }
 
-    /// Add a new database connection
+    /// Add a new database connection; `force` allows adding a duplicate of an existing one
     pub async fn add_connection(
         &self,
         config: ConnectionConfig,
         credentials: DatabaseCredentials,
+        force: bool,
     ) -> ConnectionResult<Uuid> {
         // Validate configuration
         self.credential_manager.validate_config(&config)?;
 
+        // Apply driver-specific rules (e.g. SQLite needs a file path) before anything is stored
+        let driver = DatabaseDriverFactory::try_create_driver(&config.database_type)?;
+        driver.validate_config(&config)?;
+
         // Check connection limit
         {
             let connections = self.connections.read().await;
@@ -100,6 +166,16 @@ impl ConnectionManager {
             }
         }
 
+        // Refuse redundant connections unless the caller insists
+        if !force {
+            if let Some(existing) = self.find_duplicate_connection(&config).await {
+                return Err(ConnectionError::DuplicateConnection(format!(
+                    "'{}' already points at the same host, port, database and user",
+                    existing.name
+                )));
+            }
+        }
+
         // Store credentials and configuration
         self.credential_manager.store_connection(config.clone(), credentials).await?;
 
@@ -110,7 +186,7 @@ impl ConnectionManager {
         // Add to active connections
         {
             let mut connections = self.connections.write().await;
-            connections.insert(connection_id, connection);
+            connections.insert(connection_id, Arc::new(connection));
         }
 
         // Save connections to persistent storage
@@ -119,9 +195,193 @@ impl ConnectionManager {
         Ok(connection_id)
     }
 
+    /// Like `add_connection`, but first connects with the given settings; nothing is stored
+    /// unless that test succeeds, and a failure carries the server's message
+    pub async fn add_verified_connection(
+        &self,
+        config: ConnectionConfig,
+        credentials: DatabaseCredentials,
+        force: bool,
+    ) -> ConnectionResult<Uuid> {
+        // Reject an invalid config before making a network round-trip
+        self.credential_manager.validate_config(&config)?;
+        let driver = DatabaseDriverFactory::try_create_driver(&config.database_type)?;
+        driver.validate_config(&config)?;
+
+        let result = driver.test_connection(&config, &credentials, None).await?;
+        if !result.success {
+            return Err(ConnectionError::ConnectionFailed(format!(
+                "Connection test failed: {}",
+                result.message
+            )));
+        }
+
+        self.add_connection(config, credentials, force).await
+    }
+
+    /// Run every check `add_connection` would, collecting problems instead of storing anything
+    pub async fn dry_run_add_connection(
+        &self,
+        config: &ConnectionConfig,
+        credentials: &DatabaseCredentials,
+    ) -> AddConnectionReport {
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+
+        if config.id != credentials.connection_id {
+            errors.push("Connection ID mismatch between config and credentials".to_string());
+        }
+
+        if let Err(e) = self.credential_manager.validate_config(config) {
+            errors.push(e.to_string());
+        }
+
+        match DatabaseDriverFactory::try_create_driver(&config.database_type) {
+            Ok(driver) => {
+                if let Err(e) = driver.validate_config(config) {
+                    errors.push(e.to_string());
+                }
+            }
+            Err(e) => errors.push(e.to_string()),
+        }
+
+        {
+            let connections = self.connections.read().await;
+            if connections.len() >= self.config.max_connections {
+                errors.push(format!("Maximum number of connections ({}) reached", self.config.max_connections));
+            }
+        }
+
+        let name = config.name.trim().to_lowercase();
+        if let Some(existing) = self
+            .list_connections()
+            .await
+            .into_iter()
+            .find(|existing| existing.name.trim().to_lowercase() == name)
+        {
+            warnings.push(format!("A connection named '{}' already exists ({})", existing.name, existing.id));
+        }
+
+        if let Some(existing) = self.find_duplicate_connection(config).await {
+            warnings.push(format!(
+                "'{}' already points at the same host, port, database and user",
+                existing.name
+            ));
+        }
+
+        AddConnectionReport {
+            would_succeed: errors.is_empty(),
+            errors,
+            warnings,
+        }
+    }
+
+    /// Find another stored connection targeting the same host/port/database/username
+    pub async fn find_duplicate_connection(&self, config: &ConnectionConfig) -> Option<ConnectionConfig> {
+        let hash = self.credential_manager.generate_connection_hash(config);
+        self.list_connections()
+            .await
+            .into_iter()
+            .find(|existing| {
+                existing.id != config.id
+                    && self.credential_manager.generate_connection_hash(existing) == hash
+            })
+    }
+
+    /// Export every connection's non-secret configuration
+    pub async fn export_connections(&self) -> ConnectionExportBundle {
+        let mut connections = Vec::new();
+        for config in self.list_connections().await {
+            // Missing keychain entries only cost the TLS paths, so export the config regardless
+            let credentials = self.credential_manager.get_credentials(config.id).await.ok();
+            connections.push(ExportedConnection::redacted(config, credentials.as_ref()));
+        }
+
+        ConnectionExportBundle {
+            version: EXPORT_FORMAT_VERSION,
+            exported_at: chrono::Utc::now(),
+            connections,
+        }
+    }
+
+    /// Import connections from a bundle, giving each a fresh ID.
+    /// Redacted entries are stored without credentials and reported in `needs_credentials`.
+    pub async fn import_connections(&self, bundle: ConnectionExportBundle) -> ConnectionResult<ConnectionImportResult> {
+        if bundle.version > EXPORT_FORMAT_VERSION {
+            return Err(ConnectionError::ConfigurationError(format!(
+                "Unsupported export format version {}",
+                bundle.version
+            )));
+        }
+
+        let mut result = ConnectionImportResult::default();
+
+        for exported in bundle.connections {
+            let now = chrono::Utc::now();
+            let mut config = exported.config.clone();
+            config.id = Uuid::new_v4();
+            config.created_at = now;
+            config.updated_at = now;
+            let name = config.name.clone();
+
+            if !exported.needs_password() {
+                match self.add_connection(config.clone(), exported.credentials_for(config.id), false).await {
+                    Ok(id) => result.imported.push(id),
+                    Err(e) => result.errors.push(format!("{}: {}", name, e)),
+                }
+                continue;
+            }
+
+            let validation = self.credential_manager.validate_config(&config).and_then(|_| {
+                DatabaseDriverFactory::try_create_driver(&config.database_type)?.validate_config(&config)
+            });
+            if let Err(e) = validation {
+                result.errors.push(format!("{}: {}", name, e));
+                continue;
+            }
+            if let Some(existing) = self.find_duplicate_connection(&config).await {
+                result.errors.push(format!("{}: duplicates existing connection '{}'", name, existing.name));
+                continue;
+            }
+
+            // Without a password there is nothing to put in the keychain yet
+            self.credential_manager.update_connection_config(config.clone()).await?;
+            result.imported.push(config.id);
+            result.needs_credentials.push(PendingCredentials {
+                connection_id: config.id,
+                name,
+                certificate_path: exported.certificate_path,
+                private_key_path: exported.private_key_path,
+                ca_certificate_path: exported.ca_certificate_path,
+            });
+        }
+
+        self.credential_manager.save_connections().await?;
+
+        Ok(result)
+    }
+
+    /// Stored connections whose password is not in the keychain, e.g. after the connection
+    /// file was restored on another machine; the UI should ask for these to be re-entered
+    pub async fn connections_missing_credentials(&self) -> Vec<PendingCredentials> {
+        let mut missing = Vec::new();
+        for config in self.list_connections().await {
+            if !self.credential_manager.credentials_exist(config.id).await {
+                missing.push(PendingCredentials {
+                    connection_id: config.id,
+                    name: config.name,
+                    certificate_path: None,
+                    private_key_path: None,
+                    ca_certificate_path: None,
+                });
+            }
+        }
+        missing
+    }
+
     /// Remove a database connection
     pub async fn remove_connection(&self, connection_id: Uuid) -> ConnectionResult<()> {
-        // Remove from active connections
+        // Remove from active connections, closing the pool if one was opened
         {
             let mut connections = self.connections.write().await;
             if let Some(connection) = connections.remove(&connection_id) {
@@ -139,19 +399,24 @@ impl ConnectionManager {
     }
 
     /// Update connection configuration
-    pub async fn update_connection_config(&self, config: ConnectionConfig) -> ConnectionResult<()> {
+    pub async fn update_connection_config(&self, mut config: ConnectionConfig) -> ConnectionResult<()> {
         // Validate configuration
         self.credential_manager.validate_config(&config)?;
+        DatabaseDriverFactory::try_create_driver(&config.database_type)?.validate_config(&config)?;
+
+        // Only stored connections can be updated; they keep their creation time
+        let existing = self.credential_manager.get_connection_config(config.id).await?;
+        config.created_at = existing.created_at;
+        config.updated_at = chrono::Utc::now();
 
         // Update in credential manager
         self.credential_manager.update_connection_config(config.clone()).await?;
 
-        // Update active connection if it exists
-        {
-            let mut connections = self.connections.write().await;
-            if let Some(connection) = connections.get_mut(&config.id) {
-                connection.update_config(config)?;
-            }
+        // Update active connection if it exists; pooled connections were opened with the
+        // previous settings
+        self.close_pool(config.id).await;
+        if let Some(connection) = self.connections.write().await.get_mut(&config.id) {
+            Arc::make_mut(connection).update_config(config)?;
         }
 
         // Save connections
@@ -160,6 +425,14 @@ impl ConnectionManager {
         Ok(())
     }
 
+    /// Change the schema unqualified names resolve against; the pool is reopened on next use
+    pub async fn set_default_schema(&self, connection_id: Uuid, default_schema: Option<String>) -> ConnectionResult<()> {
+        let mut config = self.credential_manager.get_connection_config(connection_id).await?;
+        config.default_schema = default_schema.filter(|schema| !schema.trim().is_empty());
+        // Also reopens the pool, whose connections were set up for the previous schema
+        self.update_connection_config(config).await
+    }
+
     /// Update connection credentials
     pub async fn update_connection_credentials(
         &self,
@@ -169,29 +442,576 @@ impl ConnectionManager {
         // Update credentials in secure storage
         self.credential_manager.update_credentials(connection_id, credentials).await?;
 
+        // The pool would otherwise keep authenticating with the old password
+        self.close_pool(connection_id).await;
+
+        // Record the change on the connection itself
+        let mut config = self.credential_manager.get_connection_config(connection_id).await?;
+        config.updated_at = chrono::Utc::now();
+        if let Some(connection) = self.connections.write().await.get_mut(&connection_id) {
+            Arc::make_mut(connection).config.updated_at = config.updated_at;
+        }
+        self.credential_manager.update_connection_config(config).await?;
+        self.credential_manager.save_connections().await?;
+
         Ok(())
     }
 
+    /// Replace a connection's password, keeping its certificate settings
+    pub async fn set_connection_password(&self, connection_id: Uuid, password: String) -> ConnectionResult<()> {
+        let mut credentials = self.credential_manager.get_credentials(connection_id).await?;
+        credentials.password = password;
+        self.update_connection_credentials(connection_id, credentials).await
+    }
+
+    /// Replace a connection's client certificate, key and CA paths, keeping its password.
+    /// The files are checked now rather than on the next connect.
+    pub async fn set_connection_certificates(
+        &self,
+        connection_id: Uuid,
+        certificate_path: Option<String>,
+        private_key_path: Option<String>,
+        ca_certificate_path: Option<String>,
+    ) -> ConnectionResult<()> {
+        let config = self.credential_manager.get_connection_config(connection_id).await?;
+        let mut credentials = self.credential_manager.get_credentials(connection_id).await?;
+        credentials.certificate_path = certificate_path;
+        credentials.private_key_path = private_key_path;
+        credentials.ca_certificate_path = ca_certificate_path;
+
+        let driver = DatabaseDriverFactory::try_create_driver(&config.database_type)?;
+        driver.build_connection_string(&config, &credentials)?;
+
+        self.update_connection_credentials(connection_id, credentials).await
+    }
+
     /// Test a connection efficiently
-    pub async fn test_connection(&self, connection_id: Uuid) -> ConnectionResult<ConnectionTestResult> {
+    pub async fn test_connection(
+        &self,
+        connection_id: Uuid,
+        cancel: Option<&CancellationToken>,
+    ) -> ConnectionResult<ConnectionTestResult> {
         // Get credentials first
         let credentials = self.credential_manager.get_credentials(connection_id).await?;
 
-        // Check if connection already exists in memory
-        {
-            let connections = self.connections.read().await;
-            if let Some(conn) = connections.get(&connection_id) {
-                // Use existing connection for testing
-                return conn.test_connection(&credentials).await;
+        // Keep the connection, and the pool a successful test opens, for later queries
+        let config = self.credential_manager.get_connection_config(connection_id).await?;
+        let connection = ensure_connection(&self.connections, config).await;
+        let result = connection.test_connection_with_cancel(&credentials, cancel).await;
+
+        self.touch_connection(connection_id).await;
+        result
+    }
+
+    /// Record that a connection was just used. Failures are logged, never surfaced,
+    /// so bookkeeping can't break the operation that triggered it
+    pub async fn touch_connection(&self, connection_id: Uuid) {
+        let updated = match self.credential_manager.get_connection_config(connection_id).await {
+            Ok(mut config) => {
+                config.last_used_at = Some(chrono::Utc::now());
+                self.store_connection_metadata(vec![config]).await
+            }
+            Err(e) => Err(e),
+        };
+
+        if let Err(e) = updated {
+            log::warn!("Failed to record last use of connection {}: {}", connection_id, e);
+        }
+    }
+
+    /// Test every stored connection concurrently, reporting each result as it arrives
+    pub async fn test_all_connections(
+        &self,
+        timeout: Duration,
+        progress: Option<mpsc::Sender<ConnectionTestProgress>>,
+    ) -> HashMap<Uuid, ConnectionTestResult> {
+        let configs = self.list_connections().await;
+        let total = configs.len();
+        let semaphore = Arc::new(Semaphore::new(BULK_TEST_CONCURRENCY));
+        let mut tasks = JoinSet::new();
+
+        for config in configs {
+            let semaphore = semaphore.clone();
+            let credential_manager = self.credential_manager.clone();
+            let connections = self.connections.clone();
+
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                let connection_id = config.id;
+
+                let test = async {
+                    let credentials = credential_manager.get_credentials(connection_id).await?;
+                    // The map is only locked to look the connection up, not while it is tested
+                    let connection = ensure_connection(&connections, config).await;
+                    connection.test_connection(&credentials).await
+                };
+
+                let result = match tokio::time::timeout(timeout, test).await {
+                    Ok(Ok(result)) => result,
+                    Ok(Err(e)) => ConnectionTestResult::failure_of_kind(
+                        ConnectionFailureKind::from_connection_error(&e),
+                        e.to_string(),
+                    ),
+                    Err(_) => ConnectionTestResult::failure_of_kind(
+                        ConnectionFailureKind::Timeout,
+                        format!("Connection test timed out after {} ms", timeout.as_millis()),
+                    ),
+                };
+
+                (connection_id, result)
+            });
+        }
+
+        let mut results = HashMap::with_capacity(total);
+        while let Some(joined) = tasks.join_next().await {
+            let (connection_id, result) = match joined {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    log::error!("Connection test task failed: {}", e);
+                    continue;
+                }
+            };
+
+            if let Some(progress) = &progress {
+                let _ = progress
+                    .send(ConnectionTestProgress {
+                        connection_id,
+                        result: result.clone(),
+                        completed: results.len() + 1,
+                        total,
+                    })
+                    .await;
+            }
+            results.insert(connection_id, result);
+        }
+
+        results
+    }
+
+    /// Get the pool for a connection, opening it on first use
+    pub async fn get_or_create_pool(&self, connection_id: Uuid) -> ConnectionResult<DatabasePool> {
+        if let Some(connection) = self.active_connection(connection_id).await {
+            if let Some(pool) = connection.current_pool().await {
+                connection.mark_active().await;
+                return Ok(pool);
             }
         }
 
-        // Connection not in memory, create temporary connection for testing
         let config = self.credential_manager.get_connection_config(connection_id).await?;
-        let temp_connection = DatabaseConnection::new(config);
+        let credentials = self.credential_manager.get_credentials(connection_id).await?;
+        let connection = ensure_connection(&self.connections, config).await;
+        connection.mark_active().await;
+        connection.get_or_create_pool(&credentials).await
+    }
 
-        // Perform test with temporary connection
-        temp_connection.test_connection(&credentials).await
+    /// The connection if it is active, taken out of the map so no lock is held while it is used
+    async fn active_connection(&self, connection_id: Uuid) -> Option<Arc<DatabaseConnection>> {
+        self.connections.read().await.get(&connection_id).cloned()
+    }
+
+    /// Close a connection's pool; the next operation opens a fresh one
+    async fn close_pool(&self, connection_id: Uuid) {
+        if let Some(connection) = self.active_connection(connection_id).await {
+            connection.close_pool().await;
+        }
+    }
+
+    /// The connection string the driver would use, with the password masked
+    pub async fn preview_connection_string(&self, connection_id: Uuid) -> ConnectionResult<String> {
+        let config = self.credential_manager.get_connection_config(connection_id).await?;
+        let credentials = self.credential_manager.get_credentials(connection_id).await?;
+        let driver = DatabaseDriverFactory::try_create_driver(&config.database_type)?;
+
+        let mut preview = redact_connection_string(&driver.build_connection_string(&config, &credentials)?);
+
+        // Belt and braces: mask the password wherever else it might appear, raw or encoded.
+        // Very short passwords are skipped as they would mask unrelated characters.
+        if credentials.password.chars().count() >= 4 {
+            let encoded = urlencoding::encode(&credentials.password).into_owned();
+            preview = preview.replace(&encoded, "****").replace(&credentials.password, "****");
+        }
+
+        Ok(preview)
+    }
+
+    /// Get pool utilization metrics for a connection
+    pub async fn get_pool_metrics(&self, connection_id: Uuid) -> ConnectionResult<PoolMetrics> {
+        let config = self.credential_manager.get_connection_config(connection_id).await?;
+        let stats = self.get_connection_stats(connection_id).await;
+
+        let pool = match self.connections.read().await.get(&connection_id) {
+            Some(connection) => connection.current_pool().await,
+            None => None,
+        };
+        Ok(match pool {
+            Some(pool) => pool.metrics(stats),
+            None => PoolMetrics::unopened(config.max_connections, stats),
+        })
+    }
+
+    /// Get pool utilization metrics for every stored connection
+    pub async fn get_all_pool_metrics(&self) -> Vec<PoolMetrics> {
+        let mut metrics = Vec::new();
+        for config in self.list_connections().await {
+            if let Ok(pool_metrics) = self.get_pool_metrics(config.id).await {
+                metrics.push(pool_metrics);
+            }
+        }
+        metrics
+    }
+
+    /// Wait for one of the `max_concurrent_queries` slots. Hold the permit for the whole
+    /// execution; dropping it frees the slot for the next query. The manager's own query
+    /// methods (`execute_query`, `execute_transaction`, `fetch_query_page`, `update_row`,
+    /// `explain_query`, `get_column_distinct_values`) take a slot themselves.
+    pub async fn acquire_query_slot(&self) -> ConnectionResult<OwnedSemaphorePermit> {
+        let wait = Duration::from_secs(self.config.query_queue_timeout_seconds as u64);
+        match tokio::time::timeout(wait, Arc::clone(&self.query_slots).acquire_owned()).await {
+            Ok(Ok(permit)) => Ok(permit),
+            Ok(Err(_)) => Err(ConnectionError::InternalError("Query slots are closed".to_string())),
+            Err(_) => Err(ConnectionError::TimeoutError(format!(
+                "Too many concurrent queries (limit {}); no slot freed up within {}s",
+                self.config.max_concurrent_queries, self.config.query_queue_timeout_seconds
+            ))),
+        }
+    }
+
+    /// Queries currently holding a slot
+    pub fn running_query_count(&self) -> usize {
+        self.config.max_concurrent_queries.max(1) - self.query_slots.available_permits()
+    }
+
+    /// Introspect the schemas, tables, views, columns and indexes of the connection's database
+    /// that match `filter`
+    pub async fn introspect_schema(
+        &self,
+        connection_id: Uuid,
+        include_system_objects: bool,
+        filter: &SchemaFilter,
+        cancel: Option<&CancellationToken>,
+    ) -> ConnectionResult<DatabaseSchema> {
+        let config = self.credential_manager.get_connection_config(connection_id).await?;
+        let pool = self.get_or_create_pool(connection_id).await?;
+        let driver = DatabaseDriverFactory::create_driver(&config.database_type);
+
+        driver.introspect_schema(&pool, &config.database, include_system_objects, filter, cancel).await
+    }
+
+    /// Build the foreign-key relationship graph for a schema
+    pub async fn get_foreign_key_graph(
+        &self,
+        connection_id: Uuid,
+        database: &str,
+        schema: &str,
+        cancel: Option<&CancellationToken>,
+    ) -> ConnectionResult<ForeignKeyGraph> {
+        let config = self.credential_manager.get_connection_config(connection_id).await?;
+        let pool = self.get_or_create_pool(connection_id).await?;
+        let driver = DatabaseDriverFactory::create_driver(&config.database_type);
+
+        let foreign_keys = driver.introspect_foreign_keys(&pool, database, schema, cancel).await?;
+        Ok(ForeignKeyGraph::from_foreign_keys(foreign_keys))
+    }
+
+    /// Get per-index usage statistics for a table
+    pub async fn get_index_usage(
+        &self,
+        connection_id: Uuid,
+        database: &str,
+        schema: &str,
+        table: &str,
+        cancel: Option<&CancellationToken>,
+    ) -> ConnectionResult<IndexUsageReport> {
+        let config = self.credential_manager.get_connection_config(connection_id).await?;
+        let pool = self.get_or_create_pool(connection_id).await?;
+        let driver = DatabaseDriverFactory::create_driver(&config.database_type);
+
+        driver.get_index_usage(&pool, database, schema, table, cancel).await
+    }
+
+    /// EXPLAIN a query, if the connection's statement policy allows both EXPLAIN and the query itself
+    pub async fn explain_query(&self, connection_id: Uuid, sql: &str) -> ConnectionResult<QueryPlan> {
+        let config = self.credential_manager.get_connection_config(connection_id).await?;
+        if !config.statement_policy.allows(StatementKind::Explain) {
+            return Err(ConnectionError::PolicyViolation(
+                "EXPLAIN statements are not allowed on this connection".to_string(),
+            ));
+        }
+        config.check_statement(sql)?;
+
+        let _query_slot = self.acquire_query_slot().await?;
+        let pool = self.get_or_create_pool(connection_id).await?;
+        let driver = DatabaseDriverFactory::create_driver(&config.database_type);
+
+        driver.explain_query(&pool, sql, None).await
+    }
+
+    /// Track a query as running; the returned token is cancelled by `cancel_query`
+    pub async fn begin_query(&self, query_id: &str, connection_id: Uuid, sql: &str) -> CancellationToken {
+        let token = CancellationToken::new();
+        let query = RunningQuery {
+            query_id: query_id.to_string(),
+            connection_id,
+            sql: sql.to_string(),
+            started_at: chrono::Utc::now(),
+        };
+
+        self.running_queries
+            .write()
+            .await
+            .insert(query_id.to_string(), (query, token.clone()));
+        token
+    }
+
+    /// Stop tracking a query once it has finished, successfully or not
+    pub async fn finish_query(&self, query_id: &str) {
+        self.running_queries.write().await.remove(query_id);
+    }
+
+    /// Queries currently in flight
+    pub async fn running_queries(&self) -> Vec<RunningQuery> {
+        let queries = self.running_queries.read().await;
+        queries.values().map(|(query, _)| query.clone()).collect()
+    }
+
+    /// Keep a finished query's result so it can be looked up by query id
+    pub async fn remember_result(&self, result: RecentResult) {
+        let mut results = self.recent_results.write().await;
+        results.retain(|existing| existing.query_id != result.query_id);
+        if results.len() >= RECENT_RESULTS_KEPT {
+            results.pop_front();
+        }
+        results.push_back(result);
+    }
+
+    /// A recently finished query's result
+    pub async fn recent_result(&self, query_id: &str) -> ConnectionResult<RecentResult> {
+        let results = self.recent_results.read().await;
+        results
+            .iter()
+            .find(|result| result.query_id == query_id)
+            .cloned()
+            .ok_or_else(|| {
+                ConnectionError::QueryFailed(format!("No recent result for query {}", query_id))
+            })
+    }
+
+    /// Request cancellation of a running query
+    pub async fn cancel_query(&self, query_id: &str) -> ConnectionResult<()> {
+        let queries = self.running_queries.read().await;
+        match queries.get(query_id) {
+            Some((_, token)) => {
+                token.cancel();
+                Ok(())
+            }
+            None => Err(ConnectionError::QueryFailed(format!(
+                "Query {} not found or already completed",
+                query_id
+            ))),
+        }
+    }
+
+    /// Distinct values of a column; callers must have checked the column exists
+    pub async fn get_column_distinct_values(
+        &self,
+        connection_id: Uuid,
+        schema: &str,
+        table: &str,
+        column: &str,
+        limit: usize,
+    ) -> ConnectionResult<DistinctValues> {
+        let config = self.credential_manager.get_connection_config(connection_id).await?;
+        let _query_slot = self.acquire_query_slot().await?;
+        let pool = self.get_or_create_pool(connection_id).await?;
+        let driver = DatabaseDriverFactory::create_driver(&config.database_type);
+
+        // One extra row tells us whether the result was cut off
+        let mut values = driver.fetch_distinct_values(&pool, schema, table, column, limit + 1, None).await?;
+        let truncated = values.len() > limit;
+        values.truncate(limit);
+
+        Ok(DistinctValues { values, truncated })
+    }
+
+    /// Features the connection's database type supports
+    pub async fn connection_features(&self, connection_id: Uuid) -> ConnectionResult<Vec<DatabaseFeature>> {
+        let config = self.credential_manager.get_connection_config(connection_id).await?;
+        let driver = DatabaseDriverFactory::try_create_driver(&config.database_type)?;
+        Ok(driver.supported_features())
+    }
+
+    /// Whether the connection's database type supports `feature`
+    pub async fn connection_supports(&self, connection_id: Uuid, feature: DatabaseFeature) -> ConnectionResult<bool> {
+        Ok(self.connection_features(connection_id).await?.contains(&feature))
+    }
+
+    /// Fail with `UnsupportedFeature` unless the connection supports `feature`.
+    /// Check this before an operation so callers see a clear message, not a raw driver error.
+    pub async fn require_feature(&self, connection_id: Uuid, feature: DatabaseFeature) -> ConnectionResult<()> {
+        let config = self.credential_manager.get_connection_config(connection_id).await?;
+        let driver = DatabaseDriverFactory::try_create_driver(&config.database_type)?;
+        if driver.supported_features().contains(&feature) {
+            Ok(())
+        } else {
+            Err(ConnectionError::UnsupportedFeature(format!(
+                "{} is not supported by {}",
+                feature, config.database_type
+            )))
+        }
+    }
+
+    /// Result column metadata for `sql` as reported by the driver, without running it
+    pub async fn describe_query_columns(&self, connection_id: Uuid, sql: &str) -> ConnectionResult<Vec<ResultColumnInfo>> {
+        let config = self.credential_manager.get_connection_config(connection_id).await?;
+        let pool = self.get_or_create_pool(connection_id).await?;
+        let driver = DatabaseDriverFactory::create_driver(&config.database_type);
+
+        driver.describe_columns(&pool, sql, None).await
+    }
+
+    /// Run a statement and collect up to `max_rows` rows of its first result set.
+    /// Where the driver can, the row cap is pushed to the database as well.
+    pub async fn execute_query(
+        &self,
+        connection_id: Uuid,
+        sql: &str,
+        max_rows: usize,
+        cancel: Option<&CancellationToken>,
+    ) -> ConnectionResult<QueryOutput> {
+        // Held until the statement finishes, capping in-flight queries across all connections
+        let _query_slot = self.acquire_query_slot().await?;
+        let config = self.credential_manager.get_connection_config(connection_id).await?;
+        let pool = self.get_or_create_pool(connection_id).await?;
+        let driver = DatabaseDriverFactory::create_driver(&config.database_type);
+
+        // One row past the cap tells the driver the result was truncated
+        let limited = driver.apply_row_limit(sql, max_rows.saturating_add(1));
+        driver.execute_query(&pool, limited.as_deref().unwrap_or(sql), max_rows, cancel).await
+    }
+
+    /// Run `statements` in order inside one transaction, committing only if all of them succeed.
+    /// Every statement must pass the connection's statement policy before anything runs;
+    /// `allow_ddl` additionally permits schema changes. Transaction control statements are
+    /// always rejected since they would end the transaction early.
+    pub async fn execute_transaction(
+        &self,
+        connection_id: Uuid,
+        statements: &[String],
+        max_rows: usize,
+        allow_ddl: bool,
+    ) -> ConnectionResult<TransactionOutcome> {
+        self.require_feature(connection_id, DatabaseFeature::Transactions).await?;
+
+        let config = self.credential_manager.get_connection_config(connection_id).await?;
+        let policy = if allow_ddl {
+            config.statement_policy.with_ddl()
+        } else {
+            config.statement_policy.clone()
+        };
+        for statement in statements {
+            if classify_statements(statement, &config.database_type).contains(&StatementKind::Transaction) {
+                return Err(ConnectionError::PolicyViolation(
+                    "Transaction control statements can't be used inside a transaction".to_string(),
+                ));
+            }
+            policy.check(statement, &config.database_type)?;
+            // Allowing DDL never lifts read-only mode
+            if config.read_only {
+                check_read_only(statement, &config.database_type)?;
+            }
+        }
+
+        let _query_slot = self.acquire_query_slot().await?;
+        let pool = self.get_or_create_pool(connection_id).await?;
+        let driver = DatabaseDriverFactory::create_driver(&config.database_type);
+        let mut transaction = driver.begin_transaction(&pool).await?;
+
+        let mut results = Vec::with_capacity(statements.len());
+        for statement in statements {
+            let started = std::time::Instant::now();
+            let outcome = transaction.execute(statement, max_rows, driver.as_ref()).await;
+            let execution_time_ms = started.elapsed().as_millis() as u64;
+
+            match outcome {
+                Ok(output) => results.push(TransactionStatementResult {
+                    statement: statement.clone(),
+                    success: true,
+                    output: Some(output),
+                    error: None,
+                    execution_time_ms,
+                }),
+                Err(e) => {
+                    results.push(TransactionStatementResult {
+                        statement: statement.clone(),
+                        success: false,
+                        output: None,
+                        error: Some(e.to_string()),
+                        execution_time_ms,
+                    });
+                    if let Err(e) = transaction.rollback().await {
+                        log::warn!("Rollback on connection {} failed: {}", connection_id, e);
+                    }
+                    return Ok(TransactionOutcome { results, committed: false });
+                }
+            }
+        }
+
+        transaction.commit().await?;
+        Ok(TransactionOutcome { results, committed: true })
+    }
+
+    /// Fetch one page of a SELECT's result; see `DatabaseDriver::fetch_page`
+    pub async fn fetch_query_page(
+        &self,
+        connection_id: Uuid,
+        sql: &str,
+        offset: usize,
+        page_size: usize,
+        cancel: Option<&CancellationToken>,
+    ) -> ConnectionResult<QueryOutput> {
+        let _query_slot = self.acquire_query_slot().await?;
+        let config = self.credential_manager.get_connection_config(connection_id).await?;
+        let pool = self.get_or_create_pool(connection_id).await?;
+        let driver = DatabaseDriverFactory::create_driver(&config.database_type);
+
+        driver.fetch_page(&pool, sql, offset, page_size, cancel).await
+    }
+
+    /// Update one row, identified by its primary-key values, under the connection's statement
+    /// policy. `column_types` are the columns' introspected types; see `DatabaseDriver::update_row`.
+    pub async fn update_row(
+        &self,
+        connection_id: Uuid,
+        schema: &str,
+        table: &str,
+        key: &BTreeMap<String, serde_json::Value>,
+        changes: &BTreeMap<String, serde_json::Value>,
+        column_types: &BTreeMap<String, String>,
+    ) -> ConnectionResult<u64> {
+        // Values are only ever bound, never interpolated
+        self.require_feature(connection_id, DatabaseFeature::PreparedStatements).await?;
+
+        let config = self.credential_manager.get_connection_config(connection_id).await?;
+        let driver = DatabaseDriverFactory::create_driver(&config.database_type);
+
+        let (sql, _) = driver.build_update_statement(schema, table, key, changes, column_types);
+        config.check_statement(&sql)?;
+
+        let _query_slot = self.acquire_query_slot().await?;
+        let pool = self.get_or_create_pool(connection_id).await?;
+        driver.update_row(&pool, schema, table, key, changes, column_types, None).await
+    }
+
+    /// Get the statistics for a connection, or empty statistics if it isn't active
+    async fn get_connection_stats(&self, connection_id: Uuid) -> ConnectionStats {
+        let connections = self.connections.read().await;
+        match connections.get(&connection_id) {
+            Some(connection) => connection.get_stats().await,
+            None => ConnectionStats {
+                connection_id,
+                ..ConnectionStats::default()
+            },
+        }
     }
 
     /// Get connection summary
@@ -203,9 +1023,86 @@ impl ConnectionManager {
         Ok(connection.get_summary().await)
     }
 
-    /// List all connections
+    /// List all connections, pinned first, then by sort order and name
     pub async fn list_connections(&self) -> Vec<ConnectionConfig> {
-        self.credential_manager.list_connections().await
+        let mut configs = self.credential_manager.list_connections().await;
+        sort_connections(&mut configs, ConnectionSort::Manual);
+        configs
+    }
+
+    /// Pin or unpin a connection
+    pub async fn set_connection_pinned(&self, connection_id: Uuid, pinned: bool) -> ConnectionResult<()> {
+        let mut config = self.credential_manager.get_connection_config(connection_id).await?;
+        config.is_pinned = pinned;
+        self.store_connection_metadata(vec![config]).await
+    }
+
+    /// Replace a connection's user metadata
+    pub async fn set_connection_metadata(
+        &self,
+        connection_id: Uuid,
+        metadata: HashMap<String, String>,
+    ) -> ConnectionResult<()> {
+        let mut config = self.credential_manager.get_connection_config(connection_id).await?;
+        config.metadata = metadata;
+        self.credential_manager.validate_config(&config)?;
+        self.store_connection_metadata(vec![config]).await
+    }
+
+    /// Connections carrying a metadata key, optionally with a specific value
+    pub async fn list_connections_with_metadata(&self, key: &str, value: Option<&str>) -> Vec<ConnectionConfig> {
+        self.list_connections()
+            .await
+            .into_iter()
+            .filter(|config| match (config.metadata.get(key), value) {
+                (Some(actual), Some(expected)) => actual == expected,
+                (Some(_), None) => true,
+                (None, _) => false,
+            })
+            .collect()
+    }
+
+    /// Assign sort orders following the given IDs; unlisted connections keep their relative order after them
+    pub async fn reorder_connections(&self, ordered_ids: &[Uuid]) -> ConnectionResult<()> {
+        let mut configs = self.list_connections().await;
+
+        if let Some(unknown) = ordered_ids.iter().find(|id| !configs.iter().any(|c| c.id == **id)) {
+            return Err(ConnectionError::ConfigurationError(format!("Connection not found: {}", unknown)));
+        }
+
+        configs.sort_by_key(|config| {
+            ordered_ids
+                .iter()
+                .position(|id| *id == config.id)
+                .unwrap_or(ordered_ids.len())
+        });
+        for (index, config) in configs.iter_mut().enumerate() {
+            config.sort_order = index as i32;
+        }
+
+        self.store_connection_metadata(configs).await
+    }
+
+    /// Persist display-only config changes without re-validating or recreating drivers
+    async fn store_connection_metadata(&self, configs: Vec<ConnectionConfig>) -> ConnectionResult<()> {
+        {
+            let mut connections = self.connections.write().await;
+            for config in &configs {
+                if let Some(connection) = connections.get_mut(&config.id) {
+                    let connection = Arc::make_mut(connection);
+                    connection.config.is_pinned = config.is_pinned;
+                    connection.config.sort_order = config.sort_order;
+                    connection.config.metadata = config.metadata.clone();
+                    connection.config.last_used_at = config.last_used_at;
+                }
+            }
+        }
+
+        for config in configs {
+            self.credential_manager.update_connection_config(config).await?;
+        }
+
+        self.credential_manager.save_connections().await
     }
 
     /// List all connection summaries
@@ -231,15 +1128,19 @@ impl ConnectionManager {
         configs.iter().any(|config| config.id == connection_id)
     }
 
+    /// Get the default result-row cap for query execution
+    pub fn default_max_rows(&self) -> usize {
+        self.config.default_max_rows
+    }
+
     /// Get supported database types
     pub fn get_supported_database_types(&self) -> Vec<DatabaseType> {
         vec![
             DatabaseType::PostgreSQL,
             DatabaseType::MySQL,
             DatabaseType::SQLite,
-            // TODO: Add when implemented
-            // DatabaseType::SqlServer,
-            // DatabaseType::Oracle,
+            DatabaseType::SqlServer,
+            DatabaseType::Oracle,
         ]
     }
 
@@ -308,6 +1209,7 @@ impl ConnectionManager {
         let connections = Arc::clone(&self.connections);
         let cleanup_interval = self.config.auto_cleanup_interval_seconds;
         let idle_timeout = self.config.idle_timeout_seconds;
+        let stopped = self.background_tasks.clone();
 
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(
@@ -315,7 +1217,10 @@ impl ConnectionManager {
             );
 
             loop {
-                interval.tick().await;
+                tokio::select! {
+                    _ = stopped.cancelled() => break,
+                    _ = interval.tick() => {}
+                }
                 
                 let mut to_remove = Vec::new();
                 {
@@ -340,6 +1245,125 @@ impl ConnectionManager {
         });
     }
 
+    /// Status changes found by the health check, for forwarding to the UI
+    pub fn get_status_receiver(&self) -> broadcast::Receiver<ConnectionStatusChange> {
+        self.status_broadcaster.subscribe()
+    }
+
+    /// Ping every open pool once, marking connections whose server stopped answering as
+    /// `Error` and ones that answer again as `Connected`. Connections already in `Error` have
+    /// no pool left, so a fresh one is opened to find out whether the server is back.
+    /// Returns (and broadcasts) the changes.
+    pub async fn check_connection_health(&self) -> Vec<ConnectionStatusChange> {
+        Self::health_check_pass(
+            &self.connections,
+            &self.credential_manager,
+            &self.status_broadcaster,
+            self.health_check_timeout(),
+        )
+        .await
+    }
+
+    fn health_check_timeout(&self) -> Duration {
+        Duration::from_secs(u64::from(self.config.connection_timeout_seconds.max(1)))
+    }
+
+    /// Start the background health check if the config sets an interval
+    fn start_health_check_task(&self) {
+        let Some(interval_seconds) = self.config.health_check_interval_seconds.filter(|seconds| *seconds > 0) else {
+            return;
+        };
+        let connections = Arc::clone(&self.connections);
+        let credential_manager = Arc::clone(&self.credential_manager);
+        let broadcaster = self.status_broadcaster.clone();
+        let timeout = self.health_check_timeout();
+        let stopped = self.background_tasks.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(u64::from(interval_seconds)));
+            // The first tick fires immediately; nothing has had a chance to fail yet
+            interval.tick().await;
+
+            loop {
+                tokio::select! {
+                    _ = stopped.cancelled() => break,
+                    _ = interval.tick() => {}
+                }
+                tokio::select! {
+                    _ = stopped.cancelled() => break,
+                    _ = Self::health_check_pass(&connections, &credential_manager, &broadcaster, timeout) => {}
+                }
+            }
+        });
+    }
+
+    async fn health_check_pass(
+        connections: &RwLock<HashMap<Uuid, Arc<DatabaseConnection>>>,
+        credential_manager: &Arc<CredentialManager>,
+        broadcaster: &broadcast::Sender<ConnectionStatusChange>,
+        timeout: Duration,
+    ) -> Vec<ConnectionStatusChange> {
+        // Cloned out so the map isn't locked while servers answer
+        let targets: Vec<Arc<DatabaseConnection>> = connections.read().await.values().cloned().collect();
+
+        let mut pings = JoinSet::new();
+        for connection in targets {
+            let credential_manager = Arc::clone(credential_manager);
+            pings.spawn(async move {
+                let pool = match connection.current_pool().await {
+                    Some(pool) => Some(pool),
+                    None if matches!(connection.get_status().await, ConnectionStatus::Error(_)) => None,
+                    // Never opened or closed while idle: nothing to check
+                    None => return None,
+                };
+
+                let probe = async {
+                    let pool = match pool {
+                        Some(pool) => pool,
+                        None => {
+                            let credentials = credential_manager.get_credentials(connection.config.id).await?;
+                            connection.get_or_create_pool(&credentials).await?
+                        }
+                    };
+                    let driver = DatabaseDriverFactory::create_driver(&connection.config.database_type);
+                    driver.ping(&pool, None).await
+                };
+                let outcome = match tokio::time::timeout(timeout, probe).await {
+                    Ok(Ok(())) => Ok(()),
+                    Ok(Err(e)) => Err(format!("Health check failed: {}", e)),
+                    Err(_) => Err(format!("Health check timed out after {}s", timeout.as_secs())),
+                };
+
+                if outcome.is_err() {
+                    // A dead pool would fail every query; the next one opens a fresh pool
+                    connection.close_pool().await;
+                }
+
+                let mut current = connection.status.write().await;
+                let next = match (&outcome, &*current) {
+                    (Err(message), _) => ConnectionStatus::Error(message.clone()),
+                    (Ok(()), ConnectionStatus::Error(_)) => ConnectionStatus::Connected,
+                    (Ok(()), _) => return None,
+                };
+                if *current == next {
+                    return None;
+                }
+                let previous = std::mem::replace(&mut *current, next.clone());
+                Some(ConnectionStatusChange { connection_id: connection.config.id, previous, status: next })
+            });
+        }
+
+        let mut changes = Vec::new();
+        while let Some(joined) = pings.join_next().await {
+            if let Ok(Some(change)) = joined {
+                log::info!("Connection {} is now {:?}", change.connection_id, change.status);
+                broadcaster.send(change.clone()).ok();
+                changes.push(change);
+            }
+        }
+        changes
+    }
+
     /// Get manager statistics
     pub async fn get_manager_stats(&self) -> ConnectionManagerStats {
         let connections = self.connections.read().await;
@@ -378,6 +1402,20 @@ pub struct ConnectionManagerStats {
     pub failed_queries: u64,
 }
 
+/// Outcome of a dry-run `add_connection`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AddConnectionReport {
+    pub would_succeed: bool,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl Drop for ConnectionManager {
+    fn drop(&mut self) {
+        self.background_tasks.cancel();
+    }
+}
+
 impl Default for ConnectionManager {
     fn default() -> Self {
         // This is a blocking implementation for Default trait
@@ -385,3 +1423,35 @@ impl Default for ConnectionManager {
         panic!("Use ConnectionManager::new() instead of default()")
     }
 }
+
+/// Track a stored connection as active so it can hold a pool, unless it already is, and
+/// return it
+async fn ensure_connection(
+    connections: &RwLock<HashMap<Uuid, Arc<DatabaseConnection>>>,
+    config: ConnectionConfig,
+) -> Arc<DatabaseConnection> {
+    if let Some(connection) = connections.read().await.get(&config.id) {
+        return Arc::clone(connection);
+    }
+    let mut connections = connections.write().await;
+    let connection = connections.entry(config.id).or_insert_with(|| Arc::new(DatabaseConnection::new(config)));
+    Arc::clone(connection)
+}
+
+/// Order connection configs for display
+pub fn sort_connections(configs: &mut [ConnectionConfig], sort: ConnectionSort) {
+    let by_name = |a: &ConnectionConfig, b: &ConnectionConfig| a.name.to_lowercase().cmp(&b.name.to_lowercase());
+
+    match sort {
+        ConnectionSort::Manual => configs.sort_by(|a, b| {
+            b.is_pinned
+                .cmp(&a.is_pinned)
+                .then(a.sort_order.cmp(&b.sort_order))
+                .then_with(|| by_name(a, b))
+        }),
+        // `None` sorts below `Some`, so never-used connections end up last
+        ConnectionSort::RecentlyUsed => configs.sort_by(|a, b| {
+            b.last_used_at.cmp(&a.last_used_at).then_with(|| by_name(a, b))
+        }),
+    }
+}
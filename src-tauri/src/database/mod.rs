@@ -2,7 +2,13 @@
 pub mod connection;
 pub mod credentials;
 pub mod drivers;
+pub mod export;
 pub mod manager;
+pub mod policy;
+pub mod pool;
+pub mod result_sql;
+pub mod schema;
+pub mod secret_store;
 pub mod security;
 pub mod types;
 
@@ -11,10 +17,16 @@ mod tests;
 
 pub use connection::DatabaseConnection;
 pub use credentials::CredentialManager;
-pub use drivers::DatabaseDriver;
+pub use drivers::{DatabaseDriver, DatabaseFeature};
+pub use export::{ConnectionExportBundle, ConnectionImportResult};
 pub use manager::ConnectionManager;
-pub use security::ThreatModel;
+pub use policy::{PermissionLevel, StatementCategory, StatementKind, StatementPolicy};
+pub use pool::{DatabasePool, PoolMetrics};
+pub use result_sql::{GeneratedSql, RecentResult, ResultColumn, ResultSqlMode};
+pub use schema::{DistinctValues, ForeignKeyGraph, ForeignKeyInfo, IndexUsageReport, SchemaFilter};
+pub use security::{ThreatModel, ThreatModelConfig};
 pub use types::{
-    ConnectionConfig, ConnectionStatus, DatabaseType, ConnectionError,
-    ConnectionResult, DatabaseCredentials, ConnectionPool
+    ConnectionConfig, ConnectionSort, ConnectionStatus, DatabaseType, ConnectionError,
+    ConnectionFailureKind, ConnectionResult, ConnectionTestProgress, DatabaseCredentials, ConnectionPool, NormalizedType, QueryOutput, QueryPlan, ResultColumnInfo, RunningQuery, SslMode,
+    TransactionOutcome, TransactionStatementResult
 };
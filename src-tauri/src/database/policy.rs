@@ -0,0 +1,419 @@
+use crate::database::types::{ConnectionError, ConnectionResult, DatabaseType};
+use serde::{Deserialize, Serialize};
+use sqlparser::ast::{Query, SetExpr, Statement};
+use sqlparser::dialect::{Dialect, GenericDialect, MsSqlDialect, MySqlDialect, PostgreSqlDialect, SQLiteDialect};
+use sqlparser::parser::Parser;
+use std::fmt;
+use std::str::FromStr;
+
+/// Coarse statement category used for policy decisions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StatementKind {
+    Select,
+    Insert,
+    Update,
+    Delete,
+    Merge,
+    Create,
+    Alter,
+    Drop,
+    Truncate,
+    Grant,
+    Revoke,
+    Transaction,
+    Explain,
+    Other,
+}
+
+impl StatementKind {
+    pub const ALL: [StatementKind; 14] = [
+        StatementKind::Select,
+        StatementKind::Insert,
+        StatementKind::Update,
+        StatementKind::Delete,
+        StatementKind::Merge,
+        StatementKind::Create,
+        StatementKind::Alter,
+        StatementKind::Drop,
+        StatementKind::Truncate,
+        StatementKind::Grant,
+        StatementKind::Revoke,
+        StatementKind::Transaction,
+        StatementKind::Explain,
+        StatementKind::Other,
+    ];
+
+    /// Kinds that change the schema rather than the data
+    pub const DDL: [StatementKind; 4] = [
+        StatementKind::Create,
+        StatementKind::Alter,
+        StatementKind::Drop,
+        StatementKind::Truncate,
+    ];
+
+    pub(crate) fn from_statement(statement: &Statement) -> Self {
+        match statement {
+            Statement::Query(_)
+            | Statement::ShowTables { .. }
+            | Statement::ShowColumns { .. }
+            | Statement::ShowCreate { .. }
+            | Statement::ShowFunctions { .. }
+            | Statement::ShowVariable { .. }
+            | Statement::ShowVariables { .. }
+            | Statement::ShowCollation { .. } => StatementKind::Select,
+            Statement::Insert { .. } | Statement::Copy { .. } => StatementKind::Insert,
+            Statement::Update { .. } => StatementKind::Update,
+            Statement::Delete { .. } => StatementKind::Delete,
+            Statement::Merge { .. } => StatementKind::Merge,
+            Statement::CreateTable { .. }
+            | Statement::CreateView { .. }
+            | Statement::CreateIndex { .. }
+            | Statement::CreateSchema { .. }
+            | Statement::CreateDatabase { .. }
+            | Statement::CreateFunction { .. }
+            | Statement::CreateProcedure { .. }
+            | Statement::CreateSequence { .. }
+            | Statement::CreateRole { .. } => StatementKind::Create,
+            Statement::AlterTable { .. }
+            | Statement::AlterIndex { .. }
+            | Statement::AlterView { .. }
+            | Statement::AlterRole { .. } => StatementKind::Alter,
+            Statement::Drop { .. } | Statement::DropFunction { .. } => StatementKind::Drop,
+            Statement::Truncate { .. } => StatementKind::Truncate,
+            Statement::Grant { .. } => StatementKind::Grant,
+            Statement::Revoke { .. } => StatementKind::Revoke,
+            Statement::StartTransaction { .. }
+            | Statement::Commit { .. }
+            | Statement::Rollback { .. }
+            | Statement::Savepoint { .. } => StatementKind::Transaction,
+            Statement::Explain { .. } | Statement::ExplainTable { .. } => StatementKind::Explain,
+            _ => StatementKind::Other,
+        }
+    }
+
+    /// Fallback for SQL the parser does not understand. Only a plain SELECT is taken at its
+    /// word; anything else, including a WITH whose CTEs may write, is `Other`, which policies
+    /// have to allow explicitly.
+    fn from_leading_keyword(sql: &str) -> Self {
+        let keyword = strip_leading_comments(sql)
+            .split(|c: char| c.is_whitespace() || c == '(')
+            .find(|word| !word.is_empty())
+            .unwrap_or("");
+
+        if keyword.eq_ignore_ascii_case("SELECT") {
+            StatementKind::Select
+        } else {
+            StatementKind::Other
+        }
+    }
+}
+
+/// SQL with any leading `--`, `#` and `/* */` comments removed
+fn strip_leading_comments(mut sql: &str) -> &str {
+    loop {
+        sql = sql.trim_start();
+        if let Some(rest) = sql.strip_prefix("--").or_else(|| sql.strip_prefix('#')) {
+            sql = rest.split_once('\n').map_or("", |(_, after)| after);
+        } else if let Some(rest) = sql.strip_prefix("/*") {
+            sql = rest.split_once("*/").map_or("", |(_, after)| after);
+        } else {
+            return sql;
+        }
+    }
+}
+
+impl StatementKind {
+    /// Broad effect of statements of this kind. Unrecognized statements count as DDL, the
+    /// most privileged category, since nothing shows they are harmless.
+    pub fn category(&self) -> StatementCategory {
+        match self {
+            StatementKind::Select | StatementKind::Explain => StatementCategory::Read,
+            StatementKind::Insert
+            | StatementKind::Update
+            | StatementKind::Delete
+            | StatementKind::Merge
+            | StatementKind::Transaction => StatementCategory::Write,
+            StatementKind::Create
+            | StatementKind::Alter
+            | StatementKind::Drop
+            | StatementKind::Truncate
+            | StatementKind::Grant
+            | StatementKind::Revoke
+            | StatementKind::Other => StatementCategory::Ddl,
+        }
+    }
+}
+
+impl fmt::Display for StatementKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            StatementKind::Select => "SELECT",
+            StatementKind::Insert => "INSERT",
+            StatementKind::Update => "UPDATE",
+            StatementKind::Delete => "DELETE",
+            StatementKind::Merge => "MERGE",
+            StatementKind::Create => "CREATE",
+            StatementKind::Alter => "ALTER",
+            StatementKind::Drop => "DROP",
+            StatementKind::Truncate => "TRUNCATE",
+            StatementKind::Grant => "GRANT",
+            StatementKind::Revoke => "REVOKE",
+            StatementKind::Transaction => "transaction control",
+            StatementKind::Explain => "EXPLAIN",
+            StatementKind::Other => "unrecognized",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Whether a statement reads data, writes data, or changes the schema or privileges
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementCategory {
+    Read,
+    Write,
+    Ddl,
+}
+
+impl fmt::Display for StatementCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatementCategory::Read => write!(f, "read"),
+            StatementCategory::Write => write!(f, "write"),
+            StatementCategory::Ddl => write!(f, "DDL"),
+        }
+    }
+}
+
+/// What a caller may run; each level includes the ones before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PermissionLevel {
+    Read,
+    Write,
+    Ddl,
+}
+
+impl PermissionLevel {
+    pub fn allows(&self, category: StatementCategory) -> bool {
+        match self {
+            PermissionLevel::Read => category == StatementCategory::Read,
+            PermissionLevel::Write => category != StatementCategory::Ddl,
+            PermissionLevel::Ddl => true,
+        }
+    }
+}
+
+impl fmt::Display for PermissionLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PermissionLevel::Read => write!(f, "read"),
+            PermissionLevel::Write => write!(f, "write"),
+            PermissionLevel::Ddl => write!(f, "DDL"),
+        }
+    }
+}
+
+impl FromStr for PermissionLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "read" | "read_only" | "readonly" => Ok(PermissionLevel::Read),
+            "write" | "read_write" => Ok(PermissionLevel::Write),
+            "ddl" | "admin" => Ok(PermissionLevel::Ddl),
+            other => Err(format!("Unknown permission level: {}", other)),
+        }
+    }
+}
+
+/// One statement of a SQL text and how it was classified
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClassifiedStatement {
+    /// The statement as parsed (comments and extra whitespace dropped), or its raw text
+    /// when the SQL couldn't be parsed
+    pub sql: String,
+    pub kind: StatementKind,
+    pub category: StatementCategory,
+}
+
+/// Per-connection list of statement kinds that may be executed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatementPolicy {
+    pub allowed: Vec<StatementKind>,
+}
+
+impl StatementPolicy {
+    /// Policy that permits every statement kind
+    pub fn allow_all() -> Self {
+        Self {
+            allowed: StatementKind::ALL.to_vec(),
+        }
+    }
+
+    /// Policy that only permits queries
+    pub fn read_only() -> Self {
+        Self {
+            allowed: vec![StatementKind::Select, StatementKind::Explain],
+        }
+    }
+
+    /// This policy with schema changes (CREATE, ALTER, DROP, TRUNCATE) also permitted
+    pub fn with_ddl(&self) -> Self {
+        let mut allowed = self.allowed.clone();
+        for kind in StatementKind::DDL {
+            if !allowed.contains(&kind) {
+                allowed.push(kind);
+            }
+        }
+        Self { allowed }
+    }
+
+    pub fn allows(&self, kind: StatementKind) -> bool {
+        self.allowed.contains(&kind)
+    }
+
+    /// Reject the SQL if any of its statements is not allowed
+    pub fn check(&self, sql: &str, database_type: &DatabaseType) -> ConnectionResult<()> {
+        for kind in classify_statements(sql, database_type) {
+            if !self.allows(kind) {
+                return Err(ConnectionError::PolicyViolation(format!(
+                    "{} statements are not allowed on this connection",
+                    kind
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for StatementPolicy {
+    /// Everything except the operations previously blocked as dangerous and statements
+    /// that couldn't be recognized; only `allow_all` lets the latter through
+    fn default() -> Self {
+        Self {
+            allowed: StatementKind::ALL
+                .into_iter()
+                .filter(|kind| {
+                    !matches!(
+                        kind,
+                        StatementKind::Drop
+                            | StatementKind::Delete
+                            | StatementKind::Truncate
+                            | StatementKind::Alter
+                            | StatementKind::Other
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+pub(crate) fn dialect_for(database_type: &DatabaseType) -> Box<dyn Dialect> {
+    match database_type {
+        DatabaseType::PostgreSQL => Box::new(PostgreSqlDialect {}),
+        DatabaseType::MySQL => Box::new(MySqlDialect {}),
+        DatabaseType::SQLite => Box::new(SQLiteDialect {}),
+        DatabaseType::SqlServer => Box::new(MsSqlDialect {}),
+        DatabaseType::Oracle => Box::new(GenericDialect {}),
+    }
+}
+
+/// Reject the SQL unless every statement parses as a query that only reads. Unlike
+/// `StatementPolicy::check` there is no keyword fallback: SQL the parser can't read is
+/// rejected, since nothing shows it is harmless.
+pub fn check_read_only(sql: &str, database_type: &DatabaseType) -> ConnectionResult<()> {
+    let statements = Parser::parse_sql(dialect_for(database_type).as_ref(), sql).map_err(|e| {
+        ConnectionError::PolicyViolation(format!(
+            "Read-only connections only run SQL that parses as a query: {}",
+            e
+        ))
+    })?;
+
+    for statement in &statements {
+        match statement {
+            Statement::Query(query) if query_reads_only(query) => {}
+            Statement::Query(_) => {
+                return Err(ConnectionError::PolicyViolation(
+                    "SELECT ... INTO and locking reads are not allowed on a read-only connection".to_string(),
+                ))
+            }
+            other => {
+                return Err(ConnectionError::PolicyViolation(format!(
+                    "{} statements are not allowed on a read-only connection",
+                    StatementKind::from_statement(other)
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A query that creates no table, takes no row locks and nests no INSERT/UPDATE
+fn query_reads_only(query: &Query) -> bool {
+    let ctes_read_only = query
+        .with
+        .as_ref()
+        .map_or(true, |with| with.cte_tables.iter().all(|cte| query_reads_only(&cte.query)));
+    ctes_read_only && query.locks.is_empty() && set_expr_reads_only(&query.body)
+}
+
+fn set_expr_reads_only(body: &SetExpr) -> bool {
+    match body {
+        SetExpr::Select(select) => select.into.is_none(),
+        SetExpr::Query(query) => query_reads_only(query),
+        SetExpr::SetOperation { left, right, .. } => set_expr_reads_only(left) && set_expr_reads_only(right),
+        SetExpr::Values(_) | SetExpr::Table(_) => true,
+        _ => false,
+    }
+}
+
+/// Classify every statement in the SQL text by kind and category. A query that writes
+/// (e.g. SELECT ... INTO) counts as a write.
+pub fn classify_statement_categories(sql: &str, database_type: &DatabaseType) -> Vec<ClassifiedStatement> {
+    match Parser::parse_sql(dialect_for(database_type).as_ref(), sql) {
+        Ok(statements) => statements
+            .iter()
+            .map(|statement| {
+                let kind = StatementKind::from_statement(statement);
+                let category = match statement {
+                    Statement::Query(query) if !query_reads_only(query) => StatementCategory::Write,
+                    _ => kind.category(),
+                };
+                ClassifiedStatement { sql: statement.to_string(), kind, category }
+            })
+            .collect(),
+        Err(_) => sql
+            .split(';')
+            .map(str::trim)
+            .filter(|statement| !statement.is_empty())
+            .map(|statement| {
+                let kind = StatementKind::from_leading_keyword(statement);
+                ClassifiedStatement { sql: statement.to_string(), kind, category: kind.category() }
+            })
+            .collect(),
+    }
+}
+
+/// The first statement in the SQL text that `level` doesn't permit
+pub fn first_blocked_statement(
+    sql: &str,
+    database_type: &DatabaseType,
+    level: PermissionLevel,
+) -> Option<ClassifiedStatement> {
+    classify_statement_categories(sql, database_type)
+        .into_iter()
+        .find(|statement| !level.allows(statement.category))
+}
+
+/// Classify every statement in the SQL text
+pub fn classify_statements(sql: &str, database_type: &DatabaseType) -> Vec<StatementKind> {
+    match Parser::parse_sql(dialect_for(database_type).as_ref(), sql) {
+        Ok(statements) => statements.iter().map(StatementKind::from_statement).collect(),
+        Err(_) => sql
+            .split(';')
+            .filter(|statement| !statement.trim().is_empty())
+            .map(StatementKind::from_leading_keyword)
+            .collect(),
+    }
+}
@@ -0,0 +1,325 @@
+use crate::database::types::{ConnectionError, ConnectionResult, ConnectionStats};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sqlx::pool::PoolConnection;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::net::TcpStream;
+use tokio_util::compat::{Compat, TokioAsyncWriteCompatExt};
+use uuid::Uuid;
+
+/// A tiberius client over a plain TCP stream
+pub type SqlServerClient = tiberius::Client<Compat<TcpStream>>;
+
+/// deadpool pool of SQL Server clients; sqlx no longer ships an MSSQL driver
+pub type SqlServerPool = deadpool::managed::Pool<SqlServerConnectionManager>;
+
+/// Underlying sqlx pool for a specific database type
+#[derive(Debug, Clone)]
+pub enum PoolHandle {
+    Postgres(sqlx::PgPool),
+    MySql(sqlx::MySqlPool),
+    Sqlite(sqlx::SqlitePool),
+    SqlServer(SqlServerPool),
+    /// OCI session pool; every call on it blocks
+    Oracle(oracle::pool::Pool),
+}
+
+/// Long-lived connection pool shared by all operations on a connection
+#[derive(Debug, Clone)]
+pub struct DatabasePool {
+    handle: PoolHandle,
+    max_connections: u32,
+    /// Number of callers currently waiting to acquire a pooled connection
+    pending_acquires: Arc<AtomicUsize>,
+}
+
+impl DatabasePool {
+    pub fn new(handle: PoolHandle, max_connections: u32) -> Self {
+        Self {
+            handle,
+            max_connections,
+            pending_acquires: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Get the underlying sqlx pool
+    pub fn handle(&self) -> &PoolHandle {
+        &self.handle
+    }
+
+    /// Total number of connections currently held by the pool (active + idle)
+    pub fn size(&self) -> u32 {
+        match &self.handle {
+            PoolHandle::Postgres(pool) => pool.size(),
+            PoolHandle::MySql(pool) => pool.size(),
+            PoolHandle::Sqlite(pool) => pool.size(),
+            PoolHandle::SqlServer(pool) => pool.status().size as u32,
+            PoolHandle::Oracle(pool) => pool.open_count().unwrap_or(0),
+        }
+    }
+
+    /// Number of idle connections in the pool
+    pub fn num_idle(&self) -> u32 {
+        let idle = match &self.handle {
+            PoolHandle::Postgres(pool) => pool.num_idle(),
+            PoolHandle::MySql(pool) => pool.num_idle(),
+            PoolHandle::Sqlite(pool) => pool.num_idle(),
+            PoolHandle::SqlServer(pool) => pool.status().available as usize,
+            PoolHandle::Oracle(pool) => {
+                let open = pool.open_count().unwrap_or(0);
+                open.saturating_sub(pool.busy_count().unwrap_or(open)) as usize
+            }
+        };
+        idle as u32
+    }
+
+    pub fn max_connections(&self) -> u32 {
+        self.max_connections
+    }
+
+    pub fn pending_acquires(&self) -> u32 {
+        self.pending_acquires.load(Ordering::SeqCst) as u32
+    }
+
+    /// Track a caller waiting on the pool; the returned guard must be held until
+    /// the connection has been acquired (or the acquire has failed)
+    pub fn track_acquire(&self) -> AcquireGuard {
+        self.pending_acquires.fetch_add(1, Ordering::SeqCst);
+        AcquireGuard {
+            counter: Arc::clone(&self.pending_acquires),
+        }
+    }
+
+    /// Acquire a PostgreSQL connection, counting the wait as a pending acquire
+    pub async fn acquire_postgres(&self) -> ConnectionResult<PoolConnection<sqlx::Postgres>> {
+        match &self.handle {
+            PoolHandle::Postgres(pool) => {
+                let _pending = self.track_acquire();
+                pool.acquire().await.map_err(acquire_error)
+            }
+            _ => Err(ConnectionError::InternalError("Pool is not a PostgreSQL pool".to_string())),
+        }
+    }
+
+    /// Acquire a MySQL connection, counting the wait as a pending acquire
+    pub async fn acquire_mysql(&self) -> ConnectionResult<PoolConnection<sqlx::MySql>> {
+        match &self.handle {
+            PoolHandle::MySql(pool) => {
+                let _pending = self.track_acquire();
+                pool.acquire().await.map_err(acquire_error)
+            }
+            _ => Err(ConnectionError::InternalError("Pool is not a MySQL pool".to_string())),
+        }
+    }
+
+    /// Acquire a SQLite connection, counting the wait as a pending acquire
+    pub async fn acquire_sqlite(&self) -> ConnectionResult<PoolConnection<sqlx::Sqlite>> {
+        match &self.handle {
+            PoolHandle::Sqlite(pool) => {
+                let _pending = self.track_acquire();
+                pool.acquire().await.map_err(acquire_error)
+            }
+            _ => Err(ConnectionError::InternalError("Pool is not a SQLite pool".to_string())),
+        }
+    }
+
+    /// Acquire a SQL Server client, counting the wait as a pending acquire
+    pub async fn acquire_sql_server(&self) -> ConnectionResult<deadpool::managed::Object<SqlServerConnectionManager>> {
+        match &self.handle {
+            PoolHandle::SqlServer(pool) => {
+                let _pending = self.track_acquire();
+                pool.get().await.map_err(sql_server_acquire_error)
+            }
+            _ => Err(ConnectionError::InternalError("Pool is not a SQL Server pool".to_string())),
+        }
+    }
+
+    /// Acquire an Oracle session with autocommit on, counting the wait as a pending acquire
+    pub async fn acquire_oracle(&self) -> ConnectionResult<oracle::Connection> {
+        match &self.handle {
+            PoolHandle::Oracle(pool) => {
+                let _pending = self.track_acquire();
+                let pool = pool.clone();
+                tokio::task::spawn_blocking(move || {
+                    let mut connection = pool.get()?;
+                    // Like the other drivers, statements outside a transaction take effect at once
+                    connection.set_autocommit(true);
+                    Ok(connection)
+                })
+                .await
+                .map_err(|e| ConnectionError::InternalError(format!("Oracle session task failed: {}", e)))?
+                .map_err(oracle_acquire_error)
+            }
+            _ => Err(ConnectionError::InternalError("Pool is not an Oracle pool".to_string())),
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        match &self.handle {
+            PoolHandle::Postgres(pool) => pool.is_closed(),
+            PoolHandle::MySql(pool) => pool.is_closed(),
+            PoolHandle::Sqlite(pool) => pool.is_closed(),
+            PoolHandle::SqlServer(pool) => pool.is_closed(),
+            // OCI has no closed flag, but every call on a closed pool fails
+            PoolHandle::Oracle(pool) => pool.open_count().is_err(),
+        }
+    }
+
+    /// Close the pool, waiting for checked-out connections to be returned
+    pub async fn close(&self) {
+        match &self.handle {
+            PoolHandle::Postgres(pool) => pool.close().await,
+            PoolHandle::MySql(pool) => pool.close().await,
+            PoolHandle::Sqlite(pool) => pool.close().await,
+            // deadpool drops checked-out clients as they are returned
+            PoolHandle::SqlServer(pool) => pool.close(),
+            PoolHandle::Oracle(pool) => {
+                let pool = pool.clone();
+                let closed = tokio::task::spawn_blocking(move || {
+                    // A default close refuses while sessions are checked out
+                    pool.close(&oracle::pool::CloseMode::Default)
+                        .or_else(|_| pool.close(&oracle::pool::CloseMode::Force))
+                })
+                .await;
+                if let Ok(Err(e)) = closed {
+                    log::warn!("Failed to close Oracle pool: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Snapshot the pool utilization together with the connection statistics
+    pub fn metrics(&self, stats: ConnectionStats) -> PoolMetrics {
+        let pool_size = self.size();
+        let idle_connections = self.num_idle().min(pool_size);
+
+        PoolMetrics {
+            connection_id: stats.connection_id,
+            pool_open: !self.is_closed(),
+            pool_size,
+            active_connections: pool_size - idle_connections,
+            idle_connections,
+            max_connections: self.max_connections,
+            pending_acquires: self.pending_acquires(),
+            stats,
+        }
+    }
+}
+
+fn acquire_error(e: sqlx::Error) -> ConnectionError {
+    match e {
+        sqlx::Error::PoolTimedOut => {
+            ConnectionError::TimeoutError("Timed out waiting for a pooled connection".to_string())
+        }
+        other => ConnectionError::ConnectionFailed(format!("Failed to acquire connection: {}", other)),
+    }
+}
+
+fn sql_server_acquire_error(e: deadpool::managed::PoolError<tiberius::error::Error>) -> ConnectionError {
+    match e {
+        deadpool::managed::PoolError::Timeout(_) => {
+            ConnectionError::TimeoutError("Timed out waiting for a pooled connection".to_string())
+        }
+        other => ConnectionError::ConnectionFailed(format!("Failed to acquire connection: {}", other)),
+    }
+}
+
+fn oracle_acquire_error(e: oracle::Error) -> ConnectionError {
+    match e.db_error().map(|db| db.code()) {
+        // ORA-24457: OCISessionGet() could not find a free session in the specified timeout period
+        Some(24457) => ConnectionError::TimeoutError("Timed out waiting for a pooled connection".to_string()),
+        _ => ConnectionError::ConnectionFailed(format!("Failed to acquire connection: {}", e)),
+    }
+}
+
+/// Open a SQL Server client, following a gateway redirect (e.g. Azure SQL) once
+pub async fn connect_sql_server(config: &tiberius::Config) -> Result<SqlServerClient, tiberius::error::Error> {
+    let tcp = TcpStream::connect(config.get_addr()).await?;
+    tcp.set_nodelay(true)?;
+
+    match tiberius::Client::connect(config.clone(), tcp.compat_write()).await {
+        Err(tiberius::error::Error::Routing { host, port }) => {
+            let mut redirected = config.clone();
+            redirected.host(&host);
+            redirected.port(port);
+
+            let tcp = TcpStream::connect(redirected.get_addr()).await?;
+            tcp.set_nodelay(true)?;
+            tiberius::Client::connect(redirected, tcp.compat_write()).await
+        }
+        result => result,
+    }
+}
+
+/// Creates SQL Server clients for the pool and checks them before reuse
+#[derive(Debug)]
+pub struct SqlServerConnectionManager {
+    config: tiberius::Config,
+}
+
+impl SqlServerConnectionManager {
+    pub fn new(config: tiberius::Config) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl deadpool::managed::Manager for SqlServerConnectionManager {
+    type Type = SqlServerClient;
+    type Error = tiberius::error::Error;
+
+    async fn create(&self) -> Result<SqlServerClient, tiberius::error::Error> {
+        connect_sql_server(&self.config).await
+    }
+
+    async fn recycle(
+        &self,
+        client: &mut SqlServerClient,
+        _metrics: &deadpool::managed::Metrics,
+    ) -> deadpool::managed::RecycleResult<tiberius::error::Error> {
+        // A client abandoned mid-query (e.g. by cancellation) fails here and is replaced
+        client.simple_query("SELECT 1").await?.into_row().await?;
+        Ok(())
+    }
+}
+
+/// Decrements the pending-acquire counter when dropped
+pub struct AcquireGuard {
+    counter: Arc<AtomicUsize>,
+}
+
+impl Drop for AcquireGuard {
+    fn drop(&mut self) {
+        self.counter.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Pool utilization metrics for monitoring
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolMetrics {
+    pub connection_id: Uuid,
+    pub pool_open: bool,
+    pub pool_size: u32,
+    pub active_connections: u32,
+    pub idle_connections: u32,
+    pub max_connections: u32,
+    pub pending_acquires: u32,
+    pub stats: ConnectionStats,
+}
+
+impl PoolMetrics {
+    /// Metrics for a connection that has not opened a pool yet
+    pub fn unopened(max_connections: u32, stats: ConnectionStats) -> Self {
+        Self {
+            connection_id: stats.connection_id,
+            pool_open: false,
+            pool_size: 0,
+            active_connections: 0,
+            idle_connections: 0,
+            max_connections,
+            pending_acquires: 0,
+            stats,
+        }
+    }
+}
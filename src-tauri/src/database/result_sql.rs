@@ -0,0 +1,113 @@
+// "Copy as" SQL for query results: a CREATE TABLE matching the result's columns, or INSERT
+// statements reproducing its rows, in the connection's dialect
+use crate::database::drivers::DatabaseDriver;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Rows kept per result, and so the most INSERT statements generated for one
+pub const MAX_GENERATED_INSERT_ROWS: usize = 1000;
+
+/// What `generate_result_sql` emits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResultSqlMode {
+    CreateTable,
+    Insert,
+}
+
+/// A result column as reported by the query, with its dialect type name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResultColumn {
+    pub name: String,
+    pub data_type: String,
+}
+
+/// Columns and leading rows of a finished query, kept so it can be referred to by query id
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecentResult {
+    pub query_id: String,
+    pub connection_id: Uuid,
+    pub columns: Vec<ResultColumn>,
+    /// At most `MAX_GENERATED_INSERT_ROWS` rows
+    pub rows: Vec<Vec<serde_json::Value>>,
+    /// Rows the query returned, including any not kept
+    pub total_rows: usize,
+}
+
+impl RecentResult {
+    pub fn new(
+        query_id: String,
+        connection_id: Uuid,
+        columns: Vec<ResultColumn>,
+        mut rows: Vec<Vec<serde_json::Value>>,
+        total_rows: usize,
+    ) -> Self {
+        rows.truncate(MAX_GENERATED_INSERT_ROWS);
+        Self { query_id, connection_id, columns, rows, total_rows }
+    }
+}
+
+/// Generated SQL, ready to paste into the editor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratedSql {
+    pub sql: String,
+    pub statement_count: usize,
+    /// Some rows were left out because of `MAX_GENERATED_INSERT_ROWS`
+    pub truncated: bool,
+}
+
+/// Build SQL for `result` targeting `table`, with types and quoting from `driver`
+pub fn generate_result_sql(
+    driver: &dyn DatabaseDriver,
+    table: &str,
+    result: &RecentResult,
+    mode: ResultSqlMode,
+) -> GeneratedSql {
+    let table = driver.quote_identifier(table);
+    let columns: Vec<String> = result.columns.iter().map(|column| driver.quote_identifier(&column.name)).collect();
+
+    match mode {
+        ResultSqlMode::CreateTable => {
+            let definitions: Vec<String> = result
+                .columns
+                .iter()
+                .zip(&columns)
+                .map(|(column, quoted)| {
+                    let data_type = driver.column_type_name(driver.normalize_type(&column.data_type));
+                    format!("    {} {}", quoted, data_type)
+                })
+                .collect();
+            GeneratedSql {
+                sql: format!("CREATE TABLE {} (\n{}\n);", table, definitions.join(",\n")),
+                statement_count: 1,
+                truncated: false,
+            }
+        }
+        ResultSqlMode::Insert => {
+            let column_list = columns.join(", ");
+            let statements: Vec<String> = result
+                .rows
+                .iter()
+                .map(|row| {
+                    let values: Vec<String> = row.iter().map(|value| sql_literal(driver, value)).collect();
+                    format!("INSERT INTO {} ({}) VALUES ({});", table, column_list, values.join(", "))
+                })
+                .collect();
+            GeneratedSql {
+                statement_count: statements.len(),
+                sql: statements.join("\n"),
+                truncated: result.total_rows > result.rows.len(),
+            }
+        }
+    }
+}
+
+/// A JSON result value as a SQL literal; arrays and objects become their JSON text
+fn sql_literal(driver: &dyn DatabaseDriver, value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => "NULL".to_string(),
+        serde_json::Value::Bool(flag) => if *flag { "TRUE" } else { "FALSE" }.to_string(),
+        serde_json::Value::Number(number) => number.to_string(),
+        serde_json::Value::String(text) => driver.quote_literal(text),
+        other => driver.quote_literal(&other.to_string()),
+    }
+}
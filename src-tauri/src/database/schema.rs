@@ -0,0 +1,417 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A single foreign-key constraint as reported by the database catalog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForeignKeyInfo {
+    pub constraint_name: String,
+    pub source_schema: Option<String>,
+    pub source_table: String,
+    pub source_columns: Vec<String>,
+    pub target_schema: Option<String>,
+    pub target_table: String,
+    pub target_columns: Vec<String>,
+    pub on_delete: String,
+    pub on_update: String,
+}
+
+/// A table participating in at least one foreign-key relationship
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableNode {
+    /// Qualified name (`schema.table`), referenced by `FkEdge::source`/`FkEdge::target`
+    pub id: String,
+    pub schema: Option<String>,
+    pub name: String,
+    pub outgoing_references: u32,
+    pub incoming_references: u32,
+}
+
+/// A foreign-key relationship between two tables
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FkEdge {
+    pub constraint_name: String,
+    pub source: String,
+    pub source_columns: Vec<String>,
+    pub target: String,
+    pub target_columns: Vec<String>,
+    pub on_delete: String,
+    pub on_update: String,
+    pub is_self_referencing: bool,
+    pub is_composite: bool,
+}
+
+/// Whole-schema relationship graph for ER diagram rendering
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ForeignKeyGraph {
+    pub nodes: Vec<TableNode>,
+    pub edges: Vec<FkEdge>,
+}
+
+impl ForeignKeyGraph {
+    /// Build the graph from raw foreign-key constraints
+    pub fn from_foreign_keys(foreign_keys: Vec<ForeignKeyInfo>) -> Self {
+        // BTreeMap keeps node order stable between calls
+        let mut nodes: BTreeMap<String, TableNode> = BTreeMap::new();
+        let mut edges = Vec::with_capacity(foreign_keys.len());
+
+        for fk in foreign_keys {
+            let source = node_id(fk.source_schema.as_deref(), &fk.source_table);
+            let target = node_id(fk.target_schema.as_deref(), &fk.target_table);
+
+            nodes
+                .entry(source.clone())
+                .or_insert_with(|| new_node(&source, fk.source_schema.clone(), &fk.source_table))
+                .outgoing_references += 1;
+            nodes
+                .entry(target.clone())
+                .or_insert_with(|| new_node(&target, fk.target_schema.clone(), &fk.target_table))
+                .incoming_references += 1;
+
+            edges.push(FkEdge {
+                constraint_name: fk.constraint_name,
+                is_self_referencing: source == target,
+                is_composite: fk.source_columns.len() > 1,
+                source,
+                source_columns: fk.source_columns,
+                target,
+                target_columns: fk.target_columns,
+                on_delete: fk.on_delete,
+                on_update: fk.on_update,
+            });
+        }
+
+        Self {
+            nodes: nodes.into_values().collect(),
+            edges,
+        }
+    }
+}
+
+fn node_id(schema: Option<&str>, table: &str) -> String {
+    match schema {
+        Some(schema) if !schema.is_empty() => format!("{}.{}", schema, table),
+        _ => table.to_string(),
+    }
+}
+
+fn new_node(id: &str, schema: Option<String>, name: &str) -> TableNode {
+    TableNode {
+        id: id.to_string(),
+        schema,
+        name: name.to_string(),
+        outgoing_references: 0,
+        incoming_references: 0,
+    }
+}
+
+/// A database's schemas with their tables, views, columns and indexes, as read from the catalog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseSchema {
+    pub name: String,
+    pub schemas: Vec<SchemaInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaInfo {
+    pub name: String,
+    pub tables: Vec<TableInfo>,
+    pub views: Vec<TableInfo>,
+    /// Not introspected yet; kept so the tree has the same shape for every driver
+    pub procedures: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableInfo {
+    pub name: String,
+    /// `table` or `view`
+    #[serde(rename = "type")]
+    pub object_type: String,
+    /// The catalog's estimate, where the database keeps one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub row_count: Option<u64>,
+    pub columns: Vec<ColumnInfo>,
+    pub indexes: Vec<IndexInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnInfo {
+    pub name: String,
+    pub data_type: String,
+    pub nullable: bool,
+    pub is_primary_key: bool,
+    pub is_foreign_key: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub precision: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scale: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_value: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexInfo {
+    pub name: String,
+    pub is_primary: bool,
+    pub is_unique: bool,
+    pub columns: Vec<String>,
+}
+
+/// One table or view row from a driver's catalog query
+#[derive(Debug, Clone)]
+pub struct TableRecord {
+    pub schema: String,
+    pub name: String,
+    pub is_view: bool,
+    pub row_count: Option<i64>,
+}
+
+/// One column row from a driver's catalog query; key flags are filled in by `KeyColumnRecord`s
+#[derive(Debug, Clone)]
+pub struct ColumnRecord {
+    pub schema: String,
+    pub table: String,
+    pub column: ColumnInfo,
+}
+
+/// A column taking part in a primary or foreign key
+#[derive(Debug, Clone)]
+pub struct KeyColumnRecord {
+    pub schema: String,
+    pub table: String,
+    pub column: String,
+    pub is_primary_key: bool,
+}
+
+/// One column of an index, in index order
+#[derive(Debug, Clone)]
+pub struct IndexColumnRecord {
+    pub schema: String,
+    pub table: String,
+    pub index: String,
+    pub is_primary: bool,
+    pub is_unique: bool,
+    pub column: String,
+}
+
+impl DatabaseSchema {
+    /// Build the schema tree from flat catalog rows. Columns must arrive in ordinal order and
+    /// index columns grouped by index in key order; rows for objects missing from `tables` are ignored.
+    pub fn assemble(
+        name: &str,
+        tables: Vec<TableRecord>,
+        columns: Vec<ColumnRecord>,
+        keys: Vec<KeyColumnRecord>,
+        index_columns: Vec<IndexColumnRecord>,
+    ) -> Self {
+        let mut objects: BTreeMap<(String, String), (bool, TableInfo)> = BTreeMap::new();
+        for table in tables {
+            let info = TableInfo {
+                name: table.name.clone(),
+                object_type: if table.is_view { "view" } else { "table" }.to_string(),
+                row_count: table.row_count.map(|count| count.max(0) as u64),
+                columns: Vec::new(),
+                indexes: Vec::new(),
+            };
+            objects.insert((table.schema, table.name), (table.is_view, info));
+        }
+
+        for record in columns {
+            if let Some((_, table)) = objects.get_mut(&(record.schema, record.table)) {
+                table.columns.push(record.column);
+            }
+        }
+
+        for key in keys {
+            let Some((_, table)) = objects.get_mut(&(key.schema, key.table)) else { continue };
+            if let Some(column) = table.columns.iter_mut().find(|c| c.name == key.column) {
+                if key.is_primary_key {
+                    column.is_primary_key = true;
+                } else {
+                    column.is_foreign_key = true;
+                }
+            }
+        }
+
+        for record in index_columns {
+            let Some((_, table)) = objects.get_mut(&(record.schema, record.table)) else { continue };
+            match table.indexes.last_mut() {
+                Some(index) if index.name == record.index => index.columns.push(record.column),
+                _ => table.indexes.push(IndexInfo {
+                    name: record.index,
+                    is_primary: record.is_primary,
+                    is_unique: record.is_unique,
+                    columns: vec![record.column],
+                }),
+            }
+        }
+
+        let mut schemas: Vec<SchemaInfo> = Vec::new();
+        for ((schema, _), (is_view, table)) in objects {
+            if schemas.last().map_or(true, |s| s.name != schema) {
+                schemas.push(SchemaInfo {
+                    name: schema,
+                    tables: Vec::new(),
+                    views: Vec::new(),
+                    procedures: Vec::new(),
+                });
+            }
+            let current = schemas.last_mut().expect("schema was just pushed");
+            if is_view {
+                current.views.push(table);
+            } else {
+                current.tables.push(table);
+            }
+        }
+
+        DatabaseSchema { name: name.to_string(), schemas }
+    }
+}
+
+/// Usage statistics for a single index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexUsage {
+    pub index_name: String,
+    pub table_name: String,
+    pub scans: u64,
+    pub rows_read: Option<u64>,
+    pub rows_fetched: Option<u64>,
+    pub size_bytes: Option<u64>,
+    pub is_unique: bool,
+    pub is_primary: bool,
+    /// Never used and not enforcing a constraint, so it only costs write overhead
+    pub removal_candidate: bool,
+}
+
+/// Index usage for a table, or the reason statistics couldn't be collected
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexUsageReport {
+    pub statistics_available: bool,
+    pub message: Option<String>,
+    pub indexes: Vec<IndexUsage>,
+}
+
+impl IndexUsageReport {
+    pub fn available(indexes: Vec<IndexUsage>) -> Self {
+        Self {
+            statistics_available: true,
+            message: None,
+            indexes,
+        }
+    }
+
+    pub fn unavailable(message: impl Into<String>) -> Self {
+        Self {
+            statistics_available: false,
+            message: Some(message.into()),
+            indexes: Vec::new(),
+        }
+    }
+}
+
+/// Distinct values of a column, capped for filter pickers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DistinctValues {
+    pub values: Vec<Option<String>>,
+    /// More distinct values exist beyond the returned ones
+    pub truncated: bool,
+}
+
+/// Optional name patterns restricting which schemas and tables are introspected.
+/// Patterns may use glob (`*`, `?`) or LIKE (`%`, `_`) wildcards and match case-insensitively.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaFilter {
+    pub schema_pattern: Option<String>,
+    pub table_pattern: Option<String>,
+}
+
+impl SchemaFilter {
+    pub fn new(schema_pattern: Option<String>, table_pattern: Option<String>) -> Self {
+        let normalize = |pattern: Option<String>| {
+            pattern
+                .map(|p| to_like_pattern(p.trim()))
+                .filter(|p| !p.is_empty() && p != "%")
+        };
+        Self {
+            schema_pattern: normalize(schema_pattern),
+            table_pattern: normalize(table_pattern),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.schema_pattern.is_none() && self.table_pattern.is_none()
+    }
+
+    /// LIKE pattern for catalog queries; `%` when schemas are unfiltered
+    pub fn schema_like(&self) -> &str {
+        self.schema_pattern.as_deref().unwrap_or("%")
+    }
+
+    /// LIKE pattern for catalog queries; `%` when tables are unfiltered
+    pub fn table_like(&self) -> &str {
+        self.table_pattern.as_deref().unwrap_or("%")
+    }
+
+    pub fn matches_schema(&self, name: &str) -> bool {
+        self.schema_pattern.as_deref().map_or(true, |p| like_matches(p, name))
+    }
+
+    pub fn matches_table(&self, name: &str) -> bool {
+        self.table_pattern.as_deref().map_or(true, |p| like_matches(p, name))
+    }
+
+    /// Suffix that keeps filtered and unfiltered results apart in the schema cache
+    pub fn cache_key_suffix(&self) -> String {
+        if self.is_empty() {
+            String::new()
+        } else {
+            format!("_s:{}_t:{}", self.schema_like(), self.table_like())
+        }
+    }
+}
+
+/// Translate glob wildcards into their LIKE equivalents
+fn to_like_pattern(pattern: &str) -> String {
+    pattern
+        .chars()
+        .map(|c| match c {
+            '*' => '%',
+            '?' => '_',
+            other => other,
+        })
+        .collect()
+}
+
+/// Case-insensitive LIKE matching (`%` = any run, `_` = any single character)
+fn like_matches(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let value: Vec<char> = value.to_lowercase().chars().collect();
+
+    // matched[j] is true when the pattern prefix so far matches value[..j]
+    let mut matched = vec![false; value.len() + 1];
+    matched[0] = true;
+
+    for p in &pattern {
+        let mut next = vec![false; value.len() + 1];
+        match p {
+            '%' => {
+                let mut any = false;
+                for j in 0..=value.len() {
+                    any |= matched[j];
+                    next[j] = any;
+                }
+            }
+            _ => {
+                for j in 0..value.len() {
+                    if matched[j] && (*p == '_' || *p == value[j]) {
+                        next[j + 1] = true;
+                    }
+                }
+            }
+        }
+        matched = next;
+    }
+
+    matched[value.len()]
+}
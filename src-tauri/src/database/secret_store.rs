@@ -0,0 +1,303 @@
+use crate::database::types::{ConnectionError, ConnectionResult};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use keyring::{Entry, Error as KeyringError};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Selects the credential backend: `keyring` (default) or `file`
+pub const BACKEND_ENV_VAR: &str = "SYMBIOTIC_CREDENTIAL_BACKEND";
+/// Passphrase for the encrypted file backend; without it a generated key file is used
+pub const PASSPHRASE_ENV_VAR: &str = "SYMBIOTIC_CREDENTIAL_KEY";
+
+const PBKDF2_ITERATIONS: u32 = 100_000;
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+
+/// Where secrets (passwords and TLS file locations) are kept, addressed by service and account
+pub trait SecretStore: std::fmt::Debug + Send + Sync {
+    fn set(&self, service: &str, account: &str, value: &str) -> ConnectionResult<()>;
+
+    /// The stored value, or `None` if there is no entry
+    fn get(&self, service: &str, account: &str) -> ConnectionResult<Option<String>>;
+
+    /// Remove an entry; removing a missing entry is not an error
+    fn delete(&self, service: &str, account: &str) -> ConnectionResult<()>;
+}
+
+/// Which `SecretStore` implementation to use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CredentialBackend {
+    /// The desktop keychain (macOS Keychain, Windows Credential Manager, Secret Service)
+    Keyring,
+    /// An AES-256-GCM encrypted file, for headless machines and CI without a keychain
+    EncryptedFile,
+}
+
+impl CredentialBackend {
+    /// Backend named by `SYMBIOTIC_CREDENTIAL_BACKEND`, defaulting to the keyring
+    pub fn from_env() -> ConnectionResult<Self> {
+        match std::env::var(BACKEND_ENV_VAR) {
+            Ok(value) => Self::parse(&value),
+            Err(_) => Ok(CredentialBackend::Keyring),
+        }
+    }
+
+    pub fn parse(value: &str) -> ConnectionResult<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "" | "keyring" | "os" => Ok(CredentialBackend::Keyring),
+            "file" | "encrypted-file" => Ok(CredentialBackend::EncryptedFile),
+            other => Err(ConnectionError::ConfigurationError(format!(
+                "Unknown credential backend '{}': expected keyring or file",
+                other
+            ))),
+        }
+    }
+
+    /// Open the store for this backend at its default location
+    pub fn open(&self) -> ConnectionResult<Box<dyn SecretStore>> {
+        match self {
+            CredentialBackend::Keyring => Ok(Box::new(KeyringStore)),
+            CredentialBackend::EncryptedFile => {
+                let dir = default_store_dir()?;
+                let passphrase = std::env::var(PASSPHRASE_ENV_VAR).ok().filter(|p| !p.is_empty());
+                let key_material = match passphrase {
+                    Some(passphrase) => passphrase.into_bytes(),
+                    None => load_or_create_key_file(&dir.join("credentials.key"))?,
+                };
+                Ok(Box::new(EncryptedFileStore::open(dir.join("credentials.enc"), &key_material)?))
+            }
+        }
+    }
+}
+
+/// Secrets in the operating system's keychain
+#[derive(Debug, Default)]
+pub struct KeyringStore;
+
+impl KeyringStore {
+    fn entry(service: &str, account: &str) -> ConnectionResult<Entry> {
+        Entry::new(service, account)
+            .map_err(|e| ConnectionError::CredentialError(format!("Failed to create keyring entry: {}", e)))
+    }
+}
+
+impl SecretStore for KeyringStore {
+    fn set(&self, service: &str, account: &str, value: &str) -> ConnectionResult<()> {
+        Self::entry(service, account)?
+            .set_password(value)
+            .map_err(|e| ConnectionError::CredentialError(format!("Failed to store {}: {}", account, e)))
+    }
+
+    fn get(&self, service: &str, account: &str) -> ConnectionResult<Option<String>> {
+        match Self::entry(service, account)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(KeyringError::NoEntry) => Ok(None),
+            Err(e) => Err(ConnectionError::CredentialError(format!("Failed to retrieve {}: {}", account, e))),
+        }
+    }
+
+    fn delete(&self, service: &str, account: &str) -> ConnectionResult<()> {
+        match Self::entry(service, account)?.delete_password() {
+            Ok(()) | Err(KeyringError::NoEntry) => Ok(()),
+            Err(e) => Err(ConnectionError::CredentialError(format!("Failed to delete {}: {}", account, e))),
+        }
+    }
+}
+
+/// On-disk layout of the encrypted store; only ciphertext and the key-derivation salt are stored
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct EncryptedFile {
+    salt: String,
+    /// `service/account` -> base64 of nonce followed by ciphertext
+    entries: BTreeMap<String, String>,
+}
+
+/// Secrets encrypted with AES-256-GCM in a single file.
+///
+/// The key is derived with PBKDF2 from either a passphrase or a generated key file. With a
+/// key file the secrets are only as safe as that file's permissions, which is still better
+/// than plain text on a machine that has no keychain.
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    key: LessSafeKey,
+    // Serializes read-modify-write cycles on the file
+    lock: Mutex<()>,
+}
+
+impl std::fmt::Debug for EncryptedFileStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptedFileStore").field("path", &self.path).finish_non_exhaustive()
+    }
+}
+
+impl EncryptedFileStore {
+    /// Open (or start) the store at `path`, deriving the encryption key from `key_material`
+    pub fn open(path: PathBuf, key_material: &[u8]) -> ConnectionResult<Self> {
+        let mut file = read_store_file(&path)?;
+        if file.salt.is_empty() {
+            let mut salt = [0u8; SALT_LEN];
+            fill_random(&mut salt)?;
+            file.salt = BASE64.encode(salt);
+            write_store_file(&path, &file)?;
+        }
+
+        let salt = BASE64
+            .decode(&file.salt)
+            .map_err(|e| ConnectionError::CredentialError(format!("Corrupt credential store salt: {}", e)))?;
+        let mut key_bytes = [0u8; KEY_LEN];
+        ring::pbkdf2::derive(
+            ring::pbkdf2::PBKDF2_HMAC_SHA256,
+            NonZeroU32::new(PBKDF2_ITERATIONS).expect("iteration count is non-zero"),
+            &salt,
+            key_material,
+            &mut key_bytes,
+        );
+        let key = UnboundKey::new(&AES_256_GCM, &key_bytes)
+            .map_err(|_| ConnectionError::CredentialError("Failed to initialize credential encryption".to_string()))?;
+
+        Ok(Self { path, key: LessSafeKey::new(key), lock: Mutex::new(()) })
+    }
+
+    fn slot(service: &str, account: &str) -> String {
+        format!("{}/{}", service, account)
+    }
+
+    fn with_file<T>(&self, f: impl FnOnce(&mut EncryptedFile) -> ConnectionResult<(T, bool)>) -> ConnectionResult<T> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut file = read_store_file(&self.path)?;
+        let (result, changed) = f(&mut file)?;
+        if changed {
+            write_store_file(&self.path, &file)?;
+        }
+        Ok(result)
+    }
+}
+
+impl SecretStore for EncryptedFileStore {
+    fn set(&self, service: &str, account: &str, value: &str) -> ConnectionResult<()> {
+        let slot = Self::slot(service, account);
+
+        let mut nonce = [0u8; NONCE_LEN];
+        fill_random(&mut nonce)?;
+        let mut sealed = value.as_bytes().to_vec();
+        // The slot name is authenticated, so ciphertext can't be moved to another entry
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(slot.as_bytes()), &mut sealed)
+            .map_err(|_| ConnectionError::CredentialError(format!("Failed to encrypt {}", account)))?;
+
+        let mut stored = nonce.to_vec();
+        stored.extend_from_slice(&sealed);
+        self.with_file(|file| {
+            file.entries.insert(slot, BASE64.encode(&stored));
+            Ok(((), true))
+        })
+    }
+
+    fn get(&self, service: &str, account: &str) -> ConnectionResult<Option<String>> {
+        let slot = Self::slot(service, account);
+        let Some(encoded) = self.with_file(|file| Ok((file.entries.get(&slot).cloned(), false)))? else {
+            return Ok(None);
+        };
+
+        let unreadable = || ConnectionError::CredentialError(format!(
+            "Failed to decrypt {}: wrong key or corrupt credential store",
+            account
+        ));
+        let mut stored = BASE64.decode(encoded).map_err(|_| unreadable())?;
+        if stored.len() < NONCE_LEN {
+            return Err(unreadable());
+        }
+        let mut sealed = stored.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&stored).map_err(|_| unreadable())?;
+        let plain = self
+            .key
+            .open_in_place(nonce, Aad::from(slot.as_bytes()), &mut sealed)
+            .map_err(|_| unreadable())?;
+
+        String::from_utf8(plain.to_vec()).map(Some).map_err(|_| unreadable())
+    }
+
+    fn delete(&self, service: &str, account: &str) -> ConnectionResult<()> {
+        let slot = Self::slot(service, account);
+        self.with_file(|file| {
+            let removed = file.entries.remove(&slot).is_some();
+            Ok(((), removed))
+        })
+    }
+}
+
+fn default_store_dir() -> ConnectionResult<PathBuf> {
+    let mut path = dirs::config_dir()
+        .ok_or_else(|| ConnectionError::ConfigurationError("Could not determine config directory".to_string()))?;
+    path.push("symbiotic-analysis");
+    Ok(path)
+}
+
+fn fill_random(buffer: &mut [u8]) -> ConnectionResult<()> {
+    SystemRandom::new()
+        .fill(buffer)
+        .map_err(|_| ConnectionError::CredentialError("Failed to generate random bytes".to_string()))
+}
+
+fn read_store_file(path: &Path) -> ConnectionResult<EncryptedFile> {
+    match std::fs::read_to_string(path) {
+        Ok(data) => serde_json::from_str(&data)
+            .map_err(|e| ConnectionError::CredentialError(format!("Corrupt credential store: {}", e))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(EncryptedFile::default()),
+        Err(e) => Err(ConnectionError::CredentialError(format!("Failed to read credential store: {}", e))),
+    }
+}
+
+fn write_store_file(path: &Path, file: &EncryptedFile) -> ConnectionResult<()> {
+    let data = serde_json::to_string_pretty(file)
+        .map_err(|e| ConnectionError::CredentialError(format!("Failed to serialize credential store: {}", e)))?;
+    write_private_file(path, data.as_bytes())
+}
+
+/// Read the generated key file, creating it with fresh random bytes on first use
+fn load_or_create_key_file(path: &Path) -> ConnectionResult<Vec<u8>> {
+    match std::fs::read(path) {
+        Ok(key) if key.len() == KEY_LEN => Ok(key),
+        Ok(_) => Err(ConnectionError::CredentialError(format!(
+            "Credential key file {} is corrupt",
+            path.display()
+        ))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let mut key = vec![0u8; KEY_LEN];
+            fill_random(&mut key)?;
+            write_private_file(path, &key)?;
+            Ok(key)
+        }
+        Err(e) => Err(ConnectionError::CredentialError(format!("Failed to read credential key file: {}", e))),
+    }
+}
+
+/// Write a file readable only by the current user, replacing it atomically
+fn write_private_file(path: &Path, data: &[u8]) -> ConnectionResult<()> {
+    let io_error = |e: std::io::Error| ConnectionError::CredentialError(format!("Failed to write {}: {}", path.display(), e));
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(io_error)?;
+    }
+
+    let temp_path = path.with_extension("tmp");
+    {
+        use std::io::Write;
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&temp_path).map_err(io_error)?;
+        file.write_all(data).map_err(io_error)?;
+        file.sync_all().map_err(io_error)?;
+    }
+    std::fs::rename(&temp_path, path).map_err(io_error)
+}
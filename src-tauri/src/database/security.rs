@@ -1,7 +1,12 @@
+use crate::database::secret_store::{CredentialBackend, KeyringStore, SecretStore};
 use crate::database::types::{ConnectionError, ConnectionResult, DatabaseCredentials};
-use keyring::{Entry, Error as KeyringError};
+use chrono::{DateTime, Utc};
 use ring::digest;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use uuid::Uuid;
@@ -13,23 +18,172 @@ use base64;
 pub struct ThreatModel {
     /// Track access attempts for rate limiting
     access_attempts: Arc<Mutex<HashMap<String, Vec<Instant>>>>,
-    /// Maximum attempts per time window
-    max_attempts: usize,
-    /// Time window for rate limiting (seconds)
-    time_window: Duration,
+    /// Rate limits, per operation or the default
+    config: ThreatModelConfig,
     /// Track suspicious activities
     suspicious_activities: Arc<Mutex<Vec<SecurityEvent>>>,
+    /// Durable copy of every event, when one is configured
+    audit_file: Option<AuditFile>,
 }
 
+/// Attempts allowed within a sliding time window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub max_attempts: usize,
+    pub time_window: Duration,
+}
+
+/// Rate limits applied by `ThreatModel::validate_access`
 #[derive(Debug, Clone)]
+pub struct ThreatModelConfig {
+    /// Limit for operations without an override
+    pub max_attempts: usize,
+    pub time_window: Duration,
+    /// Limits keyed by the operation name passed to `validate_access`
+    pub operation_limits: HashMap<String, RateLimit>,
+}
+
+impl ThreatModelConfig {
+    /// Give `operation` its own limit instead of the default
+    pub fn with_operation_limit(mut self, operation: &str, max_attempts: usize, time_window: Duration) -> Self {
+        self.operation_limits.insert(operation.to_string(), RateLimit { max_attempts, time_window });
+        self
+    }
+
+    /// Limit that applies to `operation`
+    pub fn limit_for(&self, operation: &str) -> RateLimit {
+        self.operation_limits.get(operation).copied().unwrap_or(RateLimit {
+            max_attempts: self.max_attempts,
+            time_window: self.time_window,
+        })
+    }
+}
+
+impl Default for ThreatModelConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5, // Maximum 5 attempts per time window
+            time_window: Duration::from_secs(300), // 5 minute window
+            operation_limits: HashMap::new(),
+        }
+    }
+}
+
+/// Events kept in memory; older ones remain only in the audit file
+const MAX_BUFFERED_EVENTS: usize = 1000;
+
+/// Append-only JSONL security audit file, rotated by size.
+/// `security-audit.jsonl` rolls over to `.1`, `.1` to `.2` and so on; the oldest is deleted.
+#[derive(Debug, Clone)]
+pub struct AuditFile {
+    path: PathBuf,
+    max_bytes: u64,
+    rotated_files: usize,
+    /// Held from the size check through rotation and the write, so concurrent appends
+    /// can't rotate twice or land a line in a file that is being renamed away.
+    /// Shared by clones, which write the same file.
+    write_lock: Arc<Mutex<()>>,
+}
+
+impl AuditFile {
+    pub const DEFAULT_MAX_BYTES: u64 = 5 * 1024 * 1024;
+    pub const DEFAULT_ROTATED_FILES: usize = 3;
+
+    pub fn new(path: PathBuf, max_bytes: u64, rotated_files: usize) -> Self {
+        Self {
+            path,
+            max_bytes,
+            rotated_files,
+            write_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// `security-audit.jsonl` in the application config directory
+    pub fn default_location() -> ConnectionResult<Self> {
+        let mut path = dirs::config_dir()
+            .ok_or_else(|| ConnectionError::ConfigurationError("Could not determine config directory".to_string()))?;
+        path.push("symbiotic-analysis");
+        path.push("security-audit.jsonl");
+        Ok(Self::new(path, Self::DEFAULT_MAX_BYTES, Self::DEFAULT_ROTATED_FILES))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write one event as a line, rotating first if it would push the file past the size limit
+    pub fn append(&self, event: &SecurityEvent) -> std::io::Result<()> {
+        let mut line = serde_json::to_vec(event)?;
+        line.push(b'\n');
+
+        // A writer that panicked mid-append left nothing the next one can't cope with
+        let _guard = self.write_lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let current_size = std::fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if current_size > 0 && current_size + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        file.write_all(&line)
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&self) -> std::io::Result<()> {
+        if self.rotated_files == 0 {
+            return std::fs::remove_file(&self.path);
+        }
+        let oldest = self.rotated_path(self.rotated_files);
+        if oldest.exists() {
+            std::fs::remove_file(&oldest)?;
+        }
+        for index in (1..self.rotated_files).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                std::fs::rename(&from, self.rotated_path(index + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, self.rotated_path(1))
+    }
+
+    /// The newest `limit` events across the current and rotated files, oldest first.
+    /// Lines that don't parse (e.g. a write cut short by a crash) are skipped.
+    pub fn load_recent(&self, limit: usize) -> Vec<SecurityEvent> {
+        let mut files: Vec<PathBuf> = (1..=self.rotated_files).rev().map(|i| self.rotated_path(i)).collect();
+        files.push(self.path.clone());
+
+        let mut events = Vec::new();
+        for path in files {
+            let Ok(file) = std::fs::File::open(&path) else { continue };
+            events.extend(
+                BufReader::new(file)
+                    .lines()
+                    .map_while(Result::ok)
+                    .filter_map(|line| serde_json::from_str::<SecurityEvent>(&line).ok()),
+            );
+        }
+
+        let skip = events.len().saturating_sub(limit);
+        events.split_off(skip)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecurityEvent {
     pub event_type: SecurityEventType,
-    pub timestamp: Instant,
+    pub timestamp: DateTime<Utc>,
     pub details: String,
     pub source: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SecurityEventType {
     UnauthorizedAccess,
     RateLimitExceeded,
@@ -39,17 +193,50 @@ pub enum SecurityEventType {
     InvalidRequest,
 }
 
+impl std::fmt::Display for SecurityEventType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SecurityEventType::UnauthorizedAccess => write!(f, "Unauthorized access"),
+            SecurityEventType::RateLimitExceeded => write!(f, "Rate limit exceeded"),
+            SecurityEventType::SuspiciousPattern => write!(f, "Suspicious pattern"),
+            SecurityEventType::CredentialAccess => write!(f, "Credential access"),
+            SecurityEventType::MemoryScrapingAttempt => write!(f, "Memory scraping attempt"),
+            SecurityEventType::InvalidRequest => write!(f, "Invalid request"),
+        }
+    }
+}
+
 impl ThreatModel {
     pub fn new() -> Self {
+        Self::new_with_config(ThreatModelConfig::default())
+    }
+
+    pub fn new_with_config(config: ThreatModelConfig) -> Self {
         Self {
             access_attempts: Arc::new(Mutex::new(HashMap::new())),
-            max_attempts: 5, // Maximum 5 attempts per time window
-            time_window: Duration::from_secs(300), // 5 minute window
+            config,
             suspicious_activities: Arc::new(Mutex::new(Vec::new())),
+            audit_file: None,
         }
     }
 
-    /// Validate access request and apply enhanced rate limiting
+    /// Also write events to `audit_file`, starting from the events already recorded there
+    pub fn with_audit_file(audit_file: AuditFile) -> Self {
+        let recent = audit_file.load_recent(MAX_BUFFERED_EVENTS);
+        Self {
+            suspicious_activities: Arc::new(Mutex::new(recent)),
+            audit_file: Some(audit_file),
+            ..Self::new()
+        }
+    }
+
+    /// Rate limits currently applied
+    pub fn config(&self) -> &ThreatModelConfig {
+        &self.config
+    }
+
+    /// Validate access request and apply enhanced rate limiting, using the limit configured
+    /// for `requester` (the operation name) or the default one
     pub fn validate_access(&self, requester: &str) -> ConnectionResult<()> {
         // Enhanced validation: check for suspicious requester patterns
         if requester.is_empty() || requester.len() > 256 {
@@ -61,22 +248,23 @@ impl ThreatModel {
         let mut attempts = self.access_attempts.lock()
             .map_err(|e| ConnectionError::SecurityViolation(format!("Lock error: {}", e)))?;
 
+        let limit = self.config.limit_for(requester);
         let now = Instant::now();
         // Use a hash of the requester to prevent enumeration attacks
         let key = format!("{:x}", ring::digest::digest(&ring::digest::SHA256, requester.as_bytes()));
 
         // Clean old attempts outside time window
         if let Some(request_attempts) = attempts.get_mut(&key) {
-            request_attempts.retain(|&attempt_time| now.duration_since(attempt_time) < self.time_window);
+            request_attempts.retain(|&attempt_time| now.duration_since(attempt_time) < limit.time_window);
         }
 
         // Check rate limit with progressive penalties
         let current_attempts = attempts.get(&key).map(|v| v.len()).unwrap_or(0);
-        let effective_limit = if current_attempts > self.max_attempts / 2 {
+        let effective_limit = if current_attempts > limit.max_attempts / 2 {
             // Reduce limit for suspicious activity
-            self.max_attempts / 2
+            limit.max_attempts / 2
         } else {
-            self.max_attempts
+            limit.max_attempts
         };
 
         if current_attempts >= effective_limit {
@@ -94,19 +282,28 @@ impl ThreatModel {
 
     /// Log security events for monitoring
     pub fn log_security_event(&self, event_type: SecurityEventType, details: String, source: String) {
+        let event = SecurityEvent {
+            event_type,
+            timestamp: Utc::now(),
+            details,
+            source,
+        };
+
         if let Ok(mut events) = self.suspicious_activities.lock() {
-            events.push(SecurityEvent {
-                event_type,
-                timestamp: Instant::now(),
-                details,
-                source,
-            });
+            events.push(event.clone());
 
-            // Keep only recent events (last 1000)
-            if events.len() > 1000 {
+            // Keep only recent events in memory (last 1000)
+            if events.len() > MAX_BUFFERED_EVENTS {
                 events.drain(0..100);
             }
         }
+
+        // Appended after the buffer lock is released, so rate checks never wait on the disk
+        if let Some(audit_file) = &self.audit_file {
+            if let Err(e) = audit_file.append(&event) {
+                log::warn!("Failed to write security audit file {}: {}", audit_file.path().display(), e);
+            }
+        }
     }
 
     /// Check for memory scraping patterns with advanced heuristics
@@ -140,22 +337,64 @@ impl Default for ThreatModel {
     }
 }
 
-/// Secure credential manager using native OS keychain
+/// Secure credential manager backed by the OS keychain or, where there is none, an encrypted file
 #[derive(Debug)]
 pub struct SecureCredentialManager {
     threat_model: ThreatModel,
     service_prefix: String,
+    store: Box<dyn SecretStore>,
 }
 
 impl SecureCredentialManager {
+    /// Use the backend chosen by `SYMBIOTIC_CREDENTIAL_BACKEND`, falling back to the keychain
+    /// if that backend can't be opened, and persist the security audit in the config directory
     pub fn new() -> Self {
+        let audit_file = AuditFile::default_location()
+            .map_err(|e| log::warn!("Security audit will not be persisted: {}", e))
+            .ok();
+        Self::with_audit_file(audit_file)
+    }
+
+    /// Like `new`, but persist the security audit to `audit_file`, or nowhere if it is `None`
+    pub fn with_audit_file(audit_file: Option<AuditFile>) -> Self {
+        let store = CredentialBackend::from_env()
+            .and_then(|backend| backend.open())
+            .unwrap_or_else(|e| {
+                log::error!("Credential backend unavailable, using the OS keychain: {}", e);
+                Box::new(KeyringStore)
+            });
+        let mut threat_model = match audit_file {
+            Some(audit_file) => ThreatModel::with_audit_file(audit_file),
+            None => ThreatModel::new(),
+        };
+        threat_model.config = Self::default_rate_limits();
+        Self { threat_model, ..Self::with_store(store) }
+    }
+
+    pub fn with_store(store: Box<dyn SecretStore>) -> Self {
         Self {
-            threat_model: ThreatModel::new(),
+            threat_model: ThreatModel::new_with_config(Self::default_rate_limits()),
             service_prefix: "symbiotic-analysis-db".to_string(),
+            store,
         }
     }
 
-    /// Store credentials securely in OS keychain with validation
+    /// Replace the store/retrieve/delete rate limits
+    pub fn with_rate_limits(mut self, config: ThreatModelConfig) -> Self {
+        self.threat_model.config = config;
+        self
+    }
+
+    /// Reads happen once per connection opened or tested, so they get far more headroom
+    /// than writes and deletes, which only follow user edits
+    pub fn default_rate_limits() -> ThreatModelConfig {
+        ThreatModelConfig::default()
+            .with_operation_limit("store_credentials", 20, Duration::from_secs(300))
+            .with_operation_limit("retrieve_credentials", 200, Duration::from_secs(60))
+            .with_operation_limit("delete_credentials", 20, Duration::from_secs(300))
+    }
+
+    /// Store credentials securely with validation
     pub fn store_credentials(&self, connection_id: Uuid, credentials: &DatabaseCredentials) -> ConnectionResult<()> {
         // Validate access
         self.threat_model.validate_access("store_credentials")?;
@@ -166,13 +405,13 @@ impl SecureCredentialManager {
         let service = format!("{}-{}", self.service_prefix, connection_id);
         let account = "password";
 
-        // Create keyring entry
-        let entry = Entry::new(&service, account)
-            .map_err(|e| ConnectionError::CredentialError(format!("Failed to create keyring entry: {}", e)))?;
-
         // Store password securely
-        entry.set_password(&credentials.password)
-            .map_err(|e| ConnectionError::CredentialError(format!("Failed to store password: {}", e)))?;
+        self.store.set(&service, account, &credentials.password)?;
+
+        // Store TLS file locations alongside the password
+        for (account, path) in Self::ssl_path_accounts(credentials) {
+            self.store_optional_entry(&service, account, path.as_deref())?;
+        }
 
         // Log successful credential storage (without sensitive data)
         self.threat_model.log_security_event(
@@ -219,7 +458,7 @@ impl SecureCredentialManager {
         Ok(())
     }
 
-    /// Retrieve credentials securely from OS keychain
+    /// Retrieve credentials securely
     pub fn retrieve_credentials(&self, connection_id: Uuid) -> ConnectionResult<DatabaseCredentials> {
         // Validate access
         self.threat_model.validate_access("retrieve_credentials")?;
@@ -227,16 +466,14 @@ impl SecureCredentialManager {
         let service = format!("{}-{}", self.service_prefix, connection_id);
         let account = "password";
 
-        // Create keyring entry
-        let entry = Entry::new(&service, account)
-            .map_err(|e| ConnectionError::CredentialError(format!("Failed to create keyring entry: {}", e)))?;
-
         // Retrieve password
-        let password = entry.get_password()
-            .map_err(|e| match e {
-                KeyringError::NoEntry => ConnectionError::CredentialError("Credentials not found".to_string()),
-                _ => ConnectionError::CredentialError(format!("Failed to retrieve password: {}", e)),
-            })?;
+        let password = self.store.get(&service, account)?
+            .ok_or_else(|| ConnectionError::CredentialError("Credentials not found".to_string()))?;
+
+        let mut credentials = DatabaseCredentials::new(connection_id, password);
+        credentials.certificate_path = self.retrieve_optional_entry(&service, "certificate_path")?;
+        credentials.private_key_path = self.retrieve_optional_entry(&service, "private_key_path")?;
+        credentials.ca_certificate_path = self.retrieve_optional_entry(&service, "ca_certificate_path")?;
 
         // Log credential access
         self.threat_model.log_security_event(
@@ -245,10 +482,10 @@ impl SecureCredentialManager {
             "credential_manager".to_string(),
         );
 
-        Ok(DatabaseCredentials::new(connection_id, password))
+        Ok(credentials)
     }
 
-    /// Delete credentials from OS keychain
+    /// Delete credentials
     pub fn delete_credentials(&self, connection_id: Uuid) -> ConnectionResult<()> {
         // Validate access
         self.threat_model.validate_access("delete_credentials")?;
@@ -256,16 +493,12 @@ impl SecureCredentialManager {
         let service = format!("{}-{}", self.service_prefix, connection_id);
         let account = "password";
 
-        // Create keyring entry
-        let entry = Entry::new(&service, account)
-            .map_err(|e| ConnectionError::CredentialError(format!("Failed to create keyring entry: {}", e)))?;
+        // Delete password; an already-deleted entry is fine
+        self.store.delete(&service, account)?;
 
-        // Delete password
-        entry.delete_password()
-            .map_err(|e| match e {
-                KeyringError::NoEntry => Ok(()), // Already deleted, that's fine
-                _ => Err(ConnectionError::CredentialError(format!("Failed to delete password: {}", e))),
-            })?;
+        for account in ["certificate_path", "private_key_path", "ca_certificate_path"] {
+            self.store_optional_entry(&service, account, None)?;
+        }
 
         // Log credential deletion
         self.threat_model.log_security_event(
@@ -277,16 +510,31 @@ impl SecureCredentialManager {
         Ok(())
     }
 
+    fn ssl_path_accounts(credentials: &DatabaseCredentials) -> [(&'static str, &Option<String>); 3] {
+        [
+            ("certificate_path", &credentials.certificate_path),
+            ("private_key_path", &credentials.private_key_path),
+            ("ca_certificate_path", &credentials.ca_certificate_path),
+        ]
+    }
+
+    /// Store an optional secret, removing the entry when the value is absent
+    fn store_optional_entry(&self, service: &str, account: &str, value: Option<&str>) -> ConnectionResult<()> {
+        match value {
+            Some(value) => self.store.set(service, account, value),
+            None => self.store.delete(service, account),
+        }
+    }
+
+    /// Retrieve an optional secret
+    fn retrieve_optional_entry(&self, service: &str, account: &str) -> ConnectionResult<Option<String>> {
+        self.store.get(service, account)
+    }
+
     /// Check if credentials exist for a connection
     pub fn credentials_exist(&self, connection_id: Uuid) -> bool {
         let service = format!("{}-{}", self.service_prefix, connection_id);
-        let account = "password";
-
-        if let Ok(entry) = Entry::new(&service, account) {
-            entry.get_password().is_ok()
-        } else {
-            false
-        }
+        matches!(self.store.get(&service, "password"), Ok(Some(_)))
     }
 
     /// Generate secure hash for connection validation
@@ -328,6 +576,92 @@ mod tests {
 
         // Next attempt should fail due to rate limiting
         assert!(threat_model.validate_access(requester).is_err());
+
+        let audit = threat_model.get_security_audit();
+        assert_eq!(audit.len(), 1);
+        assert_eq!(audit[0].event_type, SecurityEventType::RateLimitExceeded);
+        assert_eq!(audit[0].event_type.to_string(), "Rate limit exceeded");
+    }
+
+    #[test]
+    fn test_operation_rate_limit_overrides() {
+        let config = ThreatModelConfig::default()
+            .with_operation_limit("retrieve_credentials", 100, Duration::from_secs(60));
+        let threat_model = ThreatModel::new_with_config(config);
+
+        // A bulk connection test reads one credential per connection
+        for _ in 0..20 {
+            assert!(threat_model.validate_access("retrieve_credentials").is_ok());
+        }
+
+        // Operations without an override keep the default limit
+        for _ in 0..5 {
+            let _ = threat_model.validate_access("store_credentials");
+        }
+        assert!(threat_model.validate_access("store_credentials").is_err());
+        assert_eq!(threat_model.config().limit_for("store_credentials").max_attempts, 5);
+    }
+
+    #[test]
+    fn test_audit_file_rotates_and_reloads() {
+        let dir = std::env::temp_dir().join(format!("audit-{}", Uuid::new_v4()));
+        let audit_file = AuditFile::new(dir.join("security-audit.jsonl"), 400, 2);
+
+        let threat_model = ThreatModel::with_audit_file(audit_file.clone());
+        for i in 0..12 {
+            threat_model.log_security_event(SecurityEventType::CredentialAccess, format!("event {}", i), "test".to_string());
+        }
+
+        assert!(audit_file.rotated_path(1).exists());
+        assert!(audit_file.rotated_path(2).exists());
+        assert!(!audit_file.rotated_path(3).exists());
+        for path in [audit_file.path().to_path_buf(), audit_file.rotated_path(1)] {
+            assert!(std::fs::metadata(path).unwrap().len() <= 400);
+        }
+
+        // A fresh model picks up where the last one stopped, newest event last
+        let reloaded = ThreatModel::with_audit_file(audit_file.clone());
+        let audit = reloaded.get_security_audit();
+        assert!(!audit.is_empty() && audit.len() < 12);
+        assert_eq!(audit.last().unwrap().details, "event 11");
+        assert_eq!(audit_file.load_recent(1)[0].details, "event 11");
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_concurrent_audit_appends_lose_nothing() {
+        let dir = std::env::temp_dir().join(format!("audit-{}", Uuid::new_v4()));
+        let audit_file = AuditFile::new(dir.join("security-audit.jsonl"), 1000, 100);
+        let threat_model = Arc::new(ThreatModel::with_audit_file(audit_file.clone()));
+
+        let writers: Vec<_> = (0..8)
+            .map(|writer| {
+                let threat_model = Arc::clone(&threat_model);
+                std::thread::spawn(move || {
+                    for i in 0..25 {
+                        threat_model.log_security_event(
+                            SecurityEventType::CredentialAccess,
+                            format!("writer {} event {}", writer, i),
+                            "test".to_string(),
+                        );
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        // Every event survives rotation, and no file outgrows the limit
+        assert_eq!(audit_file.load_recent(usize::MAX).len(), 200);
+        for index in 1..=100 {
+            if let Ok(metadata) = std::fs::metadata(audit_file.rotated_path(index)) {
+                assert!(metadata.len() <= 1000);
+            }
+        }
+
+        std::fs::remove_dir_all(&dir).ok();
     }
 
     #[test]
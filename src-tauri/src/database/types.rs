@@ -1,3 +1,4 @@
+use crate::database::policy::{check_read_only, StatementPolicy};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
@@ -26,6 +27,140 @@ impl fmt::Display for DatabaseType {
     }
 }
 
+/// Canonical column type category, independent of the dialect's raw type name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NormalizedType {
+    String,
+    Integer,
+    Decimal,
+    Boolean,
+    DateTime,
+    Json,
+    Binary,
+    Other,
+}
+
+impl NormalizedType {
+    /// Strip length/precision arguments and normalize case, e.g. `VARCHAR(255)` -> `varchar`
+    pub fn base_type_name(data_type: &str) -> String {
+        let lower = data_type.trim().to_lowercase();
+        let base = match lower.find('(') {
+            Some(idx) => {
+                // Keep any suffix after the arguments, e.g. `timestamp(3) with time zone`
+                let suffix = lower[idx..].find(')').map(|end| &lower[idx + end + 1..]).unwrap_or("");
+                format!("{}{}", &lower[..idx], suffix)
+            }
+            None => lower,
+        };
+        base.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    /// Exact numeric types such as `NUMERIC(38, 10)`, whose values f64 would round
+    pub fn is_exact_numeric(data_type: &str) -> bool {
+        matches!(
+            Self::base_type_name(data_type).as_str(),
+            "numeric" | "decimal" | "dec" | "number" | "money" | "smallmoney"
+        )
+    }
+
+    /// Map type names that mean the same thing across all supported dialects
+    pub fn from_common_type(data_type: &str) -> NormalizedType {
+        let base = Self::base_type_name(data_type);
+        match base.as_str() {
+            "char" | "character" | "varchar" | "character varying" | "nchar" | "nvarchar"
+            | "text" | "clob" | "nclob" | "varchar2" | "nvarchar2" | "uuid" => NormalizedType::String,
+            "int" | "integer" | "smallint" | "bigint" | "tinyint" | "mediumint" => NormalizedType::Integer,
+            "decimal" | "numeric" | "number" | "real" | "float" | "double" | "double precision"
+            | "money" => NormalizedType::Decimal,
+            "bool" | "boolean" | "bit" => NormalizedType::Boolean,
+            "date" | "time" | "datetime" | "datetime2" | "timestamp" | "interval" => NormalizedType::DateTime,
+            "json" | "jsonb" => NormalizedType::Json,
+            "blob" | "binary" | "varbinary" | "bytea" | "raw" | "image" => NormalizedType::Binary,
+            _ if base.starts_with("timestamp") || base.starts_with("time ") => NormalizedType::DateTime,
+            _ => NormalizedType::Other,
+        }
+    }
+}
+
+/// TLS negotiation mode, mirroring libpq's `sslmode` levels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SslMode {
+    Disable,
+    Prefer,
+    Require,
+    VerifyCa,
+    VerifyFull,
+}
+
+impl SslMode {
+    /// Value for the PostgreSQL `sslmode` parameter
+    pub fn postgres_value(&self) -> &'static str {
+        match self {
+            SslMode::Disable => "disable",
+            SslMode::Prefer => "prefer",
+            SslMode::Require => "require",
+            SslMode::VerifyCa => "verify-ca",
+            SslMode::VerifyFull => "verify-full",
+        }
+    }
+
+    /// Value for the MySQL `ssl-mode` parameter
+    pub fn mysql_value(&self) -> &'static str {
+        match self {
+            SslMode::Disable => "DISABLED",
+            SslMode::Prefer => "PREFERRED",
+            SslMode::Require => "REQUIRED",
+            SslMode::VerifyCa => "VERIFY_CA",
+            SslMode::VerifyFull => "VERIFY_IDENTITY",
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        *self != SslMode::Disable
+    }
+}
+
+impl Default for SslMode {
+    fn default() -> Self {
+        SslMode::Require // Default to secure
+    }
+}
+
+impl std::str::FromStr for SslMode {
+    type Err = ConnectionError;
+
+    /// Accepts both the enum names and the libpq spellings (`verify-ca`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace(['-', '_'], "").as_str() {
+            "disable" | "disabled" => Ok(SslMode::Disable),
+            "prefer" | "preferred" => Ok(SslMode::Prefer),
+            "require" | "required" => Ok(SslMode::Require),
+            "verifyca" => Ok(SslMode::VerifyCa),
+            "verifyfull" | "verifyidentity" => Ok(SslMode::VerifyFull),
+            _ => Err(ConnectionError::ConfigurationError(format!("Invalid SSL mode: {}", s))),
+        }
+    }
+}
+
+/// Reads an `SslMode`, or the legacy `ssl_enabled` flag as `Require`/`Disable`
+fn deserialize_ssl_mode<'de, D>(deserializer: D) -> Result<SslMode, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ModeOrFlag {
+        Mode(SslMode),
+        Flag(bool),
+    }
+
+    Ok(match ModeOrFlag::deserialize(deserializer)? {
+        ModeOrFlag::Mode(mode) => mode,
+        ModeOrFlag::Flag(true) => SslMode::Require,
+        ModeOrFlag::Flag(false) => SslMode::Disable,
+    })
+}
+
 /// Connection status enumeration
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ConnectionStatus {
@@ -46,12 +181,39 @@ pub struct ConnectionConfig {
     pub port: u16,
     pub database: String,
     pub username: String,
-    pub ssl_enabled: bool,
+    /// Configs saved before `ssl_mode` existed carry an on/off `ssl_enabled` flag instead
+    #[serde(default, alias = "ssl_enabled", deserialize_with = "deserialize_ssl_mode")]
+    pub ssl_mode: SslMode,
     pub connection_timeout: u32,
     pub max_connections: u32,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
     pub additional_params: HashMap<String, String>,
+    #[serde(default)]
+    pub statement_policy: StatementPolicy,
+    /// Pinned connections are listed before all others
+    #[serde(default)]
+    pub is_pinned: bool,
+    /// User-defined position within the pinned/unpinned groups
+    #[serde(default)]
+    pub sort_order: i32,
+    /// Schema unqualified names resolve against (PostgreSQL search_path, MySQL default
+    /// database); ignored for SQLite. SQL Server sets it per login on the server, so it is
+    /// only checked to exist there
+    #[serde(default)]
+    pub default_schema: Option<String>,
+    /// Free-form user labels (owner, environment, ticket link). Purely descriptive:
+    /// never passed to drivers, unlike `additional_params`
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    /// Last time a query or test ran against this connection; survives restarts, unlike
+    /// `ConnectionStats::last_activity`
+    #[serde(default)]
+    pub last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only queries may run, whatever `statement_policy` allows. Checked on the parsed SQL,
+    /// and PostgreSQL sessions are also opened read-only
+    #[serde(default)]
+    pub read_only: bool,
 }
 
 impl ConnectionConfig {
@@ -72,13 +234,29 @@ impl ConnectionConfig {
             port,
             database,
             username,
-            ssl_enabled: true, // Default to secure
+            ssl_mode: SslMode::default(),
             connection_timeout: 30,
             max_connections: 10,
             created_at: now,
             updated_at: now,
             additional_params: HashMap::new(),
+            statement_policy: StatementPolicy::default(),
+            is_pinned: false,
+            sort_order: 0,
+            default_schema: None,
+            metadata: HashMap::new(),
+            last_used_at: None,
+            read_only: false,
+        }
+    }
+
+    /// Reject `sql` unless both the statement policy and the read-only flag allow it
+    pub fn check_statement(&self, sql: &str) -> ConnectionResult<()> {
+        self.statement_policy.check(sql, &self.database_type)?;
+        if self.read_only {
+            check_read_only(sql, &self.database_type)?;
         }
+        Ok(())
     }
 
     /// Get the keychain service name for this connection
@@ -92,13 +270,27 @@ impl ConnectionConfig {
     }
 }
 
+/// Ordering for connection lists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConnectionSort {
+    /// Pinned first, then the user's manual order, then name
+    #[default]
+    Manual,
+    /// Most recently used first; never-used connections last, by name
+    RecentlyUsed,
+}
+
 /// Database credentials (sensitive data)
 #[derive(Debug, Clone)]
 pub struct DatabaseCredentials {
     pub connection_id: Uuid,
     pub password: String,
+    /// Client certificate presented to the server
     pub certificate_path: Option<String>,
+    /// Private key for the client certificate
     pub private_key_path: Option<String>,
+    /// CA certificate used to verify the server (`VerifyCa`/`VerifyFull`)
+    pub ca_certificate_path: Option<String>,
 }
 
 impl DatabaseCredentials {
@@ -108,6 +300,7 @@ impl DatabaseCredentials {
             password,
             certificate_path: None,
             private_key_path: None,
+            ca_certificate_path: None,
         }
     }
 }
@@ -152,9 +345,24 @@ pub enum ConnectionError {
     #[error("Security violation: {0}")]
     SecurityViolation(String),
     
+    #[error("Query failed: {0}")]
+    QueryFailed(String),
+    
+    #[error("Statement policy violation: {0}")]
+    PolicyViolation(String),
+    
+    #[error("Duplicate connection: {0}")]
+    DuplicateConnection(String),
+    
+    #[error("Operation cancelled: {0}")]
+    Cancelled(String),
+    
     #[error("Unsupported database type: {0}")]
     UnsupportedDatabaseType(String),
     
+    #[error("Unsupported feature: {0}")]
+    UnsupportedFeature(String),
+    
     #[error("Internal error: {0}")]
     InternalError(String),
 }
@@ -170,6 +378,12 @@ pub struct ConnectionTestResult {
     pub response_time_ms: u64,
     pub server_version: Option<String>,
     pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Category of a failed test; `None` on success
+    #[serde(default)]
+    pub failure_kind: Option<ConnectionFailureKind>,
+    /// What the user can try next, for failed tests
+    #[serde(default)]
+    pub remediation: Option<String>,
 }
 
 impl ConnectionTestResult {
@@ -180,18 +394,151 @@ impl ConnectionTestResult {
             response_time_ms,
             server_version,
             timestamp: chrono::Utc::now(),
+            failure_kind: None,
+            remediation: None,
         }
     }
 
+    /// A failure whose cause wasn't classified
     pub fn failure(message: String) -> Self {
+        Self::failure_of_kind(ConnectionFailureKind::Unknown, message)
+    }
+
+    pub fn failure_of_kind(kind: ConnectionFailureKind, message: String) -> Self {
         Self {
             success: false,
             message,
             response_time_ms: 0,
             server_version: None,
             timestamp: chrono::Utc::now(),
+            failure_kind: Some(kind),
+            remediation: Some(kind.remediation_hint().to_string()),
+        }
+    }
+}
+
+/// Why a connection test failed, so the connection dialog can point at the right setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConnectionFailureKind {
+    /// DNS lookup failed, or nothing accepted the connection on that host and port
+    HostUnreachable,
+    /// The server rejected the username or password
+    AuthFailed,
+    /// TLS negotiation or certificate verification failed
+    TlsError,
+    /// The server is reachable but the database (or default schema, or SQLite file) doesn't exist
+    DatabaseMissing,
+    Timeout,
+    Unknown,
+}
+
+impl ConnectionFailureKind {
+    pub fn remediation_hint(&self) -> &'static str {
+        match self {
+            ConnectionFailureKind::HostUnreachable => {
+                "Check the host name and port, and that the server is running and reachable from this machine"
+            }
+            ConnectionFailureKind::AuthFailed => {
+                "Check the username and password, and that the user may connect from this host"
+            }
+            ConnectionFailureKind::TlsError => {
+                "Check the SSL mode and certificate paths, or whether the server has TLS enabled"
+            }
+            ConnectionFailureKind::DatabaseMissing => {
+                "Check the database name (and default schema), or create it on the server"
+            }
+            ConnectionFailureKind::Timeout => {
+                "The server did not answer in time; check firewalls and VPNs, or raise the connection timeout"
+            }
+            ConnectionFailureKind::Unknown => "See the error message for details",
         }
     }
+
+    /// Best-effort category for an error raised outside the driver's own test
+    pub fn from_connection_error(error: &ConnectionError) -> Self {
+        match error {
+            ConnectionError::NetworkError(_) => ConnectionFailureKind::HostUnreachable,
+            ConnectionError::AuthenticationFailed(_) => ConnectionFailureKind::AuthFailed,
+            ConnectionError::SslError(_) => ConnectionFailureKind::TlsError,
+            ConnectionError::DatabaseNotFound(_) => ConnectionFailureKind::DatabaseMissing,
+            ConnectionError::TimeoutError(_) => ConnectionFailureKind::Timeout,
+            _ => ConnectionFailureKind::Unknown,
+        }
+    }
+}
+
+/// Execution plan reported by the database's EXPLAIN
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryPlan {
+    /// Plan in the database's own structure (JSON for PostgreSQL/MySQL, plan rows for SQLite)
+    pub plan: serde_json::Value,
+    /// Planner's total cost estimate, when the database reports one
+    pub total_cost: Option<f64>,
+}
+
+/// Column of a statement's result set, as described by the database before running it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultColumnInfo {
+    pub name: String,
+    pub data_type: String,
+    /// `None` when the database can't tell (e.g. computed expressions)
+    pub nullable: Option<bool>,
+}
+
+/// Rows produced by running a statement
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryOutput {
+    /// Columns of the first result set; empty when the statement returned no rows
+    pub columns: Vec<ResultColumnInfo>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+    /// More rows were available than the row cap allowed
+    pub truncated: bool,
+    pub affected_rows: u64,
+}
+
+/// One statement run by `ConnectionManager::execute_transaction`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionStatementResult {
+    pub statement: String,
+    pub success: bool,
+    /// Present when the statement succeeded
+    pub output: Option<QueryOutput>,
+    pub error: Option<String>,
+    pub execution_time_ms: u64,
+}
+
+/// Outcome of running several statements atomically. Statements after the first failure
+/// are not run and have no entry in `results`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionOutcome {
+    pub results: Vec<TransactionStatementResult>,
+    pub committed: bool,
+}
+
+/// Incremental result emitted while testing many connections
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionTestProgress {
+    pub connection_id: Uuid,
+    pub result: ConnectionTestResult,
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// A connection's status changed, as noticed by the health check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionStatusChange {
+    pub connection_id: Uuid,
+    pub previous: ConnectionStatus,
+    pub status: ConnectionStatus,
+}
+
+/// A query currently executing through `execute_sql_query`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunningQuery {
+    pub query_id: String,
+    pub connection_id: Uuid,
+    pub sql: String,
+    pub started_at: chrono::DateTime<chrono::Utc>,
 }
 
 /// Database connection statistics
@@ -204,6 +551,13 @@ pub struct ConnectionStats {
     pub average_response_time_ms: f64,
     pub last_activity: chrono::DateTime<chrono::Utc>,
     pub uptime_seconds: u64,
+    /// Connections held by the pool; all zero until the pool is opened
+    #[serde(default)]
+    pub pool_size: u32,
+    #[serde(default)]
+    pub active_connections: u32,
+    #[serde(default)]
+    pub idle_connections: u32,
 }
 
 impl Default for ConnectionStats {
@@ -216,6 +570,9 @@ impl Default for ConnectionStats {
             average_response_time_ms: 0.0,
             last_activity: chrono::Utc::now(),
             uptime_seconds: 0,
+            pool_size: 0,
+            active_connections: 0,
+            idle_connections: 0,
         }
     }
 }
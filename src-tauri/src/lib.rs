@@ -7,28 +7,47 @@ mod database;
 // Template engine module
 mod template_engine;
 
+// SQL dialect metadata module
+mod sql_dialects;
+
+// Runtime log level and recent log buffer
+mod logging;
+
+// CSV/TSV/JSON export of query results
+mod result_export;
+
 use ai_engine::{
-    AIEngineManager, AIEngineConfig, AIEngineStatus, SQLGenerationRequest,
-    SQLGenerationResponse, SQLGenerationProgress, CancellationRequest,
-    task_manager::AITaskManager,
-    types::{AIAnalysisRequest, AIAnalysisResult}
+    AIEngineManager, AIEngineConfig, AIEngineStatus, HealthStats, SQLGenerationRequest,
+    SQLGenerationResponse, SQLGenerationProgress, SQLGenerationComplete, CancellationRequest,
+    manager::ENGINE_SHUT_DOWN_ERROR,
+    query_analysis::{schema_children, schema_node_named, single_source_table},
+    task_manager::{AITaskManager, OptimizeTaskHandler},
+    types::{ActiveAITask, AIAnalysisRequest, AIAnalysisResult, AIEngineStatusReport, AITaskType, AnalysisResultData}
 };
-use database::{ConnectionManager, ConnectionConfig, DatabaseCredentials, DatabaseType};
+use database::{ConnectionManager, ConnectionConfig, ConnectionError, ConnectionSort, ConnectionExportBundle, ConnectionTestProgress, DatabaseCredentials, DatabaseType, GeneratedSql, NormalizedType, RecentResult, ResultColumn, ResultSqlMode, ResultColumnInfo, SchemaFilter, SslMode};
+use database::drivers::DatabaseDriverFactory;
+use database::policy::{classify_statement_categories, first_blocked_statement, PermissionLevel, StatementCategory};
+use database::manager::sort_connections;
+use result_export::{ExportFormat, ExportSummary};
+use sql_dialects::completions::CompletionContext;
 use template_engine::{
     TemplateManager, Template, TemplateCategory, CreateTemplateRequest, UpdateTemplateRequest,
     CreateCategoryRequest, UpdateCategoryRequest, TemplateFilter, TemplateStatistics,
-    TemplateImportResult, ProcessedTemplate, ParameterSubstitution
+    TemplateImportResult, ImportMode, ProcessedTemplate, ParameterSubstitution, TemplateContentDiff, TemplateUsage, UpsertTemplateResult,
+    CategoryCountsChanged, TemplatePage, QueryHistoryEntry, DeletedTemplate,
+    BulkTemplateResult, CategoryCount, CategoryDeleteStrategy, CategoryNode
 };
 use std::sync::Arc;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use tauri::Manager;
 use tokio::sync::{RwLock, mpsc};
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 // Global AI Engine Manager
-type AIEngineManagerState = Arc<RwLock<Option<AIEngineManager>>>;
+type AIEngineManagerState = Arc<RwLock<Option<Arc<AIEngineManager>>>>;
 
 // Global AI Task Manager for Story 3.6
 type AITaskManagerState = Arc<AITaskManager>;
@@ -56,25 +75,320 @@ impl SchemaCacheEntry {
         }
     }
 
-    fn is_expired(&self) -> bool {
-        let elapsed = Utc::now().signed_duration_since(self.cached_at);
-        elapsed.num_seconds() as u64 > self.ttl_seconds
+    /// Whether the entry is past its TTL at `now`; taking the time keeps expiry testable
+    fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.age_seconds(now) > self.ttl_seconds as i64
+    }
+
+    fn age_seconds(&self, now: DateTime<Utc>) -> i64 {
+        now.signed_duration_since(self.cached_at).num_seconds()
     }
 }
 
 // Global Schema Cache
 type SchemaCacheState = Arc<RwLock<HashMap<String, SchemaCacheEntry>>>;
 
+/// Drop every cached introspection of a connection, whatever its filters
+async fn invalidate_schema_cache(schema_cache: &SchemaCacheState, connection_id: &str) {
+    let prefix = format!("{}_", connection_id);
+    schema_cache.write().await.retain(|key, _| !key.starts_with(&prefix));
+}
+
+/// Unexpired cached introspections of a connection, whatever filters they were loaded with
+fn cached_connection_schemas<'a>(
+    cache: &'a HashMap<String, SchemaCacheEntry>,
+    connection_id: &str,
+    now: DateTime<Utc>,
+) -> impl Iterator<Item = &'a SchemaCacheEntry> + 'a {
+    let prefix = format!("{}_", connection_id);
+    cache
+        .iter()
+        .filter(move |(key, entry)| key.starts_with(&prefix) && !entry.is_expired(now))
+        .map(|(_, entry)| entry)
+}
+
+const SCHEMA_CACHE_TTL_SECONDS: u64 = 300;
+
+/// Rows per page for `execute_sql_query_stream` when the caller doesn't ask for a size
+const DEFAULT_QUERY_PAGE_SIZE: usize = 500;
+const MAX_QUERY_PAGE_SIZE: usize = 10_000;
+
+/// Introspection past this point returns what it has so far, with a cursor to continue from
+const SCHEMA_INTROSPECTION_SOFT_DEADLINE_MS: u64 = 10_000;
+
+// System health, reported per subsystem so one failure doesn't mask the others
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SystemHealth {
+    ai_engine: AIEngineStatus,
+    database: DatabaseHealth,
+    templates: TemplateStoreHealth,
+    checked_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DatabaseHealth {
+    initialized: bool,
+    connection_count: usize,
+    /// Connections whose last test or use ended in an error
+    pool_errors: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TemplateStoreHealth {
+    db_accessible: bool,
+    template_count: u32,
+    error: Option<String>,
+}
+
+// In-flight work across the AI engine, AI task manager and database manager
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum OperationKind {
+    SqlGeneration,
+    Analysis,
+    Query,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RunningOperation {
+    id: String,
+    kind: OperationKind,
+    started_at: DateTime<Utc>,
+    description: String,
+}
+
+/// Add a canonical `normalized_type` next to every raw `data_type` in introspection output,
+/// using the connection's driver mapping (or the dialect-neutral mapping if unknown)
+fn annotate_normalized_types(value: &mut serde_json::Value, database_type: Option<&DatabaseType>) {
+    let driver = database_type.map(DatabaseDriverFactory::create_driver);
+
+    fn annotate(value: &mut serde_json::Value, normalize: &dyn Fn(&str) -> NormalizedType) {
+        match value {
+            serde_json::Value::Object(map) => {
+                if let Some(data_type) = map.get("data_type").and_then(|v| v.as_str()) {
+                    let normalized = normalize(data_type);
+                    map.insert("normalized_type".to_string(), serde_json::json!(normalized));
+                }
+                for child in map.values_mut() {
+                    annotate(child, normalize);
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    annotate(item, normalize);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    match driver {
+        Some(driver) => annotate(value, &|data_type| driver.normalize_type(data_type)),
+        None => annotate(value, &NormalizedType::from_common_type),
+    }
+}
+
+/// Drop the first `skip` tables/views (in database, schema, name order) and keep the rest while
+/// `within_budget` allows. Returns the position to resume from if any were left out.
+/// At least one object is always kept so repeated calls make progress.
+fn paginate_schema_objects(
+    databases: &mut [serde_json::Value],
+    skip: usize,
+    mut within_budget: impl FnMut() -> bool,
+) -> Option<usize> {
+    let mut position = 0;
+    let mut next_cursor = None;
+
+    for database in databases.iter_mut() {
+        let Some(schemas) = database.get_mut("schemas").and_then(|v| v.as_array_mut()) else {
+            continue;
+        };
+        for schema in schemas.iter_mut() {
+            for key in ["tables", "views"] {
+                if let Some(objects) = schema.get_mut(key).and_then(|v| v.as_array_mut()) {
+                    objects.retain(|_| {
+                        let current = position;
+                        position += 1;
+
+                        if current < skip || next_cursor.is_some() {
+                            false
+                        } else if current == skip || within_budget() {
+                            true
+                        } else {
+                            next_cursor = Some(current);
+                            false
+                        }
+                    });
+                }
+            }
+        }
+    }
+
+    next_cursor
+}
+
+/// Whether introspection output lists `column` on the given table or view
+fn schema_has_column(schema_data: &serde_json::Value, database: &str, schema: &str, table: &str, column: &str) -> bool {
+    schema_children(schema_data, "databases")
+        .iter()
+        .filter(|db| database.is_empty() || schema_node_named(db, database))
+        .flat_map(|db| schema_children(db, "schemas"))
+        .filter(|s| schema.is_empty() || schema_node_named(s, schema))
+        .flat_map(|s| schema_children(s, "tables").iter().chain(schema_children(s, "views")))
+        .filter(|t| schema_node_named(t, table))
+        .any(|t| schema_children(t, "columns").iter().any(|c| schema_node_named(c, column)))
+}
+
+/// A base table in introspection output, with the name of the schema it was found in.
+///
+/// Views are ignored: a view's key flags don't make its rows addressable.
+fn find_cached_table<'a>(
+    schema_data: &'a serde_json::Value,
+    schema: Option<&str>,
+    table: &str,
+) -> Option<(&'a str, &'a serde_json::Value)> {
+    schema_children(schema_data, "databases")
+        .iter()
+        .flat_map(|db| schema_children(db, "schemas"))
+        .filter(|s| schema.map_or(true, |schema| schema_node_named(s, schema)))
+        .find_map(|s| {
+            let found = schema_children(s, "tables").iter().find(|t| schema_node_named(t, table))?;
+            Some((s.get("name").and_then(|v| v.as_str()).unwrap_or(""), found))
+        })
+}
+
+/// Name, data type and primary-key flag of each column of a table found by `find_cached_table`
+fn cached_table_columns<'a>(table: &'a serde_json::Value) -> impl Iterator<Item = (&'a str, &'a str, bool)> + 'a {
+    schema_children(table, "columns")
+        .iter()
+        .filter_map(|c| {
+            let name = c.get("name").and_then(|v| v.as_str())?;
+            let data_type = c.get("data_type").and_then(|v| v.as_str()).unwrap_or("");
+            let is_primary_key = c.get("is_primary_key").and_then(|v| v.as_bool()).unwrap_or(false);
+            Some((name, data_type, is_primary_key))
+        })
+}
+
+/// Primary-key columns of a base table in introspection output, if the table is listed
+fn table_primary_keys(schema_data: &serde_json::Value, schema: Option<&str>, table: &str) -> Option<Vec<String>> {
+    let (_, found) = find_cached_table(schema_data, schema, table)?;
+    Some(
+        cached_table_columns(found)
+            .filter(|(_, _, is_primary_key)| *is_primary_key)
+            .map(|(name, _, _)| name.to_string())
+            .collect(),
+    )
+}
+
+/// Primary keys of `sql`'s single source table, looked up in the connection's unexpired schema cache
+async fn cached_source_primary_keys(
+    schema_cache: &SchemaCacheState,
+    connection_id: Uuid,
+    sql: &str,
+) -> Option<Vec<String>> {
+    let (schema, table) = single_source_table(sql)?;
+
+    let cache_guard = schema_cache.read().await;
+    cached_connection_schemas(&cache_guard, &connection_id.to_string(), Utc::now())
+        .find_map(|entry| table_primary_keys(&entry.schema_data, schema.as_deref(), &table))
+}
+
+/// Overlay driver-reported nullability and primary-key flags onto result columns.
+///
+/// Nullability falls back to whatever the column already carried when the driver
+/// couldn't describe the statement; `is_primary_key` is false unless known.
+fn annotate_result_columns(
+    columns: &mut [serde_json::Value],
+    described: Option<&[ResultColumnInfo]>,
+    primary_keys: Option<&[String]>,
+) {
+    for (index, column) in columns.iter_mut().enumerate() {
+        let Some(column) = column.as_object_mut() else { continue };
+        let name = column.get("name").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+
+        // Match by position first; drivers report columns in select-list order
+        let info = described.and_then(|described| {
+            described.get(index).filter(|c| c.name == name).or_else(|| described.iter().find(|c| c.name == name))
+        });
+        if let Some(info) = info {
+            column.insert("nullable".to_string(), serde_json::json!(info.nullable));
+        }
+
+        let is_primary_key = primary_keys.map_or(false, |keys| keys.iter().any(|k| *k == name));
+        column.insert("is_primary_key".to_string(), serde_json::Value::Bool(is_primary_key));
+    }
+}
+
 // Learn more about Tauri commands at https://tauri.app/v1/guides/features/command
 #[tauri::command]
 fn greet(name: &str) -> String {
     format!("Hello, {}! Welcome to the Symbiotic Analysis Environment.", name)
 }
 
+/// Result of a generation replayed from the persisted queue, which has no caller to return to
+#[derive(Debug, Clone, Serialize)]
+struct ResumedGenerationResult {
+    generation_id: String,
+    response: Option<SQLGenerationResponse>,
+    error: Option<String>,
+}
+
+/// Channels whose messages are forwarded to the frontend as generation progress and completion events
+fn generation_event_channels(
+    app_handle: &tauri::AppHandle,
+) -> (mpsc::Sender<SQLGenerationProgress>, mpsc::Sender<SQLGenerationComplete>) {
+    let (progress_tx, mut progress_rx) = mpsc::channel::<SQLGenerationProgress>(32);
+    let app_handle_clone = app_handle.clone();
+
+    // Spawn a task to emit progress events to the frontend
+    tokio::spawn(async move {
+        while let Some(progress) = progress_rx.recv().await {
+            let _ = app_handle_clone.emit_all("sql-generation-progress", &progress);
+        }
+    });
+
+    // Single terminal event so the frontend can notify when the window is in the background
+    let (complete_tx, mut complete_rx) = mpsc::channel::<SQLGenerationComplete>(1);
+    let app_handle_clone = app_handle.clone();
+    tokio::spawn(async move {
+        if let Some(complete) = complete_rx.recv().await {
+            let _ = app_handle_clone.emit_all("sql-generation-complete", &complete);
+        }
+    });
+
+    (progress_tx, complete_tx)
+}
+
+/// Replay generations left unfinished by a previous session, one at a time
+async fn resume_pending_generations(ai_manager: AIEngineManagerState, app_handle: tauri::AppHandle) {
+    // Replays can take minutes; holding the guard would block stop_ai_engine for all of them
+    let Some(manager) = ai_manager.read().await.clone() else { return };
+
+    for pending in manager.pending_generations().await {
+        log::info!("Resuming SQL generation {} from the persisted queue", pending.generation_id);
+        let _ = app_handle.emit_all("sql-generation-resumed", &pending);
+
+        let generation_id = pending.generation_id.clone();
+        let (progress_tx, complete_tx) = generation_event_channels(&app_handle);
+        let outcome = manager.resume_generation(pending, Some(progress_tx), Some(complete_tx)).await;
+
+        let result = ResumedGenerationResult {
+            generation_id,
+            error: outcome.as_ref().err().cloned(),
+            response: outcome.ok(),
+        };
+        let _ = app_handle.emit_all("sql-generation-resumed-result", &result);
+    }
+}
+
 // AI Engine Commands
+/// Start the AI engine. With `persist_queue`, accepted generations are written to disk
+/// and any left unfinished by a crash are replayed once the engine is Ready.
 #[tauri::command]
 async fn start_ai_engine(
+    persist_queue: Option<bool>,
     ai_manager: tauri::State<'_, AIEngineManagerState>,
+    app_handle: tauri::AppHandle,
 ) -> Result<String, String> {
     log::info!("Starting AI Engine via Tauri command");
 
@@ -82,11 +396,23 @@ async fn start_ai_engine(
 
     if manager_guard.is_none() {
         // Create a new AI Engine Manager with default configuration
-        let config = AIEngineConfig::default();
+        let mut config = AIEngineConfig::default();
+        if persist_queue.unwrap_or(false) {
+            config.pending_queue_path = app_handle
+                .path_resolver()
+                .app_data_dir()
+                .map(|dir| dir.join("pending-generations.json"));
+        }
         let manager = AIEngineManager::new(config);
 
         manager.start().await;
-        *manager_guard = Some(manager);
+        let ready = manager.get_status().await == AIEngineStatus::Ready;
+        *manager_guard = Some(Arc::new(manager));
+        drop(manager_guard);
+
+        if ready {
+            tokio::spawn(resume_pending_generations(ai_manager.inner().clone(), app_handle));
+        }
         Ok("AI Engine started successfully".to_string())
     } else {
         Err("AI Engine is already running".to_string())
@@ -99,6 +425,11 @@ async fn stop_ai_engine(
 ) -> Result<String, String> {
     log::info!("Stopping AI Engine via Tauri command");
 
+    // In-flight generations hold the read lock; fail them first so the write lock isn't starved
+    if let Some(manager) = ai_manager.read().await.as_ref() {
+        manager.cancel_all_generations(ENGINE_SHUT_DOWN_ERROR).await;
+    }
+
     let mut manager_guard = ai_manager.write().await;
 
     if let Some(manager) = manager_guard.take() {
@@ -115,11 +446,107 @@ async fn get_ai_engine_status(
 ) -> Result<String, String> {
     let manager_guard = ai_manager.read().await;
 
-    if let Some(manager) = manager_guard.as_ref() {
-        let status = manager.get_status().await;
-        Ok(serde_json::to_string(&status).unwrap_or_else(|_| "unknown".to_string()))
-    } else {
-        Ok(serde_json::to_string(&AIEngineStatus::Stopped).unwrap_or_else(|_| "stopped".to_string()))
+    let report = match manager_guard.as_ref() {
+        Some(manager) => manager.get_status_report().await,
+        None => {
+            let config = AIEngineConfig::default();
+            AIEngineStatusReport {
+                status: AIEngineStatus::Stopped,
+                active_generations: 0,
+                queued_generations: 0,
+                max_concurrent_generations: config.max_concurrent_generations,
+                max_queued_generations: config.max_queued_generations,
+            }
+        }
+    };
+    serde_json::to_string(&report).map_err(|e| format!("Failed to serialize AI engine status: {}", e))
+}
+
+#[tauri::command]
+async fn get_system_health(
+    ai_manager: tauri::State<'_, AIEngineManagerState>,
+    db_manager: tauri::State<'_, DatabaseManagerState>,
+    template_manager: tauri::State<'_, TemplateManagerState>,
+) -> Result<SystemHealth, String> {
+    let ai_engine = match ai_manager.read().await.as_ref() {
+        Some(manager) => manager.get_status().await,
+        None => AIEngineStatus::Stopped,
+    };
+
+    let database = match db_manager.read().await.as_ref() {
+        Some(manager) => {
+            let summaries = manager.list_connection_summaries().await;
+            DatabaseHealth {
+                initialized: true,
+                connection_count: manager.list_connections().await.len(),
+                pool_errors: summaries
+                    .iter()
+                    .filter(|summary| matches!(summary.status, database::ConnectionStatus::Error(_)))
+                    .count(),
+            }
+        }
+        None => DatabaseHealth {
+            initialized: false,
+            connection_count: 0,
+            pool_errors: 0,
+        },
+    };
+
+    let templates = match template_manager.count_templates().await {
+        Ok(template_count) => TemplateStoreHealth {
+            db_accessible: true,
+            template_count,
+            error: None,
+        },
+        Err(e) => TemplateStoreHealth {
+            db_accessible: false,
+            template_count: 0,
+            error: Some(e),
+        },
+    };
+
+    Ok(SystemHealth {
+        ai_engine,
+        database,
+        templates,
+        checked_at: Utc::now(),
+    })
+}
+
+#[tauri::command]
+fn set_log_level(level: String) -> Result<String, String> {
+    let level = logging::parse_level(&level)?;
+    logging::set_level(level)?;
+    log::info!("Log level set to {}", level);
+    Ok(level.to_string())
+}
+
+#[tauri::command]
+fn get_recent_logs(limit: Option<usize>) -> Vec<String> {
+    logging::recent_logs(limit.unwrap_or(200))
+}
+
+#[tauri::command]
+async fn get_ai_engine_logs(
+    ai_manager: tauri::State<'_, AIEngineManagerState>,
+) -> Result<Vec<String>, String> {
+    let manager_guard = ai_manager.read().await;
+
+    match manager_guard.as_ref() {
+        Some(manager) => Ok(manager.get_stderr_logs().await),
+        None => Ok(Vec::new()),
+    }
+}
+
+#[tauri::command]
+async fn get_health_stats(
+    ai_manager: tauri::State<'_, AIEngineManagerState>,
+) -> Result<Option<HealthStats>, String> {
+    let manager_guard = ai_manager.read().await;
+
+    match manager_guard.as_ref() {
+        Some(manager) => Ok(manager.get_health_stats().await),
+        None => Ok(None),
     }
 }
 
@@ -134,19 +561,29 @@ async fn generate_sql_from_prompt(
 
     let manager_guard = ai_manager.read().await;
     if let Some(manager) = manager_guard.as_ref() {
-        // Create a progress channel for real-time updates
-        let (progress_tx, mut progress_rx) = mpsc::channel::<SQLGenerationProgress>(32);
-        let app_handle_clone = app_handle.clone();
-
-        // Spawn a task to emit progress events to the frontend
-        tokio::spawn(async move {
-            while let Some(progress) = progress_rx.recv().await {
-                let _ = app_handle_clone.emit_all("sql-generation-progress", &progress);
-            }
-        });
+        let (progress_tx, complete_tx) = generation_event_channels(&app_handle);
 
         // Generate SQL with progress tracking
-        manager.generate_sql_from_prompt(request, Some(progress_tx)).await
+        manager.generate_sql_from_prompt(request, Some(progress_tx), Some(complete_tx)).await
+    } else {
+        Err("AI Engine is not initialized".to_string())
+    }
+}
+
+/// Answer the clarifying questions of an earlier generation and generate again
+#[tauri::command]
+async fn continue_sql_generation(
+    clarification_id: String,
+    answers: Vec<String>,
+    ai_manager: tauri::State<'_, AIEngineManagerState>,
+    app_handle: tauri::AppHandle,
+) -> Result<SQLGenerationResponse, String> {
+    log::info!("Continuing SQL generation after clarification: {}", clarification_id);
+
+    let manager_guard = ai_manager.read().await;
+    if let Some(manager) = manager_guard.as_ref() {
+        let (progress_tx, complete_tx) = generation_event_channels(&app_handle);
+        manager.continue_sql_generation(&clarification_id, answers, Some(progress_tx), Some(complete_tx)).await
     } else {
         Err("AI Engine is not initialized".to_string())
     }
@@ -175,13 +612,45 @@ async fn cancel_sql_generation(
 async fn analyze_sql_query(
     request: AIAnalysisRequest,
     app_handle: tauri::AppHandle,
+    ai_manager: tauri::State<'_, AIEngineManagerState>,
     task_manager: tauri::State<'_, AITaskManagerState>,
+    db_manager: tauri::State<'_, DatabaseManagerState>,
+    schema_cache: tauri::State<'_, SchemaCacheState>,
 ) -> Result<AIAnalysisResult, String> {
+    let mut request = request;
     log::info!("Starting SQL analysis: {:?} for connection: {}", request.task_type, request.connection_id);
 
+    // Gate on what the running AI Core advertised in its latest pong
+    let ai_engine = ai_manager.read().await.clone();
+    match ai_engine {
+        Some(engine) => match engine.capabilities().await {
+            Some(capabilities) => task_manager.set_capabilities(capabilities),
+            None => task_manager.clear_capabilities(),
+        },
+        None => task_manager.clear_capabilities(),
+    }
+
+    // Explain and Optimize attribute columns and look for indexes using the connection's schema
+    let needs_schema = matches!(request.task_type, AITaskType::Explain | AITaskType::Optimize);
+    if needs_schema && request.schema_context.is_none() {
+        request.schema_context = analysis_schema_context(&request.connection_id, &db_manager, &schema_cache).await;
+    }
+
+    // Plans cost extra round-trips, so they can be switched off per request
+    let wants_plans = request.task_type == AITaskType::Optimize
+        && request
+            .analysis_options
+            .as_ref()
+            .and_then(|options| options.performance_estimates)
+            .unwrap_or(true);
+    let connection_id = request.connection_id.clone();
+
     // Execute analysis using the unified task manager
     match task_manager.execute_analysis(request, app_handle).await {
-        Ok(result) => {
+        Ok(mut result) => {
+            if wants_plans && result.success {
+                attach_explain_plans(&mut result, &connection_id, &db_manager).await;
+            }
             log::info!("Analysis completed successfully: {}", result.analysis_id);
             Ok(result)
         }
@@ -192,6 +661,79 @@ async fn analyze_sql_query(
     }
 }
 
+/// Schema for an analysis request: any fresh cached introspection of the connection, else a new one.
+/// `None` when the connection can't be introspected; the handlers then work from the query alone.
+async fn analysis_schema_context(
+    connection_id: &str,
+    db_manager: &DatabaseManagerState,
+    schema_cache: &SchemaCacheState,
+) -> Option<serde_json::Value> {
+    {
+        let cache_guard = schema_cache.read().await;
+        let cached = cached_connection_schemas(&cache_guard, connection_id, Utc::now()).next();
+        if let Some(entry) = cached {
+            return Some(entry.schema_data.clone());
+        }
+    }
+
+    let uuid = Uuid::parse_str(connection_id).ok()?;
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()?;
+    match manager.introspect_schema(uuid, false, &SchemaFilter::default(), None).await {
+        Ok(schema) => serde_json::to_value(schema)
+            .ok()
+            .map(|database| serde_json::json!({ "databases": [database] })),
+        Err(e) => {
+            log::debug!("Analyzing without schema context: {}", e);
+            None
+        }
+    }
+}
+
+/// Add real EXPLAIN output for the original and rewritten query to an Optimize result.
+/// Failures (no connection, EXPLAIN disallowed, unsupported SQL) just leave the estimate as is.
+async fn attach_explain_plans(result: &mut AIAnalysisResult, connection_id: &str, db_manager: &DatabaseManagerState) {
+    let Some(data) = result.result.as_mut() else { return };
+    let AnalysisResultData::Optimization { original_query, optimized_query, .. } = &*data else { return };
+    let Ok(uuid) = Uuid::parse_str(connection_id) else { return };
+
+    let manager_guard = db_manager.read().await;
+    let Some(manager) = manager_guard.as_ref() else { return };
+
+    let explain = |sql: String| async move {
+        match manager.explain_query(uuid, &sql).await {
+            Ok(plan) => Some((plan.plan, plan.total_cost)),
+            Err(e) => {
+                log::debug!("Skipping execution plan: {}", e);
+                None
+            }
+        }
+    };
+
+    let original = explain(original_query.clone()).await;
+    let optimized = match optimized_query.clone() {
+        Some(sql) => explain(sql).await,
+        None => None,
+    };
+
+    OptimizeTaskHandler::attach_plans(data, original, optimized);
+}
+
+/// Analyses still pending or running, oldest first; each can be stopped with `cancel_sql_analysis`
+#[tauri::command]
+async fn get_active_ai_tasks(
+    task_manager: tauri::State<'_, AITaskManagerState>,
+) -> Result<Vec<ActiveAITask>, String> {
+    let now = Utc::now();
+    let mut tasks: Vec<ActiveAITask> = task_manager
+        .get_active_tasks()
+        .into_iter()
+        .map(|task| ActiveAITask::new(task, now))
+        .collect();
+    tasks.sort_by(|a, b| a.task.created_at.cmp(&b.task.created_at));
+    Ok(tasks)
+}
+
 #[tauri::command]
 async fn cancel_sql_analysis(
     analysis_id: String,
@@ -205,6 +747,72 @@ async fn cancel_sql_analysis(
     }
 }
 
+#[tauri::command]
+async fn get_running_operations(
+    ai_manager: tauri::State<'_, AIEngineManagerState>,
+    task_manager: tauri::State<'_, AITaskManagerState>,
+    db_manager: tauri::State<'_, DatabaseManagerState>,
+) -> Result<Vec<RunningOperation>, String> {
+    let mut operations = Vec::new();
+
+    if let Some(manager) = ai_manager.read().await.as_ref() {
+        operations.extend(manager.active_generations().await.into_iter().map(|generation| RunningOperation {
+            id: generation.generation_id,
+            kind: OperationKind::SqlGeneration,
+            started_at: generation.started_at,
+            description: format!("Generating SQL: {}", generation.prompt),
+        }));
+    }
+
+    operations.extend(task_manager.get_active_tasks().into_iter().map(|task| RunningOperation {
+        id: task.task_id,
+        kind: OperationKind::Analysis,
+        started_at: task.created_at,
+        description: format!("{:?} analysis ({:?})", task.task_type, task.status),
+    }));
+
+    if let Some(manager) = db_manager.read().await.as_ref() {
+        operations.extend(manager.running_queries().await.into_iter().map(|query| RunningOperation {
+            id: query.query_id,
+            kind: OperationKind::Query,
+            started_at: query.started_at,
+            description: query.sql,
+        }));
+    }
+
+    operations.sort_by_key(|operation| operation.started_at);
+    Ok(operations)
+}
+
+/// Cancel any running operation by id, dispatching to whichever subsystem owns it
+#[tauri::command]
+async fn cancel_operation(
+    id: String,
+    ai_manager: tauri::State<'_, AIEngineManagerState>,
+    task_manager: tauri::State<'_, AITaskManagerState>,
+    db_manager: tauri::State<'_, DatabaseManagerState>,
+) -> Result<String, String> {
+    log::info!("Cancelling operation: {}", id);
+
+    if let Some(manager) = ai_manager.read().await.as_ref() {
+        if manager.cancel_sql_generation(id.clone()).await.is_ok() {
+            return Ok("Generation cancelled successfully".to_string());
+        }
+    }
+
+    if task_manager.cancel_analysis(&id).is_ok() {
+        return Ok("Analysis cancelled successfully".to_string());
+    }
+
+    if let Some(manager) = db_manager.read().await.as_ref() {
+        if manager.cancel_query(&id).await.is_ok() {
+            return Ok("Query cancelled successfully".to_string());
+        }
+    }
+
+    Err(format!("Operation {} not found or already completed", id))
+}
+
 // NOTE: send_request method not implemented in architect's version
 // #[tauri::command]
 // async fn send_ai_request(
@@ -272,16 +880,6 @@ async fn cancel_sql_analysis(
 //     Err("Not implemented in current version".to_string())
 // }
 
-// NOTE: Additional methods not implemented in architect's version
-// #[tauri::command]
-// async fn get_query_history(
-//     project_id: String,
-//     limit: Option<u32>,
-//     ai_manager: tauri::State<'_, AIEngineManagerState>
-// ) -> Result<Value, String> {
-//     Err("Not implemented in current version".to_string())
-// }
-
 // #[tauri::command]
 // async fn get_schema_suggestions(
 //     project_id: String,
@@ -315,7 +913,8 @@ async fn cancel_sql_analysis(
 
 #[tauri::command]
 async fn init_database_manager(
-    db_manager: tauri::State<'_, DatabaseManagerState>
+    db_manager: tauri::State<'_, DatabaseManagerState>,
+    app_handle: tauri::AppHandle,
 ) -> Result<String, String> {
     let mut manager_guard = db_manager.write().await;
 
@@ -325,6 +924,22 @@ async fn init_database_manager(
 
     match ConnectionManager::new().await {
         Ok(manager) => {
+            // Push health check results to the frontend as they happen
+            let mut status_receiver = manager.get_status_receiver();
+            tokio::spawn(async move {
+                loop {
+                    match status_receiver.recv().await {
+                        Ok(change) => {
+                            let _ = app_handle.emit_all("connection-status-changed", &change);
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                            log::warn!("Dropped {} connection status events", skipped);
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            });
+
             *manager_guard = Some(manager);
             Ok("Database manager initialized successfully".to_string())
         }
@@ -332,6 +947,17 @@ async fn init_database_manager(
     }
 }
 
+fn parse_database_type(database_type: &str) -> Result<DatabaseType, String> {
+    match database_type {
+        "PostgreSQL" => Ok(DatabaseType::PostgreSQL),
+        "MySQL" => Ok(DatabaseType::MySQL),
+        "SQLite" => Ok(DatabaseType::SQLite),
+        "SqlServer" => Ok(DatabaseType::SqlServer),
+        "Oracle" => Ok(DatabaseType::Oracle),
+        _ => Err(format!("Unsupported database type: {}", database_type))
+    }
+}
+
 #[tauri::command]
 async fn add_database_connection(
     name: String,
@@ -342,32 +968,56 @@ async fn add_database_connection(
     username: String,
     password: String,
     ssl_enabled: bool,
+    ssl_mode: Option<String>,
+    certificate_path: Option<String>,
+    private_key_path: Option<String>,
+    ca_certificate_path: Option<String>,
+    dry_run: Option<bool>,
+    force: Option<bool>,
+    default_schema: Option<String>,
+    verify: Option<bool>,
+    read_only: Option<bool>,
     db_manager: tauri::State<'_, DatabaseManagerState>
-) -> Result<String, String> {
+) -> Result<serde_json::Value, String> {
     let manager_guard = db_manager.read().await;
     let manager = manager_guard.as_ref()
         .ok_or_else(|| "Database manager not initialized".to_string())?;
 
     // Parse database type
-    let db_type = match database_type.as_str() {
-        "PostgreSQL" => DatabaseType::PostgreSQL,
-        "MySQL" => DatabaseType::MySQL,
-        "SQLite" => DatabaseType::SQLite,
-        "SqlServer" => DatabaseType::SqlServer,
-        "Oracle" => DatabaseType::Oracle,
-        _ => return Err(format!("Unsupported database type: {}", database_type))
-    };
+    let db_type = parse_database_type(&database_type)?;
 
     // Create connection configuration
     let mut config = ConnectionConfig::new(name, db_type, host, port, database, username);
-    config.ssl_enabled = ssl_enabled;
+    // An explicit ssl_mode takes precedence over the legacy on/off flag
+    config.ssl_mode = match ssl_mode {
+        Some(mode) => mode.parse::<SslMode>().map_err(|e| e.to_string())?,
+        None if ssl_enabled => SslMode::Require,
+        None => SslMode::Disable,
+    };
+    config.default_schema = default_schema.filter(|schema| !schema.trim().is_empty());
+    config.read_only = read_only.unwrap_or(false);
 
     // Create credentials
-    let credentials = DatabaseCredentials::new(config.id, password);
+    let mut credentials = DatabaseCredentials::new(config.id, password);
+    credentials.certificate_path = certificate_path.filter(|p| !p.trim().is_empty());
+    credentials.private_key_path = private_key_path.filter(|p| !p.trim().is_empty());
+    credentials.ca_certificate_path = ca_certificate_path.filter(|p| !p.trim().is_empty());
+
+    // Validate only: report what would happen without storing anything
+    if dry_run.unwrap_or(false) {
+        let report = manager.dry_run_add_connection(&config, &credentials).await;
+        return Ok(serde_json::to_value(report).unwrap());
+    }
 
-    // Add connection
-    match manager.add_connection(config.clone(), credentials).await {
-        Ok(_) => Ok(config.id.to_string()),
+    // Add connection, connecting first when asked so a typo isn't stored
+    let force = force.unwrap_or(false);
+    let added = if verify.unwrap_or(false) {
+        manager.add_verified_connection(config.clone(), credentials, force).await
+    } else {
+        manager.add_connection(config.clone(), credentials, force).await
+    };
+    match added {
+        Ok(_) => Ok(serde_json::Value::String(config.id.to_string())),
         Err(e) => Err(format!("Failed to add connection: {}", e))
     }
 }
@@ -375,6 +1025,7 @@ async fn add_database_connection(
 #[tauri::command]
 async fn test_database_connection(
     connection_id: String,
+    operation_id: Option<String>,
     db_manager: tauri::State<'_, DatabaseManagerState>
 ) -> Result<serde_json::Value, String> {
     let manager_guard = db_manager.read().await;
@@ -384,26 +1035,21 @@ async fn test_database_connection(
     let uuid = Uuid::parse_str(&connection_id)
         .map_err(|e| format!("Invalid connection ID: {}", e))?;
 
-    match manager.test_connection(uuid).await {
+    // Registered like a query so cancel_operation can abort a test stuck on an unreachable host
+    let operation_id = operation_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+    let cancel = manager.begin_query(&operation_id, uuid, "Testing connection").await;
+    let result = manager.test_connection(uuid, Some(&cancel)).await;
+    manager.finish_query(&operation_id).await;
+
+    match result {
         Ok(result) => Ok(serde_json::to_value(result).unwrap()),
         Err(e) => Err(format!("Connection test failed: {}", e))
     }
 }
 
+/// Show the connection string that would be used, with the password masked
 #[tauri::command]
-async fn list_database_connections(
-    db_manager: tauri::State<'_, DatabaseManagerState>
-) -> Result<serde_json::Value, String> {
-    let manager_guard = db_manager.read().await;
-    let manager = manager_guard.as_ref()
-        .ok_or_else(|| "Database manager not initialized".to_string())?;
-
-    let connections = manager.list_connections().await;
-    Ok(serde_json::to_value(connections).unwrap())
-}
-
-#[tauri::command]
-async fn remove_database_connection(
+async fn preview_connection_string(
     connection_id: String,
     db_manager: tauri::State<'_, DatabaseManagerState>
 ) -> Result<String, String> {
@@ -414,30 +1060,412 @@ async fn remove_database_connection(
     let uuid = Uuid::parse_str(&connection_id)
         .map_err(|e| format!("Invalid connection ID: {}", e))?;
 
-    match manager.remove_connection(uuid).await {
-        Ok(_) => Ok("Connection removed successfully".to_string()),
-        Err(e) => Err(format!("Failed to remove connection: {}", e))
-    }
+    manager.preview_connection_string(uuid).await
+        .map_err(|e| format!("Failed to build connection string: {}", e))
 }
 
 #[tauri::command]
-async fn get_database_connection_summary(
-    connection_id: String,
-    db_manager: tauri::State<'_, DatabaseManagerState>
+async fn test_all_connections(
+    timeout_ms: Option<u64>,
+    db_manager: tauri::State<'_, DatabaseManagerState>,
+    app_handle: tauri::AppHandle,
 ) -> Result<serde_json::Value, String> {
     let manager_guard = db_manager.read().await;
     let manager = manager_guard.as_ref()
         .ok_or_else(|| "Database manager not initialized".to_string())?;
 
-    let uuid = Uuid::parse_str(&connection_id)
-        .map_err(|e| format!("Invalid connection ID: {}", e))?;
+    let timeout = std::time::Duration::from_millis(timeout_ms.unwrap_or(10_000));
 
-    match manager.get_connection_summary(uuid).await {
-        Ok(summary) => Ok(serde_json::to_value(summary).unwrap()),
+    // Forward each result to the frontend as soon as it is known
+    let (progress_tx, mut progress_rx) = mpsc::channel::<ConnectionTestProgress>(32);
+    let app_handle_clone = app_handle.clone();
+    tokio::spawn(async move {
+        while let Some(progress) = progress_rx.recv().await {
+            let _ = app_handle_clone.emit_all("connection-test-progress", &progress);
+        }
+    });
+
+    let results = manager.test_all_connections(timeout, Some(progress_tx)).await;
+    Ok(serde_json::to_value(results).unwrap())
+}
+
+#[tauri::command]
+async fn list_database_connections(
+    metadata_key: Option<String>,
+    metadata_value: Option<String>,
+    sort: Option<ConnectionSort>,
+    db_manager: tauri::State<'_, DatabaseManagerState>
+) -> Result<serde_json::Value, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let mut connections = match metadata_key {
+        Some(key) => manager.list_connections_with_metadata(&key, metadata_value.as_deref()).await,
+        None => manager.list_connections().await,
+    };
+    if let Some(sort) = sort {
+        sort_connections(&mut connections, sort);
+    }
+    Ok(serde_json::to_value(connections).unwrap())
+}
+
+#[tauri::command]
+async fn set_connection_metadata(
+    connection_id: String,
+    metadata: HashMap<String, String>,
+    db_manager: tauri::State<'_, DatabaseManagerState>
+) -> Result<String, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let uuid = Uuid::parse_str(&connection_id)
+        .map_err(|e| format!("Invalid connection ID: {}", e))?;
+
+    match manager.set_connection_metadata(uuid, metadata).await {
+        Ok(_) => Ok("Connection metadata updated".to_string()),
+        Err(e) => Err(format!("Failed to update connection: {}", e))
+    }
+}
+
+#[tauri::command]
+async fn get_connections_missing_credentials(
+    db_manager: tauri::State<'_, DatabaseManagerState>
+) -> Result<serde_json::Value, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let missing = manager.connections_missing_credentials().await;
+    Ok(serde_json::to_value(missing).unwrap())
+}
+
+#[tauri::command]
+async fn remove_database_connection(
+    connection_id: String,
+    db_manager: tauri::State<'_, DatabaseManagerState>
+) -> Result<String, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let uuid = Uuid::parse_str(&connection_id)
+        .map_err(|e| format!("Invalid connection ID: {}", e))?;
+
+    match manager.remove_connection(uuid).await {
+        Ok(_) => Ok("Connection removed successfully".to_string()),
+        Err(e) => Err(format!("Failed to remove connection: {}", e))
+    }
+}
+
+#[tauri::command]
+async fn pin_connection(
+    connection_id: String,
+    db_manager: tauri::State<'_, DatabaseManagerState>
+) -> Result<String, String> {
+    set_connection_pinned(connection_id, true, db_manager).await
+}
+
+#[tauri::command]
+async fn unpin_connection(
+    connection_id: String,
+    db_manager: tauri::State<'_, DatabaseManagerState>
+) -> Result<String, String> {
+    set_connection_pinned(connection_id, false, db_manager).await
+}
+
+async fn set_connection_pinned(
+    connection_id: String,
+    pinned: bool,
+    db_manager: tauri::State<'_, DatabaseManagerState>
+) -> Result<String, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let uuid = Uuid::parse_str(&connection_id)
+        .map_err(|e| format!("Invalid connection ID: {}", e))?;
+
+    match manager.set_connection_pinned(uuid, pinned).await {
+        Ok(_) => Ok(if pinned { "Connection pinned" } else { "Connection unpinned" }.to_string()),
+        Err(e) => Err(format!("Failed to update connection: {}", e))
+    }
+}
+
+/// Set or clear (with `None`) the schema unqualified table names resolve against
+#[tauri::command]
+async fn set_connection_default_schema(
+    connection_id: String,
+    default_schema: Option<String>,
+    db_manager: tauri::State<'_, DatabaseManagerState>,
+    schema_cache: tauri::State<'_, SchemaCacheState>
+) -> Result<String, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let uuid = Uuid::parse_str(&connection_id)
+        .map_err(|e| format!("Invalid connection ID: {}", e))?;
+
+    manager.set_default_schema(uuid, default_schema).await
+        .map_err(|e| format!("Failed to update connection: {}", e))?;
+
+    // Cached introspection was scoped to the old default
+    invalidate_schema_cache(&schema_cache, &connection_id).await;
+
+    Ok("Default schema updated".to_string())
+}
+
+/// Edit a stored connection in place, keeping its id and history; omitted fields are left unchanged
+#[tauri::command]
+async fn update_database_connection(
+    connection_id: String,
+    name: Option<String>,
+    host: Option<String>,
+    port: Option<u16>,
+    database: Option<String>,
+    username: Option<String>,
+    ssl_mode: Option<String>,
+    default_schema: Option<String>,
+    connection_timeout: Option<u32>,
+    max_connections: Option<u32>,
+    read_only: Option<bool>,
+    db_manager: tauri::State<'_, DatabaseManagerState>,
+    schema_cache: tauri::State<'_, SchemaCacheState>
+) -> Result<serde_json::Value, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let uuid = Uuid::parse_str(&connection_id)
+        .map_err(|e| format!("Invalid connection ID: {}", e))?;
+
+    let mut config = manager.get_connection(uuid).await
+        .map_err(|e| format!("Failed to get connection: {}", e))?;
+    if let Some(name) = name {
+        config.name = name;
+    }
+    if let Some(host) = host {
+        config.host = host;
+    }
+    if let Some(port) = port {
+        config.port = port;
+    }
+    if let Some(database) = database {
+        config.database = database;
+    }
+    if let Some(username) = username {
+        config.username = username;
+    }
+    if let Some(mode) = ssl_mode {
+        config.ssl_mode = mode.parse::<SslMode>().map_err(|e| e.to_string())?;
+    }
+    // An empty schema clears it
+    if let Some(schema) = default_schema {
+        config.default_schema = Some(schema).filter(|schema| !schema.trim().is_empty());
+    }
+    if let Some(timeout) = connection_timeout {
+        config.connection_timeout = timeout;
+    }
+    if let Some(max) = max_connections {
+        config.max_connections = max;
+    }
+    if let Some(read_only) = read_only {
+        config.read_only = read_only;
+    }
+
+    manager.update_connection_config(config).await
+        .map_err(|e| format!("Failed to update connection: {}", e))?;
+
+    // Cached introspection may describe a different server or database
+    invalidate_schema_cache(&schema_cache, &connection_id).await;
+
+    let updated = manager.get_connection(uuid).await
+        .map_err(|e| format!("Failed to get connection: {}", e))?;
+    Ok(serde_json::to_value(updated).unwrap())
+}
+
+/// Change a stored connection's password; certificates are set with `set_connection_certificates`
+#[tauri::command]
+async fn update_database_credentials(
+    connection_id: String,
+    password: String,
+    db_manager: tauri::State<'_, DatabaseManagerState>
+) -> Result<String, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let uuid = Uuid::parse_str(&connection_id)
+        .map_err(|e| format!("Invalid connection ID: {}", e))?;
+
+    manager.set_connection_password(uuid, password).await
+        .map_err(|e| format!("Failed to update credentials: {}", e))?;
+
+    Ok("Credentials updated".to_string())
+}
+
+/// Set the client certificate, private key and CA certificate used for TLS; empty or missing paths are cleared
+#[tauri::command]
+async fn set_connection_certificates(
+    connection_id: String,
+    certificate_path: Option<String>,
+    private_key_path: Option<String>,
+    ca_certificate_path: Option<String>,
+    db_manager: tauri::State<'_, DatabaseManagerState>
+) -> Result<String, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let uuid = Uuid::parse_str(&connection_id)
+        .map_err(|e| format!("Invalid connection ID: {}", e))?;
+
+    manager.set_connection_certificates(
+        uuid,
+        certificate_path.filter(|p| !p.trim().is_empty()),
+        private_key_path.filter(|p| !p.trim().is_empty()),
+        ca_certificate_path.filter(|p| !p.trim().is_empty()),
+    ).await.map_err(|e| format!("Failed to update certificates: {}", e))?;
+
+    Ok("Certificates updated".to_string())
+}
+
+#[tauri::command]
+async fn reorder_connections(
+    ordered_ids: Vec<String>,
+    db_manager: tauri::State<'_, DatabaseManagerState>
+) -> Result<serde_json::Value, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let ordered_ids = ordered_ids
+        .iter()
+        .map(|id| Uuid::parse_str(id).map_err(|e| format!("Invalid connection ID: {}", e)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match manager.reorder_connections(&ordered_ids).await {
+        Ok(_) => Ok(serde_json::to_value(manager.list_connections().await).unwrap()),
+        Err(e) => Err(format!("Failed to reorder connections: {}", e))
+    }
+}
+
+#[tauri::command]
+async fn export_connections(
+    db_manager: tauri::State<'_, DatabaseManagerState>
+) -> Result<serde_json::Value, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let bundle = manager.export_connections().await;
+    Ok(serde_json::to_value(bundle).unwrap())
+}
+
+#[tauri::command]
+async fn import_connections(
+    bundle: ConnectionExportBundle,
+    db_manager: tauri::State<'_, DatabaseManagerState>
+) -> Result<serde_json::Value, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    match manager.import_connections(bundle).await {
+        Ok(result) => Ok(serde_json::to_value(result).unwrap()),
+        Err(e) => Err(format!("Failed to import connections: {}", e))
+    }
+}
+
+#[tauri::command]
+async fn get_database_connection_summary(
+    connection_id: String,
+    db_manager: tauri::State<'_, DatabaseManagerState>
+) -> Result<serde_json::Value, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let uuid = Uuid::parse_str(&connection_id)
+        .map_err(|e| format!("Invalid connection ID: {}", e))?;
+
+    match manager.get_connection_summary(uuid).await {
+        Ok(summary) => Ok(serde_json::to_value(summary).unwrap()),
         Err(e) => Err(format!("Failed to get connection summary: {}", e))
     }
 }
 
+/// Recorded security events (rate-limit hits, suspicious patterns, credential access), newest first
+#[tauri::command]
+async fn get_security_audit_log(
+    limit: Option<usize>,
+    db_manager: tauri::State<'_, DatabaseManagerState>
+) -> Result<serde_json::Value, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let events: Vec<serde_json::Value> = manager
+        .get_security_audit()
+        .into_iter()
+        .rev()
+        .take(limit.unwrap_or(usize::MAX))
+        .map(|event| serde_json::json!({
+            "event_type": event.event_type.to_string(),
+            "kind": event.event_type,
+            "details": event.details,
+            "source": event.source,
+            "timestamp": event.timestamp.to_rfc3339(),
+        }))
+        .collect();
+
+    Ok(serde_json::json!(events))
+}
+
+#[tauri::command]
+async fn get_database_manager_stats(
+    db_manager: tauri::State<'_, DatabaseManagerState>
+) -> Result<serde_json::Value, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let stats = manager.get_manager_stats().await;
+    Ok(serde_json::to_value(stats).unwrap())
+}
+
+#[tauri::command]
+async fn get_pool_metrics(
+    connection_id: String,
+    db_manager: tauri::State<'_, DatabaseManagerState>
+) -> Result<serde_json::Value, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let uuid = Uuid::parse_str(&connection_id)
+        .map_err(|e| format!("Invalid connection ID: {}", e))?;
+
+    match manager.get_pool_metrics(uuid).await {
+        Ok(metrics) => Ok(serde_json::to_value(metrics).unwrap()),
+        Err(e) => Err(format!("Failed to get pool metrics: {}", e))
+    }
+}
+
+#[tauri::command]
+async fn get_all_pool_metrics(
+    db_manager: tauri::State<'_, DatabaseManagerState>
+) -> Result<serde_json::Value, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let metrics = manager.get_all_pool_metrics().await;
+    Ok(serde_json::to_value(metrics).unwrap())
+}
+
 #[tauri::command]
 async fn get_supported_database_types(
     db_manager: tauri::State<'_, DatabaseManagerState>
@@ -450,8 +1478,39 @@ async fn get_supported_database_types(
     Ok(types.iter().map(|t| t.to_string()).collect())
 }
 
+/// Features the connection's database supports, so the UI can hide what would only fail
+#[tauri::command]
+async fn get_connection_features(
+    connection_id: String,
+    db_manager: tauri::State<'_, DatabaseManagerState>
+) -> Result<serde_json::Value, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let uuid = Uuid::parse_str(&connection_id)
+        .map_err(|e| format!("Invalid connection ID: {}", e))?;
+
+    match manager.connection_features(uuid).await {
+        Ok(features) => Ok(serde_json::to_value(features).unwrap()),
+        Err(e) => Err(format!("Failed to get connection features: {}", e))
+    }
+}
+
 // Removed duplicate get_database_schema function - using enhanced version below
 
+#[tauri::command]
+async fn get_dialect_functions(database_type: String) -> Result<serde_json::Value, String> {
+    let db_type = parse_database_type(&database_type)?;
+    let functions = sql_dialects::dialect_functions(&db_type);
+
+    Ok(serde_json::json!({
+        "database_type": db_type,
+        "functions": functions,
+        "function_count": functions.len()
+    }))
+}
+
 #[tauri::command]
 async fn validate_sql_syntax(
     sql: String,
@@ -459,12 +1518,8 @@ async fn validate_sql_syntax(
     connection_id: Option<String>,
     db_manager: tauri::State<'_, DatabaseManagerState>
 ) -> Result<serde_json::Value, String> {
-    // Basic SQL syntax validation
-    let mut errors = Vec::new();
-    let mut warnings = Vec::new();
     let mut suggestions = Vec::new();
 
-    // Simple validation rules
     if sql.trim().is_empty() {
         return Ok(serde_json::json!({
             "is_valid": true,
@@ -474,32 +1529,18 @@ async fn validate_sql_syntax(
         }));
     }
 
-    // Check for basic SQL structure
-    let sql_upper = sql.to_uppercase();
-
-    // Check for SELECT without FROM (basic validation)
-    if sql_upper.contains("SELECT") && !sql_upper.contains("FROM") && !sql_upper.contains("DUAL") {
-        warnings.push(serde_json::json!({
-            "line": 1,
-            "column": 1,
-            "length": 6,
-            "message": "SELECT statement without FROM clause",
-            "severity": "warning"
-        }));
-    }
-
-    // Check for unmatched parentheses
-    let open_parens = sql.matches('(').count();
-    let close_parens = sql.matches(')').count();
-    if open_parens != close_parens {
-        errors.push(serde_json::json!({
-            "line": 1,
-            "column": 1,
-            "length": sql.len(),
-            "message": "Unmatched parentheses",
-            "severity": "error"
-        }));
-    }
+    // The connection's own database type wins over the editor's dialect setting
+    let connection_type = match (connection_id, db_manager.read().await.as_ref()) {
+        (Some(connection_id), Some(manager)) => match Uuid::parse_str(&connection_id) {
+            Ok(uuid) => manager.get_connection(uuid).await.ok().map(|config| config.database_type),
+            Err(_) => None,
+        },
+        _ => None,
+    };
+    let database_type = connection_type.or_else(|| sql_dialects::validation::dialect_database_type(&dialect));
+    let validation = sql_dialects::validation::validate(&sql, database_type.as_ref());
+    let errors = validation.errors;
+    let warnings = validation.warnings;
 
     // Add suggestions based on dialect
     match dialect.as_str() {
@@ -525,13 +1566,45 @@ async fn validate_sql_syntax(
     }))
 }
 
+/// Execute a query and return its rows.
+///
+/// `max_rows` (defaulting to the manager's `default_max_rows`) caps how many rows are
+/// fetched; when the cap is hit the response carries `"truncated": true`. Truncation is
+/// a safety net against runaway result sets, not pagination - callers that need every
+/// row must page through the results themselves.
 #[tauri::command]
 async fn execute_sql_query(
     connection_id: String,
     query: String,
     query_id: String,
-    db_manager: tauri::State<'_, DatabaseManagerState>
+    max_rows: Option<usize>,
+    permission: Option<String>,
+    db_manager: tauri::State<'_, DatabaseManagerState>,
+    schema_cache: tauri::State<'_, SchemaCacheState>,
+    template_manager: tauri::State<'_, TemplateManagerState>
+) -> Result<serde_json::Value, String> {
+    let permission = permission.map(|level| level.parse::<PermissionLevel>()).transpose()?;
+    run_sql_query(connection_id, query, query_id, max_rows, permission, &db_manager, &schema_cache, &template_manager).await
+}
+
+/// Fetch one page of a SELECT's result. Pass the returned `next_cursor` back to get the
+/// following page; each page is also emitted as a `sql-query-page` event. Pages go through
+/// the same statement policy, permission check, query slot and history as `execute_sql_query`.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+async fn execute_sql_query_stream(
+    connection_id: String,
+    query: String,
+    query_id: String,
+    page_size: Option<usize>,
+    cursor: Option<String>,
+    permission: Option<String>,
+    app_handle: tauri::AppHandle,
+    db_manager: tauri::State<'_, DatabaseManagerState>,
+    template_manager: tauri::State<'_, TemplateManagerState>
 ) -> Result<serde_json::Value, String> {
+    let permission = permission.map(|level| level.parse::<PermissionLevel>()).transpose()?;
+
     // Input validation
     if query.trim().is_empty() {
         return Err("Query cannot be empty".to_string());
@@ -541,6 +1614,13 @@ async fn execute_sql_query(
         return Err("Connection ID cannot be empty".to_string());
     }
 
+    // The cursor is the offset of the first row of the page
+    let offset = match cursor.as_deref() {
+        Some(cursor) => cursor.parse::<usize>().map_err(|_| format!("Invalid query cursor: {}", cursor))?,
+        None => 0,
+    };
+    let page_size = page_size.unwrap_or(DEFAULT_QUERY_PAGE_SIZE).clamp(1, MAX_QUERY_PAGE_SIZE);
+
     let manager_guard = db_manager.read().await;
     let manager = manager_guard.as_ref()
         .ok_or_else(|| "Database manager not initialized".to_string())?;
@@ -549,87 +1629,529 @@ async fn execute_sql_query(
         .map_err(|e| format!("Invalid connection ID format: {}", e))?;
 
     let start_time = std::time::Instant::now();
+    let cancel = manager.begin_query(&query_id, uuid, &query).await;
+    let result = fetch_registered_page(manager, uuid, &query, &query_id, offset, page_size, permission, &cancel).await;
+    manager.finish_query(&query_id).await;
+    manager.touch_connection(uuid).await;
+
+    // Later pages re-run the same query, so only the first is added to the history
+    if cursor.is_none() {
+        record_query_history(&template_manager, connection_id, query, &query_id, start_time, &result).await;
+    }
+
+    if let Ok(page) = &result {
+        if page.get("success").and_then(|v| v.as_bool()) == Some(true) {
+            let _ = app_handle.emit_all("sql-query-page", page);
+        }
+    }
+
+    result
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn fetch_registered_page(
+    manager: &ConnectionManager,
+    uuid: Uuid,
+    query: &str,
+    query_id: &str,
+    offset: usize,
+    page_size: usize,
+    permission: Option<PermissionLevel>,
+    cancel: &CancellationToken,
+) -> Result<serde_json::Value, String> {
+    let start_time = std::time::Instant::now();
+
+    let config = manager.get_connection(uuid).await
+        .map_err(|e| format!("Failed to load connection: {}", e))?;
+    if let Some(violation) = query_policy_violation(&config, query, query_id, permission, start_time) {
+        return Ok(violation);
+    }
+
+    let result = manager.fetch_query_page(uuid, query, offset, page_size, Some(cancel)).await;
+    let execution_time = start_time.elapsed().as_millis() as u64;
 
-    // Basic SQL injection prevention (for demo purposes)
-    let query_upper = query.to_uppercase();
-    let dangerous_patterns = ["DROP TABLE", "DELETE FROM", "TRUNCATE TABLE", "ALTER TABLE"];
-    for pattern in dangerous_patterns.iter() {
-        if query_upper.contains(pattern) {
+    let page = match result {
+        Ok(page) => page,
+        Err(ConnectionError::Cancelled(_)) => {
             return Ok(serde_json::json!({
                 "query_id": query_id,
                 "success": false,
-                "error": format!("Potentially dangerous operation detected: {}", pattern),
-                "execution_time": start_time.elapsed().as_millis() as u64
+                "error": "Query cancelled by user",
+                "cancelled": true,
+                "execution_time": execution_time
+            }));
+        }
+        Err(e) => {
+            return Ok(serde_json::json!({
+                "query_id": query_id,
+                "success": false,
+                "error": e.to_string(),
+                "execution_time": execution_time
             }));
         }
+    };
+
+    let columns: Vec<serde_json::Value> = page.columns
+        .iter()
+        .map(|column| serde_json::json!({
+            "name": column.name,
+            "type": column.data_type,
+            "nullable": column.nullable,
+        }))
+        .collect();
+    let row_count = page.rows.len();
+
+    Ok(serde_json::json!({
+        "query_id": query_id,
+        "columns": columns,
+        "rows": page.rows,
+        "row_count": row_count,
+        "offset": offset,
+        "page_size": page_size,
+        "next_cursor": page.truncated.then(|| (offset + row_count).to_string()),
+        "execution_time": execution_time,
+        "success": true
+    }))
+}
+
+/// Shared body of `execute_sql_query`, also used to run processed templates.
+/// Every query that reaches the database is added to the query history.
+#[allow(clippy::too_many_arguments)]
+async fn run_sql_query(
+    connection_id: String,
+    query: String,
+    query_id: String,
+    max_rows: Option<usize>,
+    permission: Option<PermissionLevel>,
+    db_manager: &DatabaseManagerState,
+    schema_cache: &SchemaCacheState,
+    history: &TemplateManager
+) -> Result<serde_json::Value, String> {
+    // Input validation
+    if query.trim().is_empty() {
+        return Err("Query cannot be empty".to_string());
+    }
+
+    if connection_id.trim().is_empty() {
+        return Err("Connection ID cannot be empty".to_string());
     }
 
-    // Simulate different query types and results
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let uuid = Uuid::parse_str(&connection_id)
+        .map_err(|e| format!("Invalid connection ID format: {}", e))?;
+
+    // Register the query so it shows up in the activity view and can be cancelled
+    let start_time = std::time::Instant::now();
+    let cancel = manager.begin_query(&query_id, uuid, &query).await;
+    let result = execute_registered_query(manager, schema_cache, uuid, query.clone(), query_id.clone(), max_rows, permission, &cancel).await;
+    manager.finish_query(&query_id).await;
+    manager.touch_connection(uuid).await;
+
+    record_query_history(history, connection_id, query, &query_id, start_time, &result).await;
+
+    result
+}
+
+/// Add a query that reached the database to the history. History is bookkeeping, so
+/// failing to write it only logs.
+async fn record_query_history(
+    history: &TemplateManager,
+    connection_id: String,
+    query: String,
+    query_id: &str,
+    start_time: std::time::Instant,
+    result: &Result<serde_json::Value, String>,
+) {
+    let outcome = result.as_ref().ok();
+    let entry = QueryHistoryEntry {
+        id: 0,
+        connection_id,
+        query,
+        execution_time_ms: outcome
+            .and_then(|value| value.get("execution_time"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or_else(|| start_time.elapsed().as_millis() as u64),
+        row_count: outcome.and_then(|value| value.get("row_count")).and_then(|v| v.as_u64()),
+        success: outcome
+            .and_then(|value| value.get("success"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        executed_at: Utc::now(),
+    };
+    if let Err(e) = history.record_query_history(&entry).await {
+        log::warn!("Failed to record query {} in history: {}", query_id, e);
+    }
+}
+
+/// Most recent executed queries first, optionally for one connection
+#[tauri::command]
+async fn get_query_history(
+    connection_id: Option<String>,
+    limit: Option<u32>,
+    template_manager: tauri::State<'_, TemplateManagerState>
+) -> Result<Vec<QueryHistoryEntry>, String> {
+    template_manager.get_query_history(connection_id.as_deref(), limit).await
+}
+
+/// Delete the query history for one connection, or all of it; returns how many entries were removed
+#[tauri::command]
+async fn clear_query_history(
+    connection_id: Option<String>,
+    template_manager: tauri::State<'_, TemplateManagerState>
+) -> Result<u32, String> {
+    template_manager.clear_query_history(connection_id.as_deref()).await
+}
+
+/// The response for a query the connection's statement policy refuses, or that needs more
+/// than the caller's permission level; `None` when it may run
+fn query_policy_violation(
+    config: &ConnectionConfig,
+    query: &str,
+    query_id: &str,
+    permission: Option<PermissionLevel>,
+    start_time: std::time::Instant,
+) -> Option<serde_json::Value> {
+    if let Err(e) = config.check_statement(query) {
+        return Some(serde_json::json!({
+            "query_id": query_id,
+            "success": false,
+            "error": e.to_string(),
+            "policy_violation": true,
+            "execution_time": start_time.elapsed().as_millis() as u64
+        }));
+    }
+
+    // The caller's permission level can only narrow what the connection allows
+    let level = permission?;
+    let blocked = first_blocked_statement(query, &config.database_type, level)?;
+    Some(serde_json::json!({
+        "query_id": query_id,
+        "success": false,
+        "error": format!(
+            "{} statements need more than {} permission: {}",
+            blocked.category, level, blocked.sql
+        ),
+        "policy_violation": true,
+        "blocked_statement": blocked.sql,
+        "blocked_category": blocked.category,
+        "execution_time": start_time.elapsed().as_millis() as u64
+    }))
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn execute_registered_query(
+    manager: &ConnectionManager,
+    schema_cache: &SchemaCacheState,
+    uuid: Uuid,
+    query: String,
+    query_id: String,
+    max_rows: Option<usize>,
+    permission: Option<PermissionLevel>,
+    cancel: &CancellationToken,
+) -> Result<serde_json::Value, String> {
+    let max_rows = max_rows.unwrap_or_else(|| manager.default_max_rows()).max(1);
+
+    let start_time = std::time::Instant::now();
+
+    // Enforce the connection's statement policy before running anything
+    let config = manager.get_connection(uuid).await
+        .map_err(|e| format!("Failed to load connection: {}", e))?;
+    if let Some(violation) = query_policy_violation(&config, &query, &query_id, permission, start_time) {
+        return Ok(violation);
+    }
+
+    let output = match manager.execute_query(uuid, &query, max_rows, Some(cancel)).await {
+        Ok(output) => output,
+        Err(ConnectionError::Cancelled(_)) => {
+            return Ok(serde_json::json!({
+                "query_id": query_id,
+                "success": false,
+                "error": "Query cancelled by user",
+                "cancelled": true,
+                "execution_time": start_time.elapsed().as_millis() as u64
+            }));
+        }
+        Err(e) => {
+            return Ok(serde_json::json!({
+                "query_id": query_id,
+                "success": false,
+                "error": e.to_string(),
+                "execution_time": start_time.elapsed().as_millis() as u64
+            }));
+        }
+    };
     let execution_time = start_time.elapsed().as_millis() as u64;
 
-    // Generate mock data based on query content for more realistic testing
-    let (mock_columns, mock_rows) = if query_upper.contains("LARGE") || query_upper.contains("STRESS") {
-        // Generate large dataset for performance testing
-        let row_count = if query_upper.contains("100000") { 100000 }
-                       else if query_upper.contains("50000") { 50000 }
-                       else { 10000 };
+    // The schema just changed, so cached introspection is stale
+    if classify_statement_categories(&query, &config.database_type)
+        .iter()
+        .any(|statement| statement.category == StatementCategory::Ddl)
+    {
+        invalidate_schema_cache(schema_cache, &uuid.to_string()).await;
+    }
 
-        let columns = vec![
-            serde_json::json!({"name": "id", "type": "INTEGER", "nullable": false}),
-            serde_json::json!({"name": "name", "type": "VARCHAR", "nullable": true}),
-            serde_json::json!({"name": "email", "type": "VARCHAR", "nullable": true}),
-            serde_json::json!({"name": "created_at", "type": "TIMESTAMP", "nullable": false}),
-            serde_json::json!({"name": "status", "type": "VARCHAR", "nullable": false}),
-        ];
+    // Statement metadata is the authority on nullability; a failed describe leaves it unknown
+    let described = match manager.describe_query_columns(uuid, &query).await {
+        Ok(described) => Some(described),
+        Err(e) => {
+            log::debug!("Column metadata unavailable for query {}: {}", query_id, e);
+            None
+        }
+    };
 
-        let rows: Vec<Vec<serde_json::Value>> = (1..=row_count).map(|i| {
-            vec![
-                serde_json::json!(i),
-                serde_json::json!(format!("User {}", i)),
-                serde_json::json!(format!("user{}@example.com", i)),
-                serde_json::json!("2025-07-31T10:00:00Z"),
-                serde_json::json!(if i % 2 == 0 { "Active" } else { "Inactive" }),
-            ]
-        }).collect();
+    // A result with no rows carries no column list of its own
+    let result_columns = match (&described, output.columns.is_empty()) {
+        (Some(described), true) => described.clone(),
+        _ => output.columns,
+    };
+    let mut columns: Vec<serde_json::Value> = result_columns
+        .iter()
+        .map(|column| serde_json::json!({
+            "name": column.name,
+            "type": column.data_type,
+            "nullable": column.nullable,
+        }))
+        .collect();
+    let primary_keys = cached_source_primary_keys(schema_cache, uuid, &query).await;
+    annotate_result_columns(&mut columns, described.as_deref(), primary_keys.as_deref());
+
+    let row_count = output.rows.len();
+
+    // Keep the result around so "copy as" SQL can be generated from it later
+    let recent_columns = result_columns
+        .iter()
+        .map(|column| ResultColumn { name: column.name.clone(), data_type: column.data_type.clone() })
+        .collect();
+    let kept_rows = output.rows.iter().take(database::result_sql::MAX_GENERATED_INSERT_ROWS).cloned().collect();
+    manager
+        .remember_result(RecentResult::new(query_id.clone(), uuid, recent_columns, kept_rows, row_count))
+        .await;
 
-        (columns, rows)
-    } else {
-        // Standard small dataset
-        let columns = vec![
-            serde_json::json!({"name": "id", "type": "INTEGER", "nullable": false}),
-            serde_json::json!({"name": "name", "type": "VARCHAR", "nullable": true}),
-            serde_json::json!({"name": "created_at", "type": "TIMESTAMP", "nullable": false})
-        ];
+    Ok(serde_json::json!({
+        "query_id": query_id,
+        "columns": columns,
+        "rows": output.rows,
+        "row_count": row_count,
+        "execution_time": execution_time,
+        "affected_rows": output.affected_rows,
+        "truncated": output.truncated,
+        // The full size of a truncated result isn't known without running it to the end
+        "total_available": if output.truncated { None } else { Some(row_count as u64) },
+        "success": true
+    }))
+}
 
-        let rows = vec![
-            vec![
-                serde_json::json!(1),
-                serde_json::json!("Sample Data"),
-                serde_json::json!("2025-07-31T10:00:00Z")
-            ],
-            vec![
-                serde_json::json!(2),
-                serde_json::json!("Test Record"),
-                serde_json::json!("2025-07-31T10:01:00Z")
-            ],
-            vec![
-                serde_json::json!(3),
-                serde_json::json!(null),
-                serde_json::json!("2025-07-31T10:02:00Z")
-            ]
-        ];
+/// Generate CREATE TABLE or INSERT statements from a recently executed query's result, in
+/// the dialect of the connection it ran on. INSERTs cover at most the first
+/// `MAX_GENERATED_INSERT_ROWS` rows.
+#[tauri::command]
+async fn generate_ddl_from_result(
+    query_id: String,
+    mode: ResultSqlMode,
+    table_name: Option<String>,
+    db_manager: tauri::State<'_, DatabaseManagerState>
+) -> Result<GeneratedSql, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let result = manager.recent_result(&query_id).await.map_err(|e| e.to_string())?;
+    let config = manager.get_connection(result.connection_id).await
+        .map_err(|e| format!("Failed to load connection: {}", e))?;
+    let driver = DatabaseDriverFactory::try_create_driver(&config.database_type).map_err(|e| e.to_string())?;
+    let table = table_name
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "query_result".to_string());
+
+    Ok(database::result_sql::generate_result_sql(driver.as_ref(), &table, &result, mode))
+}
+
+/// Write a result grid to `destination`. Pass the grid's `columns` and `rows`, or a
+/// `connection_id` and `query` to run the query again and export what it returns.
+///
+/// A re-run query must only read. It is registered under `query_id` (a fresh id when
+/// omitted) so it can be cancelled, and waits for a query slot like `execute_sql_query`.
+/// It is capped at `max_rows`; when the cap cut it short the summary carries `truncated: true`.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+async fn export_query_result(
+    columns: Option<Vec<String>>,
+    rows: Option<Vec<Vec<serde_json::Value>>>,
+    connection_id: Option<String>,
+    query: Option<String>,
+    query_id: Option<String>,
+    max_rows: Option<usize>,
+    format: ExportFormat,
+    destination: String,
+    db_manager: tauri::State<'_, DatabaseManagerState>
+) -> Result<ExportSummary, String> {
+    let (columns, rows, truncated) = match (columns, rows, connection_id, query) {
+        (Some(columns), Some(rows), _, _) => (columns, rows, false),
+        (_, _, Some(connection_id), Some(query)) => {
+            let manager_guard = db_manager.read().await;
+            let manager = manager_guard.as_ref()
+                .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+            let uuid = Uuid::parse_str(&connection_id)
+                .map_err(|e| format!("Invalid connection ID: {}", e))?;
+            let config = manager.get_connection(uuid).await
+                .map_err(|e| format!("Failed to load connection: {}", e))?;
+            let query_id = query_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+            // An export only reads, so the re-run gets read permission on top of the connection's policy
+            let violation = query_policy_violation(
+                &config,
+                &query,
+                &query_id,
+                Some(PermissionLevel::Read),
+                std::time::Instant::now(),
+            );
+            if let Some(violation) = violation {
+                return Err(violation["error"].as_str().unwrap_or("Query blocked by policy").to_string());
+            }
+
+            let max_rows = max_rows.unwrap_or_else(|| manager.default_max_rows()).max(1);
+            let cancel = manager.begin_query(&query_id, uuid, &query).await;
+            let result = manager.execute_query(uuid, &query, max_rows, Some(&cancel)).await;
+            manager.finish_query(&query_id).await;
+            manager.touch_connection(uuid).await;
+
+            let output = match result {
+                Ok(output) => output,
+                Err(ConnectionError::Cancelled(_)) => return Err("Export cancelled by user".to_string()),
+                Err(e) => return Err(format!("Query failed: {}", e)),
+            };
+            let columns = output.columns.into_iter().map(|column| column.name).collect();
+            (columns, output.rows, output.truncated)
+        }
+        _ => return Err("Provide either columns and rows, or a connection and query to export".to_string()),
+    };
+
+    // File I/O blocks, so keep it off the async runtime's worker threads
+    let mut summary = tokio::task::spawn_blocking(move || {
+        result_export::export_rows(&columns, &rows, format, std::path::Path::new(&destination))
+    })
+    .await
+    .map_err(|e| format!("Export task failed: {}", e))??;
+    summary.truncated = truncated;
+    Ok(summary)
+}
+
+/// Run several statements atomically; nothing is kept unless every statement succeeds
+#[tauri::command]
+async fn execute_transaction(
+    connection_id: String,
+    statements: Vec<String>,
+    allow_ddl: Option<bool>,
+    max_rows: Option<usize>,
+    db_manager: tauri::State<'_, DatabaseManagerState>,
+    schema_cache: tauri::State<'_, SchemaCacheState>
+) -> Result<serde_json::Value, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let uuid = Uuid::parse_str(&connection_id)
+        .map_err(|e| format!("Invalid connection ID: {}", e))?;
+    if statements.iter().all(|statement| statement.trim().is_empty()) {
+        return Err("No statements to execute".to_string());
+    }
+    let max_rows = max_rows.unwrap_or_else(|| manager.default_max_rows()).max(1);
+    let allow_ddl = allow_ddl.unwrap_or(false);
+
+    match manager.execute_transaction(uuid, &statements, max_rows, allow_ddl).await {
+        Ok(outcome) => {
+            // A committed DDL statement leaves cached introspection stale
+            if allow_ddl && outcome.committed {
+                // Without the config to classify against, assume the schema changed
+                let changed_schema = manager.get_connection(uuid).await.map_or(true, |config| {
+                    statements.iter().any(|statement| {
+                        classify_statement_categories(statement, &config.database_type)
+                            .iter()
+                            .any(|classified| classified.category == StatementCategory::Ddl)
+                    })
+                });
+                if changed_schema {
+                    invalidate_schema_cache(&schema_cache, &connection_id).await;
+                }
+            }
+            serde_json::to_value(outcome).map_err(|e| format!("Failed to serialize transaction result: {}", e))
+        }
+        Err(e @ ConnectionError::PolicyViolation(_)) => Ok(serde_json::json!({
+            "results": [],
+            "committed": false,
+            "error": e.to_string(),
+            "policy_violation": true
+        })),
+        Err(e) => Err(format!("Transaction failed: {}", e)),
+    }
+}
+
+#[tauri::command]
+async fn execute_template(
+    template_id: String,
+    connection_id: String,
+    substitutions: Vec<ParameterSubstitution>,
+    query_id: String,
+    max_rows: Option<usize>,
+    db_manager: tauri::State<'_, DatabaseManagerState>,
+    schema_cache: tauri::State<'_, SchemaCacheState>,
+    template_manager: tauri::State<'_, TemplateManagerState>,
+) -> Result<serde_json::Value, String> {
+    // Values are quoted in the connection's own literal syntax
+    let database_type = match (Uuid::parse_str(&connection_id), db_manager.read().await.as_ref()) {
+        (Ok(uuid), Some(manager)) => manager.get_connection(uuid).await.ok().map(|config| config.database_type),
+        _ => None,
+    };
+    let processed = template_manager
+        .process_template_parameters_for(template_id.clone(), substitutions, database_type.as_ref())
+        .await?;
+
+    if !processed.missing_parameters.is_empty() {
+        return Err(format!(
+            "Missing values for template parameters: {}",
+            processed.missing_parameters.join(", ")
+        ));
+    }
 
-        (columns, rows)
+    let result = run_sql_query(
+        connection_id.clone(),
+        processed.processed_content.clone(),
+        query_id,
+        max_rows,
+        None,
+        &db_manager,
+        &schema_cache,
+        &template_manager,
+    )
+    .await;
+
+    let execution_success = result
+        .as_ref()
+        .map(|value| value.get("success").and_then(|v| v.as_bool()).unwrap_or(false))
+        .unwrap_or(false);
+
+    // Usage bookkeeping must not hide the query outcome
+    if let Err(e) = template_manager.increment_usage_count(template_id.clone()).await {
+        log::warn!("Failed to increment usage for template {}: {}", template_id, e);
+    }
+    let usage = TemplateUsage {
+        template_id: template_id.clone(),
+        used_at: Utc::now(),
+        connection_id: Some(connection_id),
+        execution_success: Some(execution_success),
     };
+    if let Err(e) = template_manager.record_usage(&usage).await {
+        log::warn!("Failed to record usage for template {}: {}", template_id, e);
+    }
 
     Ok(serde_json::json!({
-        "query_id": query_id,
-        "columns": mock_columns,
-        "rows": mock_rows,
-        "row_count": mock_rows.len(),
-        "execution_time": execution_time,
-        "affected_rows": 0,
-        "success": true
+        "template_id": template_id,
+        "processed": processed,
+        "result": result?
     }))
 }
 
@@ -637,6 +2159,11 @@ async fn execute_sql_query(
 async fn get_database_schema(
     connection_id: String,
     include_system_objects: bool,
+    schema_filter: Option<String>,
+    table_filter: Option<String>,
+    cursor: Option<String>,
+    ttl_seconds: Option<u64>,
+    operation_id: Option<String>,
     db_manager: tauri::State<'_, DatabaseManagerState>,
     schema_cache: tauri::State<'_, SchemaCacheState>
 ) -> Result<serde_json::Value, String> {
@@ -644,6 +2171,13 @@ async fn get_database_schema(
     if connection_id.trim().is_empty() {
         return Err("Connection ID cannot be empty".to_string());
     }
+    let ttl_seconds = ttl_seconds.unwrap_or(SCHEMA_CACHE_TTL_SECONDS);
+
+    // A cursor continues a partial introspection from the `next_cursor` of the previous call
+    let resume_from = match cursor.as_deref() {
+        Some(cursor) => Some(cursor.parse::<usize>().map_err(|_| format!("Invalid schema cursor: {}", cursor))?),
+        None => None,
+    };
 
     let manager_guard = db_manager.read().await;
     let manager = manager_guard.as_ref()
@@ -652,24 +2186,27 @@ async fn get_database_schema(
     let uuid = Uuid::parse_str(&connection_id)
         .map_err(|e| format!("Invalid connection ID format: {}", e))?;
 
-    // Create cache key
-    let cache_key = format!("{}_{}", connection_id, include_system_objects);
+    // Without an explicit schema filter, scope to the connection's default schema ("*" lists all)
+    let config = manager.get_connection(uuid).await.ok();
+    let schema_filter = schema_filter.or_else(|| config.as_ref().and_then(|c| c.default_schema.clone()));
+    let filter = SchemaFilter::new(schema_filter, table_filter);
 
-    // Check cache first
-    {
+    // Create cache key; filtered results must never satisfy an unfiltered request
+    let cache_key = format!("{}_{}{}", connection_id, include_system_objects, filter.cache_key_suffix());
+
+    // Check cache first; only complete introspections are cached
+    if resume_from.is_none() {
         let cache_guard = schema_cache.read().await;
         if let Some(cached_entry) = cache_guard.get(&cache_key) {
-            if !cached_entry.is_expired() {
+            // A caller asking for a shorter TTL than the entry was stored with gets fresher data
+            let now = Utc::now();
+            if !cached_entry.is_expired(now) && cached_entry.age_seconds(now) <= ttl_seconds as i64 {
                 // Return cached data with updated timestamp
                 let mut cached_result = cached_entry.schema_data.clone();
                 if let Some(obj) = cached_result.as_object_mut() {
                     obj.insert("cached".to_string(), serde_json::Value::Bool(true));
                     obj.insert("cache_age_seconds".to_string(),
-                        serde_json::Value::Number(
-                            serde_json::Number::from(
-                                Utc::now().signed_duration_since(cached_entry.cached_at).num_seconds()
-                            )
-                        )
+                        serde_json::Value::Number(serde_json::Number::from(cached_entry.age_seconds(now)))
                     );
                 }
                 return Ok(cached_result);
@@ -677,205 +2214,108 @@ async fn get_database_schema(
         }
     }
 
+    // Later pages of a partial result are cut from the listing the first page introspected,
+    // rather than introspecting the whole database again for every page
+    let resume_key = format!("{}_resume", cache_key);
+    let resumed_databases = match resume_from {
+        Some(_) => schema_cache
+            .read()
+            .await
+            .get(&resume_key)
+            .filter(|entry| !entry.is_expired(Utc::now()))
+            .and_then(|entry| entry.schema_data.get("databases"))
+            .and_then(|databases| databases.as_array())
+            .cloned(),
+        None => None,
+    };
+
     let start_time = std::time::Instant::now();
 
-    // For now, return mock schema data
-    // This will be replaced with actual database introspection once drivers are complete
+    let (mut databases, full_listing) = match resumed_databases {
+        Some(databases) => (databases, None),
+        None => {
+            let operation_id = operation_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+            let cancel = manager.begin_query(&operation_id, uuid, "Introspecting schema").await;
+            let schema = manager.introspect_schema(uuid, include_system_objects, &filter, Some(&cancel)).await;
+            manager.finish_query(&operation_id).await;
+            let schema = schema.map_err(|e| format!("Failed to introspect schema: {}", e))?;
+
+            let mut databases = vec![
+                serde_json::to_value(schema).map_err(|e| format!("Failed to serialize schema: {}", e))?
+            ];
+
+            let database_type = config.map(|config| config.database_type);
+            for database in databases.iter_mut() {
+                annotate_normalized_types(database, database_type.as_ref());
+            }
+
+            let full_listing = databases.clone();
+            (databases, Some(full_listing))
+        }
+    };
     let execution_time = start_time.elapsed().as_millis() as u64;
 
-    // Generate mock schema based on connection type
-    let mock_databases = vec![
-        serde_json::json!({
-            "name": "northwind",
-            "schemas": [
-                {
-                    "name": "public",
-                    "tables": [
-                        {
-                            "name": "customers",
-                            "type": "table",
-                            "row_count": 91,
-                            "columns": [
-                                {
-                                    "name": "customer_id",
-                                    "data_type": "VARCHAR",
-                                    "nullable": false,
-                                    "is_primary_key": true,
-                                    "is_foreign_key": false,
-                                    "max_length": 5
-                                },
-                                {
-                                    "name": "company_name",
-                                    "data_type": "VARCHAR",
-                                    "nullable": false,
-                                    "is_primary_key": false,
-                                    "is_foreign_key": false,
-                                    "max_length": 40
-                                },
-                                {
-                                    "name": "contact_name",
-                                    "data_type": "VARCHAR",
-                                    "nullable": true,
-                                    "is_primary_key": false,
-                                    "is_foreign_key": false,
-                                    "max_length": 30
-                                },
-                                {
-                                    "name": "country",
-                                    "data_type": "VARCHAR",
-                                    "nullable": true,
-                                    "is_primary_key": false,
-                                    "is_foreign_key": false,
-                                    "max_length": 15
-                                }
-                            ],
-                            "indexes": [
-                                {
-                                    "name": "pk_customers",
-                                    "is_primary": true,
-                                    "is_unique": true,
-                                    "columns": ["customer_id"]
-                                }
-                            ]
-                        },
-                        {
-                            "name": "orders",
-                            "type": "table",
-                            "row_count": 830,
-                            "columns": [
-                                {
-                                    "name": "order_id",
-                                    "data_type": "INTEGER",
-                                    "nullable": false,
-                                    "is_primary_key": true,
-                                    "is_foreign_key": false
-                                },
-                                {
-                                    "name": "customer_id",
-                                    "data_type": "VARCHAR",
-                                    "nullable": true,
-                                    "is_primary_key": false,
-                                    "is_foreign_key": true,
-                                    "max_length": 5
-                                },
-                                {
-                                    "name": "order_date",
-                                    "data_type": "DATE",
-                                    "nullable": true,
-                                    "is_primary_key": false,
-                                    "is_foreign_key": false
-                                },
-                                {
-                                    "name": "required_date",
-                                    "data_type": "DATE",
-                                    "nullable": true,
-                                    "is_primary_key": false,
-                                    "is_foreign_key": false
-                                }
-                            ],
-                            "indexes": [
-                                {
-                                    "name": "pk_orders",
-                                    "is_primary": true,
-                                    "is_unique": true,
-                                    "columns": ["order_id"]
-                                },
-                                {
-                                    "name": "fk_orders_customers",
-                                    "is_primary": false,
-                                    "is_unique": false,
-                                    "columns": ["customer_id"]
-                                }
-                            ]
-                        },
-                        {
-                            "name": "products",
-                            "type": "table",
-                            "row_count": 77,
-                            "columns": [
-                                {
-                                    "name": "product_id",
-                                    "data_type": "INTEGER",
-                                    "nullable": false,
-                                    "is_primary_key": true,
-                                    "is_foreign_key": false
-                                },
-                                {
-                                    "name": "product_name",
-                                    "data_type": "VARCHAR",
-                                    "nullable": false,
-                                    "is_primary_key": false,
-                                    "is_foreign_key": false,
-                                    "max_length": 40
-                                },
-                                {
-                                    "name": "unit_price",
-                                    "data_type": "DECIMAL",
-                                    "nullable": true,
-                                    "is_primary_key": false,
-                                    "is_foreign_key": false,
-                                    "precision": 10,
-                                    "scale": 2
-                                },
-                                {
-                                    "name": "discontinued",
-                                    "data_type": "BOOLEAN",
-                                    "nullable": false,
-                                    "is_primary_key": false,
-                                    "is_foreign_key": false,
-                                    "default_value": "false"
-                                }
-                            ]
-                        }
-                    ],
-                    "views": [
-                        {
-                            "name": "customer_orders_view",
-                            "type": "view",
-                            "columns": [
-                                {
-                                    "name": "customer_name",
-                                    "data_type": "VARCHAR",
-                                    "nullable": true
-                                },
-                                {
-                                    "name": "order_count",
-                                    "data_type": "INTEGER",
-                                    "nullable": false
-                                },
-                                {
-                                    "name": "total_amount",
-                                    "data_type": "DECIMAL",
-                                    "nullable": true
-                                }
-                            ]
-                        }
-                    ],
-                    "procedures": [
-                        {
-                            "name": "get_customer_orders",
-                            "type": "procedure",
-                            "parameters": [
-                                {
-                                    "name": "customer_id",
-                                    "data_type": "VARCHAR",
-                                    "direction": "IN"
-                                }
-                            ]
-                        }
-                    ]
-                }
-            ]
-        })
-    ];
+    // Degrade to a partial result rather than failing on huge schemas
+    let soft_deadline = std::time::Duration::from_millis(SCHEMA_INTROSPECTION_SOFT_DEADLINE_MS);
+    let next_cursor = paginate_schema_objects(&mut databases, resume_from.unwrap_or(0), || {
+        start_time.elapsed() < soft_deadline
+    });
+    let partial = next_cursor.is_some();
 
-    Ok(serde_json::json!({
+    if let (true, Some(full_listing)) = (partial, full_listing) {
+        schema_cache.write().await.insert(
+            resume_key,
+            SchemaCacheEntry::new(serde_json::json!({ "databases": full_listing }), ttl_seconds),
+        );
+    }
+
+    let schema_data = serde_json::json!({
         "connection_id": connection_id,
-        "databases": mock_databases,
+        "databases": databases,
+        "filter": filter,
+        "partial": partial,
+        "next_cursor": next_cursor.map(|position| position.to_string()),
         "last_updated": chrono::Utc::now().to_rfc3339(),
         "execution_time": execution_time,
         "success": true
-    }))
+    });
+
+    if resume_from.is_none() && !partial {
+        schema_cache.write().await.insert(
+            cache_key,
+            SchemaCacheEntry::new(schema_data.clone(), ttl_seconds),
+        );
+    }
+
+    Ok(schema_data)
+}
+
+/// Forget everything cached for a connection and introspect it again
+#[tauri::command]
+async fn refresh_database_schema(
+    connection_id: String,
+    include_system_objects: bool,
+    schema_filter: Option<String>,
+    table_filter: Option<String>,
+    ttl_seconds: Option<u64>,
+    operation_id: Option<String>,
+    db_manager: tauri::State<'_, DatabaseManagerState>,
+    schema_cache: tauri::State<'_, SchemaCacheState>
+) -> Result<serde_json::Value, String> {
+    invalidate_schema_cache(&schema_cache, &connection_id).await;
+
+    get_database_schema(
+        connection_id,
+        include_system_objects,
+        schema_filter,
+        table_filter,
+        None,
+        ttl_seconds,
+        operation_id,
+        db_manager,
+        schema_cache,
+    )
+    .await
 }
 
 #[tauri::command]
@@ -907,7 +2347,7 @@ async fn get_table_details(
     let execution_time = start_time.elapsed().as_millis() as u64;
 
     // Generate detailed mock data based on table name
-    let table_details = match table_name.as_str() {
+    let mut table_details = match table_name.as_str() {
         "customers" => serde_json::json!({
             "name": "customers",
             "type": "table",
@@ -1026,6 +2466,9 @@ async fn get_table_details(
         })
     };
 
+    let database_type = manager.get_connection(uuid).await.ok().map(|config| config.database_type);
+    annotate_normalized_types(&mut table_details, database_type.as_ref());
+
     Ok(serde_json::json!({
         "connection_id": connection_id,
         "table_details": table_details,
@@ -1116,6 +2559,220 @@ async fn search_schema_objects(
     }))
 }
 
+#[tauri::command]
+async fn get_completions(
+    connection_id: String,
+    context: CompletionContext,
+    db_manager: tauri::State<'_, DatabaseManagerState>,
+    schema_cache: tauri::State<'_, SchemaCacheState>
+) -> Result<serde_json::Value, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let uuid = Uuid::parse_str(&connection_id)
+        .map_err(|e| format!("Invalid connection ID: {}", e))?;
+    let database_type = manager.get_connection(uuid).await.ok().map(|config| config.database_type);
+
+    // Completions run on every keystroke, so only the cached schema is consulted; any
+    // filter's introspection will do, the freshest one wins
+    let cache_guard = schema_cache.read().await;
+    let cached_schema = cached_connection_schemas(&cache_guard, &connection_id, Utc::now())
+        .max_by_key(|entry| entry.cached_at)
+        .map(|entry| &entry.schema_data);
+
+    let suggestions = sql_dialects::completions::complete(&context, cached_schema, database_type.as_ref());
+
+    Ok(serde_json::json!({
+        "connection_id": connection_id,
+        "suggestions": suggestions,
+        "schema_cached": cached_schema.is_some()
+    }))
+}
+
+#[tauri::command]
+async fn get_index_usage(
+    connection_id: String,
+    database: String,
+    schema: String,
+    table: String,
+    operation_id: Option<String>,
+    db_manager: tauri::State<'_, DatabaseManagerState>
+) -> Result<serde_json::Value, String> {
+    if table.trim().is_empty() {
+        return Err("Table name cannot be empty".to_string());
+    }
+
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let uuid = Uuid::parse_str(&connection_id)
+        .map_err(|e| format!("Invalid connection ID: {}", e))?;
+
+    let operation_id = operation_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+    let cancel = manager.begin_query(&operation_id, uuid, "Reading index usage").await;
+    let report = manager.get_index_usage(uuid, &database, &schema, &table, Some(&cancel)).await;
+    manager.finish_query(&operation_id).await;
+
+    match report {
+        Ok(report) => Ok(serde_json::to_value(report).unwrap()),
+        Err(e) => Err(format!("Failed to get index usage: {}", e))
+    }
+}
+
+#[tauri::command]
+async fn get_column_distinct_values(
+    connection_id: String,
+    database: String,
+    schema: String,
+    table: String,
+    column: String,
+    limit: Option<usize>,
+    db_manager: tauri::State<'_, DatabaseManagerState>,
+    schema_cache: tauri::State<'_, SchemaCacheState>
+) -> Result<serde_json::Value, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let uuid = Uuid::parse_str(&connection_id)
+        .map_err(|e| format!("Invalid connection ID: {}", e))?;
+
+    // Only columns known from introspection may be interpolated into SQL
+    let column_known = {
+        let cache_guard = schema_cache.read().await;
+        cached_connection_schemas(&cache_guard, &connection_id, Utc::now())
+            .any(|entry| schema_has_column(&entry.schema_data, &database, &schema, &table, &column))
+    };
+    if !column_known {
+        return Err(format!(
+            "Column '{}' was not found on '{}' in the cached schema; load the schema first",
+            column, table
+        ));
+    }
+
+    let limit = limit.unwrap_or(100).clamp(1, 1000);
+
+    match manager.get_column_distinct_values(uuid, &schema, &table, &column, limit).await {
+        Ok(values) => Ok(serde_json::to_value(values).unwrap()),
+        Err(e) => Err(format!("Failed to get distinct values: {}", e))
+    }
+}
+
+/// Write edited result-grid cells back to the row they came from.
+///
+/// The row must belong to a base table whose primary key is in the cached schema, and
+/// `primary_key_values` must name exactly that key. All values are bound as parameters.
+#[tauri::command]
+async fn update_result_row(
+    connection_id: String,
+    table: String,
+    schema: Option<String>,
+    primary_key_values: BTreeMap<String, serde_json::Value>,
+    changed_columns: BTreeMap<String, serde_json::Value>,
+    db_manager: tauri::State<'_, DatabaseManagerState>,
+    schema_cache: tauri::State<'_, SchemaCacheState>
+) -> Result<serde_json::Value, String> {
+    if changed_columns.is_empty() {
+        return Err("No changed columns to update".to_string());
+    }
+
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let uuid = Uuid::parse_str(&connection_id)
+        .map_err(|e| format!("Invalid connection ID: {}", e))?;
+
+    let schema = match schema {
+        Some(schema) => Some(schema),
+        None => manager.get_connection(uuid).await.ok().and_then(|c| c.default_schema),
+    };
+
+    // Identifiers come only from introspection, never straight from the grid. The table's
+    // schema is resolved there too, so the UPDATE can't land on a same-named table elsewhere
+    // on the search path.
+    let (resolved_schema, primary_keys, column_types) = {
+        let cache_guard = schema_cache.read().await;
+        let found = cached_connection_schemas(&cache_guard, &connection_id, Utc::now())
+            .find_map(|entry| {
+                let (resolved_schema, found) = find_cached_table(&entry.schema_data, schema.as_deref(), &table)?;
+                let mut primary_keys = Vec::new();
+                let mut column_types = BTreeMap::new();
+                for (name, data_type, is_primary_key) in cached_table_columns(found) {
+                    if is_primary_key {
+                        primary_keys.push(name.to_string());
+                    }
+                    column_types.insert(name.to_string(), data_type.to_string());
+                }
+                Some((resolved_schema.to_string(), primary_keys, column_types))
+            });
+        found.ok_or_else(|| format!(
+            "'{}' is not a base table in the cached schema; only results from a single table can be edited",
+            table
+        ))?
+    };
+    let unknown_columns: Vec<String> = changed_columns
+        .keys()
+        .filter(|column| !column_types.contains_key(*column))
+        .cloned()
+        .collect();
+
+    if primary_keys.is_empty() {
+        return Err(format!("Table '{}' has no primary key, so its rows can't be edited safely", table));
+    }
+    if primary_key_values.keys().collect::<BTreeSet<_>>() != primary_keys.iter().collect::<BTreeSet<_>>() {
+        return Err(format!("Primary key values must be given for exactly: {}", primary_keys.join(", ")));
+    }
+    if !unknown_columns.is_empty() {
+        return Err(format!(
+            "Columns not found on '{}' in the cached schema: {}",
+            table,
+            unknown_columns.join(", ")
+        ));
+    }
+
+    let result = manager
+        .update_row(uuid, &resolved_schema, &table, &primary_key_values, &changed_columns, &column_types)
+        .await;
+    manager.touch_connection(uuid).await;
+
+    match result {
+        Ok(affected_rows) => Ok(serde_json::json!({
+            "success": true,
+            "affected_rows": affected_rows
+        })),
+        Err(e) => Err(format!("Failed to update row: {}", e))
+    }
+}
+
+#[tauri::command]
+async fn get_foreign_key_graph(
+    connection_id: String,
+    database_name: String,
+    schema_name: String,
+    operation_id: Option<String>,
+    db_manager: tauri::State<'_, DatabaseManagerState>
+) -> Result<serde_json::Value, String> {
+    let manager_guard = db_manager.read().await;
+    let manager = manager_guard.as_ref()
+        .ok_or_else(|| "Database manager not initialized".to_string())?;
+
+    let uuid = Uuid::parse_str(&connection_id)
+        .map_err(|e| format!("Invalid connection ID: {}", e))?;
+
+    let operation_id = operation_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+    let cancel = manager.begin_query(&operation_id, uuid, "Reading foreign keys").await;
+    let graph = manager.get_foreign_key_graph(uuid, &database_name, &schema_name, Some(&cancel)).await;
+    manager.finish_query(&operation_id).await;
+
+    match graph {
+        Ok(graph) => Ok(serde_json::to_value(graph).unwrap()),
+        Err(e) => Err(format!("Failed to build foreign key graph: {}", e))
+    }
+}
+
 // Template Management Commands - Story 3.7
 
 #[tauri::command]
@@ -1126,6 +2783,17 @@ async fn create_template(
     template_manager.create_template(request).await
 }
 
+/// Create a template, or update the same-named one in its category
+#[tauri::command]
+async fn upsert_template(
+    request: CreateTemplateRequest,
+    template_manager: tauri::State<'_, TemplateManagerState>,
+) -> Result<UpsertTemplateResult, String> {
+    template_manager.upsert_template(request).await
+}
+
+/// Templates matching the filter, capped at `DEFAULT_TEMPLATE_PAGE_SIZE` unless it sets a limit.
+/// Page with `offset` (see `get_template_page`) to enumerate the whole library.
 #[tauri::command]
 async fn get_templates(
     filter: Option<TemplateFilter>,
@@ -1135,6 +2803,16 @@ async fn get_templates(
     template_manager.get_templates(filter).await
 }
 
+/// Like `get_templates`, but with the total number of matches for paging through the library
+#[tauri::command]
+async fn get_template_page(
+    filter: Option<TemplateFilter>,
+    template_manager: tauri::State<'_, TemplateManagerState>,
+) -> Result<TemplatePage, String> {
+    let filter = filter.unwrap_or_default();
+    template_manager.get_template_page(filter).await
+}
+
 #[tauri::command]
 async fn get_template_by_id(
     id: String,
@@ -1147,9 +2825,28 @@ async fn get_template_by_id(
 async fn update_template(
     id: String,
     updates: UpdateTemplateRequest,
+    app_handle: tauri::AppHandle,
     template_manager: tauri::State<'_, TemplateManagerState>,
 ) -> Result<Template, String> {
-    template_manager.update_template(id, updates).await
+    // Remember where the template was so both categories' counts can be refreshed
+    let previous_category = match &updates.category_id {
+        Some(_) => Some(template_manager.get_template_by_id(&id).await?.category_id),
+        None => None,
+    };
+
+    let template = template_manager.update_template(id, updates).await?;
+
+    if let Some(previous_category) = previous_category.filter(|category| *category != template.category_id) {
+        let moved = [previous_category, template.category_id.clone()];
+        match template_manager.category_template_counts(&moved).await {
+            Ok(counts) => {
+                let _ = app_handle.emit_all("template-categories-changed", &CategoryCountsChanged { counts });
+            }
+            Err(e) => log::warn!("Failed to refresh category counts: {}", e),
+        }
+    }
+
+    Ok(template)
 }
 
 #[tauri::command]
@@ -1160,6 +2857,84 @@ async fn delete_template(
     template_manager.delete_template(id).await
 }
 
+#[tauri::command]
+async fn bulk_delete_templates(
+    ids: Vec<String>,
+    app_handle: tauri::AppHandle,
+    template_manager: tauri::State<'_, TemplateManagerState>,
+) -> Result<Vec<BulkTemplateResult>, String> {
+    let results = template_manager.bulk_delete_templates(ids).await?;
+
+    if results.iter().any(|result| result.success) {
+        emit_all_category_counts(&app_handle, &template_manager).await;
+    }
+
+    Ok(results)
+}
+
+#[tauri::command]
+async fn bulk_move_templates(
+    ids: Vec<String>,
+    category_id: String,
+    app_handle: tauri::AppHandle,
+    template_manager: tauri::State<'_, TemplateManagerState>,
+) -> Result<Vec<BulkTemplateResult>, String> {
+    let results = template_manager.bulk_move_templates(ids, category_id).await?;
+
+    // Templates may have come from any category, so refresh all of them
+    if results.iter().any(|result| result.success) {
+        emit_all_category_counts(&app_handle, &template_manager).await;
+    }
+
+    Ok(results)
+}
+
+/// Send every category's template count in one `template-categories-changed` event
+async fn emit_all_category_counts(app_handle: &tauri::AppHandle, template_manager: &TemplateManager) {
+    match template_manager.get_categories().await {
+        Ok(categories) => {
+            let counts = categories
+                .into_iter()
+                .map(|category| CategoryCount { category_id: category.id, template_count: category.template_count })
+                .collect();
+            let _ = app_handle.emit_all("template-categories-changed", &CategoryCountsChanged { counts });
+        }
+        Err(e) => log::warn!("Failed to refresh category counts: {}", e),
+    }
+}
+
+#[tauri::command]
+async fn bulk_set_template_favorite(
+    ids: Vec<String>,
+    is_favorite: bool,
+    template_manager: tauri::State<'_, TemplateManagerState>,
+) -> Result<Vec<BulkTemplateResult>, String> {
+    template_manager.bulk_set_favorite(ids, is_favorite).await
+}
+
+#[tauri::command]
+async fn restore_template(
+    id: String,
+    template_manager: tauri::State<'_, TemplateManagerState>,
+) -> Result<Template, String> {
+    template_manager.restore_template(id).await
+}
+
+#[tauri::command]
+async fn list_deleted_templates(
+    template_manager: tauri::State<'_, TemplateManagerState>,
+) -> Result<Vec<DeletedTemplate>, String> {
+    template_manager.list_deleted_templates().await
+}
+
+#[tauri::command]
+async fn purge_deleted_templates(
+    older_than_days: u32,
+    template_manager: tauri::State<'_, TemplateManagerState>,
+) -> Result<u32, String> {
+    template_manager.purge_deleted_templates(older_than_days).await
+}
+
 #[tauri::command]
 async fn increment_template_usage(
     id: String,
@@ -1183,6 +2958,13 @@ async fn get_template_categories(
     template_manager.get_categories().await
 }
 
+#[tauri::command]
+async fn get_template_category_tree(
+    template_manager: tauri::State<'_, TemplateManagerState>,
+) -> Result<Vec<CategoryNode>, String> {
+    template_manager.get_category_tree().await
+}
+
 #[tauri::command]
 async fn update_template_category(
     id: String,
@@ -1200,6 +2982,22 @@ async fn delete_template_category(
     template_manager.delete_category(id).await
 }
 
+#[tauri::command]
+async fn delete_template_category_with_strategy(
+    id: String,
+    strategy: CategoryDeleteStrategy,
+    app_handle: tauri::AppHandle,
+    template_manager: tauri::State<'_, TemplateManagerState>,
+) -> Result<(), String> {
+    let reassigned = matches!(strategy, CategoryDeleteStrategy::Reassign(_));
+    template_manager.delete_category_with_strategy(id, strategy).await?;
+
+    if reassigned {
+        emit_all_category_counts(&app_handle, &template_manager).await;
+    }
+    Ok(())
+}
+
 #[tauri::command]
 async fn search_templates(
     query: String,
@@ -1226,9 +3024,20 @@ async fn export_templates(
 #[tauri::command]
 async fn import_templates(
     template_data: String,
+    atomic: Option<bool>,
+    mode: Option<ImportMode>,
     template_manager: tauri::State<'_, TemplateManagerState>,
 ) -> Result<TemplateImportResult, String> {
-    template_manager.import_templates(template_data).await
+    template_manager.import_templates(template_data, atomic.unwrap_or(false), mode.unwrap_or_default()).await
+}
+
+#[tauri::command]
+async fn diff_template_content(
+    template_id: String,
+    new_content: String,
+    template_manager: tauri::State<'_, TemplateManagerState>,
+) -> Result<TemplateContentDiff, String> {
+    template_manager.diff_template_content(&template_id, &new_content).await
 }
 
 #[tauri::command]
@@ -1240,17 +3049,41 @@ async fn process_template_parameters(
     template_manager.process_template_parameters(template_id, substitutions).await
 }
 
+/// Substituted SQL for a preview pane; nothing is run and usage is not counted
+#[tauri::command]
+async fn preview_template(
+    template_id: String,
+    substitutions: Option<Vec<ParameterSubstitution>>,
+    connection_id: Option<String>,
+    template_manager: tauri::State<'_, TemplateManagerState>,
+    db_manager: tauri::State<'_, DatabaseManagerState>,
+) -> Result<ProcessedTemplate, String> {
+    let database_type = match (connection_id, db_manager.read().await.as_ref()) {
+        (Some(connection_id), Some(manager)) => match Uuid::parse_str(&connection_id) {
+            Ok(uuid) => manager.get_connection(uuid).await.ok().map(|config| config.database_type),
+            Err(_) => None,
+        },
+        _ => None,
+    };
+
+    template_manager
+        .preview_template(template_id, substitutions.unwrap_or_default(), database_type.as_ref())
+        .await
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .setup(|app| {
-            if cfg!(debug_assertions) {
-                app.handle().plugin(
-                    tauri_plugin_log::Builder::default()
-                        .level(log::LevelFilter::Info)
-                        .build(),
-                )?;
-            }
+            // The plugin passes everything through; the effective level is log::max_level,
+            // which set_log_level can change at runtime
+            app.handle().plugin(
+                tauri_plugin_log::Builder::default()
+                    .level(log::LevelFilter::Trace)
+                    .target(logging::buffer_target())
+                    .build(),
+            )?;
+            log::set_max_level(logging::persisted_level());
 
             // Initialize AI Engine Manager state
             let ai_manager: AIEngineManagerState = Arc::new(RwLock::new(None));
@@ -1264,6 +3097,10 @@ pub fn run() {
             let db_manager: DatabaseManagerState = Arc::new(RwLock::new(None));
             app.manage(db_manager);
 
+            // Initialize Schema Cache state
+            let schema_cache: SchemaCacheState = Arc::new(RwLock::new(HashMap::new()));
+            app.manage(schema_cache);
+
             // Initialize Template Manager state (Story 3.7)
             let cortex_db_path = app.path_resolver()
                 .app_data_dir()
@@ -1282,45 +3119,215 @@ pub fn run() {
             start_ai_engine,
             stop_ai_engine,
             get_ai_engine_status,
+            get_ai_engine_logs,
+            get_health_stats,
+            get_system_health,
+            set_log_level,
+            get_recent_logs,
             // SQL Generation commands
             generate_sql_from_prompt,
+            continue_sql_generation,
             cancel_sql_generation,
             // SQL Analysis commands (Story 3.6)
             analyze_sql_query,
             cancel_sql_analysis,
+            // Activity monitor
+            get_active_ai_tasks,
+            get_running_operations,
+            cancel_operation,
             // Database connection management commands
             init_database_manager,
             add_database_connection,
             test_database_connection,
+            test_all_connections,
+            preview_connection_string,
             list_database_connections,
+            get_connections_missing_credentials,
             remove_database_connection,
+            pin_connection,
+            unpin_connection,
+            reorder_connections,
+            set_connection_default_schema,
+            set_connection_certificates,
+            update_database_connection,
+            update_database_credentials,
+            set_connection_metadata,
+            export_connections,
+            import_connections,
             get_database_connection_summary,
             get_supported_database_types,
+            get_connection_features,
+            get_database_manager_stats,
+            get_security_audit_log,
+            get_pool_metrics,
+            get_all_pool_metrics,
             // SQL Editor commands
             get_database_schema,
+            refresh_database_schema,
+            get_dialect_functions,
             validate_sql_syntax,
             execute_sql_query,
+            generate_ddl_from_result,
+            execute_sql_query_stream,
+            get_query_history,
+            clear_query_history,
+            execute_template,
+            execute_transaction,
+            export_query_result,
             // Schema Explorer commands
             get_table_details,
             search_schema_objects,
+            get_completions,
+            get_index_usage,
+            get_foreign_key_graph,
+            get_column_distinct_values,
+            update_result_row,
             // Template Management commands (Story 3.7)
             create_template,
+            upsert_template,
             get_templates,
+            get_template_page,
             get_template_by_id,
             update_template,
             delete_template,
+            bulk_delete_templates,
+            bulk_move_templates,
+            bulk_set_template_favorite,
+            restore_template,
+            list_deleted_templates,
+            purge_deleted_templates,
             increment_template_usage,
             create_template_category,
             get_template_categories,
+            get_template_category_tree,
             update_template_category,
             delete_template_category,
+            delete_template_category_with_strategy,
             search_templates,
             get_template_statistics,
             export_templates,
             import_templates,
-            process_template_parameters
+            diff_template_content,
+            process_template_parameters,
+            preview_template
             // NOTE: Other commands commented out - not implemented in architect's version
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_schema_cache_entry_expiry_boundaries() {
+        let entry = SchemaCacheEntry::new(serde_json::json!({}), SCHEMA_CACHE_TTL_SECONDS);
+        let ttl = Duration::seconds(SCHEMA_CACHE_TTL_SECONDS as i64);
+
+        assert!(!entry.is_expired(entry.cached_at));
+        assert!(!entry.is_expired(entry.cached_at + ttl - Duration::seconds(1)));
+        assert!(!entry.is_expired(entry.cached_at + ttl));
+        assert!(entry.is_expired(entry.cached_at + ttl + Duration::seconds(1)));
+    }
+
+    #[tokio::test]
+    async fn test_schema_cache_invalidation_is_per_connection() {
+        let cache: SchemaCacheState = Arc::new(RwLock::new(HashMap::new()));
+        for key in ["conn-a_false", "conn-a_true_s:public", "conn-ab_false"] {
+            cache.write().await.insert(key.to_string(), SchemaCacheEntry::new(serde_json::json!({}), 60));
+        }
+
+        invalidate_schema_cache(&cache, "conn-a").await;
+
+        let remaining: Vec<String> = cache.read().await.keys().cloned().collect();
+        assert_eq!(remaining, vec!["conn-ab_false".to_string()]);
+    }
+
+    #[test]
+    fn test_schema_pagination_resumes_from_cursor() {
+        let table = |name: &str| serde_json::json!({ "name": name, "columns": [] });
+        let databases = vec![serde_json::json!({
+            "name": "shop",
+            "schemas": [
+                { "name": "public", "tables": [table("customers"), table("orders")], "views": [table("order_totals")] },
+                { "name": "audit", "tables": [table("events")] }
+            ]
+        })];
+        let names = |databases: &[serde_json::Value]| -> Vec<String> {
+            databases[0]["schemas"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .flat_map(|schema| {
+                    let tables = schema["tables"].as_array().unwrap().iter();
+                    tables.chain(schema["views"].as_array().into_iter().flatten())
+                })
+                .map(|object| object["name"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        // Budget runs out after the second object
+        let mut first_page = databases.clone();
+        let mut calls = 0;
+        let cursor = paginate_schema_objects(&mut first_page, 0, || {
+            calls += 1;
+            calls < 2
+        });
+        assert_eq!(cursor, Some(2));
+        assert_eq!(names(&first_page), ["customers", "orders"]);
+
+        let mut second_page = databases.clone();
+        assert_eq!(paginate_schema_objects(&mut second_page, 2, || true), None);
+        assert_eq!(names(&second_page), ["order_totals", "events"]);
+
+        // Even with no budget left, each call makes progress
+        let mut starved = databases.clone();
+        assert_eq!(paginate_schema_objects(&mut starved, 1, || false), Some(2));
+        assert_eq!(names(&starved), ["orders"]);
+    }
+
+    #[test]
+    fn test_result_columns_get_primary_keys_from_single_table() {
+        assert_eq!(single_source_table("SELECT id, name FROM users WHERE id = 1"), Some((None, "users".to_string())));
+        assert_eq!(
+            single_source_table("select * from \"public\".\"users\" as u order by id;"),
+            Some((Some("public".to_string()), "users".to_string()))
+        );
+        assert_eq!(single_source_table("SELECT * FROM users u JOIN orders o ON o.user_id = u.id"), None);
+        assert_eq!(single_source_table("SELECT * FROM users, orders"), None);
+        assert_eq!(single_source_table("SELECT * FROM (SELECT 1) t"), None);
+        assert_eq!(single_source_table("UPDATE users SET name = 'x'"), None);
+
+        let schema_data = serde_json::json!({
+            "databases": [{"name": "app", "schemas": [{"name": "public",
+                "tables": [{"name": "users", "columns": [
+                    {"name": "id", "is_primary_key": true},
+                    {"name": "name", "is_primary_key": false}
+                ]}],
+                "views": [{"name": "active_users", "columns": [{"name": "id", "is_primary_key": true}]}]
+            }]}]
+        });
+        assert_eq!(table_primary_keys(&schema_data, Some("public"), "users"), Some(vec!["id".to_string()]));
+        assert_eq!(table_primary_keys(&schema_data, Some("other"), "users"), None);
+        assert_eq!(table_primary_keys(&schema_data, None, "active_users"), None);
+
+        let mut columns = vec![
+            serde_json::json!({"name": "id", "type": "INTEGER", "nullable": false}),
+            serde_json::json!({"name": "name", "type": "VARCHAR", "nullable": false}),
+        ];
+        let described = vec![
+            ResultColumnInfo { name: "id".to_string(), data_type: "INT4".to_string(), nullable: Some(false) },
+            ResultColumnInfo { name: "name".to_string(), data_type: "TEXT".to_string(), nullable: None },
+        ];
+        annotate_result_columns(&mut columns, Some(&described), Some(&["id".to_string()]));
+        assert_eq!(columns[0]["is_primary_key"], true);
+        assert_eq!(columns[1]["is_primary_key"], false);
+        assert_eq!(columns[1]["nullable"], serde_json::Value::Null);
+
+        annotate_result_columns(&mut columns, None, None);
+        assert_eq!(columns[0]["is_primary_key"], false);
+        assert_eq!(columns[0]["nullable"], false);
+    }
+}
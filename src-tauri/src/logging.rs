@@ -0,0 +1,80 @@
+// Runtime logging control
+// Keeps a ring buffer of recent log lines and a persisted verbosity level
+
+use log::LevelFilter;
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+
+/// Number of formatted log lines kept for `get_recent_logs`
+const RECENT_LOG_CAPACITY: usize = 1000;
+
+pub const DEFAULT_LOG_LEVEL: LevelFilter = LevelFilter::Info;
+
+static RECENT_LOGS: OnceLock<Mutex<VecDeque<String>>> = OnceLock::new();
+
+fn recent_logs_buffer() -> &'static Mutex<VecDeque<String>> {
+    RECENT_LOGS.get_or_init(|| Mutex::new(VecDeque::with_capacity(RECENT_LOG_CAPACITY)))
+}
+
+/// Log target that copies every record into the in-memory buffer
+pub fn buffer_target() -> tauri_plugin_log::Target {
+    let dispatch = fern::Dispatch::new().chain(fern::Output::call(|record| {
+        let line = format!(
+            "{} [{}] {}: {}",
+            chrono::Utc::now().to_rfc3339(),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+
+        if let Ok(mut buffer) = recent_logs_buffer().lock() {
+            if buffer.len() == RECENT_LOG_CAPACITY {
+                buffer.pop_front();
+            }
+            buffer.push_back(line);
+        }
+    }));
+
+    tauri_plugin_log::Target::new(tauri_plugin_log::TargetKind::Dispatch(dispatch))
+}
+
+/// Most recent log lines, oldest first
+pub fn recent_logs(limit: usize) -> Vec<String> {
+    let buffer = recent_logs_buffer().lock().unwrap_or_else(|e| e.into_inner());
+    let skip = buffer.len().saturating_sub(limit);
+    buffer.iter().skip(skip).cloned().collect()
+}
+
+pub fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    level
+        .trim()
+        .parse::<LevelFilter>()
+        .map_err(|_| format!("Invalid log level '{}': expected off, error, warn, info, debug or trace", level))
+}
+
+/// Change verbosity for the running app and remember it for the next launch
+pub fn set_level(level: LevelFilter) -> Result<(), String> {
+    log::set_max_level(level);
+
+    let path = level_file_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    std::fs::write(&path, level.to_string()).map_err(|e| format!("Failed to persist log level: {}", e))
+}
+
+/// Level chosen in a previous session, or the default
+pub fn persisted_level() -> LevelFilter {
+    level_file_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|level| parse_level(&level).ok())
+        .unwrap_or(DEFAULT_LOG_LEVEL)
+}
+
+fn level_file_path() -> Result<std::path::PathBuf, String> {
+    let mut path = dirs::config_dir().ok_or_else(|| "Could not determine config directory".to_string())?;
+    path.push("symbiotic-analysis");
+    path.push("log-level");
+    Ok(path)
+}
@@ -0,0 +1,176 @@
+// Query Result Export
+// Writes result grids to CSV, TSV or JSON files
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// File format for an exported result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Tsv,
+}
+
+/// What was written by `export_rows`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSummary {
+    pub path: String,
+    pub rows_written: u64,
+    pub file_size: u64,
+    /// The exported query returned more rows than its row cap, so the file is incomplete
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+/// Write `rows` under a header of `columns` to `path`, replacing any existing file.
+/// NULLs become empty fields in CSV/TSV and `null` in JSON, where each row is an object
+/// keyed by column name. A partially written file is removed on failure.
+pub fn export_rows(
+    columns: &[String],
+    rows: &[Vec<serde_json::Value>],
+    format: ExportFormat,
+    path: &Path,
+) -> Result<ExportSummary, String> {
+    let file = File::create(path)
+        .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut writer = BufWriter::new(file);
+
+    let written = match format {
+        ExportFormat::Csv => write_delimited(&mut writer, columns, rows, b','),
+        ExportFormat::Tsv => write_delimited(&mut writer, columns, rows, b'\t'),
+        ExportFormat::Json => write_json(&mut writer, columns, rows),
+    }
+    .and_then(|count| {
+        writer.flush().map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(count)
+    });
+
+    let rows_written = match written {
+        Ok(count) => count,
+        Err(e) => {
+            drop(writer);
+            std::fs::remove_file(path).ok();
+            return Err(e);
+        }
+    };
+
+    let file_size = std::fs::metadata(path)
+        .map_err(|e| format!("Failed to read size of {}: {}", path.display(), e))?
+        .len();
+
+    Ok(ExportSummary {
+        path: path.display().to_string(),
+        rows_written,
+        file_size,
+        truncated: false,
+    })
+}
+
+/// One row per record; the csv writer quotes fields containing the delimiter, quotes or newlines
+fn write_delimited<W: Write>(
+    writer: W,
+    columns: &[String],
+    rows: &[Vec<serde_json::Value>],
+    delimiter: u8,
+) -> Result<u64, String> {
+    let mut csv_writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(writer);
+
+    csv_writer.write_record(columns)
+        .map_err(|e| format!("Failed to write header: {}", e))?;
+    for row in rows {
+        csv_writer.write_record(row.iter().map(field_text))
+            .map_err(|e| format!("Failed to write row: {}", e))?;
+    }
+    csv_writer.flush()
+        .map_err(|e| format!("Failed to write rows: {}", e))?;
+
+    Ok(rows.len() as u64)
+}
+
+/// A JSON array written one object at a time, so no second copy of the result is built
+fn write_json<W: Write>(
+    mut writer: W,
+    columns: &[String],
+    rows: &[Vec<serde_json::Value>],
+) -> Result<u64, String> {
+    let io_error = |e: std::io::Error| format!("Failed to write rows: {}", e);
+
+    writer.write_all(b"[").map_err(io_error)?;
+    for (index, row) in rows.iter().enumerate() {
+        if index > 0 {
+            writer.write_all(b",").map_err(io_error)?;
+        }
+        writer.write_all(b"\n  ").map_err(io_error)?;
+
+        let object: serde_json::Map<String, serde_json::Value> = columns
+            .iter()
+            .cloned()
+            .zip(row.iter().cloned().chain(std::iter::repeat(serde_json::Value::Null)))
+            .collect();
+        serde_json::to_writer(&mut writer, &object)
+            .map_err(|e| format!("Failed to write row: {}", e))?;
+    }
+    writer.write_all(if rows.is_empty() { b"]\n" as &[u8] } else { b"\n]\n" }).map_err(io_error)?;
+
+    Ok(rows.len() as u64)
+}
+
+/// Text for one CSV/TSV field: strings as-is, NULL as empty, nested values as JSON
+fn field_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> (Vec<String>, Vec<Vec<serde_json::Value>>) {
+        let columns = vec!["id".to_string(), "note".to_string()];
+        let rows = vec![
+            vec![serde_json::json!(1), serde_json::json!("plain")],
+            vec![serde_json::json!(2), serde_json::json!("has, comma and \"quotes\"")],
+            vec![serde_json::json!(3), serde_json::Value::Null],
+        ];
+        (columns, rows)
+    }
+
+    #[test]
+    fn test_export_quotes_delimiters_and_blanks_nulls() {
+        let (columns, rows) = sample();
+        let path = std::env::temp_dir().join(format!("export-{}.csv", uuid::Uuid::new_v4()));
+
+        let summary = export_rows(&columns, &rows, ExportFormat::Csv, &path).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(written, "id,note\n1,plain\n2,\"has, comma and \"\"quotes\"\"\"\n3,\n");
+        assert_eq!(summary.rows_written, 3);
+        assert_eq!(summary.file_size, written.len() as u64);
+
+        export_rows(&columns, &rows, ExportFormat::Tsv, &path).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(written, "id\tnote\n1\tplain\n2\t\"has, comma and \"\"quotes\"\"\"\n3\t\n");
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_export_json_keeps_nulls() {
+        let (columns, rows) = sample();
+        let path = std::env::temp_dir().join(format!("export-{}.json", uuid::Uuid::new_v4()));
+
+        export_rows(&columns, &rows, ExportFormat::Json, &path).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(parsed[0], serde_json::json!({ "id": 1, "note": "plain" }));
+        assert_eq!(parsed[2]["note"], serde_json::Value::Null);
+
+        std::fs::remove_file(&path).ok();
+    }
+}
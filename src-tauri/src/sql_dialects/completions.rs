@@ -0,0 +1,183 @@
+// Schema-aware editor completions built from cached introspection data
+use super::dialect_functions;
+use super::keywords::dialect_keywords;
+use crate::database::DatabaseType;
+use serde::{Deserialize, Serialize};
+
+/// Default number of suggestions returned per request
+const DEFAULT_COMPLETION_LIMIT: usize = 50;
+
+/// What the editor knows about the cursor position
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompletionContext {
+    /// Text of the token being typed (may be empty)
+    pub partial_token: String,
+    /// Table referenced by the statement, optionally schema-qualified
+    pub table: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompletionKind {
+    Table,
+    View,
+    Column,
+    Function,
+    Keyword,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletionItem {
+    pub label: String,
+    pub kind: CompletionKind,
+    pub detail: Option<String>,
+    pub score: u32,
+}
+
+/// Rank suggestions for the partial token from the cached schema, keywords and functions
+pub fn complete(
+    context: &CompletionContext,
+    schema: Option<&serde_json::Value>,
+    database_type: Option<&DatabaseType>,
+) -> Vec<CompletionItem> {
+    let needle = context.partial_token.trim().to_lowercase();
+    let scope_table = context
+        .table
+        .as_deref()
+        .map(|table| table.rsplit('.').next().unwrap_or(table).to_lowercase());
+
+    let mut items = Vec::new();
+    let mut push = |label: &str, kind: CompletionKind, detail: Option<String>, bonus: u32| {
+        if let Some(score) = match_score(&needle, label) {
+            items.push(CompletionItem {
+                label: label.to_string(),
+                kind,
+                detail,
+                score: score + bonus,
+            });
+        }
+    };
+
+    if let Some(schema) = schema {
+        for (schema_name, table) in schema_tables(schema) {
+            let table_name = table["name"].as_str().unwrap_or_default();
+            let is_view = table["type"].as_str() == Some("view");
+            let kind = if is_view { CompletionKind::View } else { CompletionKind::Table };
+            push(table_name, kind, schema_name.map(|s| format!("{}.{}", s, table_name)), 10);
+
+            // Columns are only useful for the table in scope
+            let in_scope = scope_table.as_deref() == Some(table_name.to_lowercase().as_str());
+            if in_scope {
+                for column in table["columns"].as_array().into_iter().flatten() {
+                    if let Some(column_name) = column["name"].as_str() {
+                        let detail = column["data_type"].as_str().map(str::to_string);
+                        push(column_name, CompletionKind::Column, detail, 20);
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(database_type) = database_type {
+        for function in dialect_functions(database_type) {
+            push(function.name, CompletionKind::Function, Some(function.signature.to_string()), 5);
+        }
+    }
+
+    for keyword in dialect_keywords(database_type) {
+        push(keyword, CompletionKind::Keyword, None, 0);
+    }
+
+    items.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.label.cmp(&b.label)));
+    items.dedup_by(|a, b| a.label == b.label && a.kind == b.kind);
+    items.truncate(context.limit.unwrap_or(DEFAULT_COMPLETION_LIMIT));
+    items
+}
+
+/// Exact matches rank above prefix matches, which rank above substring matches
+fn match_score(needle: &str, label: &str) -> Option<u32> {
+    if needle.is_empty() {
+        return Some(10);
+    }
+    let label = label.to_lowercase();
+    if label == needle {
+        Some(100)
+    } else if label.starts_with(needle) {
+        Some(80)
+    } else if label.contains(needle) {
+        Some(40)
+    } else {
+        None
+    }
+}
+
+/// Every table/view object in a `get_database_schema` payload with its schema name
+fn schema_tables(schema: &serde_json::Value) -> Vec<(Option<&str>, &serde_json::Value)> {
+    let mut tables = Vec::new();
+    for database in schema["databases"].as_array().into_iter().flatten() {
+        for db_schema in database["schemas"].as_array().into_iter().flatten() {
+            let schema_name = db_schema["name"].as_str();
+            for key in ["tables", "views"] {
+                for table in db_schema[key].as_array().into_iter().flatten() {
+                    tables.push((schema_name, table));
+                }
+            }
+        }
+    }
+    tables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_schema() -> serde_json::Value {
+        serde_json::json!({
+            "databases": [{
+                "name": "northwind",
+                "schemas": [{
+                    "name": "public",
+                    "tables": [
+                        {"name": "orders", "type": "table", "columns": [
+                            {"name": "order_id", "data_type": "INTEGER"},
+                            {"name": "order_date", "data_type": "DATE"}
+                        ]},
+                        {"name": "customers", "type": "table", "columns": [
+                            {"name": "customer_id", "data_type": "VARCHAR"}
+                        ]}
+                    ]
+                }]
+            }]
+        })
+    }
+
+    #[test]
+    fn test_columns_of_table_in_scope_rank_first() {
+        let schema = sample_schema();
+        let context = CompletionContext {
+            partial_token: "ord".to_string(),
+            table: Some("public.orders".to_string()),
+            limit: None,
+        };
+
+        let items = complete(&context, Some(&schema), Some(&DatabaseType::PostgreSQL));
+        assert_eq!(items[0].kind, CompletionKind::Column);
+        assert!(items.iter().any(|i| i.label == "orders" && i.kind == CompletionKind::Table));
+        assert!(items.iter().any(|i| i.label == "ORDER BY" && i.kind == CompletionKind::Keyword));
+        // Columns of tables not in scope are not offered
+        assert!(!items.iter().any(|i| i.label == "customer_id"));
+    }
+
+    #[test]
+    fn test_completions_without_cached_schema() {
+        let context = CompletionContext {
+            partial_token: "strf".to_string(),
+            ..Default::default()
+        };
+
+        let items = complete(&context, None, Some(&DatabaseType::SQLite));
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].label, "strftime");
+        assert_eq!(items[0].kind, CompletionKind::Function);
+    }
+}
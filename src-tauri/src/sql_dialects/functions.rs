@@ -0,0 +1,158 @@
+// Curated built-in function tables; add entries here to extend completions and AI context
+use super::{FunctionCategory, SqlFunction};
+
+/// PostgreSQL built-in functions
+pub static POSTGRESQL_FUNCTIONS: &[SqlFunction] = &[
+    SqlFunction { name: "count", category: FunctionCategory::Aggregate, signature: "count(expression) -> bigint" },
+    SqlFunction { name: "sum", category: FunctionCategory::Aggregate, signature: "sum(expression) -> numeric" },
+    SqlFunction { name: "avg", category: FunctionCategory::Aggregate, signature: "avg(expression) -> numeric" },
+    SqlFunction { name: "min", category: FunctionCategory::Aggregate, signature: "min(expression) -> same as input" },
+    SqlFunction { name: "max", category: FunctionCategory::Aggregate, signature: "max(expression) -> same as input" },
+    SqlFunction { name: "coalesce", category: FunctionCategory::Conditional, signature: "coalesce(value, ...) -> same as input" },
+    SqlFunction { name: "nullif", category: FunctionCategory::Conditional, signature: "nullif(value1, value2) -> same as input" },
+    SqlFunction { name: "lower", category: FunctionCategory::String, signature: "lower(text) -> text" },
+    SqlFunction { name: "upper", category: FunctionCategory::String, signature: "upper(text) -> text" },
+    SqlFunction { name: "trim", category: FunctionCategory::String, signature: "trim(text) -> text" },
+    SqlFunction { name: "replace", category: FunctionCategory::String, signature: "replace(text, from, to) -> text" },
+    SqlFunction { name: "abs", category: FunctionCategory::Numeric, signature: "abs(number) -> number" },
+    SqlFunction { name: "round", category: FunctionCategory::Numeric, signature: "round(number, digits) -> number" },
+    SqlFunction { name: "string_agg", category: FunctionCategory::Aggregate, signature: "string_agg(text, delimiter) -> text" },
+    SqlFunction { name: "array_agg", category: FunctionCategory::Aggregate, signature: "array_agg(expression) -> array" },
+    SqlFunction { name: "bool_and", category: FunctionCategory::Aggregate, signature: "bool_and(boolean) -> boolean" },
+    SqlFunction { name: "bool_or", category: FunctionCategory::Aggregate, signature: "bool_or(boolean) -> boolean" },
+    SqlFunction { name: "length", category: FunctionCategory::String, signature: "length(text) -> integer" },
+    SqlFunction { name: "substring", category: FunctionCategory::String, signature: "substring(text from start for count) -> text" },
+    SqlFunction { name: "concat", category: FunctionCategory::String, signature: "concat(value, ...) -> text" },
+    SqlFunction { name: "concat_ws", category: FunctionCategory::String, signature: "concat_ws(separator, value, ...) -> text" },
+    SqlFunction { name: "split_part", category: FunctionCategory::String, signature: "split_part(text, delimiter, field) -> text" },
+    SqlFunction { name: "regexp_replace", category: FunctionCategory::String, signature: "regexp_replace(text, pattern, replacement [, flags]) -> text" },
+    SqlFunction { name: "left", category: FunctionCategory::String, signature: "left(text, n) -> text" },
+    SqlFunction { name: "right", category: FunctionCategory::String, signature: "right(text, n) -> text" },
+    SqlFunction { name: "position", category: FunctionCategory::String, signature: "position(substring in text) -> integer" },
+    SqlFunction { name: "ceil", category: FunctionCategory::Numeric, signature: "ceil(numeric) -> numeric" },
+    SqlFunction { name: "floor", category: FunctionCategory::Numeric, signature: "floor(numeric) -> numeric" },
+    SqlFunction { name: "mod", category: FunctionCategory::Numeric, signature: "mod(y, x) -> numeric" },
+    SqlFunction { name: "power", category: FunctionCategory::Numeric, signature: "power(a, b) -> double precision" },
+    SqlFunction { name: "random", category: FunctionCategory::Numeric, signature: "random() -> double precision" },
+    SqlFunction { name: "now", category: FunctionCategory::DateTime, signature: "now() -> timestamp with time zone" },
+    SqlFunction { name: "current_date", category: FunctionCategory::DateTime, signature: "current_date -> date" },
+    SqlFunction { name: "date_trunc", category: FunctionCategory::DateTime, signature: "date_trunc(field, timestamp) -> timestamp" },
+    SqlFunction { name: "extract", category: FunctionCategory::DateTime, signature: "extract(field from timestamp) -> numeric" },
+    SqlFunction { name: "age", category: FunctionCategory::DateTime, signature: "age(timestamp, timestamp) -> interval" },
+    SqlFunction { name: "to_char", category: FunctionCategory::Conversion, signature: "to_char(value, format) -> text" },
+    SqlFunction { name: "to_date", category: FunctionCategory::Conversion, signature: "to_date(text, format) -> date" },
+    SqlFunction { name: "to_timestamp", category: FunctionCategory::Conversion, signature: "to_timestamp(text, format) -> timestamp with time zone" },
+    SqlFunction { name: "cast", category: FunctionCategory::Conversion, signature: "cast(value as type) -> type" },
+    SqlFunction { name: "jsonb_build_object", category: FunctionCategory::Json, signature: "jsonb_build_object(key, value, ...) -> jsonb" },
+    SqlFunction { name: "jsonb_agg", category: FunctionCategory::Aggregate, signature: "jsonb_agg(expression) -> jsonb" },
+    SqlFunction { name: "jsonb_extract_path_text", category: FunctionCategory::Json, signature: "jsonb_extract_path_text(jsonb, path, ...) -> text" },
+    SqlFunction { name: "json_array_length", category: FunctionCategory::Json, signature: "json_array_length(json) -> integer" },
+    SqlFunction { name: "greatest", category: FunctionCategory::Conditional, signature: "greatest(value, ...) -> same as input" },
+    SqlFunction { name: "least", category: FunctionCategory::Conditional, signature: "least(value, ...) -> same as input" },
+    SqlFunction { name: "row_number", category: FunctionCategory::Window, signature: "row_number() over (...) -> bigint" },
+    SqlFunction { name: "rank", category: FunctionCategory::Window, signature: "rank() over (...) -> bigint" },
+    SqlFunction { name: "dense_rank", category: FunctionCategory::Window, signature: "dense_rank() over (...) -> bigint" },
+    SqlFunction { name: "lag", category: FunctionCategory::Window, signature: "lag(value [, offset [, default]]) over (...) -> same as input" },
+    SqlFunction { name: "lead", category: FunctionCategory::Window, signature: "lead(value [, offset [, default]]) over (...) -> same as input" },
+    SqlFunction { name: "current_user", category: FunctionCategory::System, signature: "current_user -> name" },
+    SqlFunction { name: "version", category: FunctionCategory::System, signature: "version() -> text" },
+    SqlFunction { name: "gen_random_uuid", category: FunctionCategory::System, signature: "gen_random_uuid() -> uuid" },
+];
+
+/// MySQL built-in functions
+pub static MYSQL_FUNCTIONS: &[SqlFunction] = &[
+    SqlFunction { name: "count", category: FunctionCategory::Aggregate, signature: "count(expression) -> bigint" },
+    SqlFunction { name: "sum", category: FunctionCategory::Aggregate, signature: "sum(expression) -> numeric" },
+    SqlFunction { name: "avg", category: FunctionCategory::Aggregate, signature: "avg(expression) -> numeric" },
+    SqlFunction { name: "min", category: FunctionCategory::Aggregate, signature: "min(expression) -> same as input" },
+    SqlFunction { name: "max", category: FunctionCategory::Aggregate, signature: "max(expression) -> same as input" },
+    SqlFunction { name: "coalesce", category: FunctionCategory::Conditional, signature: "coalesce(value, ...) -> same as input" },
+    SqlFunction { name: "nullif", category: FunctionCategory::Conditional, signature: "nullif(value1, value2) -> same as input" },
+    SqlFunction { name: "lower", category: FunctionCategory::String, signature: "lower(text) -> text" },
+    SqlFunction { name: "upper", category: FunctionCategory::String, signature: "upper(text) -> text" },
+    SqlFunction { name: "trim", category: FunctionCategory::String, signature: "trim(text) -> text" },
+    SqlFunction { name: "replace", category: FunctionCategory::String, signature: "replace(text, from, to) -> text" },
+    SqlFunction { name: "abs", category: FunctionCategory::Numeric, signature: "abs(number) -> number" },
+    SqlFunction { name: "round", category: FunctionCategory::Numeric, signature: "round(number, digits) -> number" },
+    SqlFunction { name: "group_concat", category: FunctionCategory::Aggregate, signature: "group_concat(expression [ORDER BY ...] [SEPARATOR str]) -> text" },
+    SqlFunction { name: "json_arrayagg", category: FunctionCategory::Aggregate, signature: "json_arrayagg(expression) -> json" },
+    SqlFunction { name: "char_length", category: FunctionCategory::String, signature: "char_length(str) -> int" },
+    SqlFunction { name: "substring", category: FunctionCategory::String, signature: "substring(str, pos [, len]) -> varchar" },
+    SqlFunction { name: "concat", category: FunctionCategory::String, signature: "concat(str, ...) -> varchar" },
+    SqlFunction { name: "concat_ws", category: FunctionCategory::String, signature: "concat_ws(separator, str, ...) -> varchar" },
+    SqlFunction { name: "substring_index", category: FunctionCategory::String, signature: "substring_index(str, delim, count) -> varchar" },
+    SqlFunction { name: "locate", category: FunctionCategory::String, signature: "locate(substr, str [, pos]) -> int" },
+    SqlFunction { name: "lpad", category: FunctionCategory::String, signature: "lpad(str, len, padstr) -> varchar" },
+    SqlFunction { name: "regexp_replace", category: FunctionCategory::String, signature: "regexp_replace(expr, pattern, repl) -> varchar" },
+    SqlFunction { name: "ceiling", category: FunctionCategory::Numeric, signature: "ceiling(x) -> number" },
+    SqlFunction { name: "floor", category: FunctionCategory::Numeric, signature: "floor(x) -> number" },
+    SqlFunction { name: "mod", category: FunctionCategory::Numeric, signature: "mod(n, m) -> number" },
+    SqlFunction { name: "pow", category: FunctionCategory::Numeric, signature: "pow(x, y) -> double" },
+    SqlFunction { name: "rand", category: FunctionCategory::Numeric, signature: "rand([seed]) -> double" },
+    SqlFunction { name: "now", category: FunctionCategory::DateTime, signature: "now() -> datetime" },
+    SqlFunction { name: "curdate", category: FunctionCategory::DateTime, signature: "curdate() -> date" },
+    SqlFunction { name: "date_format", category: FunctionCategory::DateTime, signature: "date_format(date, format) -> varchar" },
+    SqlFunction { name: "date_add", category: FunctionCategory::DateTime, signature: "date_add(date, INTERVAL expr unit) -> datetime" },
+    SqlFunction { name: "datediff", category: FunctionCategory::DateTime, signature: "datediff(expr1, expr2) -> int" },
+    SqlFunction { name: "timestampdiff", category: FunctionCategory::DateTime, signature: "timestampdiff(unit, datetime1, datetime2) -> int" },
+    SqlFunction { name: "str_to_date", category: FunctionCategory::Conversion, signature: "str_to_date(str, format) -> datetime" },
+    SqlFunction { name: "cast", category: FunctionCategory::Conversion, signature: "cast(expr as type) -> type" },
+    SqlFunction { name: "convert", category: FunctionCategory::Conversion, signature: "convert(expr, type) -> type" },
+    SqlFunction { name: "json_extract", category: FunctionCategory::Json, signature: "json_extract(json_doc, path, ...) -> json" },
+    SqlFunction { name: "json_object", category: FunctionCategory::Json, signature: "json_object(key, val, ...) -> json" },
+    SqlFunction { name: "json_unquote", category: FunctionCategory::Json, signature: "json_unquote(json_val) -> varchar" },
+    SqlFunction { name: "if", category: FunctionCategory::Conditional, signature: "if(condition, then_value, else_value) -> value" },
+    SqlFunction { name: "ifnull", category: FunctionCategory::Conditional, signature: "ifnull(expr1, expr2) -> value" },
+    SqlFunction { name: "row_number", category: FunctionCategory::Window, signature: "row_number() over (...) -> bigint" },
+    SqlFunction { name: "rank", category: FunctionCategory::Window, signature: "rank() over (...) -> bigint" },
+    SqlFunction { name: "lag", category: FunctionCategory::Window, signature: "lag(expr [, n [, default]]) over (...) -> value" },
+    SqlFunction { name: "database", category: FunctionCategory::System, signature: "database() -> varchar" },
+    SqlFunction { name: "last_insert_id", category: FunctionCategory::System, signature: "last_insert_id() -> bigint" },
+    SqlFunction { name: "uuid", category: FunctionCategory::System, signature: "uuid() -> varchar" },
+];
+
+/// SQLite built-in functions
+pub static SQLITE_FUNCTIONS: &[SqlFunction] = &[
+    SqlFunction { name: "count", category: FunctionCategory::Aggregate, signature: "count(expression) -> bigint" },
+    SqlFunction { name: "sum", category: FunctionCategory::Aggregate, signature: "sum(expression) -> numeric" },
+    SqlFunction { name: "avg", category: FunctionCategory::Aggregate, signature: "avg(expression) -> numeric" },
+    SqlFunction { name: "min", category: FunctionCategory::Aggregate, signature: "min(expression) -> same as input" },
+    SqlFunction { name: "max", category: FunctionCategory::Aggregate, signature: "max(expression) -> same as input" },
+    SqlFunction { name: "coalesce", category: FunctionCategory::Conditional, signature: "coalesce(value, ...) -> same as input" },
+    SqlFunction { name: "nullif", category: FunctionCategory::Conditional, signature: "nullif(value1, value2) -> same as input" },
+    SqlFunction { name: "lower", category: FunctionCategory::String, signature: "lower(text) -> text" },
+    SqlFunction { name: "upper", category: FunctionCategory::String, signature: "upper(text) -> text" },
+    SqlFunction { name: "trim", category: FunctionCategory::String, signature: "trim(text) -> text" },
+    SqlFunction { name: "replace", category: FunctionCategory::String, signature: "replace(text, from, to) -> text" },
+    SqlFunction { name: "abs", category: FunctionCategory::Numeric, signature: "abs(number) -> number" },
+    SqlFunction { name: "round", category: FunctionCategory::Numeric, signature: "round(number, digits) -> number" },
+    SqlFunction { name: "group_concat", category: FunctionCategory::Aggregate, signature: "group_concat(X [, separator]) -> text" },
+    SqlFunction { name: "total", category: FunctionCategory::Aggregate, signature: "total(X) -> real" },
+    SqlFunction { name: "length", category: FunctionCategory::String, signature: "length(X) -> integer" },
+    SqlFunction { name: "substr", category: FunctionCategory::String, signature: "substr(X, start [, length]) -> text" },
+    SqlFunction { name: "instr", category: FunctionCategory::String, signature: "instr(X, Y) -> integer" },
+    SqlFunction { name: "ltrim", category: FunctionCategory::String, signature: "ltrim(X [, Y]) -> text" },
+    SqlFunction { name: "rtrim", category: FunctionCategory::String, signature: "rtrim(X [, Y]) -> text" },
+    SqlFunction { name: "printf", category: FunctionCategory::String, signature: "printf(format, ...) -> text" },
+    SqlFunction { name: "hex", category: FunctionCategory::String, signature: "hex(X) -> text" },
+    SqlFunction { name: "random", category: FunctionCategory::Numeric, signature: "random() -> integer" },
+    SqlFunction { name: "date", category: FunctionCategory::DateTime, signature: "date(time_value, modifier, ...) -> text" },
+    SqlFunction { name: "time", category: FunctionCategory::DateTime, signature: "time(time_value, modifier, ...) -> text" },
+    SqlFunction { name: "datetime", category: FunctionCategory::DateTime, signature: "datetime(time_value, modifier, ...) -> text" },
+    SqlFunction { name: "julianday", category: FunctionCategory::DateTime, signature: "julianday(time_value, modifier, ...) -> real" },
+    SqlFunction { name: "strftime", category: FunctionCategory::DateTime, signature: "strftime(format, time_value, modifier, ...) -> text" },
+    SqlFunction { name: "cast", category: FunctionCategory::Conversion, signature: "cast(expr as type) -> type" },
+    SqlFunction { name: "typeof", category: FunctionCategory::Conversion, signature: "typeof(X) -> text" },
+    SqlFunction { name: "json_extract", category: FunctionCategory::Json, signature: "json_extract(X, path, ...) -> value" },
+    SqlFunction { name: "json_object", category: FunctionCategory::Json, signature: "json_object(label, value, ...) -> text" },
+    SqlFunction { name: "json_array_length", category: FunctionCategory::Json, signature: "json_array_length(X [, path]) -> integer" },
+    SqlFunction { name: "json_group_array", category: FunctionCategory::Aggregate, signature: "json_group_array(X) -> text" },
+    SqlFunction { name: "ifnull", category: FunctionCategory::Conditional, signature: "ifnull(X, Y) -> value" },
+    SqlFunction { name: "iif", category: FunctionCategory::Conditional, signature: "iif(condition, X, Y) -> value" },
+    SqlFunction { name: "row_number", category: FunctionCategory::Window, signature: "row_number() over (...) -> integer" },
+    SqlFunction { name: "rank", category: FunctionCategory::Window, signature: "rank() over (...) -> integer" },
+    SqlFunction { name: "lag", category: FunctionCategory::Window, signature: "lag(expr [, offset [, default]]) over (...) -> value" },
+    SqlFunction { name: "last_insert_rowid", category: FunctionCategory::System, signature: "last_insert_rowid() -> integer" },
+    SqlFunction { name: "changes", category: FunctionCategory::System, signature: "changes() -> integer" },
+    SqlFunction { name: "sqlite_version", category: FunctionCategory::System, signature: "sqlite_version() -> text" },
+];
@@ -0,0 +1,26 @@
+// Reserved words offered as completions; dialect-specific entries extend the common list
+use crate::database::DatabaseType;
+
+static COMMON_KEYWORDS: &[&str] = &[
+    "SELECT", "FROM", "WHERE", "AND", "OR", "NOT", "IN", "EXISTS", "BETWEEN", "LIKE", "IS", "NULL",
+    "AS", "DISTINCT", "JOIN", "INNER", "LEFT", "RIGHT", "FULL", "OUTER", "CROSS", "ON", "USING",
+    "GROUP BY", "HAVING", "ORDER BY", "ASC", "DESC", "LIMIT", "OFFSET", "UNION", "UNION ALL",
+    "INTERSECT", "EXCEPT", "CASE", "WHEN", "THEN", "ELSE", "END", "WITH", "INSERT INTO", "VALUES",
+    "UPDATE", "SET", "DELETE FROM", "CREATE TABLE", "CREATE VIEW", "CREATE INDEX", "ALTER TABLE",
+    "DROP TABLE", "PRIMARY KEY", "FOREIGN KEY", "REFERENCES", "DEFAULT", "BEGIN", "COMMIT", "ROLLBACK",
+];
+
+static POSTGRESQL_KEYWORDS: &[&str] = &["ILIKE", "RETURNING", "LATERAL", "FETCH FIRST", "ON CONFLICT", "DISTINCT ON"];
+static MYSQL_KEYWORDS: &[&str] = &["REPLACE INTO", "ON DUPLICATE KEY UPDATE", "STRAIGHT_JOIN", "SHOW TABLES"];
+static SQLITE_KEYWORDS: &[&str] = &["PRAGMA", "INSERT OR REPLACE", "INSERT OR IGNORE", "WITHOUT ROWID"];
+
+/// Keywords for a database type, common ones first
+pub fn dialect_keywords(database_type: Option<&DatabaseType>) -> impl Iterator<Item = &'static str> {
+    let specific: &'static [&'static str] = match database_type {
+        Some(DatabaseType::PostgreSQL) => POSTGRESQL_KEYWORDS,
+        Some(DatabaseType::MySQL) => MYSQL_KEYWORDS,
+        Some(DatabaseType::SQLite) => SQLITE_KEYWORDS,
+        _ => &[],
+    };
+    COMMON_KEYWORDS.iter().chain(specific.iter()).copied()
+}
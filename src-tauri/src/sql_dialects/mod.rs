@@ -0,0 +1,42 @@
+// SQL Dialects Module
+// Static, data-driven knowledge about each supported SQL dialect
+
+pub mod completions;
+pub mod functions;
+pub mod keywords;
+pub mod validation;
+
+use crate::database::DatabaseType;
+use serde::Serialize;
+
+/// Broad grouping for built-in functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum FunctionCategory {
+    Aggregate,
+    String,
+    Numeric,
+    DateTime,
+    Json,
+    Conditional,
+    Conversion,
+    Window,
+    System,
+}
+
+/// A built-in function available in a dialect
+#[derive(Debug, Clone, Serialize)]
+pub struct SqlFunction {
+    pub name: &'static str,
+    pub category: FunctionCategory,
+    pub signature: &'static str,
+}
+
+/// Built-in functions for a database type (empty for dialects without a curated table yet)
+pub fn dialect_functions(database_type: &DatabaseType) -> &'static [SqlFunction] {
+    match database_type {
+        DatabaseType::PostgreSQL => functions::POSTGRESQL_FUNCTIONS,
+        DatabaseType::MySQL => functions::MYSQL_FUNCTIONS,
+        DatabaseType::SQLite => functions::SQLITE_FUNCTIONS,
+        DatabaseType::SqlServer | DatabaseType::Oracle => &[],
+    }
+}
@@ -0,0 +1,157 @@
+// Editor diagnostics from actually parsing the SQL in the selected dialect
+use crate::database::policy::dialect_for;
+use crate::database::DatabaseType;
+use serde::Serialize;
+use sqlparser::ast::{Expr, SelectItem, SetExpr, Statement};
+use sqlparser::dialect::GenericDialect;
+use sqlparser::parser::{Parser, ParserError};
+
+/// A problem in the SQL text, positioned for the editor (1-based line and column)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SqlDiagnostic {
+    pub line: u64,
+    pub column: u64,
+    pub length: usize,
+    pub message: String,
+    pub severity: &'static str,
+}
+
+/// Errors and warnings for `sql`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SqlValidation {
+    pub errors: Vec<SqlDiagnostic>,
+    pub warnings: Vec<SqlDiagnostic>,
+}
+
+/// Map an editor dialect name such as "postgresql" to a database type; unknown names parse generically
+pub fn dialect_database_type(dialect: &str) -> Option<DatabaseType> {
+    match dialect.to_lowercase().as_str() {
+        "postgresql" | "postgres" => Some(DatabaseType::PostgreSQL),
+        "mysql" => Some(DatabaseType::MySQL),
+        "sqlite" => Some(DatabaseType::SQLite),
+        "sqlserver" | "mssql" | "tsql" => Some(DatabaseType::SqlServer),
+        _ => None,
+    }
+}
+
+/// Parse `sql` and report the first syntax error, or warnings about statements that parse
+/// but are unlikely to run
+pub fn validate(sql: &str, database_type: Option<&DatabaseType>) -> SqlValidation {
+    let parsed = match database_type {
+        Some(database_type) => Parser::parse_sql(dialect_for(database_type).as_ref(), sql),
+        None => Parser::parse_sql(&GenericDialect {}, sql),
+    };
+
+    match parsed {
+        Ok(statements) => SqlValidation {
+            errors: Vec::new(),
+            warnings: statements.iter().filter_map(select_without_from).collect(),
+        },
+        Err(e) => SqlValidation {
+            errors: vec![syntax_error(sql, &e)],
+            warnings: Vec::new(),
+        },
+    }
+}
+
+/// Turn a parser error into a diagnostic. sqlparser reports positions only inside the
+/// message, as a trailing " at Line: L, Column C".
+fn syntax_error(sql: &str, error: &ParserError) -> SqlDiagnostic {
+    let text = match error {
+        ParserError::TokenizerError(text) | ParserError::ParserError(text) => text.clone(),
+        ParserError::RecursionLimitExceeded => "Statement is nested too deeply".to_string(),
+    };
+
+    let (message, position) = match text.rfind(" at Line: ") {
+        Some(index) => (text[..index].to_string(), parse_location(&text[index + " at Line: ".len()..])),
+        None => (text, None),
+    };
+
+    // The offending token, when the parser names it ("..., found: SELCT")
+    let found = message
+        .rsplit_once("found: ")
+        .map(|(_, token)| token.trim())
+        .filter(|token| !token.is_empty() && *token != "EOF");
+
+    let (line, column) = position.unwrap_or_else(|| match found {
+        Some(_) => (1, 1),
+        // Input ended early: point at the end of the text
+        None => end_of(sql),
+    });
+
+    SqlDiagnostic {
+        line,
+        column,
+        length: found.map_or(1, |token| token.chars().count()),
+        message,
+        severity: "error",
+    }
+}
+
+/// Parse "L, Column C"
+fn parse_location(location: &str) -> Option<(u64, u64)> {
+    let (line, column) = location.split_once(", Column ")?;
+    let line = line.trim().parse().ok()?;
+    let column = column.trim().parse().ok()?;
+    (line > 0 && column > 0).then_some((line, column))
+}
+
+/// Line and column just past the last character
+fn end_of(sql: &str) -> (u64, u64) {
+    let trimmed = sql.trim_end();
+    let line = trimmed.lines().count().max(1) as u64;
+    let column = trimmed.lines().last().map_or(0, |last| last.chars().count()) as u64 + 1;
+    (line, column)
+}
+
+/// A SELECT with no FROM that still names columns will fail once it runs
+fn select_without_from(statement: &Statement) -> Option<SqlDiagnostic> {
+    let Statement::Query(query) = statement else { return None };
+    let SetExpr::Select(select) = query.body.as_ref() else { return None };
+    if !select.from.is_empty() {
+        return None;
+    }
+
+    let names_columns = select.projection.iter().any(|item| match item {
+        SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => {
+            matches!(expr, Expr::Identifier(_) | Expr::CompoundIdentifier(_))
+        }
+        SelectItem::Wildcard(_) | SelectItem::QualifiedWildcard(..) => true,
+    });
+
+    names_columns.then(|| SqlDiagnostic {
+        line: 1,
+        column: 1,
+        length: 6,
+        message: "SELECT statement without FROM clause".to_string(),
+        severity: "warning",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_misspelled_keyword_is_reported_with_position() {
+        let result = validate("SELECT id FROM users;\nSELCT * FROM orders", Some(&DatabaseType::PostgreSQL));
+
+        assert_eq!(result.errors.len(), 1);
+        let error = &result.errors[0];
+        assert_eq!((error.line, error.column), (2, 1));
+        assert_eq!(error.length, 5);
+        assert!(error.message.contains("SELCT"));
+    }
+
+    #[test]
+    fn test_valid_sql_only_warns_about_missing_from() {
+        assert!(validate("SELECT 1", Some(&DatabaseType::SQLite)).warnings.is_empty());
+
+        let result = validate("SELECT name", None);
+        assert!(result.errors.is_empty());
+        assert_eq!(result.warnings.len(), 1);
+
+        let truncated = validate("SELECT * FROM", Some(&DatabaseType::MySQL));
+        assert_eq!(truncated.errors.len(), 1);
+    }
+}
@@ -4,5 +4,8 @@
 pub mod template_manager;
 pub mod types;
 
+#[cfg(test)]
+mod tests;
+
 pub use template_manager::TemplateManager;
 pub use types::*;
@@ -3,21 +3,96 @@
 // Zeus Directive: Clean separation from AI and connection management systems
 
 use std::sync::{Arc, Mutex};
-use rusqlite::{Connection, params, Row};
+use std::time::Duration;
+use rusqlite::{Connection, ErrorCode, OptionalExtension, params, Row};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 use log::{info, warn, error};
 
+use crate::database::drivers::DatabaseDriverFactory;
+use crate::database::DatabaseType;
 use crate::template_engine::types::*;
 
 pub struct TemplateManager {
     db_connection: Arc<Mutex<Connection>>,
 }
 
+/// Default time SQLite waits on a lock held by another connection before returning BUSY
+pub const DEFAULT_BUSY_TIMEOUT: Duration = Duration::from_millis(5000);
+
+/// User-facing limit on a parameter value, in characters
+const MAX_PARAMETER_VALUE_CHARS: usize = 1000;
+/// Byte-size guard checked before counting characters, so huge inputs are rejected cheaply.
+/// Four bytes per character admits any value within the character limit.
+const MAX_PARAMETER_VALUE_BYTES: usize = MAX_PARAMETER_VALUE_CHARS * 4;
+
+/// Templates returned by `get_templates` when the filter sets no `limit`
+pub const DEFAULT_TEMPLATE_PAGE_SIZE: u32 = 200;
+
+/// A schema migration, run inside the transaction that records its version
+type Migration = fn(&Connection) -> Result<(), String>;
+
+/// Schema migrations in order; migration N brings the database to `user_version` N.
+/// Append new migrations here and never edit one that has shipped.
+const MIGRATIONS: &[(&str, Migration)] = &[
+    ("initial template schema", TemplateManager::migrate_initial_schema),
+    ("query history", TemplateManager::migrate_query_history),
+    ("template full-text index", TemplateManager::migrate_template_search),
+    ("template trash", TemplateManager::migrate_template_trash),
+    ("template parameter kinds", TemplateManager::migrate_parameter_kinds),
+];
+
+/// Schema version of a fully migrated template database
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Entries returned by `get_query_history` when the caller sets no limit
+pub const DEFAULT_QUERY_HISTORY_LIMIT: u32 = 100;
+const MAX_QUERY_HISTORY_LIMIT: u32 = 1000;
+
+/// Marks an unfilled placeholder in `preview_template` output, as in `[[missing:start_date]]`
+pub const PREVIEW_MISSING_PREFIX: &str = "[[missing:";
+pub const PREVIEW_MISSING_SUFFIX: &str = "]]";
+
+/// How many times a locked database is retried before falling back
+const LOCK_RETRY_ATTEMPTS: u32 = 5;
+const LOCK_RETRY_DELAY_MS: u64 = 200;
+
+/// Why opening the template database failed
+enum OpenFailure {
+    /// Another process holds a lock; worth retrying
+    Locked(String),
+    /// The file is not a usable SQLite database
+    Corrupt(String),
+    Other(String),
+}
+
+impl From<rusqlite::Error> for OpenFailure {
+    fn from(error: rusqlite::Error) -> Self {
+        match error.sqlite_error_code() {
+            Some(ErrorCode::DatabaseBusy) | Some(ErrorCode::DatabaseLocked) => OpenFailure::Locked(error.to_string()),
+            Some(ErrorCode::DatabaseCorrupt) | Some(ErrorCode::NotADatabase) => OpenFailure::Corrupt(error.to_string()),
+            _ => OpenFailure::Other(error.to_string()),
+        }
+    }
+}
+
+/// Apply the pragmas every rusqlite connection needs: wait on locks instead of failing
+/// immediately, allow concurrent readers via WAL, and enforce `ON DELETE CASCADE`
+pub(crate) fn configure_connection(conn: &Connection, busy_timeout: Duration) -> rusqlite::Result<()> {
+    conn.busy_timeout(busy_timeout)?;
+    // journal_mode reports the resulting mode as a row, so it can't go through execute
+    conn.query_row("PRAGMA journal_mode = WAL", [], |row| row.get::<_, String>(0))?;
+    conn.execute_batch("PRAGMA foreign_keys = ON")?;
+    Ok(())
+}
+
 impl TemplateManager {
     pub fn new(db_path: &str) -> Result<Self, String> {
-        let conn = Connection::open(db_path)
-            .map_err(|e| format!("Failed to open template database: {}", e))?;
+        Self::new_with_busy_timeout(db_path, DEFAULT_BUSY_TIMEOUT)
+    }
+
+    pub fn new_with_busy_timeout(db_path: &str, busy_timeout: Duration) -> Result<Self, String> {
+        let conn = Self::open_database(db_path, busy_timeout)?;
         
         let manager = Self {
             db_connection: Arc::new(Mutex::new(conn)),
@@ -30,10 +105,143 @@ impl TemplateManager {
         Ok(manager)
     }
 
+    /// Open the database, retrying while it is locked and rebuilding it if it is corrupt,
+    /// so a damaged template store never stops the app from starting
+    fn open_database(db_path: &str, busy_timeout: Duration) -> Result<Connection, String> {
+        let mut attempt = 0;
+        loop {
+            match Self::open_and_check(db_path, busy_timeout) {
+                Ok(conn) => return Ok(conn),
+                Err(OpenFailure::Locked(e)) if attempt < LOCK_RETRY_ATTEMPTS => {
+                    attempt += 1;
+                    warn!("Template database is locked (attempt {}/{}): {}", attempt, LOCK_RETRY_ATTEMPTS, e);
+                    std::thread::sleep(Duration::from_millis(LOCK_RETRY_DELAY_MS * attempt as u64));
+                }
+                Err(OpenFailure::Locked(e)) => {
+                    error!("Template database still locked after {} attempts, using a temporary in-memory store: {}", LOCK_RETRY_ATTEMPTS, e);
+                    let conn = Connection::open_in_memory()
+                        .map_err(|e| format!("Failed to open in-memory template database: {}", e))?;
+                    configure_connection(&conn, busy_timeout)
+                        .map_err(|e| format!("Failed to configure in-memory template database: {}", e))?;
+                    return Ok(conn);
+                }
+                Err(OpenFailure::Corrupt(e)) => {
+                    let backup_path = Self::backup_corrupt_database(db_path)?;
+                    warn!("Template database is corrupt ({}); moved it to {} and created a fresh one", e, backup_path);
+                    return Self::open_and_check(db_path, busy_timeout).map_err(|failure| match failure {
+                        OpenFailure::Locked(e) | OpenFailure::Corrupt(e) | OpenFailure::Other(e) => {
+                            format!("Failed to recreate template database: {}", e)
+                        }
+                    });
+                }
+                Err(OpenFailure::Other(e)) => return Err(format!("Failed to open template database: {}", e)),
+            }
+        }
+    }
+
+    fn open_and_check(db_path: &str, busy_timeout: Duration) -> Result<Connection, OpenFailure> {
+        let conn = Connection::open(db_path)?;
+        configure_connection(&conn, busy_timeout)?;
+
+        // Flush a WAL left behind by a crashed instance; fails with BUSY if another instance is live
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+
+        let integrity: String = conn.query_row("PRAGMA quick_check", [], |row| row.get(0))?;
+        if integrity != "ok" {
+            return Err(OpenFailure::Corrupt(integrity));
+        }
+
+        Ok(conn)
+    }
+
+    /// Move a corrupt database (and its WAL/SHM side files) out of the way, returning the backup path
+    fn backup_corrupt_database(db_path: &str) -> Result<String, String> {
+        let backup_path = format!("{}.corrupt-{}", db_path, Utc::now().format("%Y%m%d%H%M%S"));
+        std::fs::rename(db_path, &backup_path)
+            .map_err(|e| format!("Failed to back up corrupt template database: {}", e))?;
+
+        for suffix in ["-wal", "-shm"] {
+            let side_file = format!("{}{}", db_path, suffix);
+            if std::path::Path::new(&side_file).exists() {
+                let _ = std::fs::rename(&side_file, format!("{}{}", backup_path, suffix));
+            }
+        }
+
+        Ok(backup_path)
+    }
+
     fn initialize_schema(&self) -> Result<(), String> {
         let conn = self.db_connection.lock()
             .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
 
+        Self::run_migrations(&conn)?;
+
+        // Create default "General" category if it doesn't exist
+        self.ensure_default_category(&conn)?;
+
+        info!("Template database schema initialized successfully");
+        Ok(())
+    }
+
+    /// Bring the database up to `SCHEMA_VERSION`, one transaction per migration,
+    /// tracking progress in SQLite's `user_version` pragma
+    fn run_migrations(conn: &Connection) -> Result<(), String> {
+        let current: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to read template schema version: {}", e))?;
+
+        if current > SCHEMA_VERSION {
+            return Err(format!(
+                "Template database schema version {} is newer than this version of the app supports ({})",
+                current, SCHEMA_VERSION
+            ));
+        }
+
+        if current == SCHEMA_VERSION {
+            return Ok(());
+        }
+
+        // A migration that rebuilds a table must not set off its ON DELETE CASCADE. The pragma
+        // can't change inside a transaction, so it is off for all of them and each migration
+        // checks the keys itself before committing.
+        conn.execute_batch("PRAGMA foreign_keys = OFF")
+            .map_err(|e| format!("Failed to disable foreign keys for migration: {}", e))?;
+        let migrated = Self::run_pending_migrations(conn, current);
+        conn.execute_batch("PRAGMA foreign_keys = ON")
+            .map_err(|e| format!("Failed to re-enable foreign keys after migration: {}", e))?;
+        migrated
+    }
+
+    fn run_pending_migrations(conn: &Connection, current: u32) -> Result<(), String> {
+        for (index, (description, migrate)) in MIGRATIONS.iter().enumerate().skip(current as usize) {
+            let version = index + 1;
+            let tx = conn.unchecked_transaction()
+                .map_err(|e| format!("Failed to begin migration {}: {}", version, e))?;
+
+            migrate(&tx).map_err(|e| format!("Template schema migration {} ({}) failed: {}", version, description, e))?;
+
+            let dangling: i64 = tx.query_row("SELECT COUNT(*) FROM pragma_foreign_key_check", [], |row| row.get(0))
+                .map_err(|e| format!("Failed to check foreign keys after migration {}: {}", version, e))?;
+            if dangling > 0 {
+                return Err(format!(
+                    "Template schema migration {} ({}) left {} rows with dangling references",
+                    version, description, dangling
+                ));
+            }
+
+            // user_version is part of the database header, so it commits with the migration
+            tx.execute_batch(&format!("PRAGMA user_version = {}", version))
+                .map_err(|e| format!("Failed to record schema version {}: {}", version, e))?;
+            tx.commit().map_err(|e| format!("Failed to commit migration {}: {}", version, e))?;
+
+            info!("Migrated template database to schema version {} ({})", version, description);
+        }
+
+        Ok(())
+    }
+
+    /// Migration 1: the schema as it stood before versioning. Every statement tolerates
+    /// existing objects, so unversioned databases from earlier releases converge on it too.
+    fn migrate_initial_schema(conn: &Connection) -> Result<(), String> {
         // Create template categories table
         conn.execute(
             "CREATE TABLE IF NOT EXISTS template_categories (
@@ -72,11 +280,28 @@ impl TemplateManager {
                 name TEXT NOT NULL,
                 default_value TEXT,
                 description TEXT,
+                ordinal INTEGER NOT NULL DEFAULT 0,
                 FOREIGN KEY (template_id) REFERENCES templates(id) ON DELETE CASCADE
             )",
             [],
         ).map_err(|e| format!("Failed to create template_parameters table: {}", e))?;
 
+        // Databases created before parameters were ordered
+        Self::add_column_if_missing(conn, "template_parameters", "ordinal", "INTEGER NOT NULL DEFAULT 0")?;
+
+        // Create template usage history table
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS template_usage (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                template_id TEXT NOT NULL,
+                used_at TEXT NOT NULL,
+                connection_id TEXT,
+                execution_success INTEGER,
+                FOREIGN KEY (template_id) REFERENCES templates(id) ON DELETE CASCADE
+            )",
+            [],
+        ).map_err(|e| format!("Failed to create template_usage table: {}", e))?;
+
         // Create indexes for performance
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_templates_category_id ON templates(category_id)",
@@ -98,6 +323,11 @@ impl TemplateManager {
             [],
         ).map_err(|e| format!("Failed to create parameters index: {}", e))?;
 
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_template_usage_template_id ON template_usage(template_id)",
+            [],
+        ).map_err(|e| format!("Failed to create usage index: {}", e))?;
+
         // Additional performance indexes for large libraries
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_templates_is_favorite ON templates(is_favorite)",
@@ -125,10 +355,139 @@ impl TemplateManager {
             [],
         ).map_err(|e| format!("Failed to create update trigger: {}", e))?;
 
-        // Create default "General" category if it doesn't exist
-        self.ensure_default_category(&conn)?;
+        Ok(())
+    }
+
+    /// Migration 2: executed queries, newest first per connection
+    fn migrate_query_history(conn: &Connection) -> Result<(), String> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS query_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                connection_id TEXT NOT NULL,
+                query TEXT NOT NULL,
+                execution_time_ms INTEGER NOT NULL,
+                row_count INTEGER,
+                success INTEGER NOT NULL,
+                executed_at TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_query_history_connection
+                ON query_history(connection_id, executed_at DESC);
+            CREATE INDEX IF NOT EXISTS idx_query_history_executed_at
+                ON query_history(executed_at DESC);"
+        ).map_err(|e| format!("Failed to create query_history table: {}", e))
+    }
+
+    /// FTS5 index over template name, description and content, kept in step with the
+    /// templates table by triggers so every write path updates it. Rows are matched by
+    /// template id rather than rowid, which VACUUM may renumber on a table keyed by TEXT;
+    /// only edits to the indexed columns touch the index.
+    fn migrate_template_search(conn: &Connection) -> Result<(), String> {
+        conn.execute_batch(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS templates_fts USING fts5(
+                template_id UNINDEXED,
+                name,
+                description,
+                content,
+                tokenize = 'unicode61'
+            );
+            CREATE TRIGGER IF NOT EXISTS templates_fts_insert AFTER INSERT ON templates BEGIN
+                INSERT INTO templates_fts (template_id, name, description, content)
+                VALUES (new.id, new.name, COALESCE(new.description, ''), new.content);
+            END;
+            CREATE TRIGGER IF NOT EXISTS templates_fts_update
+            AFTER UPDATE OF name, description, content ON templates BEGIN
+                DELETE FROM templates_fts WHERE template_id = old.id;
+                INSERT INTO templates_fts (template_id, name, description, content)
+                VALUES (new.id, new.name, COALESCE(new.description, ''), new.content);
+            END;
+            CREATE TRIGGER IF NOT EXISTS templates_fts_delete AFTER DELETE ON templates BEGIN
+                DELETE FROM templates_fts WHERE template_id = old.id;
+            END;
+            DELETE FROM templates_fts;
+            INSERT INTO templates_fts (template_id, name, description, content)
+                SELECT id, name, COALESCE(description, ''), content FROM templates;"
+        ).map_err(|e| format!("Failed to create template search index: {}", e))
+    }
+
+    /// Soft deletion: `deleted_at` marks a template as in the trash. Names only need to be
+    /// unique among live templates, so the table is rebuilt without its UNIQUE(name, category_id)
+    /// constraint and a partial unique index takes its place. The `updated_at` trigger is
+    /// replaced so it writes RFC 3339 timestamps like the rest of the code and leaves moving a
+    /// template in and out of the trash alone; rows it already stamped are converted.
+    fn migrate_template_trash(conn: &Connection) -> Result<(), String> {
+        // Indexes and triggers go with the old table; keep their definitions to recreate them
+        let mut stmt = conn.prepare(
+            "SELECT sql FROM sqlite_master
+             WHERE tbl_name = 'templates' AND type IN ('index', 'trigger') AND sql IS NOT NULL"
+        ).map_err(|e| format!("Failed to read templates schema: {}", e))?;
+        let dependents = stmt.query_map([], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read templates schema: {}", e))?;
+
+        conn.execute_batch(
+            "CREATE TABLE templates_rebuilt (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                description TEXT,
+                category_id TEXT NOT NULL,
+                content TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                usage_count INTEGER NOT NULL DEFAULT 0,
+                is_favorite INTEGER NOT NULL DEFAULT 0,
+                deleted_at TEXT,
+                FOREIGN KEY (category_id) REFERENCES template_categories(id) ON DELETE CASCADE
+            );
+            INSERT INTO templates_rebuilt
+                (id, name, description, category_id, content, created_at, updated_at, usage_count, is_favorite)
+                SELECT id, name, description, category_id, content, created_at, updated_at, usage_count, is_favorite
+                FROM templates;
+            DROP TABLE templates;
+            ALTER TABLE templates_rebuilt RENAME TO templates;"
+        ).map_err(|e| format!("Failed to rebuild templates table: {}", e))?;
+
+        for sql in &dependents {
+            conn.execute_batch(sql)
+                .map_err(|e| format!("Failed to recreate templates index or trigger: {}", e))?;
+        }
+
+        conn.execute_batch(
+            "CREATE UNIQUE INDEX idx_templates_live_name ON templates(name, category_id)
+                WHERE deleted_at IS NULL;
+            DROP TRIGGER IF EXISTS update_templates_updated_at;
+            UPDATE templates SET updated_at = replace(updated_at, ' ', 'T') || 'Z'
+                WHERE updated_at NOT LIKE '%T%';
+            CREATE TRIGGER update_templates_updated_at
+            AFTER UPDATE ON templates
+            FOR EACH ROW WHEN NEW.deleted_at IS OLD.deleted_at
+            BEGIN
+                UPDATE templates SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = OLD.id;
+            END;
+            CREATE INDEX IF NOT EXISTS idx_templates_deleted_at ON templates(deleted_at);"
+        ).map_err(|e| format!("Failed to add template trash: {}", e))
+    }
+
+    /// Parameters substituted as identifiers rather than literals; existing ones stay literals
+    fn migrate_parameter_kinds(conn: &Connection) -> Result<(), String> {
+        Self::add_column_if_missing(conn, "template_parameters", "kind", "TEXT NOT NULL DEFAULT 'value'")
+    }
+
+    /// Add a column to a table created by an older version of the schema
+    fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<(), String> {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))
+            .map_err(|e| format!("Failed to inspect {} table: {}", table, e))?;
+        let exists = stmt
+            .query_map([], |row| row.get::<_, String>("name"))
+            .map_err(|e| format!("Failed to inspect {} table: {}", table, e))?
+            .filter_map(|name| name.ok())
+            .any(|name| name == column);
+
+        if !exists {
+            conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])
+                .map_err(|e| format!("Failed to add {}.{} column: {}", table, column, e))?;
+            info!("Migrated {} table: added {} column", table, column);
+        }
 
-        info!("Template database schema initialized successfully");
         Ok(())
     }
 
@@ -167,7 +526,7 @@ impl TemplateManager {
 
         // Check for duplicate name in category
         let duplicate_count: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM templates WHERE name = ?1 AND category_id = ?2",
+            "SELECT COUNT(*) FROM templates WHERE name = ?1 AND category_id = ?2 AND deleted_at IS NULL",
             params![request.name, request.category_id],
             |row| row.get(0),
         ).map_err(|e| format!("Failed to check duplicate template name: {}", e))?;
@@ -199,17 +558,19 @@ impl TemplateManager {
         ).map_err(|e| format!("Failed to insert template: {}", e))?;
 
         // Insert parameters
-        for param in &request.parameters {
+        for (ordinal, param) in request.parameters.iter().enumerate() {
             let param_id = Uuid::new_v4().to_string();
             tx.execute(
-                "INSERT INTO template_parameters (id, template_id, name, default_value, description)
-                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                "INSERT INTO template_parameters (id, template_id, name, default_value, description, ordinal, kind)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
                 params![
                     param_id,
                     template_id,
                     param.name,
                     param.default_value,
-                    param.description
+                    param.description,
+                    ordinal as u32,
+                    param.kind.as_str()
                 ],
             ).map_err(|e| format!("Failed to insert template parameter: {}", e))?;
         }
@@ -219,59 +580,127 @@ impl TemplateManager {
         info!("Created template: {} ({})", request.name, template_id);
 
         // Return the created template
-        self.get_template_by_id(&template_id).await
+        self.load_linted_template(&conn, &template_id)
     }
 
+    /// Create the template, or update content, description and parameters of the
+    /// same-named template in the same category, so seeding scripts can be re-run
+    pub async fn upsert_template(&self, request: CreateTemplateRequest) -> Result<UpsertTemplateResult, String> {
+        let existing_id: Option<String> = {
+            let conn = self.db_connection.lock()
+                .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
+
+            conn.query_row(
+                "SELECT id FROM templates WHERE name = ?1 AND category_id = ?2 AND deleted_at IS NULL",
+                params![request.name, request.category_id],
+                |row| row.get(0),
+            ).optional().map_err(|e| format!("Failed to look up existing template: {}", e))?
+        };
+
+        match existing_id {
+            Some(id) => {
+                let updates = UpdateTemplateRequest {
+                    name: None,
+                    description: request.description,
+                    category_id: None,
+                    content: Some(request.content),
+                    parameters: Some(request.parameters),
+                    is_favorite: None,
+                };
+                let template = self.update_template(id, updates).await?;
+                Ok(UpsertTemplateResult { template, created: false })
+            }
+            None => {
+                let template = self.create_template(request).await?;
+                Ok(UpsertTemplateResult { template, created: true })
+            }
+        }
+    }
+
+    /// Templates matching `filter`, at most `filter.limit` of them (`DEFAULT_TEMPLATE_PAGE_SIZE`
+    /// when unset). Callers that need the whole library must page with `offset`;
+    /// `get_template_page` reports the total to page against.
     pub async fn get_templates(&self, filter: TemplateFilter) -> Result<Vec<Template>, String> {
         let conn = self.db_connection.lock()
             .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
 
-        let mut query = String::from(
-            "SELECT id, name, description, category_id, content, created_at, updated_at, usage_count, is_favorite
-             FROM templates WHERE 1=1"
-        );
+        self.query_templates(&conn, &filter)
+    }
+
+    /// One page of templates matching `filter`, with the total number of matches
+    pub async fn get_template_page(&self, filter: TemplateFilter) -> Result<TemplatePage, String> {
+        let conn = self.db_connection.lock()
+            .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
+
+        let (where_clause, params) = Self::template_filter_clause(&filter);
+        let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        let total_count: u32 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM templates WHERE 1=1{}", where_clause),
+            &param_refs[..],
+            |row| row.get(0),
+        ).map_err(|e| format!("Failed to count templates: {}", e))?;
+
+        Ok(TemplatePage {
+            templates: self.query_templates(&conn, &filter)?,
+            total_count,
+            limit: filter.limit.unwrap_or(DEFAULT_TEMPLATE_PAGE_SIZE),
+            offset: filter.offset.unwrap_or(0),
+        })
+    }
+
+    /// `WHERE` conditions (each starting with ` AND`) and their parameters for a filter
+    fn template_filter_clause(filter: &TemplateFilter) -> (String, Vec<Box<dyn rusqlite::ToSql>>) {
+        let mut clause = String::from(" AND deleted_at IS NULL");
         let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
 
-        // Apply filters
         if let Some(search) = &filter.search_query {
-            query.push_str(" AND (name LIKE ?1 OR description LIKE ?1 OR content LIKE ?1)");
+            clause.push_str(" AND (name LIKE ?1 OR description LIKE ?1 OR content LIKE ?1)");
             params.push(Box::new(format!("%{}%", search)));
         }
 
         if let Some(category_id) = &filter.category_id {
-            query.push_str(&format!(" AND category_id = ?{}", params.len() + 1));
+            clause.push_str(&format!(" AND category_id = ?{}", params.len() + 1));
             params.push(Box::new(category_id.clone()));
         }
 
         if let Some(is_favorite) = filter.is_favorite {
-            query.push_str(&format!(" AND is_favorite = ?{}", params.len() + 1));
+            clause.push_str(&format!(" AND is_favorite = ?{}", params.len() + 1));
             params.push(Box::new(if is_favorite { 1 } else { 0 }));
         }
 
+        (clause, params)
+    }
+
+    fn query_templates(&self, conn: &Connection, filter: &TemplateFilter) -> Result<Vec<Template>, String> {
+        let (where_clause, mut params) = Self::template_filter_clause(filter);
+        let mut query = format!(
+            "SELECT id, name, description, category_id, content, created_at, updated_at, usage_count, is_favorite
+             FROM templates WHERE 1=1{}",
+            where_clause
+        );
+
         // Apply sorting
-        let sort_column = match filter.sort_by.unwrap_or_default() {
+        let sort_column = match filter.sort_by.clone().unwrap_or_default() {
             TemplateSortBy::Name => "name",
             TemplateSortBy::CreatedAt => "created_at",
             TemplateSortBy::UpdatedAt => "updated_at",
             TemplateSortBy::UsageCount => "usage_count",
         };
 
-        let sort_order = match filter.sort_order.unwrap_or_default() {
+        let sort_order = match filter.sort_order.clone().unwrap_or_default() {
             SortOrder::Asc => "ASC",
             SortOrder::Desc => "DESC",
         };
 
         query.push_str(&format!(" ORDER BY {} {}", sort_column, sort_order));
 
-        // Apply pagination
-        if let Some(limit) = filter.limit {
-            query.push_str(&format!(" LIMIT ?{}", params.len() + 1));
-            params.push(Box::new(limit));
+        // Apply pagination; an unbounded listing of a large library is never what the caller wants
+        query.push_str(&format!(" LIMIT ?{}", params.len() + 1));
+        params.push(Box::new(filter.limit.unwrap_or(DEFAULT_TEMPLATE_PAGE_SIZE)));
 
-            if let Some(offset) = filter.offset {
-                query.push_str(&format!(" OFFSET ?{}", params.len() + 1));
-                params.push(Box::new(offset));
-            }
+        if let Some(offset) = filter.offset {
+            query.push_str(&format!(" OFFSET ?{}", params.len() + 1));
+            params.push(Box::new(offset));
         }
 
         let mut stmt = conn.prepare(&query)
@@ -288,7 +717,7 @@ impl TemplateManager {
                 .map_err(|e| format!("Failed to parse template row: {}", e))?;
             
             // Load parameters for each template
-            template.parameters = self.get_template_parameters(&conn, &template.id)?;
+            template.parameters = self.get_template_parameters(conn, &template.id)?;
             templates.push(template);
         }
 
@@ -299,9 +728,14 @@ impl TemplateManager {
         let conn = self.db_connection.lock()
             .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
 
+        self.load_template(&conn, id)
+    }
+
+    /// Load a template with its parameters on a connection the caller already holds
+    fn load_template(&self, conn: &Connection, id: &str) -> Result<Template, String> {
         let mut template = conn.query_row(
             "SELECT id, name, description, category_id, content, created_at, updated_at, usage_count, is_favorite
-             FROM templates WHERE id = ?1",
+             FROM templates WHERE id = ?1 AND deleted_at IS NULL",
             params![id],
             |row| Ok(self.row_to_template_basic(row)?),
         ).map_err(|e| match e {
@@ -310,8 +744,35 @@ impl TemplateManager {
         })?;
 
         // Load parameters
-        template.parameters = self.get_template_parameters(&conn, id)?;
+        template.parameters = self.get_template_parameters(conn, id)?;
+
+        Ok(template)
+    }
+
+    /// Compare the `{{name}}` placeholders in a template's content with its declared parameters
+    pub fn lint_template(&self, template: &Template) -> TemplateLint {
+        let placeholders = template_placeholders(&template.content);
 
+        let undeclared_placeholders = placeholders.iter()
+            .filter(|name| !template.parameters.iter().any(|param| &param.name == *name))
+            .cloned()
+            .collect();
+        let unused_parameters = template.parameters.iter()
+            .filter(|param| !placeholders.contains(&param.name))
+            .map(|param| param.name.clone())
+            .collect();
+
+        TemplateLint { undeclared_placeholders, unused_parameters }
+    }
+
+    /// Load a just-written template and attach its lint findings as warnings
+    fn load_linted_template(&self, conn: &Connection, id: &str) -> Result<Template, String> {
+        let mut template = self.load_template(conn, id)?;
+        let lint = self.lint_template(&template);
+        if !lint.is_clean() {
+            warn!("Template '{}' has parameter mismatches: {:?}", template.name, lint);
+        }
+        template.warnings = lint.warnings();
         Ok(template)
     }
 
@@ -335,12 +796,14 @@ impl TemplateManager {
             usage_count: row.get::<_, u32>("usage_count")?,
             is_favorite: is_favorite_int != 0,
             parameters: Vec::new(), // Will be loaded separately
+            warnings: Vec::new(),
         })
     }
 
     fn get_template_parameters(&self, conn: &Connection, template_id: &str) -> Result<Vec<TemplateParameter>, String> {
         let mut stmt = conn.prepare(
-            "SELECT id, template_id, name, default_value, description FROM template_parameters WHERE template_id = ?1"
+            "SELECT id, template_id, name, default_value, description, ordinal, kind FROM template_parameters
+             WHERE template_id = ?1 ORDER BY ordinal, rowid"
         ).map_err(|e| format!("Failed to prepare parameters query: {}", e))?;
 
         let param_rows = stmt.query_map(params![template_id], |row| {
@@ -350,6 +813,8 @@ impl TemplateManager {
                 name: row.get("name")?,
                 default_value: row.get("default_value")?,
                 description: row.get("description")?,
+                ordinal: row.get("ordinal")?,
+                kind: ParameterKind::from_stored(&row.get::<_, String>("kind")?),
             })
         }).map_err(|e| format!("Failed to query parameters: {}", e))?;
 
@@ -381,7 +846,7 @@ impl TemplateManager {
 
         // Check if template exists
         let exists: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM templates WHERE id = ?1",
+            "SELECT COUNT(*) FROM templates WHERE id = ?1 AND deleted_at IS NULL",
             params![id],
             |row| row.get(0),
         ).map_err(|e| format!("Failed to check template existence: {}", e))?;
@@ -447,17 +912,19 @@ impl TemplateManager {
             ).map_err(|e| format!("Failed to delete old parameters: {}", e))?;
 
             // Insert new parameters
-            for param in new_parameters {
+            for (ordinal, param) in new_parameters.iter().enumerate() {
                 let param_id = Uuid::new_v4().to_string();
                 tx.execute(
-                    "INSERT INTO template_parameters (id, template_id, name, default_value, description)
-                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    "INSERT INTO template_parameters (id, template_id, name, default_value, description, ordinal, kind)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
                     params![
                         param_id,
                         id,
                         param.name,
                         param.default_value,
-                        param.description
+                        param.description,
+                        ordinal as u32,
+                        param.kind.as_str()
                     ],
                 ).map_err(|e| format!("Failed to insert updated parameter: {}", e))?;
             }
@@ -468,32 +935,189 @@ impl TemplateManager {
         info!("Updated template: {}", id);
 
         // Return updated template
-        self.get_template_by_id(&id).await
+        self.load_linted_template(&conn, &id)
     }
 
+    /// Move a template to the trash. It disappears from listings and search until restored
+    /// with `restore_template` or removed for good by `purge_deleted_templates`.
     pub async fn delete_template(&self, id: String) -> Result<(), String> {
         let conn = self.db_connection.lock()
             .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
 
         let deleted_rows = conn.execute(
-            "DELETE FROM templates WHERE id = ?1",
-            params![id],
+            "UPDATE templates SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+            params![Utc::now().to_rfc3339(), id],
         ).map_err(|e| format!("Failed to delete template: {}", e))?;
 
         if deleted_rows == 0 {
             return Err(format!("Template not found: {}", id));
         }
 
-        info!("Deleted template: {}", id);
+        info!("Moved template to trash: {}", id);
         Ok(())
     }
 
+    /// Take a template out of the trash. Fails if a live template in its category has
+    /// taken its name since it was deleted.
+    pub async fn restore_template(&self, id: String) -> Result<Template, String> {
+        let conn = self.db_connection.lock()
+            .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
+
+        let restored_rows = conn.execute(
+            "UPDATE templates SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL",
+            params![id],
+        ).map_err(|e| match e.sqlite_error_code() {
+            Some(ErrorCode::ConstraintViolation) => format!(
+                "Can't restore template {}: another template in its category now has the same name; rename or delete that one first",
+                id
+            ),
+            _ => format!("Failed to restore template: {}", e),
+        })?;
+
+        if restored_rows == 0 {
+            return Err(format!("Template not in trash: {}", id));
+        }
+
+        info!("Restored template: {}", id);
+        self.load_template(&conn, &id)
+    }
+
+    /// Templates in the trash, most recently deleted first
+    pub async fn list_deleted_templates(&self) -> Result<Vec<DeletedTemplate>, String> {
+        let conn = self.db_connection.lock()
+            .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id, name, description, category_id, content, created_at, updated_at, usage_count, is_favorite, deleted_at
+             FROM templates WHERE deleted_at IS NOT NULL
+             ORDER BY deleted_at DESC"
+        ).map_err(|e| format!("Failed to prepare trash query: {}", e))?;
+
+        let rows = stmt.query_map([], |row| {
+            let deleted_at_str: String = row.get("deleted_at")?;
+            Ok(DeletedTemplate {
+                template: self.row_to_template_basic(row)?,
+                deleted_at: DateTime::parse_from_rfc3339(&deleted_at_str)
+                    .map_err(|_| rusqlite::Error::InvalidColumnType(0, "deleted_at".to_string(), rusqlite::types::Type::Text))?
+                    .with_timezone(&Utc),
+            })
+        }).map_err(|e| format!("Failed to list deleted templates: {}", e))?;
+
+        let mut deleted = Vec::new();
+        for row in rows {
+            let mut entry = row.map_err(|e| format!("Failed to parse template row: {}", e))?;
+            entry.template.parameters = self.get_template_parameters(&conn, &entry.template.id)?;
+            deleted.push(entry);
+        }
+
+        Ok(deleted)
+    }
+
+    /// Permanently delete templates that have been in the trash for at least `older_than_days`
+    /// days (0 empties the trash). Returns how many were removed.
+    pub async fn purge_deleted_templates(&self, older_than_days: u32) -> Result<u32, String> {
+        let conn = self.db_connection.lock()
+            .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
+
+        let cutoff = Utc::now() - chrono::Duration::days(i64::from(older_than_days));
+        let purged = conn.execute(
+            "DELETE FROM templates WHERE deleted_at IS NOT NULL AND deleted_at <= ?1",
+            params![cutoff.to_rfc3339()],
+        ).map_err(|e| format!("Failed to purge deleted templates: {}", e))?;
+
+        info!("Purged {} deleted templates", purged);
+        Ok(purged as u32)
+    }
+
+    /// Move several templates to the trash in one transaction
+    pub async fn bulk_delete_templates(&self, ids: Vec<String>) -> Result<Vec<BulkTemplateResult>, String> {
+        let deleted_at = Utc::now().to_rfc3339();
+        self.bulk_update(ids, |conn, id| {
+            conn.execute(
+                "UPDATE templates SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+                params![deleted_at, id],
+            ).map_err(|e| format!("Failed to delete template: {}", e))
+        })
+    }
+
+    /// Move several templates into `category_id` in one transaction. A template whose name is
+    /// already used in that category fails on its own without affecting the others.
+    pub async fn bulk_move_templates(&self, ids: Vec<String>, category_id: String) -> Result<Vec<BulkTemplateResult>, String> {
+        {
+            let conn = self.db_connection.lock()
+                .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
+            self.validate_category_exists(&conn, &category_id)?;
+        }
+
+        self.bulk_update(ids, |conn, id| {
+            let name: Option<String> = conn.query_row(
+                "SELECT name FROM templates WHERE id = ?1 AND deleted_at IS NULL",
+                params![id],
+                |row| row.get(0),
+            ).optional().map_err(|e| format!("Failed to look up template: {}", e))?;
+            let Some(name) = name else { return Ok(0) };
+
+            conn.execute(
+                "UPDATE templates SET category_id = ?1 WHERE id = ?2",
+                params![category_id, id],
+            ).map_err(|e| match e.sqlite_error_code() {
+                Some(ErrorCode::ConstraintViolation) => {
+                    format!("Template name '{}' already exists in this category", name)
+                }
+                _ => format!("Failed to move template: {}", e),
+            })
+        })
+    }
+
+    /// Mark or unmark several templates as favorites in one transaction
+    pub async fn bulk_set_favorite(&self, ids: Vec<String>, is_favorite: bool) -> Result<Vec<BulkTemplateResult>, String> {
+        self.bulk_update(ids, |conn, id| {
+            conn.execute(
+                "UPDATE templates SET is_favorite = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+                params![if is_favorite { 1 } else { 0 }, id],
+            ).map_err(|e| format!("Failed to update template: {}", e))
+        })
+    }
+
+    /// Apply `update` to each id inside one transaction. `update` returns the number of rows it
+    /// changed, so 0 reports the template as not found. Failures are per id; everything that
+    /// succeeded is committed.
+    fn bulk_update(
+        &self,
+        ids: Vec<String>,
+        update: impl Fn(&Connection, &str) -> Result<usize, String>,
+    ) -> Result<Vec<BulkTemplateResult>, String> {
+        let conn = self.db_connection.lock()
+            .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
+
+        let tx = conn.unchecked_transaction()
+            .map_err(|e| format!("Failed to begin bulk update: {}", e))?;
+
+        let results: Vec<BulkTemplateResult> = ids
+            .into_iter()
+            .map(|id| {
+                let error = match update(&*tx, &id) {
+                    Ok(0) => Some(format!("Template not found: {}", id)),
+                    Ok(_) => None,
+                    Err(e) => Some(e),
+                };
+                BulkTemplateResult { id, success: error.is_none(), error }
+            })
+            .collect();
+
+        tx.commit().map_err(|e| format!("Failed to commit bulk update: {}", e))?;
+
+        info!("Bulk update: {} of {} templates changed",
+              results.iter().filter(|result| result.success).count(), results.len());
+        Ok(results)
+    }
+
     pub async fn increment_usage_count(&self, id: String) -> Result<(), String> {
         let conn = self.db_connection.lock()
             .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
 
         let updated_rows = conn.execute(
-            "UPDATE templates SET usage_count = usage_count + 1 WHERE id = ?1",
+            "UPDATE templates SET usage_count = usage_count + 1 WHERE id = ?1 AND deleted_at IS NULL",
             params![id],
         ).map_err(|e| format!("Failed to increment usage count: {}", e))?;
 
@@ -504,6 +1128,101 @@ impl TemplateManager {
         Ok(())
     }
 
+    /// Append a usage event (e.g. a template run against a connection) to the history
+    pub async fn record_usage(&self, usage: &TemplateUsage) -> Result<(), String> {
+        let conn = self.db_connection.lock()
+            .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
+
+        conn.execute(
+            "INSERT INTO template_usage (template_id, used_at, connection_id, execution_success)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                usage.template_id,
+                usage.used_at.to_rfc3339(),
+                usage.connection_id,
+                usage.execution_success
+            ],
+        ).map_err(|e| format!("Failed to record template usage: {}", e))?;
+
+        info!("Recorded usage of template {} on connection {:?}", usage.template_id, usage.connection_id);
+        Ok(())
+    }
+
+    // Query History Operations
+    pub async fn record_query_history(&self, entry: &QueryHistoryEntry) -> Result<(), String> {
+        let conn = self.db_connection.lock()
+            .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
+
+        conn.execute(
+            "INSERT INTO query_history (connection_id, query, execution_time_ms, row_count, success, executed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                entry.connection_id,
+                entry.query,
+                entry.execution_time_ms as i64,
+                entry.row_count.map(|count| count as i64),
+                entry.success,
+                entry.executed_at.to_rfc3339()
+            ],
+        ).map_err(|e| format!("Failed to record query history: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Most recent entries first, optionally for a single connection
+    pub async fn get_query_history(
+        &self,
+        connection_id: Option<&str>,
+        limit: Option<u32>,
+    ) -> Result<Vec<QueryHistoryEntry>, String> {
+        let conn = self.db_connection.lock()
+            .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
+
+        let limit = limit.unwrap_or(DEFAULT_QUERY_HISTORY_LIMIT).clamp(1, MAX_QUERY_HISTORY_LIMIT);
+        let mut stmt = conn.prepare(
+            "SELECT id, connection_id, query, execution_time_ms, row_count, success, executed_at
+             FROM query_history
+             WHERE ?1 IS NULL OR connection_id = ?1
+             ORDER BY executed_at DESC, id DESC
+             LIMIT ?2"
+        ).map_err(|e| format!("Failed to prepare query history query: {}", e))?;
+
+        let rows = stmt.query_map(params![connection_id, limit], |row| {
+            let executed_at: String = row.get("executed_at")?;
+            Ok(QueryHistoryEntry {
+                id: row.get("id")?,
+                connection_id: row.get("connection_id")?,
+                query: row.get("query")?,
+                execution_time_ms: row.get::<_, i64>("execution_time_ms")?.max(0) as u64,
+                row_count: row.get::<_, Option<i64>>("row_count")?.map(|count| count.max(0) as u64),
+                success: row.get("success")?,
+                executed_at: DateTime::parse_from_rfc3339(&executed_at)
+                    .map_err(|_| rusqlite::Error::InvalidColumnType(6, "executed_at".to_string(), rusqlite::types::Type::Text))?
+                    .with_timezone(&Utc),
+            })
+        }).map_err(|e| format!("Failed to query history: {}", e))?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            entries.push(row.map_err(|e| format!("Failed to parse history entry: {}", e))?);
+        }
+        Ok(entries)
+    }
+
+    /// Delete history, for one connection or everything; returns the number of entries removed
+    pub async fn clear_query_history(&self, connection_id: Option<&str>) -> Result<u32, String> {
+        let conn = self.db_connection.lock()
+            .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
+
+        let deleted = conn.execute(
+            "DELETE FROM query_history WHERE ?1 IS NULL OR connection_id = ?1",
+            params![connection_id],
+        ).map_err(|e| format!("Failed to clear query history: {}", e))?;
+
+        info!("Cleared {} query history entries", deleted);
+        Ok(deleted as u32)
+    }
+
     // Category Management Operations
     pub async fn create_category(&self, request: CreateCategoryRequest) -> Result<TemplateCategory, String> {
         let conn = self.db_connection.lock()
@@ -557,7 +1276,7 @@ impl TemplateManager {
         let mut stmt = conn.prepare(
             "SELECT c.id, c.name, c.parent_id, c.created_at, COUNT(t.id) as template_count
              FROM template_categories c
-             LEFT JOIN templates t ON c.id = t.category_id
+             LEFT JOIN templates t ON c.id = t.category_id AND t.deleted_at IS NULL
              GROUP BY c.id, c.name, c.parent_id, c.created_at
              ORDER BY c.name"
         ).map_err(|e| format!("Failed to prepare categories query: {}", e))?;
@@ -583,6 +1302,34 @@ impl TemplateManager {
         Ok(categories)
     }
 
+    /// Current template counts for the given categories, e.g. after templates moved between them
+    pub async fn category_template_counts(&self, category_ids: &[String]) -> Result<Vec<CategoryCount>, String> {
+        let conn = self.db_connection.lock()
+            .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
+
+        let mut counts: Vec<CategoryCount> = Vec::new();
+        for category_id in category_ids {
+            if counts.iter().any(|count| &count.category_id == category_id) {
+                continue;
+            }
+
+            let template_count: u32 = conn.query_row(
+                "SELECT COUNT(*) FROM templates WHERE category_id = ?1 AND deleted_at IS NULL",
+                params![category_id],
+                |row| row.get(0),
+            ).map_err(|e| format!("Failed to count templates in category: {}", e))?;
+
+            counts.push(CategoryCount { category_id: category_id.clone(), template_count });
+        }
+
+        Ok(counts)
+    }
+
+    /// Categories as a forest of nested nodes, siblings sorted by name
+    pub async fn get_category_tree(&self) -> Result<Vec<CategoryNode>, String> {
+        Ok(build_category_tree(self.get_categories().await?))
+    }
+
     pub async fn update_category(&self, id: String, updates: UpdateCategoryRequest) -> Result<TemplateCategory, String> {
         let conn = self.db_connection.lock()
             .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
@@ -657,9 +1404,9 @@ impl TemplateManager {
         let conn = self.db_connection.lock()
             .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
 
-        // Check if category has templates
+        // Check if category has templates; trashed ones are deleted along with it
         let template_count: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM templates WHERE category_id = ?1",
+            "SELECT COUNT(*) FROM templates WHERE category_id = ?1 AND deleted_at IS NULL",
             params![id],
             |row| row.get(0),
         ).map_err(|e| format!("Failed to check category templates: {}", e))?;
@@ -692,6 +1439,99 @@ impl TemplateManager {
         Ok(())
     }
 
+    /// Delete a category that may still have templates or child categories, handling them
+    /// according to `strategy`. Everything happens in one transaction.
+    pub async fn delete_category_with_strategy(&self, id: String, strategy: CategoryDeleteStrategy) -> Result<(), String> {
+        let conn = self.db_connection.lock()
+            .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
+
+        let tx = conn.unchecked_transaction()
+            .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+
+        self.validate_category_exists(&tx, &id)?;
+        let subtree = Self::category_subtree(&tx, &id)?;
+
+        match strategy {
+            CategoryDeleteStrategy::Reassign(target_id) => {
+                self.validate_category_exists(&tx, &target_id)?;
+                if subtree.contains(&target_id) {
+                    return Err("Cannot reassign to the category being deleted or one of its descendants".to_string());
+                }
+
+                // Trashed templates give way to a same-named template on the other side
+                tx.execute(
+                    "DELETE FROM templates
+                     WHERE deleted_at IS NOT NULL
+                       AND ((category_id = ?1 AND name IN (SELECT name FROM templates WHERE category_id = ?2))
+                         OR (category_id = ?2 AND name IN (SELECT name FROM templates WHERE category_id = ?1)))",
+                    params![id, target_id],
+                ).map_err(|e| format!("Failed to purge deleted templates: {}", e))?;
+
+                let clash: Option<String> = tx.query_row(
+                    "SELECT s.name FROM templates s
+                     JOIN templates t ON t.name = s.name AND t.category_id = ?2
+                     WHERE s.category_id = ?1
+                     LIMIT 1",
+                    params![id, target_id],
+                    |row| row.get(0),
+                ).optional().map_err(|e| format!("Failed to check template names: {}", e))?;
+                if let Some(name) = clash {
+                    return Err(format!("Cannot reassign: template name '{}' already exists in the target category", name));
+                }
+
+                tx.execute(
+                    "UPDATE templates SET category_id = ?1 WHERE category_id = ?2",
+                    params![target_id, id],
+                ).map_err(|e| format!("Failed to move templates: {}", e))?;
+                tx.execute(
+                    "UPDATE template_categories SET parent_id = ?1 WHERE parent_id = ?2",
+                    params![target_id, id],
+                ).map_err(|e| format!("Failed to move child categories: {}", e))?;
+                tx.execute(
+                    "DELETE FROM template_categories WHERE id = ?1",
+                    params![id],
+                ).map_err(|e| format!("Failed to delete category: {}", e))?;
+            }
+            CategoryDeleteStrategy::CascadeDelete => {
+                for category_id in &subtree {
+                    tx.execute(
+                        "DELETE FROM templates WHERE category_id = ?1",
+                        params![category_id],
+                    ).map_err(|e| format!("Failed to delete templates: {}", e))?;
+                }
+                for category_id in &subtree {
+                    tx.execute(
+                        "DELETE FROM template_categories WHERE id = ?1",
+                        params![category_id],
+                    ).map_err(|e| format!("Failed to delete category: {}", e))?;
+                }
+            }
+        }
+
+        tx.commit().map_err(|e| format!("Failed to commit category deletion: {}", e))?;
+
+        info!("Deleted category {} ({} categories in subtree)", id, subtree.len());
+        Ok(())
+    }
+
+    /// A category's id followed by the ids of all its descendants
+    fn category_subtree(conn: &Connection, id: &str) -> Result<Vec<String>, String> {
+        let mut stmt = conn.prepare(
+            "WITH RECURSIVE subtree(id) AS (
+                SELECT ?1
+                UNION
+                SELECT c.id FROM template_categories c JOIN subtree s ON c.parent_id = s.id
+             )
+             SELECT id FROM subtree"
+        ).map_err(|e| format!("Failed to prepare category subtree query: {}", e))?;
+
+        let ids = stmt.query_map(params![id], |row| row.get(0))
+            .map_err(|e| format!("Failed to load category subtree: {}", e))?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(|e| format!("Failed to load category subtree: {}", e))?;
+        Ok(ids)
+    }
+
     fn would_create_circular_reference(&self, conn: &Connection, category_id: &str, proposed_parent_id: &str) -> Result<bool, String> {
         // Check if the proposed parent is actually a descendant of the current category
         let mut current_parent = Some(proposed_parent_id.to_string());
@@ -712,26 +1552,75 @@ impl TemplateManager {
     }
 
     // Search and Statistics Operations
+    /// Templates matching every word of `query`, most relevant first. BM25 weights a
+    /// hit in the name above one in the description, and both above one in the content.
     pub async fn search_templates(&self, query: String) -> Result<Vec<Template>, String> {
-        let filter = TemplateFilter {
-            search_query: Some(query),
-            ..Default::default()
+        let Some(match_expression) = fts_match_expression(&query) else {
+            // Nothing the index can match on; fall back to a plain substring filter
+            let filter = TemplateFilter {
+                search_query: Some(query),
+                ..Default::default()
+            };
+            return self.get_templates(filter).await;
         };
-        self.get_templates(filter).await
+
+        let conn = self.db_connection.lock()
+            .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT t.id, t.name, t.description, t.category_id, t.content, t.created_at, t.updated_at,
+                    t.usage_count, t.is_favorite
+             FROM templates_fts
+             JOIN templates t ON t.id = templates_fts.template_id
+             WHERE templates_fts MATCH ?1 AND t.deleted_at IS NULL
+             ORDER BY bm25(templates_fts, 0.0, 10.0, 4.0, 1.0), t.name
+             LIMIT ?2"
+        ).map_err(|e| format!("Failed to prepare template search: {}", e))?;
+
+        let rows = stmt.query_map(params![match_expression, DEFAULT_TEMPLATE_PAGE_SIZE], |row| {
+            self.row_to_template_basic(row)
+        }).map_err(|e| format!("Failed to search templates: {}", e))?;
+
+        let mut templates = Vec::new();
+        for row in rows {
+            let mut template = row.map_err(|e| format!("Failed to parse template row: {}", e))?;
+            template.parameters = self.get_template_parameters(&conn, &template.id)?;
+            templates.push(template);
+        }
+
+        Ok(templates)
+    }
+
+    /// Cheap liveness probe: fails if the database can't be queried
+    pub async fn count_templates(&self) -> Result<u32, String> {
+        let conn = self.db_connection.lock()
+            .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
+
+        conn.query_row("SELECT COUNT(*) FROM templates WHERE deleted_at IS NULL", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to count templates: {}", e))
     }
 
     pub async fn get_template_statistics(&self) -> Result<TemplateStatistics, String> {
+        self.get_dashboard_snapshot().await
+    }
+
+    /// Totals plus the most-used, recent and favorite lists, read under one lock
+    /// in one transaction so the lists agree with each other and with the totals
+    pub async fn get_dashboard_snapshot(&self) -> Result<TemplateStatistics, String> {
         let conn = self.db_connection.lock()
             .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
 
+        let tx = conn.unchecked_transaction()
+            .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+
         // Get total counts
-        let total_templates: u32 = conn.query_row(
-            "SELECT COUNT(*) FROM templates",
+        let total_templates: u32 = tx.query_row(
+            "SELECT COUNT(*) FROM templates WHERE deleted_at IS NULL",
             [],
             |row| row.get(0),
         ).map_err(|e| format!("Failed to get template count: {}", e))?;
 
-        let total_categories: u32 = conn.query_row(
+        let total_categories: u32 = tx.query_row(
             "SELECT COUNT(*) FROM template_categories",
             [],
             |row| row.get(0),
@@ -744,7 +1633,7 @@ impl TemplateManager {
             limit: Some(10),
             ..Default::default()
         };
-        let most_used_templates = self.get_templates(most_used_filter).await?;
+        let most_used_templates = self.query_templates(&tx, &most_used_filter)?;
 
         // Get recent templates (top 10)
         let recent_filter = TemplateFilter {
@@ -753,7 +1642,7 @@ impl TemplateManager {
             limit: Some(10),
             ..Default::default()
         };
-        let recent_templates = self.get_templates(recent_filter).await?;
+        let recent_templates = self.query_templates(&tx, &recent_filter)?;
 
         // Get favorite templates
         let favorite_filter = TemplateFilter {
@@ -762,15 +1651,30 @@ impl TemplateManager {
             sort_order: Some(SortOrder::Asc),
             ..Default::default()
         };
-        let favorite_templates = self.get_templates(favorite_filter).await?;
+        let favorite_templates = self.query_templates(&tx, &favorite_filter)?;
+
+        let category_usage = Self::query_category_usage(&tx)?;
+
+        tx.commit().map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+        Ok(TemplateStatistics {
+            total_templates,
+            total_categories,
+            most_used_templates,
+            recent_templates,
+            favorite_templates,
+            category_usage,
+        })
+    }
 
-        // Get category usage statistics
+    /// Template count and summed usage per category, busiest first
+    fn query_category_usage(conn: &Connection) -> Result<Vec<CategoryUsage>, String> {
         let mut stmt = conn.prepare(
             "SELECT c.id, c.name, c.parent_id, c.created_at,
                     COUNT(t.id) as template_count,
                     COALESCE(SUM(t.usage_count), 0) as total_usage
              FROM template_categories c
-             LEFT JOIN templates t ON c.id = t.category_id
+             LEFT JOIN templates t ON c.id = t.category_id AND t.deleted_at IS NULL
              GROUP BY c.id, c.name, c.parent_id, c.created_at
              ORDER BY total_usage DESC"
         ).map_err(|e| format!("Failed to prepare category usage query: {}", e))?;
@@ -797,14 +1701,7 @@ impl TemplateManager {
             category_usage.push(usage_result.map_err(|e| format!("Failed to parse category usage: {}", e))?);
         }
 
-        Ok(TemplateStatistics {
-            total_templates,
-            total_categories,
-            most_used_templates,
-            recent_templates,
-            favorite_templates,
-            category_usage,
-        })
+        Ok(category_usage)
     }
 
     // Import/Export Operations
@@ -844,7 +1741,10 @@ impl TemplateManager {
             .map_err(|e| format!("Failed to serialize export data: {}", e))
     }
 
-    pub async fn import_templates(&self, template_data: String) -> Result<TemplateImportResult, String> {
+    /// Import an export bundle. With `atomic`, any category or template error rolls back the
+    /// whole import; otherwise everything that succeeded is kept. `mode` decides what happens
+    /// to templates whose name is already taken in their category.
+    pub async fn import_templates(&self, template_data: String, atomic: bool, mode: ImportMode) -> Result<TemplateImportResult, String> {
         // SECURITY: Validate import data size to prevent DoS attacks
         if template_data.len() > 10_000_000 { // 10MB limit
             return Err("Import data exceeds maximum size limit of 10MB".to_string());
@@ -863,34 +1763,45 @@ impl TemplateManager {
         let mut imported_templates = 0;
         let mut imported_categories = 0;
         let mut skipped_duplicates = 0;
+        let mut overwritten_templates = 0;
+        let mut renamed_templates = 0;
         let mut errors = Vec::new();
 
         // Begin transaction
         let tx = conn.unchecked_transaction()
             .map_err(|e| format!("Failed to begin import transaction: {}", e))?;
 
-        // Import categories first
+        // Imported categories get fresh ids so they can't collide with existing ones; a
+        // category whose name already exists is merged into it. Templates follow the map.
+        let mut category_ids: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+        let mut new_categories = Vec::new();
         for category in &export.categories {
-            // Check if category already exists
-            let exists: i64 = tx.query_row(
-                "SELECT COUNT(*) FROM template_categories WHERE name = ?1",
+            let existing: Option<String> = tx.query_row(
+                "SELECT id FROM template_categories WHERE name = ?1",
                 params![category.name],
                 |row| row.get(0),
-            ).map_err(|e| format!("Failed to check category existence: {}", e))?;
+            ).optional().map_err(|e| format!("Failed to check category existence: {}", e))?;
 
-            if exists > 0 {
-                skipped_duplicates += 1;
-                continue;
+            match existing {
+                Some(id) => {
+                    category_ids.insert(category.id.clone(), id);
+                    skipped_duplicates += 1;
+                }
+                None => {
+                    category_ids.insert(category.id.clone(), Uuid::new_v4().to_string());
+                    new_categories.push(category);
+                }
             }
+        }
 
-            // Import category
+        // Parents are linked once every category exists, whatever order the export lists them in
+        for category in &new_categories {
             match tx.execute(
                 "INSERT INTO template_categories (id, name, parent_id, created_at)
-                 VALUES (?1, ?2, ?3, ?4)",
+                 VALUES (?1, ?2, NULL, ?3)",
                 params![
-                    category.id,
+                    category_ids[&category.id],
                     category.name,
-                    category.parent_id,
                     category.created_at.to_rfc3339()
                 ],
             ) {
@@ -899,29 +1810,67 @@ impl TemplateManager {
             }
         }
 
+        for category in &new_categories {
+            let Some(parent_id) = category.parent_id.as_ref().and_then(|parent| category_ids.get(parent)) else {
+                continue;
+            };
+            if let Err(e) = tx.execute(
+                "UPDATE template_categories SET parent_id = ?1 WHERE id = ?2",
+                params![parent_id, category_ids[&category.id]],
+            ) {
+                errors.push(format!("Failed to link category '{}' to its parent: {}", category.name, e));
+            }
+        }
+
         // Import templates
         for template in &export.templates {
+            // Categories not in the export are expected to exist already
+            let category_id = category_ids.get(&template.category_id).unwrap_or(&template.category_id);
+
             // Check if template already exists in the same category
-            let exists: i64 = tx.query_row(
-                "SELECT COUNT(*) FROM templates WHERE name = ?1 AND category_id = ?2",
-                params![template.name, template.category_id],
+            let existing_id: Option<String> = tx.query_row(
+                "SELECT id FROM templates WHERE name = ?1 AND category_id = ?2 AND deleted_at IS NULL",
+                params![template.name, category_id],
                 |row| row.get(0),
-            ).map_err(|e| format!("Failed to check template existence: {}", e))?;
+            ).optional().map_err(|e| format!("Failed to check template existence: {}", e))?;
 
-            if exists > 0 {
-                skipped_duplicates += 1;
-                continue;
-            }
+            let (template_id, name) = match (existing_id, mode) {
+                (None, _) => (template.id.clone(), template.name.clone()),
+                (Some(_), ImportMode::Skip) => {
+                    skipped_duplicates += 1;
+                    continue;
+                }
+                (Some(id), ImportMode::Overwrite) => {
+                    let updated = tx.execute(
+                        "UPDATE templates SET description = ?1, content = ?2 WHERE id = ?3",
+                        params![template.description, template.content, id],
+                    ).and_then(|_| tx.execute("DELETE FROM template_parameters WHERE template_id = ?1", params![id]));
+
+                    match updated {
+                        Ok(_) => {
+                            Self::import_parameters(&tx, &id, template, true, &mut errors);
+                            overwritten_templates += 1;
+                        }
+                        Err(e) => errors.push(format!("Failed to overwrite template '{}': {}", template.name, e)),
+                    }
+                    continue;
+                }
+                (Some(_), ImportMode::Rename) => {
+                    let name = Self::unused_template_name(&tx, &template.name, category_id)?;
+                    (Uuid::new_v4().to_string(), name)
+                }
+            };
+            let renamed = name != template.name;
 
             // Import template
             match tx.execute(
                 "INSERT INTO templates (id, name, description, category_id, content, created_at, updated_at, usage_count, is_favorite)
                  VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
                 params![
-                    template.id,
-                    template.name,
+                    template_id,
+                    name,
                     template.description,
-                    template.category_id,
+                    category_id,
                     template.content,
                     template.created_at.to_rfc3339(),
                     template.updated_at.to_rfc3339(),
@@ -930,77 +1879,184 @@ impl TemplateManager {
                 ],
             ) {
                 Ok(_) => {
-                    // Import parameters
-                    for param in &template.parameters {
-                        if let Err(e) = tx.execute(
-                            "INSERT INTO template_parameters (id, template_id, name, default_value, description)
-                             VALUES (?1, ?2, ?3, ?4, ?5)",
-                            params![
-                                param.id,
-                                param.template_id,
-                                param.name,
-                                param.default_value,
-                                param.description
-                            ],
-                        ) {
-                            errors.push(format!("Failed to import parameter '{}' for template '{}': {}", param.name, template.name, e));
-                        }
+                    Self::import_parameters(&tx, &template_id, template, renamed, &mut errors);
+                    if renamed {
+                        renamed_templates += 1;
+                    } else {
+                        imported_templates += 1;
                     }
-                    imported_templates += 1;
                 }
                 Err(e) => errors.push(format!("Failed to import template '{}': {}", template.name, e)),
             }
         }
 
+        if atomic && !errors.is_empty() {
+            tx.rollback().map_err(|e| format!("Failed to roll back import transaction: {}", e))?;
+            warn!("Import rolled back after {} errors", errors.len());
+
+            return Ok(TemplateImportResult {
+                imported_templates: 0,
+                imported_categories: 0,
+                skipped_duplicates,
+                overwritten_templates: 0,
+                renamed_templates: 0,
+                errors,
+                rolled_back: true,
+            });
+        }
+
         tx.commit().map_err(|e| format!("Failed to commit import transaction: {}", e))?;
 
-        info!("Import completed: {} templates, {} categories, {} skipped, {} errors",
-              imported_templates, imported_categories, skipped_duplicates, errors.len());
+        info!("Import completed: {} templates, {} categories, {} skipped, {} overwritten, {} renamed, {} errors",
+              imported_templates, imported_categories, skipped_duplicates, overwritten_templates, renamed_templates, errors.len());
 
         Ok(TemplateImportResult {
             imported_templates,
             imported_categories,
             skipped_duplicates,
+            overwritten_templates,
+            renamed_templates,
             errors,
+            rolled_back: false,
+        })
+    }
+
+    /// Insert an imported template's parameters under `template_id`. Exports list parameters
+    /// in order; older ones carry no ordinal. `fresh_ids` is needed whenever the exported ids
+    /// may already be taken, i.e. unless the template itself was inserted under its own id.
+    fn import_parameters(
+        tx: &Connection,
+        template_id: &str,
+        template: &Template,
+        fresh_ids: bool,
+        errors: &mut Vec<String>,
+    ) {
+        for (ordinal, param) in template.parameters.iter().enumerate() {
+            let param_id = if fresh_ids { Uuid::new_v4().to_string() } else { param.id.clone() };
+            if let Err(e) = tx.execute(
+                "INSERT INTO template_parameters (id, template_id, name, default_value, description, ordinal, kind)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    param_id,
+                    template_id,
+                    param.name,
+                    param.default_value,
+                    param.description,
+                    ordinal as u32,
+                    param.kind.as_str()
+                ],
+            ) {
+                errors.push(format!("Failed to import parameter '{}' for template '{}': {}", param.name, template.name, e));
+            }
+        }
+    }
+
+    /// First of "<name> (imported)", "<name> (imported 2)", ... not yet used in the category
+    fn unused_template_name(tx: &Connection, name: &str, category_id: &str) -> Result<String, String> {
+        for attempt in 1u32.. {
+            let candidate = match attempt {
+                1 => format!("{} (imported)", name),
+                n => format!("{} (imported {})", name, n),
+            };
+            let taken: i64 = tx.query_row(
+                "SELECT COUNT(*) FROM templates WHERE name = ?1 AND category_id = ?2 AND deleted_at IS NULL",
+                params![candidate, category_id],
+                |row| row.get(0),
+            ).map_err(|e| format!("Failed to check template existence: {}", e))?;
+
+            if taken == 0 {
+                return Ok(candidate);
+            }
+        }
+        unreachable!("ran out of import suffixes")
+    }
+
+    /// Line diff between a template's stored content and `new_content`, plus the parameters the
+    /// edit would add, remove or rename. Nothing is saved.
+    pub async fn diff_template_content(&self, template_id: &str, new_content: &str) -> Result<TemplateContentDiff, String> {
+        let template = self.get_template_by_id(template_id).await?;
+        let diff = similar::TextDiff::from_lines(template.content.as_str(), new_content);
+
+        let lines = diff
+            .iter_all_changes()
+            .map(|change| ContentDiffLine {
+                change: match change.tag() {
+                    similar::ChangeTag::Equal => DiffChange::Equal,
+                    similar::ChangeTag::Insert => DiffChange::Insert,
+                    similar::ChangeTag::Delete => DiffChange::Delete,
+                },
+                old_line: change.old_index().map(|index| index as u32 + 1),
+                new_line: change.new_index().map(|index| index as u32 + 1),
+                text: change.value().trim_end_matches(['\r', '\n']).to_string(),
+            })
+            .collect::<Vec<_>>();
+        let has_changes = lines.iter().any(|line| line.change != DiffChange::Equal);
+        let unified_diff = diff.unified_diff().context_radius(3).header("saved", "proposed").to_string();
+
+        let stored: Vec<String> = template.parameters.iter().map(|param| param.name.clone()).collect();
+        let parameters = diff_parameters(&stored, &template.content, new_content);
+
+        Ok(TemplateContentDiff {
+            template_id: template.id,
+            has_changes,
+            lines,
+            unified_diff,
+            parameters,
         })
     }
 
     // Parameter Processing Operations
     pub async fn process_template_parameters(&self, template_id: String, substitutions: Vec<ParameterSubstitution>) -> Result<ProcessedTemplate, String> {
+        self.process_template_parameters_for(template_id, substitutions, None).await
+    }
+
+    /// Substitute parameter values into a template using `database_type`'s escaping (standard
+    /// SQL when unknown), so a value can never end its literal early. A placeholder inside a
+    /// string literal, as in `'%{{name}}%'`, is escaped in place; elsewhere numbers go in as-is,
+    /// identifier parameters are quoted as identifiers and other values become quoted strings.
+    pub async fn process_template_parameters_for(
+        &self,
+        template_id: String,
+        substitutions: Vec<ParameterSubstitution>,
+        database_type: Option<&DatabaseType>,
+    ) -> Result<ProcessedTemplate, String> {
         let template = self.get_template_by_id(&template_id).await?;
-        let mut processed_content = template.content.clone();
         let mut applied_substitutions = Vec::new();
         let mut missing_parameters = Vec::new();
 
-        // Create substitution map with sanitized values
-        let mut substitution_map: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+        let mut supplied: std::collections::HashMap<String, String> = std::collections::HashMap::new();
         for substitution in substitutions {
-            let sanitized_value = self.sanitize_parameter_value(&substitution.value)?;
-            substitution_map.insert(substitution.parameter_name, sanitized_value);
+            self.validate_parameter_value(&substitution.value)?;
+            supplied.insert(substitution.parameter_name, substitution.value);
         }
 
-        // Process each parameter
+        // Values for each declared parameter, falling back to its default
+        let mut values: std::collections::HashMap<String, String> = std::collections::HashMap::new();
         for param in &template.parameters {
-            let placeholder = format!("{{{{{}}}}}", param.name);
-
-            if let Some(value) = substitution_map.get(&param.name) {
-                processed_content = processed_content.replace(&placeholder, value);
-                applied_substitutions.push(ParameterSubstitution {
-                    parameter_name: param.name.clone(),
-                    value: value.clone(),
-                });
-            } else if let Some(default_value) = &param.default_value {
-                let sanitized_default = self.sanitize_parameter_value(default_value)?;
-                processed_content = processed_content.replace(&placeholder, &sanitized_default);
-                applied_substitutions.push(ParameterSubstitution {
-                    parameter_name: param.name.clone(),
-                    value: sanitized_default,
-                });
-            } else {
-                missing_parameters.push(param.name.clone());
-            }
+            let value = match (supplied.get(&param.name), &param.default_value) {
+                (Some(value), _) => value.clone(),
+                (None, Some(default_value)) => {
+                    self.validate_parameter_value(default_value)?;
+                    default_value.clone()
+                }
+                (None, None) => {
+                    missing_parameters.push(param.name.clone());
+                    continue;
+                }
+            };
+            applied_substitutions.push(ParameterSubstitution {
+                parameter_name: param.name.clone(),
+                value: value.clone(),
+            });
+            values.insert(param.name.clone(), value);
         }
 
+        // Missing parameters stay visible in the output
+        let kinds = parameter_kinds(&template.parameters);
+        let processed_content = render_placeholders(&template.content, &values, &kinds, database_type, &|name| {
+            format!("{{{{{}}}}}", name)
+        });
+
         Ok(ProcessedTemplate {
             original_content: template.content,
             processed_content,
@@ -1009,14 +2065,72 @@ impl TemplateManager {
         })
     }
 
-    /// SECURITY CRITICAL: Sanitizes parameter values to prevent SQL injection attacks
-    /// This function must be maintained and updated as new attack vectors are discovered
-    fn sanitize_parameter_value(&self, value: &str) -> Result<String, String> {
-        // Input validation: check length
-        if value.len() > 1000 {
-            return Err("Parameter value exceeds maximum length of 1000 characters".to_string());
+    /// Render a template for display without running it or counting a use. Defaults fill in
+    /// for values not supplied, and every placeholder still unfilled, declared or not, is shown
+    /// as `[[missing:name]]` and listed in `missing_parameters`. Values are not validated, so
+    /// the preview renders even for input `process_template_parameters` would refuse.
+    pub async fn preview_template(
+        &self,
+        template_id: String,
+        substitutions: Vec<ParameterSubstitution>,
+        database_type: Option<&DatabaseType>,
+    ) -> Result<ProcessedTemplate, String> {
+        let template = self.get_template_by_id(&template_id).await?;
+
+        let mut values: std::collections::HashMap<String, String> = template.parameters.iter()
+            .filter_map(|param| Some((param.name.clone(), param.default_value.clone()?)))
+            .collect();
+        for substitution in substitutions {
+            values.insert(substitution.parameter_name, substitution.value);
         }
 
+        let applied_substitutions = template.parameters.iter()
+            .filter_map(|param| Some(ParameterSubstitution {
+                parameter_name: param.name.clone(),
+                value: values.get(&param.name)?.clone(),
+            }))
+            .collect();
+        let missing_parameters = template_placeholders(&template.content)
+            .into_iter()
+            .filter(|name| !values.contains_key(name))
+            .collect();
+
+        let kinds = parameter_kinds(&template.parameters);
+        let processed_content = render_placeholders(&template.content, &values, &kinds, database_type, &|name| {
+            format!("{}{}{}", PREVIEW_MISSING_PREFIX, name, PREVIEW_MISSING_SUFFIX)
+        });
+
+        Ok(ProcessedTemplate {
+            original_content: template.content,
+            processed_content,
+            substitutions: applied_substitutions,
+            missing_parameters,
+        })
+    }
+
+    /// Reject parameter values that are too long or contain control characters.
+    /// Quoting, not content filtering, is what keeps values from altering the statement.
+    fn validate_parameter_value(&self, value: &str) -> Result<(), String> {
+        // Input validation: check length in characters, after a cheap byte-size guard
+        if value.len() > MAX_PARAMETER_VALUE_BYTES || value.chars().count() > MAX_PARAMETER_VALUE_CHARS {
+            return Err(format!(
+                "Parameter value exceeds maximum length of {} characters",
+                MAX_PARAMETER_VALUE_CHARS
+            ));
+        }
+
+        if value.chars().any(|c| c.is_control() && c != '\t' && c != '\n' && c != '\r') {
+            return Err("Parameter value contains invalid control characters".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// SECURITY CRITICAL: Rejects imported content matching known SQL injection patterns
+    /// This function must be maintained and updated as new attack vectors are discovered
+    fn reject_dangerous_content(&self, value: &str) -> Result<(), String> {
+        self.validate_parameter_value(value)?;
+
         // Check for dangerous SQL injection patterns
         let dangerous_patterns = [
             // SQL DML/DDL commands
@@ -1049,12 +2163,7 @@ impl TemplateManager {
             return Err("Parameter value contains characters that are not allowed for security reasons".to_string());
         }
 
-        // Validate that the value doesn't contain excessive whitespace or control characters
-        if value.chars().any(|c| c.is_control() && c != '\t' && c != '\n' && c != '\r') {
-            return Err("Parameter value contains invalid control characters".to_string());
-        }
-
-        Ok(value.to_string())
+        Ok(())
     }
 
     /// SECURITY CRITICAL: Validates import data to prevent malicious imports
@@ -1071,7 +2180,7 @@ impl TemplateManager {
         // Validate each template
         for template in &export.templates {
             // Validate template name
-            if template.name.is_empty() || template.name.len() > 255 {
+            if template.name.is_empty() || template.name.chars().count() > 255 {
                 return Err(format!("Invalid template name: '{}'", template.name));
             }
 
@@ -1081,7 +2190,7 @@ impl TemplateManager {
             }
 
             // Validate template content for dangerous patterns
-            self.sanitize_parameter_value(&template.content)
+            self.reject_dangerous_content(&template.content)
                 .map_err(|e| format!("Template '{}' contains dangerous content: {}", template.name, e))?;
 
             // Validate parameters
@@ -1090,12 +2199,12 @@ impl TemplateManager {
             }
 
             for param in &template.parameters {
-                if param.name.is_empty() || param.name.len() > 100 {
+                if param.name.is_empty() || param.name.chars().count() > 100 {
                     return Err(format!("Invalid parameter name in template '{}'", template.name));
                 }
 
                 if let Some(default_value) = &param.default_value {
-                    self.sanitize_parameter_value(default_value)
+                    self.reject_dangerous_content(default_value)
                         .map_err(|e| format!("Parameter '{}' in template '{}' has dangerous default value: {}", param.name, template.name, e))?;
                 }
             }
@@ -1103,7 +2212,7 @@ impl TemplateManager {
 
         // Validate each category
         for category in &export.categories {
-            if category.name.is_empty() || category.name.len() > 255 {
+            if category.name.is_empty() || category.name.chars().count() > 255 {
                 return Err(format!("Invalid category name: '{}'", category.name));
             }
         }
@@ -1111,3 +2220,280 @@ impl TemplateManager {
         Ok(())
     }
 }
+
+/// Nest categories under their parents, keeping the input's sibling order. A category whose
+/// parent doesn't exist becomes a root; so does one category of any parent cycle, which breaks it.
+/// Both are logged, since the database shouldn't contain either.
+pub(crate) fn build_category_tree(categories: Vec<TemplateCategory>) -> Vec<CategoryNode> {
+    use std::collections::{HashMap, HashSet};
+
+    let ids: HashSet<String> = categories.iter().map(|category| category.id.clone()).collect();
+    let mut children: HashMap<String, Vec<TemplateCategory>> = HashMap::new();
+    let mut roots = Vec::new();
+    let mut pending = Vec::new();
+
+    for category in categories {
+        match category.parent_id.clone() {
+            Some(parent_id) if ids.contains(&parent_id) => {
+                pending.push(category.id.clone());
+                children.entry(parent_id).or_default().push(category);
+            }
+            Some(parent_id) => {
+                warn!("Category '{}' refers to missing parent {}; showing it at the top level", category.name, parent_id);
+                roots.push(category);
+            }
+            None => roots.push(category),
+        }
+    }
+
+    fn attach(category: TemplateCategory, children: &mut HashMap<String, Vec<TemplateCategory>>) -> CategoryNode {
+        let nested = children.remove(&category.id).unwrap_or_default();
+        CategoryNode {
+            children: nested.into_iter().map(|child| attach(child, children)).collect(),
+            category,
+        }
+    }
+
+    let mut tree: Vec<CategoryNode> = roots.into_iter().map(|root| attach(root, &mut children)).collect();
+
+    // Whatever wasn't reached from a root sits on a parent cycle
+    for id in pending {
+        let Some(parent_id) = children.keys().find(|parent_id| {
+            children[*parent_id].iter().any(|category| category.id == id)
+        }).cloned() else {
+            continue;
+        };
+        let siblings = children.get_mut(&parent_id).expect("parent was just found");
+        let position = siblings.iter().position(|category| category.id == id).expect("category was just found");
+        let category = siblings.remove(position);
+        warn!("Category '{}' is part of a parent cycle; showing it at the top level", category.name);
+        tree.push(attach(category, &mut children));
+    }
+
+    tree
+}
+
+/// Distinct `{{name}}` placeholders in template content, in order of first use
+fn template_placeholders(content: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut rest = content;
+
+    while let Some(start) = rest.find("{{") {
+        let after_open = &rest[start + 2..];
+        let Some(end) = after_open.find("}}") else { break };
+        let name = &after_open[..end];
+        if !name.is_empty() && !names.iter().any(|existing| existing == name) {
+            names.push(name.to_string());
+        }
+        rest = &after_open[end + 2..];
+    }
+
+    names
+}
+
+/// Parameters gained and lost by replacing `old_content` with `new_content`. A removed and an
+/// added placeholder are reported as a rename when they are the next unpaired ones in order of
+/// first use and appear the same number of times.
+fn diff_parameters(stored: &[String], old_content: &str, new_content: &str) -> ParameterDiff {
+    let proposed = template_placeholders(new_content);
+    let mut removed: Vec<String> = stored.iter().filter(|name| !proposed.contains(name)).cloned().collect();
+    let mut added: Vec<String> = proposed.iter().filter(|name| !stored.contains(name)).cloned().collect();
+
+    let occurrences = |content: &str, name: &str| content.matches(&format!("{{{{{}}}}}", name)).count();
+    let mut renamed = Vec::new();
+    let mut i = 0;
+    while i < removed.len() && !added.is_empty() {
+        if occurrences(old_content, &removed[i]) == occurrences(new_content, &added[0]) {
+            renamed.push(ParameterRename { from: removed.remove(i), to: added.remove(0) });
+        } else {
+            i += 1;
+        }
+    }
+
+    ParameterDiff { added, removed, renamed }
+}
+
+/// Each declared parameter's kind, by name
+fn parameter_kinds(parameters: &[TemplateParameter]) -> std::collections::HashMap<String, ParameterKind> {
+    parameters.iter().map(|param| (param.name.clone(), param.kind)).collect()
+}
+
+/// Replace each `{{name}}` in one pass, so substituted text is never rescanned.
+/// Placeholders without a value are replaced by `unfilled(name)`.
+///
+/// How a value goes in depends on where the placeholder sits in the SQL around it: inside a
+/// string literal, as in `LIKE '%{{q}}%'`, it is escaped for that literal without adding
+/// quotes; in a comment it is kept on one line and can't close the comment; elsewhere an
+/// identifier parameter is quoted as an identifier, a number goes in as-is and anything else
+/// becomes a quoted string literal.
+fn render_placeholders(
+    content: &str,
+    values: &std::collections::HashMap<String, String>,
+    kinds: &std::collections::HashMap<String, ParameterKind>,
+    database_type: Option<&DatabaseType>,
+    unfilled: &dyn Fn(&str) -> String,
+) -> String {
+    let mysql = matches!(database_type, Some(DatabaseType::MySQL));
+    let mut output = String::with_capacity(content.len());
+    let mut context = SqlContext::Code;
+    let mut rest = content;
+
+    while let Some(start) = rest.find("{{") {
+        let after_open = &rest[start + 2..];
+        let Some(end) = after_open.find("}}") else { break };
+        let name = &after_open[..end];
+        let after_close = &after_open[end + 2..];
+
+        // Only the template's own text moves the context; substituted values never do
+        let before = &rest[..start];
+        context = context.advance(before, mysql);
+        output.push_str(before);
+        rest = after_close;
+
+        let Some(value) = values.get(name) else {
+            output.push_str(&unfilled(name));
+            continue;
+        };
+
+        match context {
+            SqlContext::StringLiteral { quote, backslash_escapes } => {
+                let value = if backslash_escapes { value.replace('\\', "\\\\") } else { value.clone() };
+                output.push_str(&value.replace(quote, &format!("{}{}", quote, quote)))
+            }
+            SqlContext::QuotedIdentifier(close) => {
+                output.push_str(&value.replace(close, &format!("{}{}", close, close)))
+            }
+            SqlContext::LineComment => output.push_str(&value.replace(['\r', '\n'], " ")),
+            SqlContext::BlockComment => output.push_str(&value.replace("*/", "* /")),
+            SqlContext::Code => match kinds.get(name).copied().unwrap_or_default() {
+                ParameterKind::Identifier => output.push_str(&quote_identifier_path(value, database_type)),
+                ParameterKind::Value if is_numeric_literal(value) => {
+                    let number = value.trim();
+                    // `x -{{n}}` with n = -1 must not become the comment marker `--1`
+                    if number.starts_with('-') && output.ends_with('-') {
+                        output.push(' ');
+                    }
+                    output.push_str(number);
+                }
+                ParameterKind::Value => output.push_str(&quote_literal(value, database_type)),
+            },
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Where a position in SQL text falls, as far as substituting a value there is concerned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SqlContext {
+    Code,
+    /// Inside a string literal closed by `quote`; `backslash_escapes` when a backslash escapes
+    /// the next character, as in MySQL strings and PostgreSQL `E'...'` strings
+    StringLiteral { quote: char, backslash_escapes: bool },
+    /// Inside an identifier quoted with `"` or a backtick; holds the closing quote
+    QuotedIdentifier(char),
+    LineComment,
+    BlockComment,
+}
+
+impl SqlContext {
+    /// The context at the end of `text`, starting from this one. In MySQL `"` opens a string
+    /// literal rather than an identifier.
+    fn advance(self, text: &str, mysql: bool) -> SqlContext {
+        let mut context = self;
+        let mut chars = text.chars().peekable();
+        // The two characters before `c`, to spot an `E` prefix standing on its own
+        let mut previous: [Option<char>; 2] = [None, None];
+
+        while let Some(c) = chars.next() {
+            context = match context {
+                SqlContext::Code => match c {
+                    '\'' => {
+                        let escape_prefix = matches!(previous[1], Some('E' | 'e'))
+                            && !previous[0].map_or(false, |p| p.is_alphanumeric() || p == '_');
+                        SqlContext::StringLiteral { quote: c, backslash_escapes: mysql || escape_prefix }
+                    }
+                    '"' if mysql => SqlContext::StringLiteral { quote: c, backslash_escapes: true },
+                    '"' | '`' => SqlContext::QuotedIdentifier(c),
+                    '-' if chars.peek() == Some(&'-') => {
+                        chars.next();
+                        SqlContext::LineComment
+                    }
+                    '/' if chars.peek() == Some(&'*') => {
+                        chars.next();
+                        SqlContext::BlockComment
+                    }
+                    _ => SqlContext::Code,
+                },
+                SqlContext::StringLiteral { backslash_escapes: true, .. } if c == '\\' => {
+                    chars.next();
+                    context
+                }
+                // A doubled quote closes and immediately reopens the literal
+                SqlContext::StringLiteral { quote, .. } if c == quote => SqlContext::Code,
+                SqlContext::QuotedIdentifier(close) if c == close => SqlContext::Code,
+                SqlContext::LineComment if c == '\n' => SqlContext::Code,
+                SqlContext::BlockComment if c == '*' && chars.peek() == Some(&'/') => {
+                    chars.next();
+                    SqlContext::Code
+                }
+                unchanged => unchanged,
+            };
+            previous = [previous[1], Some(c)];
+        }
+
+        context
+    }
+}
+
+/// A plain decimal number that can be spliced in unquoted
+fn is_numeric_literal(value: &str) -> bool {
+    let digits = value.trim().strip_prefix('-').unwrap_or(value.trim());
+    let mut parts = digits.splitn(2, '.');
+    let whole = parts.next().unwrap_or("");
+    let fraction = parts.next();
+    !whole.is_empty()
+        && whole.chars().all(|c| c.is_ascii_digit())
+        && fraction.map_or(true, |f| !f.is_empty() && f.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Escape a value for use inside a string literal closed by `quote`, doubling backslashes too
+/// where they act as escapes
+fn escape_literal(value: &str, quote: char, backslash_escapes: bool) -> String {
+    let escaped = if backslash_escapes { value.replace('\\', "\\\\") } else { value.to_string() };
+    escaped.replace(quote, &format!("{}{}", quote, quote))
+}
+
+/// Quote a value as a string literal; MySQL treats backslashes as escapes by default
+fn quote_literal(value: &str, database_type: Option<&DatabaseType>) -> String {
+    let mysql = matches!(database_type, Some(DatabaseType::MySQL));
+    format!("'{}'", escape_literal(value, '\'', mysql))
+}
+
+/// Quote a possibly schema-qualified name, e.g. `sales.orders`, one part at a time with the
+/// dialect's identifier quoting (standard double quotes when the database is unknown)
+fn quote_identifier_path(value: &str, database_type: Option<&DatabaseType>) -> String {
+    let driver = database_type.and_then(|db_type| DatabaseDriverFactory::try_create_driver(db_type).ok());
+    value
+        .trim()
+        .split('.')
+        .map(|part| match &driver {
+            Some(driver) => driver.quote_identifier(part),
+            None => format!("\"{}\"", part.replace('"', "\"\"")),
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// FTS5 query requiring every word of `query` as a prefix, each quoted so user input
+/// can't use FTS operators; `None` when there are no words
+fn fts_match_expression(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("\"{}\"*", term))
+        .collect();
+
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
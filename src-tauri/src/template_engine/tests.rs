@@ -0,0 +1,1363 @@
+use super::template_manager::TemplateManager;
+use super::types::ImportMode;
+use uuid::Uuid;
+
+/// Fresh database path under the system temp directory
+fn temp_db_path(label: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("cortex-{}-{}.db", label, Uuid::new_v4()))
+}
+
+fn remove_db_files(path: &std::path::Path) {
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+}
+
+#[tokio::test]
+async fn test_corrupt_database_is_backed_up_and_recreated() {
+    let path = temp_db_path("corrupt");
+    std::fs::write(&path, b"this is definitely not a sqlite database, just some text padding it out").unwrap();
+
+    let manager = TemplateManager::new(path.to_str().unwrap()).expect("corrupt database should be recovered");
+    assert!(manager.get_categories().await.unwrap().iter().any(|c| c.id == "default-general"));
+
+    let dir = path.parent().unwrap();
+    let file_name = path.file_name().unwrap().to_str().unwrap().to_string();
+    let backups: Vec<_> = std::fs::read_dir(dir)
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.starts_with(&format!("{}.corrupt-", file_name))
+        })
+        .collect();
+    assert_eq!(backups.len(), 1, "the corrupt file should be kept as a backup");
+
+    for backup in backups {
+        let _ = std::fs::remove_file(backup.path());
+    }
+    remove_db_files(&path);
+}
+
+#[tokio::test]
+async fn test_connection_pragmas_enable_wal_and_cascade() {
+    use super::template_manager::{configure_connection, DEFAULT_BUSY_TIMEOUT};
+    use super::types::{CreateParameterRequest, CreateTemplateRequest};
+    use rusqlite::Connection;
+
+    let path = temp_db_path("pragmas");
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+
+    let template = manager
+        .create_template(CreateTemplateRequest {
+            name: "Orders by date".to_string(),
+            description: None,
+            category_id: "default-general".to_string(),
+            content: "SELECT * FROM orders WHERE created_at > {{start_date}}".to_string(),
+            parameters: vec![CreateParameterRequest {
+                name: "start_date".to_string(),
+                default_value: Some("2024-01-01".to_string()),
+                description: None,
+                kind: Default::default(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    // A second connection configured the same way sees WAL mode and enforces cascades
+    let conn = Connection::open(&path).unwrap();
+    configure_connection(&conn, DEFAULT_BUSY_TIMEOUT).unwrap();
+
+    let journal_mode: String = conn.query_row("PRAGMA journal_mode", [], |row| row.get(0)).unwrap();
+    assert_eq!(journal_mode.to_lowercase(), "wal");
+    let foreign_keys: i64 = conn.query_row("PRAGMA foreign_keys", [], |row| row.get(0)).unwrap();
+    assert_eq!(foreign_keys, 1);
+
+    conn.execute("DELETE FROM templates WHERE id = ?1", [&template.id]).unwrap();
+    let remaining: i64 = conn
+        .query_row("SELECT COUNT(*) FROM template_parameters WHERE template_id = ?1", [&template.id], |row| row.get(0))
+        .unwrap();
+    assert_eq!(remaining, 0, "deleting a template must cascade to its parameters");
+
+    drop(conn);
+    drop(manager);
+    remove_db_files(&path);
+}
+
+#[tokio::test]
+async fn test_delete_template_removes_parameters() {
+    use super::types::{CreateParameterRequest, CreateTemplateRequest};
+    use rusqlite::Connection;
+
+    let path = temp_db_path("delete-cascade");
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+
+    let template = manager
+        .create_template(CreateTemplateRequest {
+            name: "Top customers".to_string(),
+            description: None,
+            category_id: "default-general".to_string(),
+            content: "SELECT * FROM customers WHERE country = {{country}} LIMIT {{limit}}".to_string(),
+            parameters: vec![
+                CreateParameterRequest {
+                    name: "country".to_string(),
+                    default_value: None,
+                    description: None,
+                    kind: Default::default(),
+                },
+                CreateParameterRequest {
+                    name: "limit".to_string(),
+                    default_value: Some("10".to_string()),
+                    description: None,
+                    kind: Default::default(),
+                },
+            ],
+        })
+        .await
+        .unwrap();
+    assert_eq!(template.parameters.len(), 2);
+
+    manager.delete_template(template.id.clone()).await.unwrap();
+    assert_eq!(manager.purge_deleted_templates(0).await.unwrap(), 1);
+
+    let conn = Connection::open(&path).unwrap();
+    let remaining: i64 = conn
+        .query_row("SELECT COUNT(*) FROM template_parameters", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(remaining, 0, "purging a template must not orphan parameters");
+
+    drop(conn);
+    drop(manager);
+    remove_db_files(&path);
+}
+
+#[tokio::test]
+async fn test_deleted_templates_go_to_trash() {
+    use super::types::{CreateTemplateRequest, TemplateFilter};
+
+    let path = temp_db_path("trash");
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+
+    let request = || CreateTemplateRequest {
+        name: "Weekly signups".to_string(),
+        description: None,
+        category_id: "default-general".to_string(),
+        content: "SELECT COUNT(*) FROM signups".to_string(),
+        parameters: vec![],
+    };
+    let template = manager.create_template(request()).await.unwrap();
+
+    manager.delete_template(template.id.clone()).await.unwrap();
+    assert!(manager.delete_template(template.id.clone()).await.is_err());
+    assert!(manager.get_template_by_id(&template.id).await.is_err());
+    assert!(manager.get_templates(TemplateFilter::default()).await.unwrap().is_empty());
+    assert!(manager.search_templates("signups".to_string()).await.unwrap().is_empty());
+    assert_eq!(manager.count_templates().await.unwrap(), 0);
+
+    let trash = manager.list_deleted_templates().await.unwrap();
+    assert_eq!(trash.len(), 1);
+    assert_eq!(trash[0].template.id, template.id);
+
+    let restored = manager.restore_template(template.id.clone()).await.unwrap();
+    assert_eq!(restored.name, "Weekly signups");
+    assert!(manager.restore_template(template.id.clone()).await.is_err());
+    assert_eq!(manager.search_templates("signups".to_string()).await.unwrap().len(), 1);
+
+    // Recently trashed templates survive a purge by age
+    manager.delete_template(template.id.clone()).await.unwrap();
+    assert_eq!(manager.purge_deleted_templates(30).await.unwrap(), 0);
+    assert_eq!(manager.list_deleted_templates().await.unwrap().len(), 1);
+
+    // A new template may reuse a trashed template's name; the trashed one stays in the trash
+    // but can't be restored while the name is taken
+    let replacement = manager.create_template(request()).await.unwrap();
+    assert_ne!(replacement.id, template.id);
+    assert_eq!(manager.list_deleted_templates().await.unwrap().len(), 1);
+    let conflict = manager.restore_template(template.id.clone()).await.unwrap_err();
+    assert!(conflict.contains("same name"), "{}", conflict);
+
+    manager.delete_template(replacement.id.clone()).await.unwrap();
+    assert_eq!(manager.list_deleted_templates().await.unwrap().len(), 2);
+    assert_eq!(manager.restore_template(template.id.clone()).await.unwrap().id, template.id);
+
+    drop(manager);
+    remove_db_files(&path);
+}
+
+#[tokio::test]
+async fn test_bulk_template_operations_report_each_id() {
+    use super::types::{CreateCategoryRequest, CreateTemplateRequest, TemplateFilter};
+
+    let path = temp_db_path("bulk");
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+
+    let archive = manager
+        .create_category(CreateCategoryRequest { name: "Archive".to_string(), parent_id: None })
+        .await
+        .unwrap();
+    let create = |name: &str, category_id: &str| CreateTemplateRequest {
+        name: name.to_string(),
+        description: None,
+        category_id: category_id.to_string(),
+        content: "SELECT 1".to_string(),
+        parameters: vec![],
+    };
+    let mut ids = Vec::new();
+    for name in ["Daily", "Weekly", "Monthly"] {
+        ids.push(manager.create_template(create(name, "default-general")).await.unwrap().id);
+    }
+    // "Weekly" can't move into Archive, which already has one
+    manager.create_template(create("Weekly", &archive.id)).await.unwrap();
+
+    assert!(manager.bulk_move_templates(ids.clone(), "no-such-category".to_string()).await.is_err());
+
+    let mut request = ids.clone();
+    request.push("missing".to_string());
+    let results = manager.bulk_move_templates(request, archive.id.clone()).await.unwrap();
+    assert_eq!(results.iter().map(|r| r.success).collect::<Vec<_>>(), vec![true, false, true, false]);
+    assert!(results[1].error.as_deref().unwrap().contains("already exists"));
+    assert!(results[3].error.as_deref().unwrap().contains("not found"));
+    assert_eq!(manager.get_template_by_id(&ids[0]).await.unwrap().category_id, archive.id);
+    assert_eq!(manager.get_template_by_id(&ids[1]).await.unwrap().category_id, "default-general");
+
+    let results = manager.bulk_set_favorite(ids[..2].to_vec(), true).await.unwrap();
+    assert!(results.iter().all(|r| r.success));
+    let favorites = manager
+        .get_templates(TemplateFilter { is_favorite: Some(true), ..Default::default() })
+        .await
+        .unwrap();
+    assert_eq!(favorites.len(), 2);
+
+    let results = manager.bulk_delete_templates(vec![ids[0].clone(), ids[0].clone(), ids[2].clone()]).await.unwrap();
+    assert_eq!(results.iter().map(|r| r.success).collect::<Vec<_>>(), vec![true, false, true]);
+    assert_eq!(manager.list_deleted_templates().await.unwrap().len(), 2);
+    assert_eq!(manager.count_templates().await.unwrap(), 2);
+
+    drop(manager);
+    remove_db_files(&path);
+}
+
+#[tokio::test]
+async fn test_delete_category_with_strategy() {
+    use super::types::{CategoryDeleteStrategy, CreateCategoryRequest, CreateTemplateRequest};
+
+    let path = temp_db_path("category-strategy");
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+
+    let category = |name: &str, parent_id: Option<&str>| CreateCategoryRequest {
+        name: name.to_string(),
+        parent_id: parent_id.map(str::to_string),
+    };
+    let template = |name: &str, category_id: &str| CreateTemplateRequest {
+        name: name.to_string(),
+        description: None,
+        category_id: category_id.to_string(),
+        content: "SELECT 1".to_string(),
+        parameters: vec![],
+    };
+
+    // Sales > Regional > EMEA, with a template at each of the top two levels
+    let sales = manager.create_category(category("Sales", None)).await.unwrap();
+    let regional = manager.create_category(category("Regional", Some(&sales.id))).await.unwrap();
+    let emea = manager.create_category(category("EMEA", Some(&regional.id))).await.unwrap();
+    manager.create_template(template("Pipeline", &sales.id)).await.unwrap();
+    manager.create_template(template("By region", &regional.id)).await.unwrap();
+
+    let reassign_to = |target: &str| CategoryDeleteStrategy::Reassign(target.to_string());
+    assert!(manager.delete_category_with_strategy(sales.id.clone(), reassign_to(&sales.id)).await.is_err());
+    assert!(manager.delete_category_with_strategy(sales.id.clone(), reassign_to(&emea.id)).await.is_err());
+    assert!(manager.delete_category_with_strategy(sales.id.clone(), reassign_to("missing")).await.is_err());
+
+    // A name clash in the target aborts without changing anything
+    let clash = manager.create_template(template("Pipeline", "default-general")).await.unwrap();
+    let err = manager.delete_category_with_strategy(sales.id.clone(), reassign_to("default-general")).await.unwrap_err();
+    assert!(err.contains("Pipeline"), "unexpected error {}", err);
+    assert!(manager.get_categories().await.unwrap().iter().any(|c| c.id == sales.id));
+
+    manager.delete_template(clash.id).await.unwrap();
+    manager.delete_category_with_strategy(sales.id.clone(), reassign_to("default-general")).await.unwrap();
+    let categories = manager.get_categories().await.unwrap();
+    assert!(!categories.iter().any(|c| c.id == sales.id));
+    let regional_now = categories.iter().find(|c| c.id == regional.id).unwrap();
+    assert_eq!(regional_now.parent_id.as_deref(), Some("default-general"));
+    assert_eq!(categories.iter().find(|c| c.id == "default-general").unwrap().template_count, 1);
+
+    manager.delete_category_with_strategy(regional.id.clone(), CategoryDeleteStrategy::CascadeDelete).await.unwrap();
+    let categories = manager.get_categories().await.unwrap();
+    assert!(!categories.iter().any(|c| c.id == regional.id || c.id == emea.id));
+    assert_eq!(manager.count_templates().await.unwrap(), 1);
+
+    drop(manager);
+    remove_db_files(&path);
+}
+
+#[tokio::test]
+async fn test_category_tree_nests_children() {
+    use super::types::CreateCategoryRequest;
+
+    let path = temp_db_path("category-tree");
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+
+    let sales = manager
+        .create_category(CreateCategoryRequest { name: "Sales".to_string(), parent_id: None })
+        .await
+        .unwrap();
+    for name in ["Regional", "Accounts"] {
+        manager
+            .create_category(CreateCategoryRequest { name: name.to_string(), parent_id: Some(sales.id.clone()) })
+            .await
+            .unwrap();
+    }
+
+    let tree = manager.get_category_tree().await.unwrap();
+    let roots: Vec<&str> = tree.iter().map(|node| node.category.name.as_str()).collect();
+    assert_eq!(roots, vec!["General", "Sales"]);
+    let children: Vec<&str> = tree[1].children.iter().map(|node| node.category.name.as_str()).collect();
+    assert_eq!(children, vec!["Accounts", "Regional"]);
+
+    drop(manager);
+    remove_db_files(&path);
+}
+
+#[test]
+fn test_category_tree_surfaces_orphans_and_cycles() {
+    use super::template_manager::build_category_tree;
+    use super::types::TemplateCategory;
+    use chrono::Utc;
+
+    let category = |id: &str, parent_id: Option<&str>| TemplateCategory {
+        id: id.to_string(),
+        name: id.to_string(),
+        parent_id: parent_id.map(str::to_string),
+        created_at: Utc::now(),
+        template_count: 0,
+    };
+
+    let tree = build_category_tree(vec![
+        category("a", Some("b")),
+        category("b", Some("a")),
+        category("child", Some("a")),
+        category("orphan", Some("gone")),
+        category("root", None),
+    ]);
+
+    let roots: Vec<&str> = tree.iter().map(|node| node.category.id.as_str()).collect();
+    assert_eq!(roots, vec!["orphan", "root", "a"]);
+    let under_a: Vec<&str> = tree[2].children.iter().map(|node| node.category.id.as_str()).collect();
+    assert_eq!(under_a, vec!["b", "child"]);
+    assert!(tree[2].children[0].children.is_empty(), "the cycle must be broken");
+}
+
+#[tokio::test]
+async fn test_upsert_template_is_idempotent() {
+    use super::types::{CreateParameterRequest, CreateTemplateRequest};
+
+    let path = temp_db_path("upsert");
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+
+    let request = |content: &str, parameter: &str| CreateTemplateRequest {
+        name: "Daily signups".to_string(),
+        description: None,
+        category_id: "default-general".to_string(),
+        content: content.to_string(),
+        parameters: vec![CreateParameterRequest {
+            name: parameter.to_string(),
+            default_value: None,
+            description: None,
+            kind: Default::default(),
+        }],
+    };
+
+    let first = manager
+        .upsert_template(request("SELECT * FROM signups WHERE day = {{day}}", "day"))
+        .await
+        .unwrap();
+    assert!(first.created);
+
+    let second = manager
+        .upsert_template(request("SELECT * FROM signups WHERE date = {{date}}", "date"))
+        .await
+        .unwrap();
+    assert!(!second.created);
+    assert_eq!(second.template.id, first.template.id);
+    assert!(second.template.content.contains("{{date}}"));
+    assert_eq!(second.template.parameters.len(), 1);
+    assert_eq!(second.template.parameters[0].name, "date");
+    assert_eq!(manager.count_templates().await.unwrap(), 1);
+
+    drop(manager);
+    remove_db_files(&path);
+}
+
+#[tokio::test]
+async fn test_parameter_order_round_trips() {
+    use super::types::{CreateParameterRequest, CreateTemplateRequest, UpdateTemplateRequest};
+
+    let path = temp_db_path("param-order");
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+
+    let parameters = |names: &[&str]| -> Vec<CreateParameterRequest> {
+        names
+            .iter()
+            .map(|name| CreateParameterRequest {
+                name: name.to_string(),
+                default_value: None,
+                description: None,
+                kind: Default::default(),
+            })
+            .collect()
+    };
+    let names = |template: &super::types::Template| -> Vec<String> {
+        template.parameters.iter().map(|p| p.name.clone()).collect()
+    };
+
+    let template = manager
+        .create_template(CreateTemplateRequest {
+            name: "Orders in range".to_string(),
+            description: None,
+            category_id: "default-general".to_string(),
+            content: "SELECT * FROM orders WHERE created_at BETWEEN {{start_date}} AND {{end_date}} AND status = {{status}}"
+                .to_string(),
+            parameters: parameters(&["start_date", "end_date", "status"]),
+        })
+        .await
+        .unwrap();
+    assert_eq!(names(&template), ["start_date", "end_date", "status"]);
+
+    let reloaded = manager.get_template_by_id(&template.id).await.unwrap();
+    assert_eq!(names(&reloaded), ["start_date", "end_date", "status"]);
+    assert_eq!(reloaded.parameters.iter().map(|p| p.ordinal).collect::<Vec<_>>(), [0, 1, 2]);
+
+    let updated = manager
+        .update_template(
+            template.id.clone(),
+            UpdateTemplateRequest {
+                name: None,
+                description: None,
+                category_id: None,
+                content: None,
+                parameters: Some(parameters(&["status", "start_date", "end_date"])),
+                is_favorite: None,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(names(&updated), ["status", "start_date", "end_date"]);
+
+    drop(manager);
+    remove_db_files(&path);
+}
+
+#[tokio::test]
+async fn test_parameter_ordinal_migration() {
+    use rusqlite::Connection;
+
+    let path = temp_db_path("ordinal-migration");
+    {
+        // Parameters table as created before ordinals existed
+        let conn = Connection::open(&path).unwrap();
+        conn.execute(
+            "CREATE TABLE template_parameters (
+                id TEXT PRIMARY KEY,
+                template_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                default_value TEXT,
+                description TEXT
+            )",
+            [],
+        )
+        .unwrap();
+    }
+
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+
+    let conn = Connection::open(&path).unwrap();
+    let has_ordinal: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('template_parameters') WHERE name = 'ordinal'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(has_ordinal, 1);
+
+    drop(conn);
+    drop(manager);
+    remove_db_files(&path);
+}
+
+#[tokio::test]
+async fn test_category_counts_follow_moved_template() {
+    use super::types::{CategoryCount, CreateCategoryRequest, CreateTemplateRequest, UpdateTemplateRequest};
+
+    let path = temp_db_path("category-counts");
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+
+    let reports = manager
+        .create_category(CreateCategoryRequest {
+            name: "Reports".to_string(),
+            parent_id: None,
+        })
+        .await
+        .unwrap();
+
+    let template = manager
+        .create_template(CreateTemplateRequest {
+            name: "Monthly revenue".to_string(),
+            description: None,
+            category_id: "default-general".to_string(),
+            content: "SELECT SUM(total) FROM orders".to_string(),
+            parameters: vec![],
+        })
+        .await
+        .unwrap();
+
+    manager
+        .update_template(
+            template.id.clone(),
+            UpdateTemplateRequest {
+                name: None,
+                description: None,
+                category_id: Some(reports.id.clone()),
+                content: None,
+                parameters: None,
+                is_favorite: None,
+            },
+        )
+        .await
+        .unwrap();
+
+    let counts = manager
+        .category_template_counts(&["default-general".to_string(), reports.id.clone(), reports.id.clone()])
+        .await
+        .unwrap();
+    assert_eq!(
+        counts,
+        vec![
+            CategoryCount { category_id: "default-general".to_string(), template_count: 0 },
+            CategoryCount { category_id: reports.id.clone(), template_count: 1 },
+        ]
+    );
+
+    drop(manager);
+    remove_db_files(&path);
+}
+
+/// Export bundle whose second template points at a category that doesn't exist
+fn export_with_broken_template() -> String {
+    use super::types::{Template, TemplateCategory, TemplateExport};
+    use chrono::Utc;
+
+    let template = |name: &str, category_id: &str| Template {
+        id: Uuid::new_v4().to_string(),
+        name: name.to_string(),
+        description: None,
+        category_id: category_id.to_string(),
+        content: "SELECT 1".to_string(),
+        parameters: vec![],
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        usage_count: 0,
+        is_favorite: false,
+        warnings: vec![],
+    };
+
+    let export = TemplateExport {
+        templates: vec![template("Fine", "imported-category"), template("Orphan", "missing-category")],
+        categories: vec![TemplateCategory {
+            id: "imported-category".to_string(),
+            name: "Imported".to_string(),
+            parent_id: None,
+            created_at: Utc::now(),
+            template_count: 1,
+        }],
+        export_version: "1.0".to_string(),
+        exported_at: Utc::now(),
+    };
+    serde_json::to_string(&export).unwrap()
+}
+
+#[tokio::test]
+async fn test_atomic_import_rolls_back_on_error() {
+    let path = temp_db_path("atomic-import");
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+
+    let result = manager.import_templates(export_with_broken_template(), true, ImportMode::Skip).await.unwrap();
+    assert!(result.rolled_back);
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.imported_templates, 0);
+    assert_eq!(result.imported_categories, 0);
+    assert_eq!(manager.count_templates().await.unwrap(), 0);
+    assert!(!manager.get_categories().await.unwrap().iter().any(|c| c.id == "imported-category"));
+
+    // Best effort keeps what succeeded
+    let result = manager.import_templates(export_with_broken_template(), false, ImportMode::Skip).await.unwrap();
+    assert!(!result.rolled_back);
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.imported_templates, 1);
+    assert_eq!(manager.count_templates().await.unwrap(), 1);
+
+    drop(manager);
+    remove_db_files(&path);
+}
+
+#[tokio::test]
+async fn test_import_remaps_conflicting_category_ids() {
+    use super::types::{Template, TemplateCategory, TemplateExport};
+    use chrono::Utc;
+
+    let path = temp_db_path("import-remap");
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+
+    let category = |id: &str, name: &str, parent_id: Option<&str>| TemplateCategory {
+        id: id.to_string(),
+        name: name.to_string(),
+        parent_id: parent_id.map(str::to_string),
+        created_at: Utc::now(),
+        template_count: 0,
+    };
+    let template = |name: &str, category_id: &str| Template {
+        id: Uuid::new_v4().to_string(),
+        name: name.to_string(),
+        description: None,
+        category_id: category_id.to_string(),
+        content: "SELECT 1".to_string(),
+        parameters: vec![],
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        usage_count: 0,
+        is_favorite: false,
+        warnings: vec![],
+    };
+
+    // "default-general" already exists here under another name; the child is listed before its parent
+    let export = TemplateExport {
+        templates: vec![template("Revenue", "default-general"), template("Churn", "child")],
+        categories: vec![category("child", "Child", Some("default-general")), category("default-general", "Finance", None)],
+        export_version: "1.0".to_string(),
+        exported_at: Utc::now(),
+    };
+    let result = manager.import_templates(serde_json::to_string(&export).unwrap(), true, ImportMode::Skip).await.unwrap();
+    assert!(result.errors.is_empty(), "unexpected errors {:?}", result.errors);
+    assert_eq!(result.imported_categories, 2);
+    assert_eq!(result.imported_templates, 2);
+
+    let categories = manager.get_categories().await.unwrap();
+    let finance = categories.iter().find(|c| c.name == "Finance").unwrap();
+    let child = categories.iter().find(|c| c.name == "Child").unwrap();
+    assert_ne!(finance.id, "default-general");
+    assert_eq!(child.parent_id.as_deref(), Some(finance.id.as_str()));
+
+    let general = categories.iter().find(|c| c.id == "default-general").unwrap();
+    assert_ne!(general.name, "Finance", "the existing category must be left alone");
+
+    let counts = manager
+        .category_template_counts(&[finance.id.clone(), child.id.clone(), "default-general".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(counts.iter().map(|c| c.template_count).collect::<Vec<_>>(), vec![1, 1, 0]);
+
+    // A category that matches by name is merged instead of duplicated
+    let export = TemplateExport {
+        templates: vec![template("Margin", "elsewhere")],
+        categories: vec![category("elsewhere", "Finance", None)],
+        export_version: "1.0".to_string(),
+        exported_at: Utc::now(),
+    };
+    let result = manager.import_templates(serde_json::to_string(&export).unwrap(), true, ImportMode::Skip).await.unwrap();
+    assert!(result.errors.is_empty(), "unexpected errors {:?}", result.errors);
+    assert_eq!(result.imported_categories, 0);
+    assert_eq!(result.imported_templates, 1);
+    let counts = manager.category_template_counts(&[finance.id.clone()]).await.unwrap();
+    assert_eq!(counts[0].template_count, 2);
+
+    drop(manager);
+    remove_db_files(&path);
+}
+
+#[tokio::test]
+async fn test_import_modes_for_existing_templates() {
+    use super::types::{CreateParameterRequest, CreateTemplateRequest, Template, TemplateExport, TemplateParameter};
+    use chrono::Utc;
+
+    let path = temp_db_path("import-modes");
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+
+    let existing = manager
+        .create_template(CreateTemplateRequest {
+            name: "Orders".to_string(),
+            description: Some("old".to_string()),
+            category_id: "default-general".to_string(),
+            content: "SELECT * FROM orders WHERE id = {{id}}".to_string(),
+            parameters: vec![CreateParameterRequest { name: "id".to_string(), default_value: None, description: None, kind: Default::default() }],
+        })
+        .await
+        .unwrap();
+
+    // The pack's copy shares the existing template's ids, as a re-imported export would
+    let pack = || {
+        let export = TemplateExport {
+            templates: vec![Template {
+                id: existing.id.clone(),
+                name: "Orders".to_string(),
+                description: Some("new".to_string()),
+                category_id: "default-general".to_string(),
+                content: "SELECT * FROM orders WHERE status = {{status}}".to_string(),
+                parameters: vec![TemplateParameter {
+                    id: existing.parameters[0].id.clone(),
+                    template_id: existing.id.clone(),
+                    name: "status".to_string(),
+                    default_value: Some("open".to_string()),
+                    description: None,
+                    ordinal: 0,
+                    kind: Default::default(),
+                }],
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                usage_count: 0,
+                is_favorite: false,
+                warnings: vec![],
+            }],
+            categories: vec![],
+            export_version: "1.0".to_string(),
+            exported_at: Utc::now(),
+        };
+        serde_json::to_string(&export).unwrap()
+    };
+
+    let result = manager.import_templates(pack(), true, ImportMode::Skip).await.unwrap();
+    assert_eq!((result.skipped_duplicates, result.overwritten_templates, result.renamed_templates), (1, 0, 0));
+    assert_eq!(manager.get_template_by_id(&existing.id).await.unwrap().description.as_deref(), Some("old"));
+
+    let result = manager.import_templates(pack(), true, ImportMode::Overwrite).await.unwrap();
+    assert!(result.errors.is_empty(), "unexpected errors {:?}", result.errors);
+    assert_eq!((result.imported_templates, result.overwritten_templates), (0, 1));
+    let updated = manager.get_template_by_id(&existing.id).await.unwrap();
+    assert_eq!(updated.description.as_deref(), Some("new"));
+    assert!(updated.content.contains("{{status}}"));
+    assert_eq!(updated.parameters.len(), 1);
+    assert_eq!(updated.parameters[0].name, "status");
+
+    for expected in ["Orders (imported)", "Orders (imported 2)"] {
+        let result = manager.import_templates(pack(), true, ImportMode::Rename).await.unwrap();
+        assert!(result.errors.is_empty(), "unexpected errors {:?}", result.errors);
+        assert_eq!(result.renamed_templates, 1);
+        let names: Vec<String> = manager
+            .get_templates(Default::default())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert!(names.iter().any(|name| name == expected), "missing {} in {:?}", expected, names);
+    }
+    assert_eq!(manager.count_templates().await.unwrap(), 3);
+
+    drop(manager);
+    remove_db_files(&path);
+}
+
+#[tokio::test]
+async fn test_parameter_length_counts_characters() {
+    use super::types::{CreateParameterRequest, CreateTemplateRequest, ParameterSubstitution};
+
+    let path = temp_db_path("unicode-length");
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+
+    let template = manager
+        .create_template(CreateTemplateRequest {
+            name: "顧客検索".to_string(),
+            description: None,
+            category_id: "default-general".to_string(),
+            content: "SELECT * FROM customers WHERE note LIKE {{note}}".to_string(),
+            parameters: vec![CreateParameterRequest {
+                name: "note".to_string(),
+                default_value: None,
+                description: None,
+                kind: Default::default(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let substitute = |value: String| {
+        vec![ParameterSubstitution {
+            parameter_name: "note".to_string(),
+            value,
+        }]
+    };
+
+    // 1000 three-byte characters is 3000 bytes but within the 1000-character limit
+    let at_limit = "漢".repeat(1000);
+    assert!(manager.process_template_parameters(template.id.clone(), substitute(at_limit)).await.is_ok());
+
+    let over_limit = "漢".repeat(1001);
+    let error = manager
+        .process_template_parameters(template.id.clone(), substitute(over_limit))
+        .await
+        .unwrap_err();
+    assert!(error.contains("1000 characters"));
+
+    // Four-byte characters at the limit still pass the byte-size guard
+    let emoji = "😀".repeat(1000);
+    assert!(manager.process_template_parameters(template.id.clone(), substitute(emoji)).await.is_ok());
+
+    drop(manager);
+    remove_db_files(&path);
+}
+
+#[tokio::test]
+async fn test_template_listing_is_capped_by_default() {
+    use super::template_manager::DEFAULT_TEMPLATE_PAGE_SIZE;
+    use super::types::{CreateTemplateRequest, TemplateFilter};
+
+    let path = temp_db_path("page-cap");
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+
+    for i in 0..DEFAULT_TEMPLATE_PAGE_SIZE + 30 {
+        manager
+            .create_template(CreateTemplateRequest {
+                name: format!("Report {:03}", i),
+                description: None,
+                category_id: "default-general".to_string(),
+                content: format!("SELECT {}", i),
+                parameters: vec![],
+            })
+            .await
+            .unwrap();
+    }
+    let total = manager.count_templates().await.unwrap();
+
+    let templates = manager.get_templates(TemplateFilter::default()).await.unwrap();
+    assert_eq!(templates.len() as u32, DEFAULT_TEMPLATE_PAGE_SIZE);
+
+    let first = manager.get_template_page(TemplateFilter::default()).await.unwrap();
+    assert_eq!(first.templates.len() as u32, DEFAULT_TEMPLATE_PAGE_SIZE);
+    assert_eq!(first.total_count, total);
+
+    let rest = manager
+        .get_template_page(TemplateFilter { offset: Some(DEFAULT_TEMPLATE_PAGE_SIZE), ..Default::default() })
+        .await
+        .unwrap();
+    assert_eq!(rest.templates.len() as u32, total - DEFAULT_TEMPLATE_PAGE_SIZE);
+    assert_eq!(rest.total_count, total);
+
+    // The total honours the same filters as the page
+    let searched = manager
+        .get_template_page(TemplateFilter { search_query: Some("Report 00".to_string()), limit: Some(5), ..Default::default() })
+        .await
+        .unwrap();
+    assert_eq!(searched.templates.len(), 5);
+    assert_eq!(searched.total_count, 10);
+
+    remove_db_files(&path);
+}
+
+#[tokio::test]
+async fn test_dashboard_snapshot_lists_favorites_and_most_used() {
+    use super::types::{CreateTemplateRequest, UpdateTemplateRequest};
+
+    let path = temp_db_path("dashboard");
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+
+    let mut ids = Vec::new();
+    for name in ["Alpha", "Beta", "Gamma"] {
+        let template = manager
+            .create_template(CreateTemplateRequest {
+                name: name.to_string(),
+                description: None,
+                category_id: "default-general".to_string(),
+                content: "SELECT 1".to_string(),
+                parameters: vec![],
+            })
+            .await
+            .unwrap();
+        ids.push(template.id);
+    }
+
+    manager
+        .update_template(ids[2].clone(), UpdateTemplateRequest {
+            name: None,
+            description: None,
+            category_id: None,
+            content: None,
+            parameters: None,
+            is_favorite: Some(true),
+        })
+        .await
+        .unwrap();
+    for _ in 0..3 {
+        manager.increment_usage_count(ids[1].clone()).await.unwrap();
+    }
+
+    // Used to deadlock by re-locking the connection for each list
+    let snapshot = manager.get_template_statistics().await.unwrap();
+    assert_eq!(snapshot.total_templates, manager.count_templates().await.unwrap());
+    assert_eq!(snapshot.most_used_templates[0].id, ids[1]);
+    let favorites: Vec<&str> = snapshot.favorite_templates.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(favorites, vec!["Gamma"]);
+    assert!(snapshot.category_usage.iter().any(|usage| usage.total_usage >= 3));
+
+    drop(manager);
+    remove_db_files(&path);
+}
+
+#[tokio::test]
+async fn test_schema_version_is_recorded_and_enforced() {
+    use super::template_manager::SCHEMA_VERSION;
+    use rusqlite::Connection;
+
+    let path = temp_db_path("schema-version");
+    {
+        // An unversioned database from before migrations existed
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE template_categories (id TEXT PRIMARY KEY, name TEXT NOT NULL UNIQUE, parent_id TEXT,
+                 created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP)",
+        )
+        .unwrap();
+    }
+
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+    drop(manager);
+
+    let conn = Connection::open(&path).unwrap();
+    let version: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap();
+    assert_eq!(version, SCHEMA_VERSION);
+
+    // Reopening an up-to-date database is a no-op
+    assert!(TemplateManager::new(path.to_str().unwrap()).is_ok());
+
+    // A database written by a newer release is refused rather than modified
+    conn.execute_batch(&format!("PRAGMA user_version = {}", SCHEMA_VERSION + 1)).unwrap();
+    drop(conn);
+    assert!(TemplateManager::new(path.to_str().unwrap()).is_err());
+
+    remove_db_files(&path);
+}
+
+#[tokio::test]
+async fn test_trash_migration_keeps_rows_and_frees_trashed_names() {
+    use super::types::CreateTemplateRequest;
+    use rusqlite::Connection;
+
+    let path = temp_db_path("trash-migration");
+    {
+        // Rebuilding the templates table must not cascade into its parameters
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE template_categories (id TEXT PRIMARY KEY, name TEXT NOT NULL UNIQUE, parent_id TEXT,
+                 created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP);
+             CREATE TABLE templates (id TEXT PRIMARY KEY, name TEXT NOT NULL, description TEXT,
+                 category_id TEXT NOT NULL, content TEXT NOT NULL,
+                 created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP, updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                 usage_count INTEGER NOT NULL DEFAULT 0, is_favorite INTEGER NOT NULL DEFAULT 0,
+                 FOREIGN KEY (category_id) REFERENCES template_categories(id) ON DELETE CASCADE,
+                 UNIQUE(name, category_id));
+             CREATE TABLE template_parameters (id TEXT PRIMARY KEY, template_id TEXT NOT NULL, name TEXT NOT NULL,
+                 default_value TEXT, description TEXT,
+                 FOREIGN KEY (template_id) REFERENCES templates(id) ON DELETE CASCADE);
+             INSERT INTO template_categories (id, name) VALUES ('default-general', 'General');
+             INSERT INTO templates (id, name, category_id, content, created_at, updated_at)
+                 VALUES ('legacy', 'Legacy report', 'default-general', 'SELECT {{n}}',
+                         '2024-01-01 00:00:00', '2024-01-01 00:00:00');
+             INSERT INTO template_parameters (id, template_id, name) VALUES ('legacy-n', 'legacy', 'n');",
+        )
+        .unwrap();
+    }
+
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+    let legacy = manager.get_template_by_id("legacy").await.unwrap();
+    assert_eq!(legacy.parameters.len(), 1);
+
+    // The name is only unique among live templates now
+    manager.delete_template("legacy".to_string()).await.unwrap();
+    manager
+        .create_template(CreateTemplateRequest {
+            name: "Legacy report".to_string(),
+            description: None,
+            category_id: "default-general".to_string(),
+            content: "SELECT 1".to_string(),
+            parameters: vec![],
+        })
+        .await
+        .unwrap();
+    assert_eq!(manager.search_templates("legacy".to_string()).await.unwrap().len(), 1);
+
+    drop(manager);
+    remove_db_files(&path);
+}
+
+#[tokio::test]
+async fn test_query_history_round_trip() {
+    use super::types::QueryHistoryEntry;
+    use chrono::{Duration, Utc};
+
+    let path = temp_db_path("query-history");
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+
+    let now = Utc::now();
+    for (i, connection_id) in ["a", "b", "a"].iter().enumerate() {
+        manager
+            .record_query_history(&QueryHistoryEntry {
+                id: 0,
+                connection_id: connection_id.to_string(),
+                query: format!("SELECT {}", i),
+                execution_time_ms: 5,
+                row_count: (i != 1).then_some(1),
+                success: i != 1,
+                executed_at: now + Duration::seconds(i as i64),
+            })
+            .await
+            .unwrap();
+    }
+
+    let all = manager.get_query_history(None, None).await.unwrap();
+    let queries: Vec<&str> = all.iter().map(|e| e.query.as_str()).collect();
+    assert_eq!(queries, vec!["SELECT 2", "SELECT 1", "SELECT 0"]);
+    assert!(!all[1].success);
+    assert_eq!(all[1].row_count, None);
+
+    let for_a = manager.get_query_history(Some("a"), Some(1)).await.unwrap();
+    assert_eq!(for_a.len(), 1);
+    assert_eq!(for_a[0].query, "SELECT 2");
+
+    assert_eq!(manager.clear_query_history(Some("a")).await.unwrap(), 2);
+    assert_eq!(manager.get_query_history(None, None).await.unwrap().len(), 1);
+    assert_eq!(manager.clear_query_history(None).await.unwrap(), 1);
+
+    drop(manager);
+    remove_db_files(&path);
+}
+
+#[tokio::test]
+async fn test_template_lint_flags_parameter_mismatches() {
+    use super::types::{CreateParameterRequest, CreateTemplateRequest, UpdateTemplateRequest};
+
+    let path = temp_db_path("lint");
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+
+    let template = manager
+        .create_template(CreateTemplateRequest {
+            name: "Mismatched".to_string(),
+            description: None,
+            category_id: "default-general".to_string(),
+            content: "SELECT * FROM orders WHERE status = {{status}} AND region = {{region}} OR status = {{status}}"
+                .to_string(),
+            parameters: ["status", "limit"]
+                .iter()
+                .map(|name| CreateParameterRequest {
+                    name: name.to_string(),
+                    default_value: None,
+                    description: None,
+                    kind: Default::default(),
+                })
+                .collect(),
+        })
+        .await
+        .unwrap();
+
+    let lint = manager.lint_template(&template);
+    assert_eq!(lint.undeclared_placeholders, ["region"]);
+    assert_eq!(lint.unused_parameters, ["limit"]);
+    assert_eq!(template.warnings.len(), 2);
+
+    // Fixing the content clears the warnings
+    let updated = manager
+        .update_template(
+            template.id.clone(),
+            UpdateTemplateRequest {
+                name: None,
+                description: None,
+                category_id: None,
+                content: Some("SELECT * FROM orders WHERE status = {{status}} LIMIT {{limit}}".to_string()),
+                parameters: None,
+                is_favorite: None,
+            },
+        )
+        .await
+        .unwrap();
+    assert!(updated.warnings.is_empty());
+
+    drop(manager);
+    remove_db_files(&path);
+}
+
+#[tokio::test]
+async fn test_parameter_values_are_quoted_instead_of_rejected() {
+    use super::types::{CreateParameterRequest, CreateTemplateRequest, ParameterSubstitution};
+    use crate::database::DatabaseType;
+
+    let path = temp_db_path("quoted-values");
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+
+    let parameter = |name: &str| CreateParameterRequest {
+        name: name.to_string(),
+        default_value: None,
+        description: None,
+        kind: Default::default(),
+    };
+    let template = manager
+        .create_template(CreateTemplateRequest {
+            name: "Customer lookup".to_string(),
+            description: None,
+            category_id: "default-general".to_string(),
+            content: "SELECT * FROM customers WHERE last_name = {{name}} AND sort = '{{sort}}' LIMIT {{limit}}"
+                .to_string(),
+            parameters: vec![parameter("name"), parameter("sort"), parameter("limit")],
+        })
+        .await
+        .unwrap();
+
+    let substitutions = |name: &str| {
+        vec![
+            ParameterSubstitution { parameter_name: "name".to_string(), value: name.to_string() },
+            ParameterSubstitution { parameter_name: "sort".to_string(), value: "updated_at".to_string() },
+            ParameterSubstitution { parameter_name: "limit".to_string(), value: "10".to_string() },
+        ]
+    };
+
+    let processed = manager
+        .process_template_parameters(template.id.clone(), substitutions("O'Brien"))
+        .await
+        .unwrap();
+    assert_eq!(
+        processed.processed_content,
+        "SELECT * FROM customers WHERE last_name = 'O''Brien' AND sort = 'updated_at' LIMIT 10"
+    );
+
+    // An injection attempt stays inside its literal
+    let processed = manager
+        .process_template_parameters(template.id.clone(), substitutions("x'; DROP TABLE customers; --"))
+        .await
+        .unwrap();
+    assert!(processed.processed_content.contains("last_name = 'x''; DROP TABLE customers; --' AND"));
+
+    // MySQL reads backslashes as escapes, so they are doubled there
+    let processed = manager
+        .process_template_parameters_for(template.id.clone(), substitutions("a\\' OR 1=1"), Some(&DatabaseType::MySQL))
+        .await
+        .unwrap();
+    assert!(processed.processed_content.contains("last_name = 'a\\\\'' OR 1=1' AND"));
+
+    drop(manager);
+    remove_db_files(&path);
+}
+
+#[tokio::test]
+async fn test_parameter_quoting_follows_the_surrounding_sql() {
+    use super::types::{CreateParameterRequest, CreateTemplateRequest, ParameterKind, ParameterSubstitution};
+    use crate::database::DatabaseType;
+
+    let path = temp_db_path("quoting-context");
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+
+    let parameter = |name: &str, kind: ParameterKind| CreateParameterRequest {
+        name: name.to_string(),
+        default_value: None,
+        description: None,
+        kind,
+    };
+    let template = manager
+        .create_template(CreateTemplateRequest {
+            name: "Search".to_string(),
+            description: None,
+            category_id: "default-general".to_string(),
+            content: "SELECT * FROM {{table}} WHERE name LIKE '%{{q}}%' AND score > 10 -{{offset}} -- by {{q}}"
+                .to_string(),
+            parameters: vec![
+                parameter("table", ParameterKind::Identifier),
+                parameter("q", ParameterKind::Value),
+                parameter("offset", ParameterKind::Value),
+            ],
+        })
+        .await
+        .unwrap();
+    assert_eq!(template.parameters[0].kind, ParameterKind::Identifier);
+
+    let substitutions = |table: &str, q: &str, offset: &str| {
+        vec![
+            ParameterSubstitution { parameter_name: "table".to_string(), value: table.to_string() },
+            ParameterSubstitution { parameter_name: "q".to_string(), value: q.to_string() },
+            ParameterSubstitution { parameter_name: "offset".to_string(), value: offset.to_string() },
+        ]
+    };
+
+    // Inside a literal the value is escaped but not quoted again; a negative number after `-`
+    // must not turn into a comment
+    let processed = manager
+        .process_template_parameters(template.id.clone(), substitutions("sales.orders", "ab", "-5"))
+        .await
+        .unwrap();
+    assert_eq!(
+        processed.processed_content,
+        "SELECT * FROM \"sales\".\"orders\" WHERE name LIKE '%ab%' AND score > 10 - -5 -- by ab"
+    );
+
+    // A quote in the value can't close the literal it sits in
+    let processed = manager
+        .process_template_parameters(template.id.clone(), substitutions("orders", "' OR 1=1 --", "0"))
+        .await
+        .unwrap();
+    assert!(processed.processed_content.contains("LIKE '%'' OR 1=1 --%' AND"));
+
+    // Identifiers use the dialect's quoting, and MySQL literals escape backslashes as well
+    let processed = manager
+        .process_template_parameters_for(
+            template.id.clone(),
+            substitutions("weird`name", "a\\' OR 1=1", "0"),
+            Some(&DatabaseType::MySQL),
+        )
+        .await
+        .unwrap();
+    assert!(processed.processed_content.starts_with("SELECT * FROM `weird``name` WHERE name LIKE '%a\\\\'' OR 1=1%'"));
+
+    // MySQL double quotes and PostgreSQL E-strings are literals where backslashes escape
+    let quoted = manager
+        .create_template(CreateTemplateRequest {
+            name: "Quoted".to_string(),
+            description: None,
+            category_id: "default-general".to_string(),
+            content: "SELECT * FROM t WHERE a = \"{{q}}\" AND b = E'{{q}}'".to_string(),
+            parameters: vec![parameter("q", ParameterKind::Value)],
+        })
+        .await
+        .unwrap();
+    let only_q = |q: &str| vec![ParameterSubstitution { parameter_name: "q".to_string(), value: q.to_string() }];
+    let processed = manager
+        .process_template_parameters_for(quoted.id.clone(), only_q("x\\\" OR 1=1 -- "), Some(&DatabaseType::MySQL))
+        .await
+        .unwrap();
+    assert_eq!(
+        processed.processed_content,
+        "SELECT * FROM t WHERE a = \"x\\\\\"\" OR 1=1 -- \" AND b = E'x\\\\\" OR 1=1 -- '"
+    );
+    let processed = manager
+        .process_template_parameters_for(quoted.id.clone(), only_q("x\\' OR 1=1 -- "), Some(&DatabaseType::PostgreSQL))
+        .await
+        .unwrap();
+    assert_eq!(
+        processed.processed_content,
+        "SELECT * FROM t WHERE a = \"x\\' OR 1=1 -- \" AND b = E'x\\\\'' OR 1=1 -- '"
+    );
+
+    drop(manager);
+    remove_db_files(&path);
+}
+
+#[tokio::test]
+async fn test_preview_marks_unfilled_placeholders() {
+    use super::types::{CreateParameterRequest, CreateTemplateRequest, ParameterSubstitution};
+
+    let path = temp_db_path("preview");
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+
+    let template = manager
+        .create_template(CreateTemplateRequest {
+            name: "Preview".to_string(),
+            description: None,
+            category_id: "default-general".to_string(),
+            content: "SELECT * FROM orders WHERE status = {{status}} AND placed_at > {{since}} LIMIT {{limit}}"
+                .to_string(),
+            parameters: vec![
+                CreateParameterRequest {
+                    name: "status".to_string(),
+                    default_value: Some("open".to_string()),
+                    description: None,
+                    kind: Default::default(),
+                },
+                CreateParameterRequest {
+                    name: "since".to_string(),
+                    default_value: None,
+                    description: None,
+                    kind: Default::default(),
+                },
+            ],
+        })
+        .await
+        .unwrap();
+
+    let preview = manager.preview_template(template.id.clone(), vec![], None).await.unwrap();
+    assert_eq!(
+        preview.processed_content,
+        "SELECT * FROM orders WHERE status = 'open' AND placed_at > [[missing:since]] LIMIT [[missing:limit]]"
+    );
+    assert_eq!(preview.missing_parameters, ["since", "limit"]);
+
+    // Values process_template_parameters would refuse still render
+    let control = ParameterSubstitution {
+        parameter_name: "since".to_string(),
+        value: "bad\u{7}value".to_string(),
+    };
+    let preview = manager.preview_template(template.id.clone(), vec![control], None).await.unwrap();
+    assert!(preview.processed_content.contains("placed_at > 'bad\u{7}value'"));
+
+    let unused = manager.get_template_by_id(&template.id).await.unwrap();
+    assert_eq!(unused.usage_count, 0);
+
+    drop(manager);
+    remove_db_files(&path);
+}
+
+#[tokio::test]
+async fn test_search_ranks_name_matches_first_and_tracks_changes() {
+    use super::types::{CreateTemplateRequest, UpdateTemplateRequest};
+
+    let path = temp_db_path("fts-search");
+    let manager = TemplateManager::new(path.to_str().unwrap()).unwrap();
+
+    let create = |name: &str, content: &str| CreateTemplateRequest {
+        name: name.to_string(),
+        description: None,
+        category_id: "default-general".to_string(),
+        content: content.to_string(),
+        parameters: vec![],
+    };
+    let buried = manager
+        .create_template(create("Monthly totals", "SELECT month, SUM(amount) AS revenue FROM sales GROUP BY month"))
+        .await
+        .unwrap();
+    let named = manager
+        .create_template(create("Revenue by region", "SELECT region, SUM(amount) FROM sales GROUP BY region"))
+        .await
+        .unwrap();
+
+    let names = |templates: &[super::types::Template]| -> Vec<String> {
+        templates.iter().map(|t| t.name.clone()).collect()
+    };
+
+    // A name hit outranks a content hit, and words match as prefixes
+    let results = manager.search_templates("reven".to_string()).await.unwrap();
+    assert_eq!(names(&results), ["Revenue by region", "Monthly totals"]);
+
+    // Writes to unindexed columns leave the index alone
+    manager.increment_usage_count(named.id.clone()).await.unwrap();
+    let results = manager.search_templates("region".to_string()).await.unwrap();
+    assert_eq!(names(&results), ["Revenue by region"]);
+
+    manager
+        .update_template(
+            buried.id.clone(),
+            UpdateTemplateRequest {
+                name: None,
+                description: None,
+                category_id: None,
+                content: Some("SELECT month, SUM(amount) FROM sales GROUP BY month".to_string()),
+                parameters: None,
+                is_favorite: None,
+            },
+        )
+        .await
+        .unwrap();
+    manager.delete_template(named.id.clone()).await.unwrap();
+    assert!(manager.search_templates("revenue".to_string()).await.unwrap().is_empty());
+
+    // FTS syntax in the query is treated as plain words
+    let results = manager.search_templates("\"monthly\" OR".to_string()).await.unwrap();
+    assert!(results.is_empty());
+    let results = manager.search_templates("monthly totals".to_string()).await.unwrap();
+    assert_eq!(names(&results), ["Monthly totals"]);
+
+    drop(manager);
+    remove_db_files(&path);
+}
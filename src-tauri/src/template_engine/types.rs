@@ -17,6 +17,30 @@ pub struct Template {
     pub updated_at: DateTime<Utc>,
     pub usage_count: u32,
     pub is_favorite: bool,
+    // Lint findings reported when the template is created or updated
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+// Mismatches between a template's {{placeholders}} and its declared parameters
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TemplateLint {
+    pub undeclared_placeholders: Vec<String>, // Used in the content but never declared
+    pub unused_parameters: Vec<String>,       // Declared but never used in the content
+}
+
+impl TemplateLint {
+    pub fn is_clean(&self) -> bool {
+        self.undeclared_placeholders.is_empty() && self.unused_parameters.is_empty()
+    }
+
+    pub fn warnings(&self) -> Vec<String> {
+        let undeclared = self.undeclared_placeholders.iter()
+            .map(|name| format!("Placeholder {{{{{}}}}} has no declared parameter and will not be substituted", name));
+        let unused = self.unused_parameters.iter()
+            .map(|name| format!("Parameter '{}' is declared but never used in the template", name));
+        undeclared.chain(unused).collect()
+    }
 }
 
 // Template Parameter for dynamic templates
@@ -27,6 +51,35 @@ pub struct TemplateParameter {
     pub name: String, // e.g., "start_date" for {{start_date}}
     pub default_value: Option<String>,
     pub description: Option<String>,
+    #[serde(default)]
+    pub ordinal: u32, // Position in the template's parameter list
+    #[serde(default)]
+    pub kind: ParameterKind,
+}
+
+// How a parameter's value is substituted into the template's SQL
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ParameterKind {
+    #[default]
+    Value,      // A literal: a number as-is, anything else quoted as a string
+    Identifier, // A table, column or schema name, quoted as an identifier
+}
+
+impl ParameterKind {
+    // Stored form in the template_parameters.kind column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ParameterKind::Value => "value",
+            ParameterKind::Identifier => "identifier",
+        }
+    }
+
+    pub fn from_stored(value: &str) -> Self {
+        match value {
+            "identifier" => ParameterKind::Identifier,
+            _ => ParameterKind::Value,
+        }
+    }
 }
 
 // Template Category for organization
@@ -39,6 +92,13 @@ pub struct TemplateCategory {
     pub template_count: u32, // Computed field for UI display
 }
 
+// A category with its child categories, as returned by `get_category_tree`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryNode {
+    pub category: TemplateCategory,
+    pub children: Vec<CategoryNode>,
+}
+
 // Request types for template operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateTemplateRequest {
@@ -54,6 +114,8 @@ pub struct CreateParameterRequest {
     pub name: String,
     pub default_value: Option<String>,
     pub description: Option<String>,
+    #[serde(default)]
+    pub kind: ParameterKind,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,6 +128,21 @@ pub struct UpdateTemplateRequest {
     pub is_favorite: Option<bool>,
 }
 
+// A template in the trash, as listed by `list_deleted_templates`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedTemplate {
+    #[serde(flatten)]
+    pub template: Template,
+    pub deleted_at: DateTime<Utc>,
+}
+
+// Outcome of an idempotent create-or-update
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpsertTemplateResult {
+    pub template: Template,
+    pub created: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateCategoryRequest {
     pub name: String,
@@ -90,6 +167,15 @@ pub struct TemplateFilter {
     pub offset: Option<u32>,
 }
 
+/// A page of `get_templates` results and the total number of templates matching the filter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplatePage {
+    pub templates: Vec<Template>,
+    pub total_count: u32,
+    pub limit: u32,
+    pub offset: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TemplateSortBy {
     Name,
@@ -113,6 +199,20 @@ pub struct TemplateUsage {
     pub execution_success: Option<bool>,
 }
 
+// One executed query, kept so it can be audited or re-run in a later session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryHistoryEntry {
+    /// Assigned by the database; ignored when recording
+    pub id: i64,
+    pub connection_id: String,
+    pub query: String,
+    pub execution_time_ms: u64,
+    /// Rows returned; `None` when the query failed
+    pub row_count: Option<u64>,
+    pub success: bool,
+    pub executed_at: DateTime<Utc>,
+}
+
 // Import/Export types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TemplateExport {
@@ -122,12 +222,30 @@ pub struct TemplateExport {
     pub exported_at: DateTime<Utc>,
 }
 
+// What `import_templates` does with a template whose name already exists in its category
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImportMode {
+    #[default]
+    Skip,      // Keep the existing template and count the import as a duplicate
+    Overwrite, // Replace the existing template's description, content and parameters
+    Rename,    // Import alongside it as "<name> (imported)"
+}
+
+// Outcome of `import_templates`. A best-effort import keeps every item that succeeded, so a
+// failure can leave the library partially imported; an atomic import rolls back on the first
+// error (reported with zero imported counts) at the cost of discarding the items that were fine.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TemplateImportResult {
     pub imported_templates: u32,
     pub imported_categories: u32,
     pub skipped_duplicates: u32,
+    #[serde(default)]
+    pub overwritten_templates: u32,
+    #[serde(default)]
+    pub renamed_templates: u32,
     pub errors: Vec<String>,
+    #[serde(default)]
+    pub rolled_back: bool, // Atomic import discarded everything because of `errors`
 }
 
 // Parameter substitution types
@@ -145,6 +263,45 @@ pub struct ProcessedTemplate {
     pub missing_parameters: Vec<String>,
 }
 
+// Preview of an edit: the stored content against the proposed content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateContentDiff {
+    pub template_id: String,
+    pub has_changes: bool,
+    pub lines: Vec<ContentDiffLine>,
+    pub unified_diff: String, // Same change as `lines`, as a unified diff for display
+    pub parameters: ParameterDiff,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContentDiffLine {
+    pub change: DiffChange,
+    pub old_line: Option<u32>, // 1-based line in the stored content
+    pub new_line: Option<u32>, // 1-based line in the proposed content
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DiffChange {
+    Equal,
+    Insert,
+    Delete,
+}
+
+// Placeholder changes between the stored parameters and the proposed content
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParameterDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub renamed: Vec<ParameterRename>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParameterRename {
+    pub from: String,
+    pub to: String,
+}
+
 // Statistics and analytics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TemplateStatistics {
@@ -163,6 +320,34 @@ pub struct CategoryUsage {
     pub total_usage: u32,
 }
 
+// What `delete_category_with_strategy` does with a category's templates and child categories
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CategoryDeleteStrategy {
+    Reassign(String), // Move them into this category, which must survive the deletion
+    CascadeDelete,    // Delete the category's whole subtree along with every template in it
+}
+
+// Per-template outcome of a bulk delete, move or favorite change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BulkTemplateResult {
+    pub id: String,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+// Payload of the `template-categories-changed` event, sent when templates move between categories
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryCountsChanged {
+    pub counts: Vec<CategoryCount>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CategoryCount {
+    pub category_id: String,
+    pub template_count: u32,
+}
+
 // Error types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TemplateError {
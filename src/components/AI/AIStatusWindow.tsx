@@ -281,7 +281,7 @@ export const useAIEngineStatus = () => {
   const pollStatus = async () => {
     try {
       const statusJson = await invoke('get_ai_engine_status') as string;
-      const engineStatus = JSON.parse(statusJson);
+      const engineStatus = JSON.parse(statusJson).status;
       
       setStatus({
         status: engineStatus.toLowerCase(),
@@ -29,6 +29,7 @@ interface SQLGenerationResponse {
   confidence_score?: number;
   warnings?: string[];
   clarifying_questions?: string[];
+  pending_clarification_id?: string;
   error_message?: string;
   generation_time_ms?: number;
 }
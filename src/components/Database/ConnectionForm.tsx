@@ -2,7 +2,7 @@ import React, { useState, useEffect } from 'react';
 import { Button } from '../UI/Button';
 import { DatabaseTypeSelector } from './DatabaseTypeSelector';
 import { useDatabaseTypes } from './hooks/useDatabaseTypes';
-import type { DatabaseConnection, ConnectionFormData, ConnectionFormErrors, DatabaseType } from './types';
+import type { DatabaseConnection, ConnectionFormData, ConnectionFormErrors, DatabaseType, SslMode } from './types';
 import './ConnectionForm.css';
 
 interface ConnectionFormProps {
@@ -28,7 +28,7 @@ export const ConnectionForm: React.FC<ConnectionFormProps> = ({
     database: '',
     username: '',
     password: '',
-    ssl_enabled: true,
+    ssl_mode: 'Require',
     connection_timeout: 30,
     max_connections: 10,
     additional_params: {},
@@ -48,7 +48,7 @@ export const ConnectionForm: React.FC<ConnectionFormProps> = ({
         database: connection.database,
         username: connection.username,
         password: '', // Never pre-fill password for security
-        ssl_enabled: connection.ssl_enabled,
+        ssl_mode: connection.ssl_mode,
         connection_timeout: connection.connection_timeout,
         max_connections: connection.max_connections,
         additional_params: connection.additional_params,
@@ -64,7 +64,7 @@ export const ConnectionForm: React.FC<ConnectionFormProps> = ({
         database: template.database,
         username: template.username,
         password: '',
-        ssl_enabled: template.ssl_enabled,
+        ssl_mode: template.ssl_mode,
         connection_timeout: 30,
         max_connections: 10,
         additional_params: {},
@@ -83,7 +83,7 @@ export const ConnectionForm: React.FC<ConnectionFormProps> = ({
       port: template.port,
       database: prev.database || template.database,
       username: prev.username || template.username,
-      ssl_enabled: template.ssl_enabled,
+      ssl_mode: template.ssl_mode,
     }));
     setErrors({});
   };
@@ -320,15 +320,19 @@ export const ConnectionForm: React.FC<ConnectionFormProps> = ({
           {formData.database_type !== 'SQLite' && (
             <div className="form-row">
               <div className="form-group">
-                <label className="checkbox-label">
-                  <input
-                    type="checkbox"
-                    checked={formData.ssl_enabled}
-                    onChange={(e) => handleInputChange('ssl_enabled', e.target.checked)}
-                    disabled={isLoading}
-                  />
-                  Enable SSL/TLS encryption
-                </label>
+                <label htmlFor="ssl_mode">SSL/TLS Mode</label>
+                <select
+                  id="ssl_mode"
+                  value={formData.ssl_mode}
+                  onChange={(e) => handleInputChange('ssl_mode', e.target.value as SslMode)}
+                  disabled={isLoading}
+                >
+                  <option value="Disable">Disabled</option>
+                  <option value="Prefer">Prefer (encrypt when available)</option>
+                  <option value="Require">Require</option>
+                  <option value="VerifyCa">Verify CA</option>
+                  <option value="VerifyFull">Verify CA and host name</option>
+                </select>
               </div>
             </div>
           )}
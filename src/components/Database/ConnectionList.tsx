@@ -161,7 +161,7 @@ export const ConnectionList: React.FC<ConnectionListProps> = ({
               <div className="meta-item">
                 <span className="meta-label">SSL:</span>
                 <span className="meta-value">
-                  {connection.ssl_enabled ? '✅ Enabled' : '❌ Disabled'}
+                  {connection.ssl_mode === 'Disable' ? '❌ Disabled' : `✅ ${connection.ssl_mode}`}
                 </span>
               </div>
             </div>
@@ -69,7 +69,8 @@ export function useConnectionManager() {
         database: sanitizedFormData.database,
         username: sanitizedFormData.username,
         password: sanitizedFormData.password,
-        sslEnabled: sanitizedFormData.ssl_enabled,
+        sslEnabled: sanitizedFormData.ssl_mode !== 'Disable',
+        sslMode: sanitizedFormData.ssl_mode,
       });
 
       await loadConnections();
@@ -1,6 +1,14 @@
 import { useState, useEffect, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
-import type { DatabaseType, DatabaseTypeInfo } from '../types';
+import type { DatabaseType, DatabaseTypeInfo, SslMode } from '../types';
+
+interface ConnectionTemplate {
+  host: string;
+  port: number;
+  database: string;
+  username: string;
+  ssl_mode: SslMode;
+}
 
 // Static database type information
 const DATABASE_TYPE_INFO: Record<DatabaseType, DatabaseTypeInfo> = {
@@ -86,7 +94,7 @@ export function useDatabaseTypes() {
   }, [supportedTypes]);
 
   // Get connection string template for a database type
-  const getConnectionTemplate = useCallback((type: DatabaseType) => {
+  const getConnectionTemplate = useCallback((type: DatabaseType): ConnectionTemplate => {
     switch (type) {
       case 'PostgreSQL':
         return {
@@ -94,7 +102,7 @@ export function useDatabaseTypes() {
           port: 5432,
           database: 'postgres',
           username: 'postgres',
-          ssl_enabled: true,
+          ssl_mode: 'Require',
         };
       case 'MySQL':
         return {
@@ -102,7 +110,7 @@ export function useDatabaseTypes() {
           port: 3306,
           database: 'mysql',
           username: 'root',
-          ssl_enabled: true,
+          ssl_mode: 'Require',
         };
       case 'SQLite':
         return {
@@ -110,7 +118,7 @@ export function useDatabaseTypes() {
           port: 0,
           database: './database.db',
           username: '',
-          ssl_enabled: false,
+          ssl_mode: 'Disable',
         };
       case 'SqlServer':
         return {
@@ -118,7 +126,7 @@ export function useDatabaseTypes() {
           port: 1433,
           database: 'master',
           username: 'sa',
-          ssl_enabled: true,
+          ssl_mode: 'Require',
         };
       case 'Oracle':
         return {
@@ -126,7 +134,7 @@ export function useDatabaseTypes() {
           port: 1521,
           database: 'XE',
           username: 'system',
-          ssl_enabled: true,
+          ssl_mode: 'Require',
         };
       default:
         return {
@@ -134,7 +142,7 @@ export function useDatabaseTypes() {
           port: 5432,
           database: '',
           username: '',
-          ssl_enabled: true,
+          ssl_mode: 'Require',
         };
     }
   }, []);
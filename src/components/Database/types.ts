@@ -7,7 +7,7 @@ export interface DatabaseConnection {
   port: number;
   database: string;
   username: string;
-  ssl_enabled: boolean;
+  ssl_mode: SslMode;
   connection_timeout: number;
   max_connections: number;
   created_at: string;
@@ -22,6 +22,14 @@ export type DatabaseType =
   | 'SqlServer'
   | 'Oracle';
 
+// TLS negotiation levels, mirroring libpq's sslmode
+export type SslMode =
+  | 'Disable'
+  | 'Prefer'
+  | 'Require'
+  | 'VerifyCa'
+  | 'VerifyFull';
+
 export type ConnectionStatus = 
   | 'Disconnected'
   | 'Connecting'
@@ -62,7 +70,7 @@ export interface ConnectionFormData {
   database: string;
   username: string;
   password: string;
-  ssl_enabled: boolean;
+  ssl_mode: SslMode;
   connection_timeout: number;
   max_connections: number;
   additional_params: Record<string, string>;
@@ -19,12 +19,16 @@ export interface Template {
   is_favorite: boolean;
 }
 
+// How a parameter's value is substituted: a literal, or a quoted table/column name
+export type ParameterKind = 'Value' | 'Identifier';
+
 export interface TemplateParameter {
   id: string;
   template_id: string;
   name: string;
   default_value?: string;
   description?: string;
+  kind?: ParameterKind;
 }
 
 export interface TemplateCategory {
@@ -47,6 +51,7 @@ export interface CreateParameterRequest {
   name: string;
   default_value?: string;
   description?: string;
+  kind?: ParameterKind;
 }
 
 export interface UpdateTemplateRequest {